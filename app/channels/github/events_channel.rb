@@ -10,6 +10,7 @@
 # - On subscribe: validates GitHub App access, replays pending messages
 # - Real-time: new messages broadcast via github_events:{repo} stream
 # - CLI acks via perform("ack", { id: N })
+# - CLI reports messages it couldn't process via perform("mark_failed", { id: N, reason: "..." })
 #
 # Stream: github_events:{repo}
 #
@@ -32,6 +33,14 @@ module Github
       msg&.acknowledge! unless msg&.acknowledged?
     end
 
+    def mark_failed(data)
+      msg = Integrations::Github::Message.for_repo(@repo).find_by(id: data["id"])
+      if msg&.pending?
+        msg.mark_failed!(data["reason"])
+        Rails.logger.warn "[Github::EventsChannel] Message #{msg.id} failed: #{msg.failure_reason}"
+      end
+    end
+
     private
 
     def validate_github_access!
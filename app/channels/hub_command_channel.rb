@@ -14,6 +14,7 @@ require_relative "concerns/health_status"
 # - On subscribe: replay unacked hub commands from start_from sequence
 # - Real-time: new messages broadcast via after_create_commit callbacks
 # - CLI acks hub commands via perform("ack", { sequence: N })
+# - CLI reports commands it couldn't process via perform("mark_failed", { sequence: N, reason: "..." })
 # - CLI sends heartbeat via perform("heartbeat", { agents: [...] })
 #
 # Stream: hub_command:{hub_id}
@@ -56,12 +57,24 @@ class HubCommandChannel < ApplicationCable::Channel
     end
   end
 
+  def mark_failed(data)
+    sequence = data["sequence"].to_i
+    msg = @hub.hub_commands.find_by(sequence: sequence)
+    if msg&.pending?
+      msg.mark_failed!(data["reason"])
+      Rails.logger.warn "[HubCommandChannel] Hub command sequence #{sequence} failed: #{msg.failure_reason}"
+    end
+  end
+
   def heartbeat(data)
     updated_hub = Hub.update(@hub.id, alive: true, last_seen_at: Time.current)
     raise ActiveRecord::RecordInvalid, updated_hub if updated_hub.errors.any?
     @hub = updated_hub
 
     Rails.logger.debug "[HubCommandChannel] Heartbeat from hub=#{@hub.id}"
+    if data["missed_heartbeat"]
+      Rails.logger.info "[HubCommandChannel] Hub #{@hub.id} reconnected after missing heartbeats"
+    end
   end
 
   # Relay opaque encrypted envelope from CLI → specific browser
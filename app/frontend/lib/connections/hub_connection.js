@@ -14,6 +14,7 @@
  *   - hubRecoveryState - { state, ... }
  *   - hubReady - { state: "ready", ... }
  *   - agentConfig - { agents, accessories, workspaces }
+ *   - hubEvent - { event, data, seq, at, historical }
  *
  * Usage:
  *   HubTransport is acquired only by HubSession via hub-bridge/hub-store.
@@ -26,6 +27,9 @@
 import { HubRoute } from "connections/hub_route";
 import { applyEntityFrame, isEntityFrame } from "../../store/entities";
 
+// Matches the hub-side ring buffer size (`MAX_EVENTS` in lib/event_log.lua).
+const MAX_HUB_EVENTS = 100;
+
 /**
  * Extract a `{ [surfaceName]: subpath }` prime map from the current
  * browser URL. Called at subscribe time so the hub can apply the map
@@ -67,6 +71,7 @@ export class HubTransport extends HubRoute {
     super(key, options, manager);
     this._hubRecoveryState = null;
     this._uiRouteRegistry = [];
+    this._hubEvents = [];
 
     this._hasHubRecoveryStateSnapshot = false;
     this._hasUiRouteRegistrySnapshot = false;
@@ -87,6 +92,15 @@ export class HubTransport extends HubRoute {
     return this._hasUiRouteRegistrySnapshot;
   }
 
+  /**
+   * Recent hub events (agent created/deleted, lifecycle transitions,
+   * workspace closes), oldest first. Seeded by the hub's replay on
+   * subscribe and extended by live `hub_event` frames.
+   */
+  hubEvents() {
+    return this._hubEvents;
+  }
+
   // ========== Connection overrides ==========
 
   channelName() {
@@ -179,6 +193,25 @@ export class HubTransport extends HubRoute {
         });
         break;
 
+      case "hub_event": {
+        // Timeline entry from lib/event_log.lua. Replayed history
+        // (`historical: true`) can overlap live frames that raced the
+        // subscribe, so skip any seq already seen.
+        if (this._hubEvents.some((entry) => entry.seq === message.seq)) break;
+        const entry = {
+          event: message.event,
+          data: message.data || {},
+          seq: message.seq,
+          at: message.at,
+          historical: message.historical === true,
+        };
+        this._hubEvents = [...this._hubEvents, entry]
+          .sort((a, b) => a.seq - b.seq)
+          .slice(-MAX_HUB_EVENTS);
+        this.emit("hubEvent", entry);
+        break;
+      }
+
       case "session_types":
         this.emit("sessionTypes", {
           agentId: message.agent_id,
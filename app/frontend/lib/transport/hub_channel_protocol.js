@@ -1,5 +1,9 @@
 import { parseBinaryBundle } from "matrix/bundle"
 
+// PTY frame codec ids (high nibble of the flags byte) to DecompressionStream
+// formats. Must match CompressionCodec::id in cli/src/channel/compression.rs.
+const COMPRESSION_CODEC_FORMATS = { 0: "gzip", 2: "deflate" }
+
 export class HubChannelProtocol {
   #pendingSubscriptions = new Map()
   #callbacks
@@ -181,9 +185,14 @@ export class HubChannelProtocol {
 
     let rawBytes
     if (compressed) {
+      const format = COMPRESSION_CODEC_FORMATS[flags >> 4]
+      if (!format) {
+        console.warn("[WebRTCTransport] Unknown PTY compression codec:", flags >> 4)
+        return
+      }
       const stream = new Blob([payload])
         .stream()
-        .pipeThrough(new DecompressionStream("gzip"))
+        .pipeThrough(new DecompressionStream(format))
       rawBytes = new Uint8Array(await new Response(stream).arrayBuffer())
     } else {
       rawBytes = payload instanceof Uint8Array ? payload : new Uint8Array(payload)
@@ -824,6 +824,12 @@ class HubPeerConnection {
         for (const candidate of (decrypted.candidates || [])) {
           await this.#peerLifecycle.handleIceCandidate(hubId, candidate)
         }
+        return
+      }
+
+      if (decrypted.type === "error") {
+        console.warn(`[WebRTCTransport] Hub rejected connection (${decrypted.code}): ${decrypted.message}`)
+        this.#emit("connection:error", { hubId, code: decrypted.code, message: decrypted.message })
       }
     } catch (error) {
       console.error("[WebRTCTransport] Signal decryption/handling error:", error)
@@ -1,3 +1,7 @@
+// Codecs this browser can decode for PTY frames, advertised in every offer.
+// Names map to DecompressionStream formats; the hub picks its preferred match.
+const SUPPORTED_COMPRESSION_CODECS = ["deflate", "gzip"]
+
 export class HubPeerLifecycle {
   #callbacks
   #constants
@@ -128,6 +132,7 @@ export class HubPeerLifecycle {
     const envelope = await this.#callbacks.encryptSignal(hubId, {
       type: "offer",
       sdp: offer.sdp,
+      codecs: SUPPORTED_COMPRESSION_CODECS,
     })
     subscription.perform("signal", { envelope })
     conn.offerSentAt = performance.now()
@@ -348,6 +353,7 @@ export class HubPeerLifecycle {
       const envelope = await this.#callbacks.encryptSignal(hubId, {
         type: "offer",
         sdp: offer.sdp,
+        codecs: SUPPORTED_COMPRESSION_CODECS,
       })
       subscription.perform("signal", { envelope })
 
@@ -0,0 +1,65 @@
+import { describe, it, expect, beforeEach, vi } from 'vitest'
+
+// Under test: `hub_event` frames from the hub's event log
+// (cli/lua/lib/event_log.lua). Replayed history and live frames land in
+// `hubEvents()` in seq order without duplicates, and each new entry is
+// emitted as `hubEvent`.
+
+import { HubTransport } from '../lib/connections/hub_connection'
+
+function frame(seq, historical = false) {
+  return {
+    v: 2,
+    type: 'hub_event',
+    event: 'agent_created',
+    data: { session_uuid: `sess-${seq}` },
+    seq,
+    at: 1700000000 + seq,
+    historical,
+  }
+}
+
+describe('HubTransport hub_event frames', () => {
+  let transport
+  let emitted
+
+  beforeEach(() => {
+    // Sidestep the manager wiring; handleMessage only needs the base
+    // class's processMessage and emit.
+    transport = Object.create(HubTransport.prototype)
+    transport._hubEvents = []
+    transport.processMessage = () => false
+    emitted = []
+    transport.emit = vi.fn((name, payload) => emitted.push([name, payload]))
+  })
+
+  it('records replayed and live events in seq order', () => {
+    transport.handleMessage(frame(2))
+    transport.handleMessage(frame(1, true))
+
+    expect(transport.hubEvents().map((entry) => entry.seq)).toEqual([1, 2])
+    expect(transport.hubEvents()[0].historical).toBe(true)
+    expect(emitted.map(([name]) => name)).toEqual(['hubEvent', 'hubEvent'])
+  })
+
+  it('skips a replayed event already delivered live', () => {
+    transport.handleMessage(frame(1))
+    transport.handleMessage(frame(1, true))
+
+    expect(transport.hubEvents()).toHaveLength(1)
+    expect(emitted).toHaveLength(1)
+  })
+
+  it('does not fall through to the generic message event', () => {
+    transport.handleMessage(frame(1))
+
+    expect(emitted.some(([name]) => name === 'message')).toBe(false)
+  })
+
+  it('keeps only the most recent 100 events', () => {
+    for (let seq = 1; seq <= 105; seq += 1) transport.handleMessage(frame(seq))
+
+    expect(transport.hubEvents()).toHaveLength(100)
+    expect(transport.hubEvents()[0].seq).toBe(6)
+  })
+})
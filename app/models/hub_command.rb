@@ -9,11 +9,11 @@ class HubCommand < ApplicationRecord
   }
   validates :payload, presence: true
   validates :status, presence: true, inclusion: {
-    in: %w[pending acknowledged],
+    in: %w[pending acknowledged failed],
     message: "%{value} is not a valid status"
   }
 
-  scope :unacked_from, ->(seq) { where("sequence > ?", seq).where.not(status: "acknowledged").order(sequence: :asc) }
+  scope :unacked_from, ->(seq) { where("sequence > ?", seq).where(status: "pending").order(sequence: :asc) }
 
   before_create :set_default_status
   after_create_commit :broadcast_to_hub_command_channel
@@ -31,6 +31,20 @@ class HubCommand < ApplicationRecord
     status == "acknowledged"
   end
 
+  # The CLI could not process this command. Failed commands are not
+  # replayed, so a malformed command can't be redelivered forever.
+  def mark_failed!(reason)
+    update!(status: "failed", failure_reason: reason.to_s.truncate(500))
+  end
+
+  def failed?
+    status == "failed"
+  end
+
+  def pending?
+    status == "pending"
+  end
+
   private
 
   def set_default_status
@@ -12,7 +12,7 @@ module Integrations
       validates :repo, presence: true
       validates :payload, presence: true
       validates :status, presence: true, inclusion: {
-        in: %w[pending acknowledged],
+        in: %w[pending acknowledged failed],
         message: "%{value} is not a valid status"
       }
 
@@ -32,6 +32,20 @@ module Integrations
         status == "acknowledged"
       end
 
+      # The CLI could not process this message. Only pending messages are
+      # replayed, so a malformed event can't be redelivered forever.
+      def mark_failed!(reason)
+        update!(status: "failed", failure_reason: reason.to_s.truncate(500))
+      end
+
+      def failed?
+        status == "failed"
+      end
+
+      def pending?
+        status == "pending"
+      end
+
       def github_mention?
         event_type == "github_mention"
       end
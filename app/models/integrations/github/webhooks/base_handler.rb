@@ -32,6 +32,11 @@ module Integrations
           text.to_s.match?(/@trybotster\b/i)
         end
 
+        # Label names on the payload's issue or pull request.
+        def label_names(key)
+          Array(payload.dig(key, "labels")).filter_map { |label| label["name"] }
+        end
+
         def bot_author?(author)
           author == "trybotster" || author&.downcase&.end_with?("[bot]")
         end
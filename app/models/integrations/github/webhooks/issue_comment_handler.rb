@@ -23,6 +23,7 @@ module Integrations
             is_pr: target_is_pr,
             source_type: source_type,
             routed_info: routed_info,
+            labels: label_names("issue"),
             installation_id: installation_id
           )
         end
@@ -48,6 +48,7 @@ module Integrations
             issue_body: issue_body,
             issue_url: issue_url,
             is_pr: false,
+            labels: label_names("issue"),
             installation_id: installation_id
           )
         end
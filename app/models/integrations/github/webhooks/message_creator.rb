@@ -18,6 +18,7 @@ module Integrations
           @source_type = params[:source_type]
           @routed_info = params[:routed_info]
           @installation_id = params[:installation_id]
+          @labels = params[:labels] || []
         end
 
         def call
@@ -40,6 +41,7 @@ module Integrations
               issue_body: @issue_body,
               issue_url: @issue_url,
               is_pr: @is_pr,
+              labels: @labels,
               context: formatted_context,
               installation_id: @installation_id
             }
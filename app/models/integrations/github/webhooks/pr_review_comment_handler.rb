@@ -23,6 +23,7 @@ module Integrations
             is_pr: target_is_pr,
             source_type: "pr_review_comment",
             routed_info: routed_info,
+            labels: label_names("pull_request"),
             installation_id: installation_id
           )
         end
@@ -48,6 +48,7 @@ module Integrations
             issue_body: pr_body,
             issue_url: pr_url,
             is_pr: true,
+            labels: label_names("pull_request"),
             installation_id: installation_id
           )
         end
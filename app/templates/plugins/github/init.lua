@@ -23,6 +23,13 @@
 -- events are plaintext over TLS, no E2E encryption needed).
 
 local Agent = require("lib.agent")
+local CleanupPolicy = require("lib.cleanup_policy")
+local CommandAck = require("lib.command_ack")
+local CommandFilter = require("lib.command_filter")
+local CommandStats = require("lib.command_stats")
+local MentionBatcher = require("lib.mention_batcher")
+local ProcessedCommands = require("lib.processed_commands")
+local PromptLimit = require("lib.prompt_limit")
 local hooks = require("hub.hooks")
 
 local repo = hub.detect_repo()
@@ -210,30 +217,16 @@ local function find_matching_agent(event_repo, payload)
     return nil
 end
 
---- Format a notification for an existing agent about a new mention.
--- @param payload table The event payload
--- @return string
-local function format_notification(payload)
-    local prompt = payload.prompt or payload.context or payload.comment_body
-    if prompt then
-        return string.format(
-            "=== NEW MENTION (automated notification) ===\n\n%s\n\n==================",
-            prompt
-        )
-    end
-    return "=== NEW MENTION (automated notification) ===\nNew mention\n=================="
-end
-
---- Notify an existing agent by writing to its single PTY session.
+--- Notify an existing agent of a new mention.
+-- Goes through the same debounce as create_agent mentions, so a burst of
+-- comments is submitted once (see lib/mention_batcher.lua), and is held to
+-- max_prompt_chars (see lib/prompt_limit.lua).
 -- @param agent Agent
 -- @param payload table
 local function notify_agent(agent, payload)
-    if agent.session then
-        agent.session:send_message(format_notification(payload))
-        log.info(string.format("GitHub: notified existing agent %s", agent.session_uuid))
-    else
-        log.warn(string.format("GitHub: cannot notify agent %s (no session)", agent.session_uuid))
-    end
+    local prompt = payload.prompt or payload.context or payload.comment_body
+    MentionBatcher.notify(agent, PromptLimit.apply(prompt or "New mention"))
+    log.info(string.format("GitHub: queued mention for existing agent %s", agent.session_uuid))
 end
 
 -- ============================================================================
@@ -293,53 +286,82 @@ end)
 -- Event Channel
 -- ============================================================================
 
+--- Route a Github::EventsChannel message (settled by CommandAck).
+-- @param message table { id, event_type, payload, repo }
+local function route_event(message)
+    local payload = message.payload or {}
+    local event_repo = message.repo or repo
+
+    if message.event_type == "agent_cleanup" then
+        -- PR closed or issue closed — close matching agents by workspace
+        -- name; cleanup_policy decides what happens to their worktrees.
+        if payload.issue_number then
+            local ws_name = github_workspace_name(event_repo, payload.issue_number)
+            local matches = Agent.find_by_workspace(ws_name)
+            local policy = CleanupPolicy.resolve()
+            for _, agent in ipairs(matches) do
+                events.emit("command_message", CleanupPolicy.delete_command(agent, policy))
+            end
+        end
+        return
+    end
+
+    -- New mention — find existing agent or create a new one
+    local allowed, skip_reason = CommandFilter.allows(message.event_type, payload)
+    if not allowed then
+        log.info(string.format("GitHub: skipping %s for %s: %s",
+            tostring(message.event_type), event_repo, tostring(skip_reason)))
+        return
+    end
+
+    local existing = find_matching_agent(event_repo, payload)
+    if existing then
+        -- New agents are gated in handlers/agents.lua; this shortcut must
+        -- not deliver mentions that path would refuse.
+        if not config.repo_allowed(event_repo) then
+            log.warn(string.format("GitHub: skipping mention for repo not in allowed_repos: %s", event_repo))
+            return
+        end
+        notify_agent(existing, payload)
+        CommandStats.record("reused")
+        return
+    end
+
+    local issue_num = payload.issue_number
+    local ws_name = github_workspace_name(event_repo, issue_num)
+    events.emit("command_message", {
+        type = "create_agent",
+        issue_or_branch = issue_num and tostring(issue_num),
+        prompt = payload.prompt or payload.context or payload.comment_body,
+        repo = event_repo,
+        metadata = {
+            issue_number = issue_num,
+            invocation_url = payload.issue_url,
+            workspace = ws_name,
+            workspace_metadata = { repo = event_repo, issue_number = issue_num },
+        },
+    })
+end
+
 local conn = action_cable.connect()
 
 -- The callback receives (message, channel_id) from the primitive,
 -- so we use channel_id directly — no upvalue capture needed.
+-- Pending messages are replayed on every subscribe, so each one is either
+-- acked or reported failed; a message that raises must not come back forever.
 action_cable.subscribe(conn, "Github::EventsChannel",
     { repo = repo },
     function(message, channel_id)
-        local payload = message.payload or {}
-        local event_repo = message.repo or repo
-
-        if message.event_type == "agent_cleanup" then
-            -- PR closed or issue closed — delete matching agents by workspace name
-            if payload.issue_number then
-                local ws_name = github_workspace_name(event_repo, payload.issue_number)
-                local matches = Agent.find_by_workspace(ws_name)
-                for _, agent in ipairs(matches) do
-                    events.emit("command_message", {
-                        type = "delete_agent",
-                        agent_id = agent.session_uuid,
-                        delete_worktree = false,
-                    })
-                end
-            end
-        else
-            -- New mention — find existing agent or create a new one
-            local existing = find_matching_agent(event_repo, payload)
-            if existing then
-                notify_agent(existing, payload)
-            else
-                local issue_num = payload.issue_number
-                local ws_name = github_workspace_name(event_repo, issue_num)
-                events.emit("command_message", {
-                    type = "create_agent",
-                    issue_or_branch = issue_num and tostring(issue_num),
-                    prompt = payload.prompt or payload.context or payload.comment_body,
-                    repo = event_repo,
-                    metadata = {
-                        issue_number = issue_num,
-                        invocation_url = payload.issue_url,
-                        workspace = ws_name,
-                        workspace_metadata = { repo = event_repo, issue_number = issue_num },
-                    },
-                })
-            end
+        CommandStats.record_fetched()
+        -- A restart between routing and the ack reaching the server replays
+        -- the message; ack the repeat without acting on it twice.
+        if ProcessedCommands.seen(message.id, "github") then
+            log.info(string.format("GitHub: skipping already processed event %s", tostring(message.id)))
+            CommandAck.ack(channel_id, message, "id")
+            return
         end
-
-        action_cable.perform(channel_id, "ack", { id = message.id })
+        ProcessedCommands.record(message.id, "github")
+        CommandAck.settle(channel_id, message, route_event, "id")
     end
 )
 
@@ -0,0 +1,136 @@
+-- Agent restore handler (hot-reloadable)
+--
+-- Keeps lib/agent_snapshot.lua current and, once startup recovery is done,
+-- respawns agents whose session process did not survive the restart.
+--
+-- Live session processes are re-adopted by handlers/session_recovery.lua.
+-- After a reboot or a crashed session process only the worktree is left, so
+-- each agent from the previous run's snapshot that wasn't recovered is
+-- spawned again in its worktree with the same agent name, target and
+-- workspace. The task prompt is not replayed (the agent may have finished
+-- it already) and scrollback is not restored. The old session manifest is
+-- closed so it isn't picked up again.
+
+local Agent = require("lib.agent")
+local AgentSnapshot = require("lib.agent_snapshot")
+local state = require("hub.state")
+
+local M = {}
+
+-- Snapshot from the previous run, read before recovery rewrites it.
+-- Survives hot-reload so a reload doesn't restore twice.
+local pending = state.get("agent_restore", { loaded = false, done = false, entries = {} })
+if not pending.loaded then
+    pending.loaded = true
+    pending.entries = AgentSnapshot.read()
+end
+
+--- Close the session manifest of an agent that was respawned.
+local function close_old_manifest(entry)
+    local data_dir = config.data_dir and config.data_dir() or nil
+    if not data_dir or not entry.workspace_id or not entry.session_uuid then return end
+    local ws = require("lib.workspace_store")
+    local manifest = ws.read_session(data_dir, entry.workspace_id, entry.session_uuid)
+    if manifest and manifest.status ~= "closed" then
+        manifest.status = "closed"
+        manifest.updated_at = os.date("!%Y-%m-%dT%H:%M:%SZ", os.time())
+        pcall(ws.write_session, data_dir, entry.workspace_id, entry.session_uuid, manifest)
+        ws.append_event(data_dir, entry.workspace_id, entry.session_uuid, "resurrected")
+    end
+end
+
+--- Respawn snapshot agents that aren't running.
+-- Entries whose session is live (recovered) or whose worktree is gone are
+-- skipped.
+-- @param entries array Snapshot entries (see AgentSnapshot.entries)
+-- @return array Restored agents
+function M.restore(entries)
+    local agents = require("handlers.agents")
+    -- Decide liveness up front so a respawned agent is never mistaken for a
+    -- later entry's recovered session.
+    local live = {}
+    for _, session in ipairs(Agent.list()) do
+        live[session.session_uuid] = true
+    end
+
+    local restored = {}
+    for _, entry in ipairs(entries or {}) do
+        if entry.session_uuid and live[entry.session_uuid] then
+            goto continue
+        end
+        if type(entry.worktree_path) ~= "string" or not fs.exists(entry.worktree_path) then
+            log.info(string.format("Not restoring agent %s: worktree %s is gone",
+                tostring(entry.session_uuid), tostring(entry.worktree_path)))
+            goto continue
+        end
+
+        local metadata = {}
+        for k, v in pairs(entry.metadata or {}) do
+            metadata[k] = v
+        end
+        metadata.issue_number = metadata.issue_number or entry.issue_number
+        metadata.workspace_id = entry.workspace_id
+        metadata.workspace = entry.workspace_name
+
+        local agent, err = agents.handle_create_agent(
+            entry.branch_name, nil, entry.worktree_path, nil, entry.agent_name, metadata, {
+                target_id = entry.target_id,
+                target_path = entry.target_path,
+                target_repo = entry.target_repo,
+            }
+        )
+        if agent then
+            if agent.session_uuid ~= entry.session_uuid then
+                close_old_manifest(entry)
+            end
+            restored[#restored + 1] = agent
+            log.info(string.format("Restored agent %s in %s as %s",
+                tostring(entry.session_uuid), entry.worktree_path, agent.session_uuid))
+        else
+            log.warn(string.format("Failed to restore agent %s: %s",
+                tostring(entry.session_uuid), tostring(err)))
+        end
+
+        ::continue::
+    end
+    return restored
+end
+
+local function write_snapshot()
+    AgentSnapshot.write()
+end
+
+hooks.on("agent_created", "agent_snapshot", write_snapshot)
+hooks.on("after_agent_close", "agent_snapshot", write_snapshot)
+-- An agent whose process exits drops out of the snapshot.
+hooks.on("session_updated", "agent_snapshot", function(data)
+    if type(data) == "table" and type(data.fields) == "table" and data.fields.status then
+        write_snapshot()
+    end
+end)
+
+local event_sub = events.on("hub_recovery_state", function(info)
+    if type(info) ~= "table" or info.state ~= "sessions_recovered" or pending.done then
+        return
+    end
+    pending.done = true
+    local entries = pending.entries
+    pending.entries = {}
+    local restored = M.restore(entries)
+    if #restored > 0 then
+        log.info(string.format("Restored %d agent(s) from the previous run", #restored))
+    end
+    write_snapshot()
+end)
+
+function M._before_reload()
+    hooks.off("agent_created", "agent_snapshot")
+    hooks.off("after_agent_close", "agent_snapshot")
+    hooks.off("session_updated", "agent_snapshot")
+    if event_sub then
+        events.off(event_sub)
+        event_sub = nil
+    end
+end
+
+return M
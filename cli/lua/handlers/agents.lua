@@ -19,11 +19,37 @@ local SessionClosePolicy = require("lib.session_close_policy")
 local Accessory = require("lib.accessory")
 local ConfigResolver = require("lib.config_resolver")
 local TargetContext = require("lib.target_context")
+local MentionBatcher = require("lib.mention_batcher")
+local CommandStats = require("lib.command_stats")
+local PromptLimit = require("lib.prompt_limit")
 
 -- ============================================================================
 -- Input Parsing
 -- ============================================================================
 
+local DEFAULT_BRANCH_TEMPLATE = "botster-issue-{issue}"
+
+--- Build the branch name for an issue from `branch_template` in config.json.
+-- `{issue}` is the issue number and `{user}` is $USER. Templates without
+-- exactly one `{issue}` fall back to the default, matching the Rust side.
+-- @param issue_number number
+-- @return string
+local function branch_name_for_issue(issue_number)
+    local template = DEFAULT_BRANCH_TEMPLATE
+    if type(config) == "table" and type(config.get) == "function" then
+        local ok, value = pcall(config.get, "branch_template")
+        if ok and type(value) == "string" then
+            local _, count = value:gsub("{issue}", "")
+            if count == 1 then
+                template = value
+            end
+        end
+    end
+    local user = (os.getenv("USER") or ""):gsub("%%", "%%%%")
+    local issue = tostring(issue_number)
+    return (template:gsub("{user}", user):gsub("{issue}", issue))
+end
+
 --- Parse an issue-or-branch string into structured fields.
 -- @param issue_or_branch string  Issue number or branch name
 -- @return issue_number number|nil
@@ -31,7 +57,7 @@ local TargetContext = require("lib.target_context")
 local function parse_issue_or_branch(issue_or_branch)
     local issue_number = tonumber(issue_or_branch)
     if issue_number then
-        return issue_number, "botster-issue-" .. issue_number
+        return issue_number, branch_name_for_issue(issue_number)
     else
         return nil, issue_or_branch
     end
@@ -182,6 +208,36 @@ local function notify_lifecycle(agent_id, status, extra)
     hooks.notify("agent_lifecycle", payload)
 end
 
+--- Stages an agent creation can fail at, reported as `failure.stage`.
+local STAGE = {
+    TARGET = "target",      -- interceptor or spawn target resolution
+    PROFILE = "profile",    -- agent name or config resolution
+    LIMIT = "limit",        -- max_concurrent / max_sessions
+    WORKTREE = "worktree",  -- worktree lookup or creation
+    SPAWN = "spawn",        -- session startup
+}
+
+--- Report a failed agent creation.
+-- Broadcasts a "failed" lifecycle carrying the failure fields.
+-- @param agent_id string Lifecycle id
+-- @param stage string One of STAGE
+-- @param err string Error for the lifecycle payload
+-- @param message string|nil Error returned to the caller (default err)
+-- @param extra table|nil Extra failure fields (code, worktrees)
+-- @return nil
+-- @return string Error message
+-- @return table Failure { stage, error, code?, worktrees? }
+local function creation_failed(agent_id, stage, err, message, extra)
+    local failure = { stage = stage, error = err }
+    if extra then
+        for k, v in pairs(extra) do
+            failure[k] = v
+        end
+    end
+    notify_lifecycle(agent_id, "failed", failure)
+    return nil, message or err, failure
+end
+
 -- ============================================================================
 -- Agent Spawning (internal)
 -- ============================================================================
@@ -189,6 +245,65 @@ end
 -- Forward declaration so spawn_agent can call spawn_accessory
 local spawn_accessory
 
+--- Count running agents in total and for one profile (agent name).
+-- Pinned agents are left out: pinning keeps an agent around on purpose
+-- (it is exempt from idle auto-close), and counting it would let a pinned
+-- agent hold a slot forever and block every new spawn.
+-- @param profile string Agent name from config (e.g., "codex")
+-- @return number profile_active
+-- @return number total_active
+local function count_active_agents(profile)
+    local profile_active, total_active = 0, 0
+    for _, session in ipairs(Agent.list()) do
+        if session.session_type == "agent" and session.status ~= "closed" and not session.pinned then
+            total_active = total_active + 1
+            local name = session.agent_name
+                or (session._session_config and session._session_config.name)
+            if name == profile then
+                profile_active = profile_active + 1
+            end
+        end
+    end
+    return profile_active, total_active
+end
+
+--- Failure code for a spawn refused by a limit.
+local LIMIT_REACHED = "limit_reached"
+
+--- Check per-profile max_concurrent and the global max_sessions cap.
+-- @param profile string|nil Agent name from config
+-- @param branch_name string Lifecycle id for the refused spawn
+-- @return nil|true nil when refused (followed by creation_failed values), true when allowed
+local function check_spawn_limit(profile, branch_name)
+    if not config.spawn_limit_error then
+        return true
+    end
+    local profile_active, total_active = count_active_agents(profile)
+    local limit_err = config.spawn_limit_error(profile, profile_active, total_active)
+    if limit_err then
+        log.warn(string.format("Not spawning agent for %s: %s", branch_name, limit_err))
+        return creation_failed(branch_name, STAGE.LIMIT, limit_err, nil, { code = LIMIT_REACHED })
+    end
+    return true
+end
+
+--- Worktrees an agent could be reopened in, for worktree failures.
+-- @param target table Resolved target
+-- @param repo_root string|nil Repo root for non-runtime targets
+-- @return table Array of { path, branch }
+local function available_worktrees(target, repo_root)
+    local ok, list
+    if target_uses_current_runtime(target) then
+        ok, list = pcall(worktree.list)
+    else
+        ok, list = pcall(worktree.list_for_root, repo_root or target.target_path)
+    end
+    if not ok or type(list) ~= "table" then
+        return {}
+    end
+    return list
+end
+
 --- Spawn an agent in an existing worktree.
 --
 -- @param branch_name string
@@ -199,13 +314,16 @@ local spawn_accessory
 -- @param metadata table|nil     Plugin metadata
 -- @param workspace_manifest table|nil  Workspace manifest { agents[], accessories[] }
 -- @param target table           Explicit target context
+-- @param worktree_reused boolean|nil  true if wt_path already existed, false if just created,
+--                                     nil when no worktree applies (main repo / non-git)
 -- @return Agent|nil             The created agent, or nil on error
 -- @return string|nil            Error message (nil on success)
-local function spawn_agent(branch_name, wt_path, prompt, client, agent_name, metadata, workspace_manifest, target)
+-- @return table|nil             Failure (see creation_failed)
+local function spawn_agent(branch_name, wt_path, prompt, client, agent_name, metadata, workspace_manifest, target,
+                           worktree_reused)
     local resolved_target, target_err = resolve_target(target, metadata)
     if not resolved_target then
-        notify_lifecycle(branch_name, "failed", { error = tostring(target_err) })
-        return nil, tostring(target_err)
+        return creation_failed(branch_name, STAGE.TARGET, tostring(target_err))
     end
 
     local repo = resolved_target.target_repo or repo_label_for_target(resolved_target)
@@ -225,13 +343,18 @@ local function spawn_agent(branch_name, wt_path, prompt, client, agent_name, met
         local msg = string.format("Config resolution failed for agent '%s': %s",
             tostring(agent_name), tostring(err))
         log.error(msg)
-        notify_lifecycle(branch_name, "failed", { error = tostring(err) })
-        return nil, msg
+        return creation_failed(branch_name, STAGE.PROFILE, tostring(err), msg)
     end
 
     -- Pick the agent config
     local session_config = pick_agent_config(resolved, agent_name)
 
+    -- Re-check limits: agents may have spawned while a worktree was created
+    local allowed, limit_err, limit_failure = check_spawn_limit(agent_name or session_config.name, branch_name)
+    if not allowed then
+        return nil, limit_err, limit_failure
+    end
+
     -- Default dimensions
     local dims = { rows = 24, cols = 80 }
 
@@ -268,14 +391,14 @@ local function spawn_agent(branch_name, wt_path, prompt, client, agent_name, met
         session = session_config,
         dims = dims,
         agent_name = agent_name,
+        worktree_reused = worktree_reused,
     })
 
     if not ok then
         local msg = string.format("Failed to spawn agent for %s: %s",
             branch_name, tostring(agent))
         log.error(msg)
-        notify_lifecycle(branch_name, "failed", { error = tostring(agent) })
-        return nil, msg
+        return creation_failed(branch_name, STAGE.SPAWN, tostring(agent), msg)
     end
 
     -- Notify via hooks (connections.lua observes and broadcasts to clients)
@@ -397,6 +520,7 @@ end
 -- @param target table|nil            Explicit target context
 -- @return Agent|nil
 -- @return string|nil
+-- @return table|nil                  Failure { stage, error, code?, worktrees? }
 local function handle_create_agent(issue_or_branch, prompt, from_worktree, client, agent_name, metadata, target)
     local early_id = issue_or_branch or "main"
 
@@ -412,8 +536,7 @@ local function handle_create_agent(issue_or_branch, prompt, from_worktree, clien
     })
     if params == nil then
         log.info("before_agent_create interceptor blocked agent creation")
-        notify_lifecycle(early_id, "failed", { error = "Blocked by interceptor" })
-        return nil, "Blocked by interceptor"
+        return creation_failed(early_id, STAGE.TARGET, "Blocked by interceptor")
     end
     issue_or_branch = params.issue_or_branch
     prompt = params.prompt
@@ -422,11 +545,16 @@ local function handle_create_agent(issue_or_branch, prompt, from_worktree, clien
     metadata = params.metadata
     target = params.target
 
+    local truncated
+    prompt, truncated = PromptLimit.apply(prompt)
+    if truncated then
+        log.info(string.format("Truncated prompt for %s to max_prompt_chars", early_id))
+    end
+
     local resolved_target, target_err = resolve_target(target, metadata)
     if not resolved_target then
         log.error(string.format("Target resolution failed: %s", tostring(target_err)))
-        notify_lifecycle(early_id, "failed", { error = tostring(target_err) })
-        return nil, tostring(target_err)
+        return creation_failed(early_id, STAGE.TARGET, tostring(target_err))
     end
     metadata = TargetContext.with_metadata(metadata, resolved_target)
 
@@ -435,11 +563,16 @@ local function handle_create_agent(issue_or_branch, prompt, from_worktree, clien
     local resolved_name, name_err = resolve_agent_name(device_root, resolved_target.target_path, agent_name)
     if name_err then
         log.error(string.format("Agent resolution failed: %s", name_err))
-        notify_lifecycle(early_id, "failed", { error = name_err })
-        return nil, "Agent resolution failed: " .. name_err
+        return creation_failed(early_id, STAGE.PROFILE, name_err, "Agent resolution failed: " .. name_err)
     end
     agent_name = resolved_name
 
+    -- Refuse before creating a worktree that no agent would use
+    local allowed, limit_err, limit_failure = check_spawn_limit(agent_name, early_id)
+    if not allowed then
+        return nil, limit_err, limit_failure
+    end
+
     -- Check for workspace manifest to auto-spawn accessories
     local workspace_manifest = nil
     if metadata and metadata.workspace_config then
@@ -473,12 +606,22 @@ local function handle_create_agent(issue_or_branch, prompt, from_worktree, clien
     end
 
     -- Find or create worktree
+    local worktree_reused
     local wt_path = from_worktree
+    if wt_path and not fs.exists(wt_path) then
+        log.error(string.format("Worktree not found for %s: %s", branch_name, wt_path))
+        return creation_failed(branch_name, STAGE.WORKTREE, "Worktree not found: " .. wt_path, nil, {
+            worktrees = available_worktrees(resolved_target, worktree_root),
+        })
+    end
+    -- Worktrees are tagged with their issue, so one on a renamed branch is
+    -- still reused.
+    local issue_number = metadata and tonumber(metadata.issue_number)
     if not wt_path then
         if target_uses_current_runtime(resolved_target) then
-            wt_path = worktree.find(branch_name)
+            wt_path = worktree.find(branch_name, issue_number)
         else
-            wt_path = worktree.find_for_root(worktree_root, branch_name)
+            wt_path = worktree.find_for_root(worktree_root, branch_name, issue_number)
         end
     end
 
@@ -521,18 +664,23 @@ local function handle_create_agent(issue_or_branch, prompt, from_worktree, clien
             return nil  -- Agent spawning continues in worktree_created event handler
         end
 
-        local ok, created_or_err = pcall(worktree.create_for_root, worktree_root, branch_name)
+        local ok, created_or_err = pcall(worktree.create_for_root, worktree_root, branch_name, issue_number)
         if not ok then
-            notify_lifecycle(branch_name, "failed", { error = tostring(created_or_err) })
-            return nil, tostring(created_or_err)
+            return creation_failed(branch_name, STAGE.WORKTREE, tostring(created_or_err), nil, {
+                worktrees = available_worktrees(resolved_target, worktree_root),
+            })
         end
         wt_path = created_or_err
+        worktree_reused = false
     else
         log.info(string.format("Worktree found for %s at %s", branch_name, wt_path))
+        notify_lifecycle(branch_name, "reusing_worktree", { worktree_path = wt_path })
+        worktree_reused = true
     end
 
     return spawn_agent(
-        branch_name, wt_path, prompt, client, agent_name, metadata, workspace_manifest, resolved_target
+        branch_name, wt_path, prompt, client, agent_name, metadata, workspace_manifest, resolved_target,
+        worktree_reused
     )
 end
 
@@ -599,12 +747,18 @@ end
 --- Handle a request to delete a session (agent or accessory).
 -- @param session_uuid string       Session UUID
 -- @param delete_worktree boolean   Whether to also delete the worktree
+-- @param cleanup_policy string|nil "delete" or "archive" to remove the
+--   worktree via worktree.cleanup (which keeps unpushed work) instead
 -- @return boolean
-local function handle_delete_session(session_uuid, delete_worktree)
+-- @return string|nil Why the worktree was kept although deletion was requested
+-- @return table|nil { code = "worktree_in_use", sessions = {uuid...} } naming
+--   the sessions still running in the worktree
+local function handle_delete_session(session_uuid, delete_worktree, cleanup_policy)
     -- Interceptor: plugins can block deletion
     local cfg = hooks.call("before_agent_delete", {
         session_uuid = session_uuid,
         delete_worktree = delete_worktree,
+        cleanup_policy = cleanup_policy,
     })
     if cfg == nil then
         log.info("before_agent_delete interceptor blocked deletion")
@@ -612,6 +766,7 @@ local function handle_delete_session(session_uuid, delete_worktree)
     end
     session_uuid = cfg.session_uuid
     delete_worktree = cfg.delete_worktree
+    cleanup_policy = cfg.cleanup_policy
 
     local agent = Agent.get(session_uuid)
     if not agent then
@@ -624,7 +779,9 @@ local function handle_delete_session(session_uuid, delete_worktree)
     -- Broadcast: stopping
     notify_lifecycle(uuid, "stopping")
 
-    -- Guard: skip worktree deletion if other agents are still running in it
+    -- Guard: skip worktree deletion if other agents are still running in it.
+    -- The session itself still closes.
+    local kept_err, kept
     if delete_worktree then
         local still_running = SessionClosePolicy.other_active_sessions(agent, Agent.list())
         if #still_running > 0 then
@@ -632,15 +789,17 @@ local function handle_delete_session(session_uuid, delete_worktree)
             for _, other in ipairs(still_running) do
                 still_running_ids[#still_running_ids + 1] = other.session_uuid
             end
-            log.warn(string.format(
+            kept_err = string.format(
                 "Cannot delete worktree — session(s) [%s] still running in it",
-                table.concat(still_running_ids, ", ")))
+                table.concat(still_running_ids, ", "))
+            kept = { code = "worktree_in_use", sessions = still_running_ids }
+            log.warn(kept_err)
             delete_worktree = false
         end
     end
 
     -- Close the agent (kills PTY session)
-    agent:close(delete_worktree)
+    agent:close(delete_worktree, cleanup_policy)
 
     if delete_worktree then
         notify_lifecycle(uuid, "removing_worktree")
@@ -649,37 +808,125 @@ local function handle_delete_session(session_uuid, delete_worktree)
     -- Notify via hooks
     hooks.notify("agent_deleted", uuid)
 
-    return true
+    return true, kept_err, kept
 end
 
 -- Keep backward-compat name
 local handle_delete_agent = handle_delete_session
 
--- ============================================================================
--- Event Listeners
--- ============================================================================
+--- Resolve the latest session config for a running session.
+-- @param agent table Session instance
+-- @return table|nil Session config
+-- @return string|nil error message
+local function resolve_session_config(agent)
+    local device_root = config.data_dir and config.data_dir() or nil
+    local resolved, err = ConfigResolver.resolve_all({
+        device_root = device_root,
+        repo_root = agent.target_path,
+        require_agent = false,
+    })
+    if not resolved then
+        return nil, tostring(err)
+    end
 
---- Format a notification string for an existing agent.
-local function format_notification(message)
-    local prompt = message.prompt
-    if prompt then
-        return string.format(
-            "=== NEW MENTION (automated notification) ===\n\n%s\n\n==================",
-            prompt
-        )
-    else
-        return "=== NEW MENTION (automated notification) ===\nNew mention\n=================="
+    if agent.session_type == "accessory" then
+        return pick_accessory_config(resolved, agent.session_name), nil
     end
+    return pick_agent_config(resolved, agent.agent_name), nil
 end
 
---- Notify an existing agent of a new mention via PTY input.
-local function notify_existing_agent(agent, text)
-    if agent.session then
-        agent.session:send_message(text)
-        log.info("Sent notification to existing agent: " .. agent.session_uuid)
-    else
-        log.warn("Cannot notify agent (no session): " .. agent.session_uuid)
+--- Handle a request to re-run an agent's initialization script.
+-- Re-resolves config so the latest `initialization` file is used, then
+-- restarts the session so a fresh shell sources it (see Session:reinit).
+-- Refuses while the agent is mid-task unless `force` is set. The restart
+-- completes asynchronously; its outcome is broadcast as a "reinitialized"
+-- or "reinit_failed" lifecycle.
+-- @param session_uuid string  Session UUID
+-- @param force boolean|nil     Reinit even when the session is busy
+-- @return boolean
+-- @return string|nil
+local function handle_reinit_session(session_uuid, force)
+    local agent = Agent.get(session_uuid)
+    if not agent then
+        log.warn("Cannot reinit unknown session: " .. tostring(session_uuid))
+        return false, "unknown session"
+    end
+
+    local session_config, err = resolve_session_config(agent)
+    if err then
+        log.error(string.format("Config resolution failed for reinit of %s: %s",
+            agent.session_uuid, err))
+        return false, err
+    end
+    if not session_config or not session_config.init_script then
+        return false, "no initialization script configured"
+    end
+
+    local started, reinit_err = agent:reinit(session_config, { force = force }, function(ok, done_err)
+        if not ok then
+            notify_lifecycle(agent.session_uuid, "reinit_failed", { error = done_err })
+            return
+        end
+        notify_lifecycle(agent.session_uuid, "reinitialized")
+        hooks.notify("agent_reinitialized", agent:info())
+    end)
+    if not started then
+        log.warn(string.format("Reinit of %s skipped: %s", agent.session_uuid, tostring(reinit_err)))
+        notify_lifecycle(agent.session_uuid, "reinit_failed", { error = reinit_err })
+        return false, reinit_err
     end
+    return true, nil
+end
+
+--- Handle a request to restart a session in place.
+-- Kills the session's shell and spawns a fresh one in the same worktree
+-- with the latest config, re-sourcing the init script. The session UUID,
+-- prompt, and worktree are kept so the agent can resume its task. The
+-- respawn completes asynchronously; its outcome is broadcast as a
+-- "running" or "restart_failed" lifecycle.
+-- @param session_uuid string  Session UUID
+-- @return boolean Whether the restart was started
+-- @return string|nil
+local function handle_restart_session(session_uuid)
+    local agent = Agent.get(session_uuid)
+    if not agent then
+        log.warn("Cannot restart unknown session: " .. tostring(session_uuid))
+        return false, "unknown session"
+    end
+
+    local session_config, err = resolve_session_config(agent)
+    if err then
+        log.warn(string.format("Config resolution failed for restart of %s, using creation config: %s",
+            agent.session_uuid, err))
+    end
+
+    notify_lifecycle(agent.session_uuid, "restarting")
+    local started, restart_err = agent:restart(session_config, function(ok, done_err)
+        if not ok then
+            notify_lifecycle(agent.session_uuid, "restart_failed", { error = done_err })
+            return
+        end
+        notify_lifecycle(agent.session_uuid, "running")
+        hooks.notify("agent_restarted", agent:info())
+    end)
+    if not started then
+        notify_lifecycle(agent.session_uuid, "restart_failed", { error = restart_err })
+        return false, restart_err
+    end
+    return true, nil
+end
+
+
+-- ============================================================================
+-- Event Listeners
+-- ============================================================================
+
+--- Notify an existing agent of a new mention.
+-- Rapid mentions are coalesced into one submit (see lib/mention_batcher.lua).
+-- Each mention is held to max_prompt_chars (see lib/prompt_limit.lua).
+local function notify_existing_agent(agent, prompt)
+    local body = PromptLimit.apply(prompt or "New mention")
+    MentionBatcher.notify(agent, body)
 end
 
 -- Track event subscriptions for cleanup on hot-reload
@@ -708,6 +955,14 @@ _event_subs[#_event_subs + 1] = events.on("command_message", function(message)
             end
         end
 
+        -- Webhook and plugin mentions both land here; skip repos outside
+        -- allowed_repos before notifying or creating anything.
+        local msg_repo = command_target.target_repo or message.repo
+        if not config.repo_allowed(msg_repo) then
+            log.warn(string.format("Skipping create_agent for repo not in allowed_repos: %s", tostring(msg_repo)))
+            return
+        end
+
         -- Check if any agents already exist for this workspace — notify them
         if issue_or_branch then
             local meta = TargetContext.with_metadata(message.metadata, command_target)
@@ -743,11 +998,11 @@ _event_subs[#_event_subs + 1] = events.on("command_message", function(message)
             end
 
             if #existing > 0 then
-                local notification = format_notification(message)
                 for _, agent in ipairs(existing) do
                     log.info("Agent exists for " .. agent.session_uuid .. ", sending notification")
-                    notify_existing_agent(agent, notification)
+                    notify_existing_agent(agent, message.prompt)
                 end
+                CommandStats.record("reused")
                 return
             end
         end
@@ -769,9 +1024,12 @@ _event_subs[#_event_subs + 1] = events.on("command_message", function(message)
             end
             -- Accept both "profile" (legacy) and "agent_name" (new)
             local agent_name = message.agent_name or message.profile
-            handle_create_agent(issue_or_branch, message.prompt, message.from_worktree, nil, agent_name, meta, command_target)
+            -- nil without an error means creation continues after async worktree setup
+            local agent, create_err = handle_create_agent(issue_or_branch, message.prompt, message.from_worktree, nil, agent_name, meta, command_target)
+            CommandStats.record((agent or not create_err) and "spawned" or "failed")
         else
             log.warn("command_message create_agent missing issue_or_branch")
+            CommandStats.record("failed")
         end
 
     elseif msg_type == "create_accessory" then
@@ -788,7 +1046,7 @@ _event_subs[#_event_subs + 1] = events.on("command_message", function(message)
     elseif msg_type == "delete_agent" or msg_type == "delete_session" then
         local session_id = message.id or message.agent_id or message.session_uuid or message.session_key
         if session_id then
-            handle_delete_session(session_id, message.delete_worktree or false)
+            handle_delete_session(session_id, message.delete_worktree or false, message.cleanup_policy)
         else
             log.warn("command_message delete missing session identifier")
         end
@@ -831,14 +1089,15 @@ _event_subs[#_event_subs + 1] = events.on("worktree_created", function(info)
         agent_name,
         metadata,
         workspace_manifest,
-        target
+        target,
+        false
     )
 end)
 
 _event_subs[#_event_subs + 1] = events.on("worktree_create_failed", function(info)
     log.error(string.format("Async worktree creation failed for %s: %s",
         info.branch, info.error))
-    notify_lifecycle(info.branch or "unknown", "failed", { error = info.error })
+    creation_failed(info.branch or "unknown", STAGE.WORKTREE, info.error)
 end)
 
 -- ============================================================================
@@ -846,10 +1105,14 @@ end)
 -- ============================================================================
 
 local M = {
+    LIMIT_REACHED = LIMIT_REACHED,
+    STAGE = STAGE,
     handle_create_agent = handle_create_agent,
     handle_delete_agent = handle_delete_agent,
     handle_create_accessory = handle_create_accessory,
     handle_delete_session = handle_delete_session,
+    handle_reinit_session = handle_reinit_session,
+    handle_restart_session = handle_restart_session,
 }
 
 -- Lifecycle hooks for hot-reload
@@ -11,15 +11,36 @@
 local commands = require("lib.commands")
 local TargetContext = require("lib.target_context")
 
-local function send_command_error(client, sub_id, error_type, message)
+local function send_command_error(client, sub_id, error_type, message, failure)
     if not client then return end
     client:send({
         subscriptionId = sub_id,
         type = error_type or "error",
         error = message,
+        stage = failure and failure.stage or nil,
+        code = failure and failure.code or nil,
+        worktrees = failure and failure.worktrees or nil,
+        sessions = failure and failure.sessions or nil,
     })
 end
 
+--- Create an agent, reporting a failed creation to the requesting client with
+-- its stage (and the available worktrees when a worktree couldn't be used).
+-- Success is reported by the agent_created broadcast, never from here.
+-- @return boolean false when creation failed
+local function create_agent_for_client(client, sub_id, issue_or_branch, prompt, from_worktree, agent_name,
+                                       metadata, target)
+    local _, create_err, failure = require("handlers.agents").handle_create_agent(
+        issue_or_branch, prompt, from_worktree, client, agent_name, metadata, target
+    )
+    if create_err then
+        log.warn(string.format("create_agent failed: %s", tostring(create_err)))
+        send_command_error(client, sub_id, "error", create_err, failure)
+        return false
+    end
+    return true
+end
+
 local function send_spawn_target_feedback(client, sub_id, tone, message)
     if not client then return end
     client:send({
@@ -289,6 +310,10 @@ commands.register("create_agent", function(client, sub_id, command)
         metadata.workspace_id = workspace_id
         metadata.workspace = workspace_name
     end
+    -- Pinned agents are exempt from idle auto-close (Session lifts this out of metadata).
+    if command.pinned then
+        metadata.pinned = true
+    end
 
     -- Optional workspace template for auto-spawning accessory bundles.
     local workspace_config_name = command.workspace_template
@@ -308,9 +333,10 @@ commands.register("create_agent", function(client, sub_id, command)
         end
     end
 
-    require("handlers.agents").handle_create_agent(
-        issue_or_branch, prompt, from_worktree, client, agent_name, metadata, target
-    )
+    if not create_agent_for_client(client, sub_id, issue_or_branch, prompt, from_worktree, agent_name,
+            metadata, target) then
+        return
+    end
     log.info(string.format("Create agent request: %s (agent: %s, workspace: %s, target: %s)",
         tostring(issue_or_branch or "main"), tostring(agent_name or "auto"),
         tostring(workspace_id or workspace_name or "none"),
@@ -445,10 +471,11 @@ commands.register("update_session", function(_client, _sub_id, command)
         return
     end
 
-    -- Only allow updating label and task (not arbitrary fields)
+    -- Only allow updating label, task, and pinned (not arbitrary fields)
     local fields = {}
     if command.label ~= nil then fields.label = command.label end
     if command.task ~= nil then fields.task = command.task end
+    if command.pinned ~= nil then fields.pinned = command.pinned == true end
 
     if next(fields) then
         session:update(fields)
@@ -459,7 +486,7 @@ commands.register("update_session", function(_client, _sub_id, command)
                 return parts
             end)(), ", ")))
     end
-end, { description = "Update session label or task" })
+end, { description = "Update session label, task, or pinned flag" })
 
 commands.register("reopen_worktree", function(client, _sub_id, command)
     local path = command.path
@@ -479,27 +506,61 @@ commands.register("reopen_worktree", function(client, _sub_id, command)
             metadata.workspace_id = command.workspace_id
             metadata.workspace = command.workspace_name
         end
-        require("handlers.agents").handle_create_agent(
-            branch, prompt, path, client, agent_name, metadata, target
-        )
+        if not create_agent_for_client(client, _sub_id, branch, prompt, path, agent_name, metadata, target) then
+            return
+        end
         log.info(string.format("Reopen worktree request: %s", path))
     else
         log.warn("reopen_worktree missing path")
     end
 end, { description = "Reopen an existing worktree as an agent" })
 
-commands.register("delete_agent", function(_client, _sub_id, command)
+commands.register("delete_agent", function(client, sub_id, command)
     local session_id = command.id or command.agent_id or command.session_uuid or command.session_key
     local delete_worktree = command.delete_worktree or false
 
     if session_id then
-        require("handlers.agents").handle_delete_session(session_id, delete_worktree)
+        local _, kept_err, kept = require("handlers.agents").handle_delete_session(
+            session_id, delete_worktree)
+        if kept_err then
+            send_command_error(client, sub_id, "error", kept_err, kept)
+        end
         log.info(string.format("Delete session request: %s", session_id))
     else
         log.warn("delete_agent missing session identifier")
     end
 end, { description = "Delete a session (agent or accessory, optionally with worktree)" })
 
+commands.register("reinit_agent", function(_client, _sub_id, command)
+    local session_id = command.id or command.agent_id or command.session_uuid or command.session_key
+
+    if session_id then
+        local ok, err = require("handlers.agents").handle_reinit_session(session_id, command.force or false)
+        if ok then
+            log.info(string.format("Reinit session request: %s", session_id))
+        else
+            log.warn(string.format("reinit_agent failed for %s: %s", session_id, tostring(err)))
+        end
+    else
+        log.warn("reinit_agent missing session identifier")
+    end
+end, { description = "Restart a session so it re-runs the latest initialization script" })
+
+commands.register("restart_agent", function(_client, _sub_id, command)
+    local session_id = command.id or command.agent_id or command.session_uuid or command.session_key
+
+    if session_id then
+        local ok, err = require("handlers.agents").handle_restart_session(session_id)
+        if ok then
+            log.info(string.format("Restart session request: %s", session_id))
+        else
+            log.warn(string.format("restart_agent failed for %s: %s", session_id, tostring(err)))
+        end
+    else
+        log.warn("restart_agent missing session identifier")
+    end
+end, { description = "Kill and respawn a session's shell in place, keeping its worktree and prompt" })
+
 commands.register("toggle_hosted_preview", function(_client, _sub_id, command)
     local Session = require("lib.session")
     local HostedPreview = require("lib.hosted_preview")
@@ -537,12 +598,16 @@ commands.register("toggle_hosted_preview", function(_client, _sub_id, command)
 end, { description = "Enable or disable a Cloudflare-hosted preview for a forwarded session" })
 
 -- Alias: delete_session → delete_agent
-commands.register("delete_session", function(_client, _sub_id, command)
+commands.register("delete_session", function(client, sub_id, command)
     local session_id = command.id or command.session_uuid or command.agent_id or command.session_key
     local delete_worktree = command.delete_worktree or false
 
     if session_id then
-        require("handlers.agents").handle_delete_session(session_id, delete_worktree)
+        local _, kept_err, kept = require("handlers.agents").handle_delete_session(
+            session_id, delete_worktree)
+        if kept_err then
+            send_command_error(client, sub_id, "error", kept_err, kept)
+        end
         log.info(string.format("Delete session request: %s", session_id))
     else
         log.warn("delete_session missing session identifier")
@@ -656,6 +721,24 @@ commands.register("dev_rebuild", function(_client, _sub_id, _command)
     hub.dev_rebuild()
 end, { description = "Dev: cargo build then exec-restart — agents survive (requires cargo on PATH)" })
 
+commands.register("set_log_level", function(client, sub_id, command)
+    local level = command.level
+    if type(level) ~= "string" or level == "" then
+        send_command_error(client, sub_id, "error", "set_log_level requires level")
+        return
+    end
+    local ok, err = log.set_level(level)
+    if client then
+        client:send({
+            subscriptionId = sub_id,
+            type = "log_level",
+            level = log.level(),
+            success = ok,
+            error = not ok and tostring(err) or nil,
+        })
+    end
+end, { description = "Change the hub log filter without restarting (RUST_LOG syntax, e.g. debug)" })
+
 -- ============================================================================
 -- Update Commands
 -- ============================================================================
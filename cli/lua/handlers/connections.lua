@@ -11,6 +11,7 @@
 --     - `ui_tree_snapshot`  (via lib.tree_snapshot)
 --     - `ui_route_registry`  (via Client:send_ui_route_registry)
 --     - `transient_event`   (built inline below for pty notifications)
+--     - `hub_event`         (via lib.event_log, replayed on subscribe)
 --   Hooks like `agent_created` / `agent_deleted` / `session_updated`
 --   are local Lua identifiers; their handlers route through EB.
 --   Selection lives on the client — both renderers maintain their own.
@@ -24,6 +25,7 @@ local ClientSessionPayload = require("lib.client_session_payload")
 local Session = require("lib.session")
 local pty_clients = require("lib.pty_clients")
 local EB = require("lib.entity_broadcast")
+local EventLog = require("lib.event_log")
 
 -- Shared client registry - all transports register here
 local clients = state.get("connections.clients", {})
@@ -122,6 +124,7 @@ end
 -- Wire up EB to use the broadcast loop. EB.upsert / EB.patch / EB.remove
 -- now ship frames straight to every hub-channel subscriber.
 EB.set_broadcaster(broadcast_frame_to_hub)
+EventLog.set_broadcaster(broadcast_frame_to_hub)
 
 --- Broadcast `ui_tree_snapshot` frames for every hub-channel subscriber.
 ---
@@ -201,6 +204,13 @@ hooks.on("agent_created", "broadcast_agent_created", function(info)
         payload.id or payload.session_uuid or "?"))
 
     EB.upsert("session", payload)
+    EventLog.record("agent_created", {
+        session_uuid = info.session_uuid,
+        session_type = info.session_type,
+        display_name = info.display_name,
+        label = info.label,
+        branch_name = info.branch_name,
+    })
     -- Workspaces list may have grown — re-snapshot since workspace patches
     -- are not granular enough to capture "this session now belongs here".
     -- EB.upsert resolves the entity id via `payload[id_field] or payload.id`
@@ -223,6 +233,7 @@ hooks.on("agent_deleted", "broadcast_agent_deleted", function(agent_id)
 
     if agent_id then
         EB.remove("session", agent_id)
+        EventLog.record("agent_deleted", { session_uuid = agent_id })
     end
 
     -- Surviving sessions might leave a workspace empty. Re-snapshot the
@@ -239,6 +250,24 @@ hooks.on("agent_deleted", "broadcast_agent_deleted", function(agent_id)
     end
 end)
 
+-- Completion sentinel: watch each agent's worktree for `.botster_done` so the
+-- agent can report a deterministic completion (see lib/completion_sentinel.lua).
+hooks.on("agent_created", "watch_completion_sentinel", function(info)
+    if Session.is_system_session(info) or info.session_type ~= "agent" then
+        return
+    end
+    local agent = info.session_uuid and Agent.get(info.session_uuid)
+    if agent then
+        require("lib.completion_sentinel").watch(agent)
+    end
+end)
+
+hooks.on("agent_deleted", "unwatch_completion_sentinel", function(agent_id)
+    if agent_id then
+        require("lib.completion_sentinel").unwatch(agent_id)
+    end
+end)
+
 -- Global callable by Rust to update per-client focus state.
 function _set_pty_focused(session_uuid, peer_id, focused)
     if session_uuid then
@@ -448,6 +477,10 @@ hooks.on("pty_output", "idle_activity_reset", function(ctx, _data)
         session:update({ is_idle = false })
     end
 
+    if session.session_type == "agent" then
+        require("lib.trust_prompt").scan(session, _data)
+    end
+
     timer.after_idle("idle:" .. uuid, IDLE_THRESHOLD_SECS, function()
         local s = Agent.get(uuid)
         if s and not s.is_idle then
@@ -466,7 +499,27 @@ hooks.on("agent_lifecycle", "broadcast_lifecycle", function(info)
         info.agent_id or "?", info.status or "?"))
     if info.agent_id and info.status then
         EB.patch("session", info.agent_id, { status = info.status })
+        EventLog.record("agent_lifecycle", info)
+    end
+end)
+
+-- A restarted agent has a new PTY under the same session UUID. Forwarders
+-- still point at the killed process, so rebuild them for every client
+-- watching that terminal.
+hooks.on("agent_restarted", "reattach_restarted_terminals", function(info)
+    local session_uuid = info and info.session_uuid
+    if not session_uuid then return end
+    for peer_id, client in pairs(clients) do
+        local ok, err = pcall(client.reattach_terminal, client, session_uuid)
+        if not ok then
+            log.warn(string.format("reattach_terminal failed for %s: %s",
+                peer_id:sub(1, 8), tostring(err)))
+        end
     end
+    EventLog.record("agent_restarted", {
+        session_uuid = session_uuid,
+        branch_name = info.branch_name,
+    })
 end)
 
 -- Wire protocol B2 fix: when a workspace transitions to closed (fired
@@ -481,6 +534,7 @@ hooks.on("workspace_closed", "broadcast_workspace_closed", function(info)
     local ws_id = info and info.workspace_id
     if not ws_id then return end
     EB.patch("workspace", ws_id, { status = "closed" })
+    EventLog.record("workspace_closed", { workspace_id = ws_id })
 end)
 
 -- ============================================================================
@@ -565,17 +619,25 @@ end)
 _event_subs[#_event_subs + 1] = events.on("process_exited", function(data)
     local session_uuid = data.session_uuid
     local exit_code = data.exit_code
-    log.info(string.format("Process exited for %s (code=%s)",
-        session_uuid or "?", tostring(exit_code)))
+    local signal = data.signal
+    log.info(string.format("Process exited for %s (code=%s, signal=%s)",
+        session_uuid or "?", tostring(exit_code), tostring(signal)))
 
     local HostedPreview = require("lib.hosted_preview")
     if HostedPreview.handle_process_exited(data) then
         return
     end
 
+    -- A clean exit (code 0, or unknown) is "exited"; a non-zero code or a
+    -- killing signal is "failed".
+    local failed = signal ~= nil or (exit_code ~= nil and exit_code ~= 0)
     local agent = (session_uuid and Agent.get(session_uuid))
     if agent then
-        agent:update({ status = "exited" })
+        agent:update({
+            status = failed and "failed" or "exited",
+            exit_code = exit_code,
+            exit_signal = signal,
+        })
     end
 end)
 
@@ -647,6 +709,8 @@ function M._before_reload()
     end
     hooks.off("agent_created", "broadcast_agent_created")
     hooks.off("agent_deleted", "broadcast_agent_deleted")
+    hooks.off("agent_created", "watch_completion_sentinel")
+    hooks.off("agent_deleted", "unwatch_completion_sentinel")
     hooks.off("agent_lifecycle", "broadcast_lifecycle")
     hooks.off("_pty_notification_raw", "enrich_and_dispatch")
     hooks.off("pty_notification", "push_notification")
@@ -658,6 +722,7 @@ function M._before_reload()
     hooks.off("client_disconnected", "unfocus_on_disconnect")
     hooks.off("surfaces_changed", "broadcast_ui_route_registry")
     hooks.off("workspace_closed", "broadcast_workspace_closed")
+    hooks.off("agent_restarted", "reattach_restarted_terminals")
     timer.cancel("ui_route_registry_broadcast")
     -- Wire protocol B6 fix: we DO NOT clear the broadcaster here. The
     -- top-level `EB.set_broadcaster(broadcast_frame_to_hub)` on reload
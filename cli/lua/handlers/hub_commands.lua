@@ -3,8 +3,10 @@
 -- Manages the HubCommandChannel subscription:
 --   - Forwards decrypted signaling/control messages to the Rust Hub
 --   - Routes command messages to Lua event system
---   - Acks commands by sequence number
+--   - Acks commands by sequence number (or reports them failed, see lib/command_ack.lua)
+--   - Skips commands already processed before a restart (lib/processed_commands.lua)
 --   - Sends application-level heartbeat every 30s (agent status sync)
+--   - Emits per-heartbeat command counts via the `poll_completed` hook
 --   - Relays outgoing WebRTC signals through encrypted ActionCable pipe
 --
 -- NOTE: ActionCable protocol pings are handled automatically by the
@@ -17,6 +19,11 @@
 local state = require("hub.state")
 local Agent = require("lib.agent")
 local TargetContext = require("lib.target_context")
+local CommandStats = require("lib.command_stats")
+local CommandFilter = require("lib.command_filter")
+local CleanupPolicy = require("lib.cleanup_policy")
+local CommandAck = require("lib.command_ack")
+local ProcessedCommands = require("lib.processed_commands")
 
 local function resolve_webhook_target(payload)
     payload = payload or {}
@@ -36,6 +43,72 @@ local function resolve_webhook_target(payload)
     return TargetContext.find_by_repo(payload.target_repo)
 end
 
+--- Route a HubCommandChannel command message (settled by CommandAck).
+local function route_command(message)
+    local event_type = message.event_type or ""
+    local allowed, skip_reason = CommandFilter.allows(event_type, message.payload)
+    if not allowed then
+        log.info(string.format("Skipping %s command: %s", event_type, tostring(skip_reason)))
+    elseif event_type == "create_agent" then
+        local payload = message.payload or {}
+        local resolved_target, target_err = resolve_webhook_target(payload)
+        if not resolved_target then
+            log.warn(string.format("Ignoring webhook create_agent without admitted target: %s", tostring(target_err)))
+            return
+        end
+        local cmd_repo = resolved_target.target_repo or resolved_target.repo
+        local issue_num = payload.issue_number
+        -- Build workspace name inline
+        local ws_name = nil
+        if cmd_repo and issue_num then
+            ws_name = cmd_repo .. "#" .. tostring(issue_num)
+        end
+        events.emit("command_message", {
+            type = "create_agent",
+            issue_or_branch = issue_num and tostring(issue_num),
+            prompt = payload.prompt or payload.context or payload.comment_body,
+            repo = cmd_repo,
+            target_id = resolved_target.target_id,
+            target_path = resolved_target.target_path,
+            target_repo = resolved_target.target_repo,
+            metadata = {
+                issue_number = issue_num,
+                invocation_url = payload.issue_url,
+                workspace = ws_name,
+                workspace_metadata = cmd_repo and { repo = cmd_repo, issue_number = issue_num } or nil,
+                target_id = resolved_target.target_id,
+                target_path = resolved_target.target_path,
+                target_repo = resolved_target.target_repo,
+            },
+        })
+    elseif event_type == "agent_cleanup" then
+        local payload = message.payload or {}
+        local resolved_target, target_err = resolve_webhook_target(payload)
+        if not resolved_target then
+            log.warn(string.format("Ignoring webhook agent_cleanup without admitted target: %s", tostring(target_err)))
+            return
+        end
+        local cmd_repo = resolved_target.target_repo or resolved_target.repo or ""
+        if payload.issue_number then
+            local ws_name = cmd_repo .. "#" .. tostring(payload.issue_number)
+            local matches = Agent.find_by_workspace(ws_name, resolved_target)
+            if #matches == 0 then
+                for _, agent in ipairs(Agent.find_by_meta("issue_number", payload.issue_number)) do
+                    if TargetContext.matches(agent, resolved_target) then
+                        matches[#matches + 1] = agent
+                    end
+                end
+            end
+            local policy = CleanupPolicy.resolve()
+            for _, agent in ipairs(matches) do
+                events.emit("command_message", CleanupPolicy.delete_command(agent, policy))
+            end
+        end
+    else
+        log.warn("Unhandled command event_type: " .. event_type)
+    end
+end
+
 -- Persistent handles across hot-reloads
 local handles = state.get("hub_commands.handles", {})
 
@@ -70,84 +143,26 @@ handles.channel = action_cable.subscribe(handles.conn, "HubCommandChannel",
         if msg_type == "signal" or msg_type == "bundle_request" then
             hub.handle_signaling_message(message)
         elseif msg_type == "message" then
-            local event_type = message.event_type or ""
-
-            if event_type == "create_agent" then
-                local payload = message.payload or {}
-                local resolved_target, target_err = resolve_webhook_target(payload)
-                if not resolved_target then
-                    log.warn(string.format("Ignoring webhook create_agent without admitted target: %s", tostring(target_err)))
-                    goto ack_message
-                end
-                local cmd_repo = resolved_target.target_repo or resolved_target.repo
-                local issue_num = payload.issue_number
-                -- Build workspace name inline
-                local ws_name = nil
-                if cmd_repo and issue_num then
-                    ws_name = cmd_repo .. "#" .. tostring(issue_num)
-                end
-                events.emit("command_message", {
-                    type = "create_agent",
-                    issue_or_branch = issue_num and tostring(issue_num),
-                    prompt = payload.prompt or payload.context or payload.comment_body,
-                    repo = cmd_repo,
-                    target_id = resolved_target.target_id,
-                    target_path = resolved_target.target_path,
-                    target_repo = resolved_target.target_repo,
-                    metadata = {
-                        issue_number = issue_num,
-                        invocation_url = payload.issue_url,
-                        workspace = ws_name,
-                        workspace_metadata = cmd_repo and { repo = cmd_repo, issue_number = issue_num } or nil,
-                        target_id = resolved_target.target_id,
-                        target_path = resolved_target.target_path,
-                        target_repo = resolved_target.target_repo,
-                    },
-                })
-            elseif event_type == "agent_cleanup" then
-                local payload = message.payload or {}
-                local resolved_target, target_err = resolve_webhook_target(payload)
-                if not resolved_target then
-                    log.warn(string.format("Ignoring webhook agent_cleanup without admitted target: %s", tostring(target_err)))
-                    goto ack_message
-                end
-                local cmd_repo = resolved_target.target_repo or resolved_target.repo or ""
-                if payload.issue_number then
-                    local ws_name = cmd_repo .. "#" .. tostring(payload.issue_number)
-                    local matches = Agent.find_by_workspace(ws_name, resolved_target)
-                    if #matches == 0 then
-                        for _, agent in ipairs(Agent.find_by_meta("issue_number", payload.issue_number)) do
-                            if TargetContext.matches(agent, resolved_target) then
-                                matches[#matches + 1] = agent
-                            end
-                        end
-                    end
-                    for _, agent in ipairs(matches) do
-                        events.emit("command_message", {
-                            type = "delete_agent",
-                            agent_id = agent.session_uuid,
-                            delete_worktree = false,
-                        })
-                    end
-                end
+            CommandStats.record_fetched()
+            if ProcessedCommands.seen(message.id) then
+                log.info(string.format("Skipping already processed command %s (sequence %s)",
+                    tostring(message.id), tostring(message.sequence)))
+                CommandAck.ack(channel_id, message)
             else
-                log.warn("Unhandled command event_type: " .. event_type)
-            end
-
-            -- Ack by sequence
-            ::ack_message::
-            if message.sequence then
-                action_cable.perform(channel_id, "ack", { sequence = message.sequence })
+                ProcessedCommands.record(message.id)
+                CommandAck.settle(channel_id, message, route_command)
             end
         end
     end
 )
 
--- Send heartbeat helper (used by timer)
+-- Send heartbeat helper (used by timer). Each heartbeat also closes a
+-- command delivery cycle and emits its counts as `poll_completed`.
 local function send_heartbeat()
     if handles.channel then
         action_cable.perform(handles.channel, "heartbeat", {})
     end
+    CommandStats.flush()
 end
 
 -- Cancel old heartbeat timer before creating a new one
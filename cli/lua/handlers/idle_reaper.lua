@@ -0,0 +1,106 @@
+-- Idle auto-close handler (hot-reloadable)
+--
+-- Closes agents that stay idle for `idle_close_secs` (config.json). Unset or
+-- 0 disables it. Pinned agents are never closed automatically; an explicit
+-- close still works for them.
+--
+-- Driven by the is_idle/pinned changes that flow through Session:update
+-- (see the idle detection in handlers/connections.lua), so nothing polls.
+-- The worktree is always kept; only the session is closed.
+
+local Agent = require("lib.agent")
+
+local M = {}
+
+-- Session UUIDs with a pending close timer, so reload can cancel them.
+local scheduled = {}
+
+local function timer_id(session_uuid)
+    return "idle_close:" .. session_uuid
+end
+
+--- Configured idle close delay in seconds, or nil when disabled.
+-- @return number|nil
+function M.close_secs()
+    local secs = tonumber((config.get("idle_close_secs")))
+    if secs and secs > 0 then
+        return secs
+    end
+    return nil
+end
+
+--- Whether a session may be closed for being idle.
+-- @param session table|nil Session instance
+-- @return boolean
+function M.reapable(session)
+    return session ~= nil
+        and session.session_type == "agent"
+        and session.status ~= "closed"
+        and session.is_idle == true
+        and session.pinned ~= true
+end
+
+--- Cancel a pending idle close.
+-- @param session_uuid string
+function M.cancel(session_uuid)
+    if scheduled[session_uuid] then
+        scheduled[session_uuid] = nil
+        timer.cancel(timer_id(session_uuid))
+    end
+end
+
+--- Start (or restart) the idle close countdown for a session.
+-- No-op when auto-close is disabled or the session is exempt.
+-- @param session table Session instance
+function M.schedule(session)
+    local secs = M.close_secs()
+    if not secs or not M.reapable(session) then
+        return
+    end
+
+    local uuid = session.session_uuid
+    scheduled[uuid] = true
+    timer.after_idle(timer_id(uuid), secs, function()
+        scheduled[uuid] = nil
+        local current = Agent.get(uuid)
+        -- Re-check: the agent may have been pinned or woken since scheduling.
+        if not M.reapable(current) then
+            return
+        end
+        log.info(string.format("Closing agent %s after %ds idle (idle_close_secs)", uuid, secs))
+        require("handlers.agents").handle_delete_session(uuid, false)
+    end)
+end
+
+hooks.on("session_updated", "idle_reaper", function(info)
+    local fields = info.fields or {}
+    if fields.is_idle == nil and fields.pinned == nil then
+        return
+    end
+
+    local session = Agent.get(info.session_uuid)
+    if M.reapable(session) then
+        M.schedule(session)
+    else
+        M.cancel(info.session_uuid)
+    end
+end)
+
+hooks.on("agent_deleted", "idle_reaper_cancel", function(session_uuid)
+    M.cancel(session_uuid)
+end)
+
+-- Pick up agents that were already idle when this module (re)loaded.
+for _, session in ipairs(Agent.list()) do
+    M.schedule(session)
+end
+
+function M._before_reload()
+    hooks.off("session_updated", "idle_reaper")
+    hooks.off("agent_deleted", "idle_reaper_cancel")
+    for session_uuid in pairs(scheduled) do
+        M.cancel(session_uuid)
+    end
+end
+
+return M
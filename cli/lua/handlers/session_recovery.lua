@@ -99,6 +99,7 @@ local function recover_session(record, socket_info, recovered, seen_keys)
         prompt            = sess.prompt,
         label             = sess.label,
         task              = sess.task,
+        pinned            = sess.pinned,
         in_worktree       = sess.in_worktree,
         handle            = handle,
         dims              = { rows = rows, cols = cols },
@@ -247,6 +247,9 @@ safe_require("handlers.connections")
 -- Must load after connections (uses broadcast_hub_event)
 safe_require("handlers.agents")
 
+-- Close agents left idle past idle_close_secs (skips pinned agents)
+safe_require("handlers.idle_reaper")
+
 -- Load ActionCable handlers (hub commands)
 -- Must load after agents (emits command_message events)
 safe_require("handlers.hub_commands")
@@ -425,6 +428,11 @@ do
     end
 end
 
+-- Load agent restore handler (snapshots agents; respawns the ones whose
+-- session process died once recovery finishes). Must load before recovery
+-- so it reads the previous run's snapshot first.
+safe_require("handlers.agent_restore")
+
 -- Load session recovery handler (reconnects to surviving session processes on Hub restart)
 safe_require("handlers.session_recovery")
 
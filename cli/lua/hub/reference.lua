@@ -21,6 +21,8 @@ M.primitives = {
             { sig = "log.warn(msg)",  desc = "Warning level" },
             { sig = "log.error(msg)", desc = "Error level" },
             { sig = "log.debug(msg)", desc = "Debug level" },
+            { sig = "log.set_level(spec)", desc = "Change the process log filter (RUST_LOG syntax) → ok, err" },
+            { sig = "log.level()",    desc = "Current log filter spec or nil" },
         },
     },
     {
@@ -96,6 +98,8 @@ M.primitives = {
             { sig = "config.server_url()", desc = "Botster server URL" },
             { sig = "config.env(key)",     desc = "Read environment variable" },
             { sig = "config.find_available_port(start, finish, excluded?)", desc = "Find first bindable localhost port in range" },
+            { sig = "config.reserve_port(start, finish, excluded?)", desc = "Bind and hold the first free localhost port in range" },
+            { sig = "config.release_port(port)", desc = "Free a port held by config.reserve_port" },
         },
     },
     {
@@ -114,11 +118,12 @@ M.primitives = {
         methods = {
             { sig = "worktree.list()",             desc = "All worktrees → array of {path, branch}" },
             { sig = "worktree.exists(branch)",     desc = "Check if worktree exists for branch" },
-            { sig = "worktree.find(branch)",       desc = "Find worktree path for branch → string or nil" },
+            { sig = "worktree.find(branch, issue?)", desc = "Find worktree path for branch (or tagged with issue) → string or nil" },
             { sig = "worktree.repo_root()",        desc = "Repo root directory" },
             { sig = "worktree.create(branch)",     desc = "Sync create worktree (blocks event loop)" },
             { sig = "worktree.create_async(opts)", desc = "Async create — fires worktree_created/worktree_create_failed events" },
-            { sig = "worktree.delete(path, branch)", desc = "Async delete worktree" },
+            { sig = "worktree.delete(path, branch, env?)", desc = "Async delete worktree, sourcing .botster_teardown with env first" },
+            { sig = "worktree.cleanup(path, branch, policy, env?)", desc = "Async delete/archive worktree (\"delete\"|\"keep\"|\"archive\"), refusing to drop unpushed work" },
         },
     },
     {
@@ -284,6 +289,8 @@ M.hooks = {
         { name = "agent_created",          data = "session info table",                desc = "Intrinsic session info emitted on create/recovery; client transports may decorate it further" },
         { name = "agent_deleted",          data = "session_uuid string",              desc = "Agent removed from registry" },
         { name = "agent_lifecycle",        data = "{session_uuid, status, ...}",      desc = "Status change during creation flow" },
+        { name = "agent_restarted",        data = "session info table",                desc = "Agent shell killed and respawned in place (same session_uuid)" },
+        { name = "agent_completed",        data = "{session_uuid, result}",            desc = "Agent wrote .botster_done; result is the parsed completion object" },
         { name = "session_updated",        data = "{session_uuid}",                   desc = "Any field changed via Session:update()" },
 
         -- Client lifecycle
@@ -306,6 +313,7 @@ M.hooks = {
 
         -- Commands
         { name = "after_hub_command",      data = "{command, client, sub_id, success, error}", desc = "After a hub command executed" },
+        { name = "poll_completed",         data = "{fetched, spawned, reused, failed}",  desc = "Server command counts for the last heartbeat cycle (skipped when quiet)" },
 
         -- Worktree / workspace
         { name = "worktree_created",       data = "{path, branch}",                  desc = "Worktree created (from hooks.notify in agents.lua)" },
@@ -321,7 +329,7 @@ M.hooks = {
 
     interceptors = {
         { name = "before_agent_create",     data = "{issue_or_branch, prompt, profile_name, ...}", returns = "modified params or nil to block" },
-        { name = "before_agent_delete",     data = "{session_uuid, delete_worktree}",              returns = "modified config or nil to block" },
+        { name = "before_agent_delete",     data = "{session_uuid, delete_worktree, cleanup_policy}", returns = "modified config or nil to block" },
         { name = "before_command",          data = "{type, args, peer_id}",                        returns = "modified command or nil to block" },
         { name = "before_hub_command",      data = "command table",                                returns = "modified or nil to block" },
         { name = "before_client_subscribe", data = "{client, sub_id, ...}",                        returns = "modified context or nil to block" },
@@ -336,8 +344,8 @@ M.hooks = {
 
 M.events = {
     { name = "shutdown",               data = "nil",                           desc = "Hub shutting down" },
-    { name = "process_exited",         data = "{session_uuid, session_name, exit_code}", desc = "PTY process exited" },
-    { name = "session_process_exited", data = "{session_uuid, exit_code}",     desc = "Session process exited (distinct from PTY)" },
+    { name = "process_exited",         data = "{session_uuid, session_name, exit_code, signal}", desc = "PTY process exited" },
+    { name = "session_process_exited", data = "{session_uuid, exit_code, signal}", desc = "Session process exited (distinct from PTY)" },
     { name = "session_reconnected",    data = "{session_uuid}",               desc = "Hub reconnected to session after reader death" },
     { name = "connection_code_ready",  data = "{url, qr_ascii}",              desc = "Pairing QR code generated" },
     { name = "connection_code_error",  data = "error string",                  desc = "Pairing code generation failed" },
@@ -383,6 +391,8 @@ M.session_info_fields = {
     { name = "label",          type = "string?",     desc = "User-assigned label" },
     { name = "task",           type = "string?",     desc = "Current task description" },
     { name = "is_idle",        type = "boolean",     desc = "True if no recent PTY output" },
+    { name = "idle_seconds",   type = "number",      desc = "Seconds since the last PTY output (at info() time)" },
+    { name = "pinned",         type = "boolean",     desc = "Exempt from idle auto-close (idle_close_secs)" },
 }
 
 -- =============================================================================
@@ -432,6 +442,8 @@ M.lua_libs = {
             { sig = "agent:update(fields)",          desc = "Update fields (label, task, status, etc.)" },
             { sig = "agent:set_meta(key, value)",    desc = "Set metadata key-value" },
             { sig = "agent:get_meta(key)",           desc = "Get metadata value" },
+            { sig = "agent:last_activity()",         desc = "Epoch seconds of the last PTY output" },
+            { sig = "agent:idle_seconds()",          desc = "Seconds since the last PTY output" },
             { sig = "agent.session_uuid",            desc = "Session UUID (field, not method)" },
         },
     },
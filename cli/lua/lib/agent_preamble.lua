@@ -0,0 +1,125 @@
+-- Preamble prepended to an agent's task prompt.
+--
+-- Agents spawned for a task (issue, mention, or an explicit prompt) get a
+-- short instruction block ahead of the task description in BOTSTER_PROMPT,
+-- telling them they run without a human at the keyboard. Sessions without a
+-- prompt are left alone.
+--
+-- A repo can replace the whole prompt with a `.botster_prompt_template` file
+-- at its root. `{task}`, `{repo}`, `{branch}` and `{issue_number}` are
+-- substituted; if the template has no `{task}`, the task is appended. The
+-- same variables are substituted in preambles.
+--
+-- Config keys (config.json):
+--   profiles.<agent_name>.agent_preamble  string  text placed before the task
+--                                                 (default DEFAULT; "" disables)
+
+local M = {}
+
+--- The preamble used when a profile doesn't set one.
+M.DEFAULT = "IMPORTANT: You are an autonomous AI agent operating without user input. "
+    .. "Your task is considered complete when you either comment on the GitHub issue "
+    .. "or open a pull request (or both)."
+
+local function profile_settings(agent_name)
+    if not agent_name or type(config) ~= "table" or type(config.get) ~= "function" then
+        return nil
+    end
+    local ok, profiles = pcall(config.get, "profiles")
+    if not ok or type(profiles) ~= "table" then
+        return nil
+    end
+    local settings = profiles[agent_name]
+    if type(settings) ~= "table" then
+        return nil
+    end
+    return settings
+end
+
+--- Resolve the preamble for an agent profile.
+-- @param agent_name string|nil Config agent name (e.g. "claude")
+-- @return string|nil Preamble text, or nil when disabled
+function M.for_profile(agent_name)
+    local settings = profile_settings(agent_name)
+    local preamble = settings and settings.agent_preamble
+    if preamble == nil then
+        return M.DEFAULT
+    end
+    if type(preamble) ~= "string" or preamble == "" then
+        return nil
+    end
+    return preamble
+end
+
+--- Template file looked up at the root of the agent's worktree.
+M.TEMPLATE_FILE = ".botster_prompt_template"
+
+--- Substitute `{name}` placeholders from `vars`; unknown names are left as-is.
+-- @param template string
+-- @param vars table Placeholder values keyed by name
+-- @return string
+function M.render(template, vars)
+    return (template:gsub("{([%w_]+)}", function(name)
+        local value = vars[name]
+        if value ~= nil then
+            return tostring(value)
+        end
+    end))
+end
+
+--- Read the repo's prompt template, if it has a non-empty one.
+-- @param worktree_path string|nil
+-- @return string|nil
+local function read_template(worktree_path)
+    if type(worktree_path) ~= "string" or worktree_path == ""
+        or type(fs) ~= "table" or type(fs.read) ~= "function" then
+        return nil
+    end
+    local path = worktree_path .. "/" .. M.TEMPLATE_FILE
+    if not fs.exists(path) then
+        return nil
+    end
+    local content, err = fs.read(path)
+    if not content then
+        log.warn(string.format("Failed to read %s: %s", path, tostring(err)))
+        return nil
+    end
+    if content:match("^%s*$") then
+        return nil
+    end
+    return content
+end
+
+--- Build the agent prompt from the repo template or the profile's preamble.
+-- @param prompt string|nil Task description
+-- @param agent_name string|nil Config agent name
+-- @param context table|nil { worktree_path, repo, branch, issue_number }
+-- @return string|nil Rendered prompt, or the prompt unchanged
+function M.apply(prompt, agent_name, context)
+    if type(prompt) ~= "string" or prompt == "" then
+        return prompt
+    end
+    context = context or {}
+    local vars = {
+        task = prompt,
+        repo = context.repo,
+        branch = context.branch,
+        issue_number = context.issue_number,
+    }
+
+    local template = read_template(context.worktree_path)
+    if template then
+        if not template:find("{task}", 1, true) then
+            template = template:gsub("%s+$", "") .. "\n\n{task}"
+        end
+        return M.render(template, vars)
+    end
+
+    local preamble = M.for_profile(agent_name)
+    if not preamble then
+        return prompt
+    end
+    return M.render(preamble, vars) .. "\n\n" .. prompt
+end
+
+return M
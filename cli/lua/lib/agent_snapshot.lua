@@ -0,0 +1,101 @@
+-- Snapshot of the hub's running agents.
+--
+-- Written to `{data_dir}/agents_snapshot.json` whenever an agent is created,
+-- closed, or changes status, so the agent list can be rebuilt after a
+-- restart that took the session processes down with it (see
+-- handlers/agent_restore.lua). Agents whose process already exited or
+-- failed are left out: a restart must not bring them back. Each entry
+-- carries what is needed to respawn the agent in its worktree; the task
+-- prompt is not kept, so a respawn never repeats work already done.
+
+local Agent = require("lib.agent")
+local Session = require("lib.session")
+
+local M = {}
+
+-- Statuses of agents that are no longer running.
+local STOPPED = { closed = true, exited = true, failed = true }
+
+--- File name under `config.data_dir()`.
+M.FILE = "agents_snapshot.json"
+
+local function file_path()
+    if type(config) ~= "table" or type(config.data_dir) ~= "function" then
+        return nil
+    end
+    local ok, dir = pcall(config.data_dir)
+    if not ok or type(dir) ~= "string" or dir == "" then
+        return nil
+    end
+    return dir .. "/" .. M.FILE
+end
+
+--- Snapshot entries for the running agents, in creation order.
+-- @return array of { session_uuid, repo, issue_number, branch_name, worktree_path,
+--   agent_name, target_id, target_path, target_repo, workspace_id,
+--   workspace_name, metadata }
+function M.entries()
+    local entries = {}
+    for _, agent in ipairs(Agent.list()) do
+        if agent.session_type == "agent" and not STOPPED[agent.status]
+            and not Session.is_system_session(agent) then
+            local metadata = agent.metadata or {}
+            entries[#entries + 1] = {
+                session_uuid = agent.session_uuid,
+                repo = agent.repo,
+                issue_number = metadata.issue_number,
+                branch_name = agent.branch_name,
+                worktree_path = agent.worktree_path,
+                agent_name = agent.agent_name,
+                target_id = agent.target_id,
+                target_path = agent.target_path,
+                target_repo = agent.target_repo,
+                workspace_id = agent._workspace_id,
+                workspace_name = agent._workspace_name,
+                metadata = metadata,
+            }
+        end
+    end
+    return entries
+end
+
+--- Write the snapshot of the running agents.
+-- Goes through a temp file and rename, so a crash mid-write leaves the
+-- previous snapshot rather than a truncated one.
+-- @return boolean true when written
+function M.write()
+    local path = file_path()
+    if not path then return false end
+    local ok, content = pcall(json.encode, M.entries())
+    if not ok then
+        log.warn(string.format("Failed to encode agent snapshot: %s", tostring(content)))
+        return false
+    end
+    local tmp = path .. ".tmp"
+    local written, err = fs.write(tmp, content)
+    if written then
+        written, err = fs.rename(tmp, path)
+    end
+    if not written then
+        log.warn(string.format("Failed to write %s: %s", path, tostring(err)))
+        pcall(fs.delete, tmp)
+        return false
+    end
+    return true
+end
+
+--- Read the last written snapshot.
+-- @return array Snapshot entries (empty when missing or unreadable)
+function M.read()
+    local path = file_path()
+    if not path or not fs.exists(path) then return {} end
+    local content = fs.read(path)
+    local ok, entries = pcall(json.decode, content or "")
+    if not ok or type(entries) ~= "table" then
+        log.warn("Ignoring unreadable " .. path)
+        return {}
+    end
+    return entries
+end
+
+return M
@@ -0,0 +1,45 @@
+-- Worktree cleanup policy for closed issues.
+--
+-- When an issue or PR closes, its agents are closed and `cleanup_policy`
+-- in config.json decides what happens to their worktrees. Used by both
+-- server paths that deliver `agent_cleanup`: hub_commands.lua and the
+-- GitHub plugin.
+--
+-- Config key (config.json):
+--   cleanup_policy  string  "keep" (default) leaves the worktree alone,
+--                           "delete" removes it and its branch, "archive"
+--                           tags the branch as archive/<branch> first.
+--                           Unknown values fall back to "keep".
+
+local M = {}
+
+M.DEFAULT = "keep"
+
+local POLICIES = { delete = true, keep = true, archive = true }
+
+--- Resolve the configured cleanup policy.
+-- @return string "delete", "keep" or "archive"
+function M.resolve()
+    if type(config) == "table" and type(config.get) == "function" then
+        local ok, value = pcall(config.get, "cleanup_policy")
+        if ok and POLICIES[value] then
+            return value
+        end
+    end
+    return M.DEFAULT
+end
+
+--- Build the delete_agent command that closes `agent` under `policy`.
+-- @param agent table Agent to close
+-- @param policy string Resolved policy (see M.resolve)
+-- @return table command_message payload
+function M.delete_command(agent, policy)
+    return {
+        type = "delete_agent",
+        agent_id = agent.session_uuid,
+        delete_worktree = policy ~= "keep",
+        cleanup_policy = policy,
+    }
+end
+
+return M
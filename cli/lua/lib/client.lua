@@ -39,6 +39,8 @@ function Client.new(peer_id, transport)
         -- the right `state.path` into each surface's render dispatcher.
         -- Unset entries default to "/".
         surface_subpaths = {},
+        -- Set once lib.event_log has replayed history to this peer.
+        event_log_replayed = false,
         -- Wire protocol: `selected_session_uuid` is GONE. Selection
         -- moved to the client (web ui-presentation-store, TUI widget_state).
         -- Trees are no longer per-client; the same ui_tree_snapshot ships
@@ -260,6 +262,7 @@ function Client:handle_subscribe(msg)
         --   3. surface_subpaths priming (so cold-load deep links land on
         --      the right sub-page on the first ui_tree_snapshot)
         --   4. ui_tree_snapshot per surface (force=true for priming)
+        --   5. hub_event replay (see lib.event_log)
         local reg_ok, reg_err = pcall(self.send_ui_route_registry, self, sub_id)
         if not reg_ok then
             log.warn(string.format(
@@ -289,6 +292,15 @@ function Client:handle_subscribe(msg)
                 "send_ui_tree_snapshots failed for %s: %s",
                 self.peer_id:sub(1, 8), tostring(err)))
         end
+
+        -- Replay recent hub events last, once the stores and trees exist
+        -- for the timeline to reference. Only the first hub subscribe.
+        local replay_ok, replay_err = pcall(require("lib.event_log").replay_to, self, sub_id)
+        if not replay_ok then
+            log.warn(string.format(
+                "event_log.replay_to failed for %s: %s",
+                self.peer_id:sub(1, 8), tostring(replay_err)))
+        end
     elseif channel == "mcp" then
         -- MCP is pull-based: the client sends tools/list when ready.
         self.subscriptions[sub_id].caller_context = params.context or {}
@@ -336,6 +348,26 @@ function Client:setup_terminal_subscription(sub_id, session_uuid, rows, cols)
         sub_id:sub(1, 16), session_uuid:sub(1, 16), cols, rows))
 end
 
+--- Rebuild the forwarders of every terminal subscription on a session.
+-- Used after the session's PTY was restarted in place: the subscriptions
+-- stay, but their forwarders streamed from the killed process.
+-- @param session_uuid string Restarted session
+-- @return number Subscriptions reattached
+function Client:reattach_terminal(session_uuid)
+    local count = 0
+    for sub_id, sub in pairs(self.subscriptions) do
+        if sub.channel == "terminal" and sub.session_uuid == session_uuid then
+            local forwarder = self.forwarders[sub_id]
+            if forwarder then
+                forwarder:stop()
+            end
+            self:setup_terminal_subscription(sub_id, session_uuid, sub.rows, sub.cols)
+            count = count + 1
+        end
+    end
+    return count
+end
+
 --- Send the current UI tree snapshots to a HubChannel subscription.
 --
 -- Wire protocol: trees are no longer per-client — selection moved to the
@@ -0,0 +1,49 @@
+-- Settles server-delivered messages with the server.
+--
+-- Every command message is answered exactly once: `ack` when routing
+-- finishes (including commands that were deliberately skipped), or
+-- `mark_failed` with the error when routing raises. Without the failure
+-- report a malformed command is never acked and the server replays it on
+-- every reconnect.
+--
+-- HubCommandChannel identifies messages by `sequence`; Github::EventsChannel
+-- by `id`. Callers pass the key their channel uses (default "sequence").
+
+local M = {}
+
+--- Ack a command message (no-op without a key).
+-- @param channel_id any ActionCable channel handle
+-- @param message table Command message
+-- @param key string|nil Field identifying the message (default "sequence")
+function M.ack(channel_id, message, key)
+    key = key or "sequence"
+    if message[key] then
+        action_cable.perform(channel_id, "ack", { [key] = message[key] })
+    end
+end
+
+--- Route a command message and settle it with the server.
+-- @param channel_id any ActionCable channel handle
+-- @param message table Command message ({ sequence, event_type, payload })
+-- @param route function(message) Routes the command; may raise
+-- @param key string|nil Field identifying the message (default "sequence")
+-- @return boolean true if routing succeeded
+function M.settle(channel_id, message, route, key)
+    key = key or "sequence"
+    local ok, err = pcall(route, message)
+    if not ok then
+        log.warn(string.format("Failed to process %s command (%s %s): %s",
+            tostring(message.event_type), key, tostring(message[key]), tostring(err)))
+    end
+    if ok then
+        M.ack(channel_id, message, key)
+    elseif message[key] then
+        action_cable.perform(channel_id, "mark_failed", {
+            [key] = message[key],
+            reason = tostring(err),
+        })
+    end
+    return ok
+end
+
+return M
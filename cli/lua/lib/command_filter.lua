@@ -0,0 +1,121 @@
+-- Server command filter for specialized hubs.
+--
+-- Lets a hub act only on some of the mentions the server sends it, e.g.
+-- only comment mentions or only issues labelled `bug`. Consulted by the
+-- GitHub plugin for `github_mention` events and by hub_commands.lua for
+-- `create_agent` commands. Skipped mentions are still acked so they are not
+-- redelivered. Cleanup events are never filtered: they only touch agents
+-- this hub already runs.
+--
+-- Config keys (config.json), both default to empty (accept everything):
+--   event_type_allowlist  string[]  event type ("github_mention",
+--                                   "create_agent") or GitHub source type
+--                                   ("issue_comment", "pr_comment", ...)
+--   label_allowlist       string[]  issue/PR labels, matched against
+--                                   `payload.labels`. Payloads without a
+--                                   `labels` field match no label.
+
+local M = {}
+
+-- Event types that spawn or notify an agent (and so can be filtered).
+local MENTION_EVENTS = { create_agent = true, github_mention = true }
+
+local function config_list(key)
+    if type(config) ~= "table" or type(config.get) ~= "function" then
+        return {}
+    end
+    local ok, value = pcall(config.get, key)
+    if not ok or type(value) ~= "table" then
+        return {}
+    end
+    return value
+end
+
+local function normalize(value)
+    if type(value) ~= "string" then
+        return nil
+    end
+    value = value:match("^%s*(.-)%s*$"):lower()
+    if value == "" then
+        return nil
+    end
+    return value
+end
+
+local function to_set(list)
+    local set, any = {}, false
+    for _, item in ipairs(list) do
+        local key = normalize(item)
+        if key then
+            set[key] = true
+            any = true
+        end
+    end
+    return any and set or nil
+end
+
+--- GitHub event that produced a command, when the server recorded it.
+-- @param payload table Command payload
+-- @return string|nil
+function M.source_type(payload)
+    local context = type(payload.structured_context) == "table" and payload.structured_context
+    local source = context and type(context.source) == "table" and context.source
+    return (source and source.type) or payload.source_type
+end
+
+--- Label names attached to a command payload.
+-- Accepts plain strings or GitHub label objects (`{ name = "bug" }`).
+-- @param payload table Command payload
+-- @return string[]|nil nil when the payload carries no `labels` field
+function M.labels(payload)
+    if type(payload.labels) ~= "table" then
+        return nil
+    end
+    local names = {}
+    for _, label in ipairs(payload.labels) do
+        local name = type(label) == "table" and label.name or label
+        if type(name) == "string" then
+            names[#names + 1] = name
+        end
+    end
+    return names
+end
+
+--- Decide whether this hub should act on a server command.
+-- @param event_type string Event type (e.g. "github_mention", "create_agent")
+-- @param payload table|nil Command payload
+-- @return boolean allowed
+-- @return string|nil reason when skipped
+function M.allows(event_type, payload)
+    payload = payload or {}
+    if not MENTION_EVENTS[event_type] then
+        return true
+    end
+
+    local event_types = to_set(config_list("event_type_allowlist"))
+    if event_types then
+        local source = M.source_type(payload)
+        if not event_types[normalize(event_type)] and not event_types[normalize(source)] then
+            return false, string.format("event type %s not in event_type_allowlist",
+                tostring(source or event_type))
+        end
+    end
+
+    local labels = to_set(config_list("label_allowlist"))
+    if labels then
+        local matched = false
+        for _, name in ipairs(M.labels(payload) or {}) do
+            if labels[normalize(name)] then
+                matched = true
+                break
+            end
+        end
+        if not matched then
+            return false, "no label in label_allowlist"
+        end
+    end
+
+    return true
+end
+
+return M
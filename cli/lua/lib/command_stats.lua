@@ -0,0 +1,75 @@
+-- Per-cycle counters for server command delivery.
+--
+-- The hub receives webhook commands over HubCommandChannel rather than by
+-- polling, so a "poll cycle" here is one heartbeat interval. Counters are
+-- accumulated while commands are routed and flushed on each heartbeat as a
+-- `poll_completed` hook, and recorded in the hub event log (lib/event_log.lua)
+-- so the TUI and browsers get a live view of throughput without scraping
+-- logs.
+--
+-- Counts:
+--   fetched  command messages and GitHub plugin events received from the
+--            server
+--   spawned  create_agent commands that started a new agent
+--   reused   create_agent commands delivered to an existing agent
+--   failed   create_agent commands that could not be handled
+
+local state = require("hub.state")
+local hooks = require("hub.hooks")
+local EventLog = require("lib.event_log")
+
+local M = {}
+
+local counts = state.get("command_stats.counts", {
+    fetched = 0,
+    spawned = 0,
+    reused = 0,
+    failed = 0,
+})
+
+--- Count one command message received from the server.
+function M.record_fetched()
+    counts.fetched = (counts.fetched or 0) + 1
+end
+
+--- Count the outcome of a routed create_agent command.
+-- @param outcome string "spawned" | "reused" | "failed"
+function M.record(outcome)
+    if counts[outcome] == nil or outcome == "fetched" then
+        log.warn("command_stats: unknown outcome " .. tostring(outcome))
+        return
+    end
+    counts[outcome] = counts[outcome] + 1
+end
+
+--- Snapshot of the current cycle's counters.
+-- @return table { fetched, spawned, reused, failed }
+function M.snapshot()
+    return {
+        fetched = counts.fetched or 0,
+        spawned = counts.spawned or 0,
+        reused = counts.reused or 0,
+        failed = counts.failed or 0,
+    }
+end
+
+--- Close the current cycle: emit `poll_completed`, record it in the event
+-- log, and reset the counters.
+-- Quiet cycles (nothing fetched or routed) emit nothing.
+-- @return table|nil The emitted counts, or nil for a quiet cycle
+function M.flush()
+    local snapshot = M.snapshot()
+    counts.fetched, counts.spawned, counts.reused, counts.failed = 0, 0, 0, 0
+
+    if snapshot.fetched + snapshot.spawned + snapshot.reused + snapshot.failed == 0 then
+        return nil
+    end
+
+    log.info(string.format("Poll completed: fetched=%d spawned=%d reused=%d failed=%d",
+        snapshot.fetched, snapshot.spawned, snapshot.reused, snapshot.failed))
+    hooks.notify("poll_completed", snapshot)
+    EventLog.record("poll_completed", snapshot)
+    return snapshot
+end
+
+return M
@@ -0,0 +1,210 @@
+-- Completion sentinel protocol for agents.
+--
+-- "Agent exited" and output heuristics are unreliable signals that an agent
+-- finished its task. Instead, the agent (or its init script) writes a
+-- `.botster_done` file at the root of its worktree when it is done. The hub
+-- watches for that file, marks the session complete, and emits a structured
+-- notification.
+--
+-- File format (JSON object):
+--
+--   { "action": "pr_opened", "number": 123, "url": "https://github.com/o/r/pull/123" }
+--   { "action": "commented", "url": "https://github.com/o/r/issues/7#issuecomment-1" }
+--
+-- Optional fields: "summary" (string) is shown in the notification body.
+--
+-- On a valid file the session gets `completion = <parsed result>`, an
+-- `agent_lifecycle` "completed" event, a `completion` PTY-style notification
+-- (toast + web push), and an `agent_completed` hook carrying
+-- { session_uuid, result } for plugins such as completion webhooks.
+--
+-- The file is then renamed to `.botster_done.consumed`, so a worktree that
+-- is reused or restored for a new session does not complete it straight
+-- away. A sentinel last modified before the session was created is ignored
+-- for the same reason. Both names are listed in the repo's `info/exclude`
+-- when the hub creates a worktree, so `git add -A` does not commit them.
+
+local state = require("hub.state")
+local hooks = require("hub.hooks")
+
+local M = {}
+
+M.FILENAME = ".botster_done"
+M.CONSUMED_FILENAME = ".botster_done.consumed"
+
+--- Required fields for each supported action.
+M.ACTIONS = {
+    pr_opened = { "number", "url" },
+    commented = { "url" },
+}
+
+-- Active watch IDs keyed by session_uuid (persistent across reloads).
+local watch_ids = state.get("completion_sentinel.watch_ids", {})
+
+--- Path of the sentinel file for a session.
+-- @param session table Session with worktree_path
+-- @return string|nil
+function M.path_for(session)
+    local root = session and session.worktree_path
+    if type(root) ~= "string" or root == "" then return nil end
+    return root .. "/" .. M.FILENAME
+end
+
+--- Parse and validate sentinel file contents.
+-- @param content string Raw file contents
+-- @return table|nil Parsed result
+-- @return string|nil Error message
+function M.parse(content)
+    if type(content) ~= "string" or not content:match("%S") then
+        return nil, "empty sentinel file"
+    end
+    local ok, result = pcall(json.decode, content)
+    if not ok or type(result) ~= "table" then
+        return nil, "sentinel file is not a JSON object"
+    end
+    local required = M.ACTIONS[result.action]
+    if not required then
+        return nil, string.format("unknown action %q", tostring(result.action))
+    end
+    for _, field in ipairs(required) do
+        if result[field] == nil then
+            return nil, string.format("action %q requires %q", result.action, field)
+        end
+    end
+    if result.action == "pr_opened" and not tonumber(result.number) then
+        return nil, "pr_opened number must be numeric"
+    end
+    return result, nil
+end
+
+local function notification_body(result)
+    if type(result.summary) == "string" and result.summary ~= "" then
+        return result.summary
+    end
+    if result.action == "pr_opened" then
+        return string.format("Opened PR #%s", tostring(result.number))
+    end
+    return "Posted a comment"
+end
+
+--- Mark a session complete with a parsed sentinel result.
+-- Idempotent: a session that already has a completion is left unchanged.
+-- @param session table Session instance
+-- @param result table Parsed sentinel result
+-- @return boolean true when the session transitioned to completed
+function M.complete(session, result)
+    if session.completion then return false end
+
+    log.info(string.format("Session %s completed via %s: %s %s",
+        session.session_uuid, M.FILENAME, result.action, tostring(result.url)))
+
+    session:update({ completion = result })
+    hooks.notify("agent_lifecycle", {
+        agent_id = session.session_uuid,
+        status = "completed",
+        completion = result,
+    })
+    hooks.notify("_pty_notification_raw", {
+        session_uuid = session.session_uuid,
+        session_name = session.session_name,
+        type = "completion",
+        title = "Agent finished",
+        body = notification_body(result),
+        url = result.url,
+    })
+    hooks.notify("agent_completed", {
+        session_uuid = session.session_uuid,
+        result = result,
+    })
+    return true
+end
+
+--- Whether the sentinel at `path` predates `session`, i.e. was left behind
+-- by an earlier agent in the same worktree.
+local function is_stale(session, path)
+    local created_at = tonumber(session.created_at)
+    local stat = fs.stat(path)
+    local modified = stat and tonumber(stat.modified)
+    return created_at ~= nil and modified ~= nil and modified < created_at
+end
+
+--- Move a handled sentinel aside so it cannot complete a later session.
+local function consume(session, path)
+    local consumed = session.worktree_path .. "/" .. M.CONSUMED_FILENAME
+    local ok, err = fs.rename(path, consumed)
+    if not ok then
+        log.warn(string.format("Session %s: cannot rename %s: %s",
+            session.session_uuid, M.FILENAME, tostring(err)))
+        fs.delete(path)
+    end
+end
+
+--- Read the sentinel file for a session and complete it if valid.
+-- A sentinel older than the session is ignored; a valid one is consumed.
+-- @param session table Session instance
+-- @return boolean true when the session transitioned to completed
+function M.check(session)
+    if not session or session.completion then return false end
+    local path = M.path_for(session)
+    if not path or not fs.exists(path) then return false end
+    if is_stale(session, path) then
+        log.info(string.format("Session %s: ignoring %s left from an earlier session",
+            session.session_uuid, M.FILENAME))
+        return false
+    end
+
+    local content, read_err = fs.read(path)
+    if not content then
+        log.warn(string.format("Session %s: cannot read %s: %s",
+            session.session_uuid, path, tostring(read_err)))
+        return false
+    end
+    local result, parse_err = M.parse(content)
+    if not result then
+        log.warn(string.format("Session %s: ignoring invalid %s: %s",
+            session.session_uuid, M.FILENAME, parse_err))
+        return false
+    end
+    local completed = M.complete(session, result)
+    if completed then consume(session, path) end
+    return completed
+end
+
+--- Start watching a session's worktree for the sentinel file.
+-- Also checks once immediately so a file written before the watch (or
+-- during a hub restart) is picked up.
+-- @param session table Session instance
+function M.watch(session)
+    if not session or watch_ids[session.session_uuid] then return end
+    local root = session.worktree_path
+    if type(root) ~= "string" or root == "" or not fs.exists(root) then return end
+
+    local session_uuid = session.session_uuid
+    local ok, wid = pcall(watch.directory, root, { recursive = false, pattern = M.FILENAME }, function(event)
+        if event.kind == "delete" then return end
+        local current = require("lib.session").get(session_uuid)
+        if current then
+            M.check(current)
+        end
+    end)
+    if ok and wid then
+        watch_ids[session_uuid] = wid
+    elseif not ok then
+        log.warn(string.format("Session %s: cannot watch for %s: %s",
+            session_uuid, M.FILENAME, tostring(wid)))
+    end
+
+    M.check(session)
+end
+
+--- Stop watching a session's worktree.
+-- @param session_uuid string
+function M.unwatch(session_uuid)
+    local wid = watch_ids[session_uuid]
+    if wid then
+        watch_ids[session_uuid] = nil
+        pcall(watch.unwatch, wid)
+    end
+end
+
+return M
@@ -0,0 +1,126 @@
+-- Replayable hub event log.
+--
+-- Keeps the most recent hub events (agent created/deleted, lifecycle
+-- transitions, workspace closes) in a bounded ring buffer so a browser that
+-- subscribes mid-session can render a timeline immediately instead of
+-- starting from a blank slate. Entity state still arrives via
+-- `entity_snapshot`; this log only carries what *happened*.
+--
+-- Wire envelope (carries `v = 2` like the entity frames):
+--   { type = "hub_event", event, data, seq, at, historical }
+--
+-- `seq` is monotonic per hub process; `at` is a unix timestamp (seconds).
+-- Live events go to every hub-channel subscriber with `historical = false`.
+-- On a client's first hub subscribe, `replay_to` ships the buffered events
+-- oldest-first with `historical = true`. Every subscriber gets the same
+-- history; there is no per-client filtering.
+--
+-- Hot-reload contract: the buffer and seq counter live in `hub.state`, so
+-- reloading this module keeps the history. The broadcaster is transient —
+-- `connections.lua` re-installs it on load, as it does for entity_broadcast.
+
+local state = require("hub.state")
+
+local M = {}
+
+--- Maximum number of events kept for replay.
+M.MAX_EVENTS = 100
+
+-- { entries = { frame, ... } (oldest first), seq = integer }
+local log_state = state.get("event_log", { entries = {}, seq = 0 })
+
+local broadcaster = function(_frame) end
+
+--- Install the per-frame transport hook. Passing nil restores the no-op.
+function M.set_broadcaster(fn)
+    if fn == nil then
+        broadcaster = function(_frame) end
+        return
+    end
+    assert(type(fn) == "function", "event_log.set_broadcaster requires a function")
+    broadcaster = fn
+end
+
+local function copy(frame, historical)
+    local out = {}
+    for k, v in pairs(frame) do out[k] = v end
+    out.historical = historical
+    return out
+end
+
+--- Record an event and broadcast it to current hub subscribers.
+-- @param event string Event name (e.g. "agent_created")
+-- @param data table|nil Event payload (plain data, json-encodable)
+-- @return table The stored frame
+function M.record(event, data)
+    log_state.seq = log_state.seq + 1
+    local frame = {
+        v = 2,
+        type = "hub_event",
+        event = event,
+        data = data or {},
+        seq = log_state.seq,
+        at = os.time(),
+    }
+
+    local entries = log_state.entries
+    entries[#entries + 1] = frame
+    while #entries > M.MAX_EVENTS do
+        table.remove(entries, 1)
+    end
+
+    local ok, err = pcall(broadcaster, copy(frame, false))
+    if not ok then
+        log.warn(string.format("event_log: broadcaster threw on %s: %s",
+            tostring(event), tostring(err)))
+    end
+    return frame
+end
+
+--- Replay the buffered events to a newly subscribed client.
+-- Only the client's first hub subscription gets the replay; later ones
+-- (duplicate tabs on the same peer, resubscribes) would see duplicates.
+-- @param client table Client instance (must support :send)
+-- @param sub_id string|nil Subscription ID to route the frames to
+-- @return number Events sent
+function M.replay_to(client, sub_id)
+    assert(client and type(client.send) == "function",
+        "event_log.replay_to: client must support :send(msg)")
+    if client.event_log_replayed then
+        return 0
+    end
+    client.event_log_replayed = true
+
+    for _, frame in ipairs(log_state.entries) do
+        local message = copy(frame, true)
+        if sub_id ~= nil then message.subscriptionId = sub_id end
+        client:send(message)
+    end
+    if #log_state.entries > 0 then
+        log.info(string.format("event_log: replayed %d event(s) to sub=%s",
+            #log_state.entries, tostring(sub_id or "nil")))
+    end
+    return #log_state.entries
+end
+
+--- Buffered events, oldest first (tests + diagnostics).
+function M.entries()
+    return log_state.entries
+end
+
+function M._before_reload()
+    log.info("event_log.lua reloading")
+end
+
+function M._after_reload()
+    log.info("event_log.lua reloaded")
+end
+
+--- Clear the buffer, seq counter, and broadcaster. Test-only.
+function M._reset_for_tests()
+    log_state.entries = {}
+    log_state.seq = 0
+    broadcaster = function(_frame) end
+end
+
+return M
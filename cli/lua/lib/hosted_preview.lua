@@ -13,9 +13,13 @@ local state = require("hub.state")
 local Accessory = require("lib.accessory")
 local Session = require("lib.session")
 local TargetContext = require("lib.target_context")
+local PreviewPortState = require("lib.preview_port_state")
 
 local M = {}
 
+--- Re-probes of an unreachable tunnel before the preview is marked failed.
+M.MAX_PROBE_RETRIES = 3
+
 local connector_output_buffers = state.get("hosted_preview.connector_output_buffers", {})
 local CLOUDFLARED_INSTALL_URL =
     "https://developers.cloudflare.com/cloudflare-one/connections/connect-networks/downloads/"
@@ -204,6 +208,8 @@ function M.enable(parent)
     end
 
     connector_output_buffers[connector.session_uuid] = ""
+    -- A new tunnel gets a fresh retry budget.
+    PreviewPortState.clear(parent._port)
     parent:update({
         hosted_preview = preview_state_for(parent, {
             status = "starting",
@@ -289,6 +295,7 @@ function M.handle_dns_ready(data)
     end
 
     if data.ready then
+        PreviewPortState.record_success(parent._port)
         parent:update({
             hosted_preview = preview_state_for(parent, {
                 status = "running",
@@ -299,6 +306,17 @@ function M.handle_dns_ready(data)
             }),
         })
     else
+        local entry = PreviewPortState.record_failure(parent._port)
+        local failures = entry and entry.retries or 0
+        local _, hostname = trycloudflare_url_from_text(url)
+        if entry and hostname and failures <= M.MAX_PROBE_RETRIES then
+            log.info(string.format("Hosted preview for %s not reachable yet (port %s), re-probing (%d/%d)",
+                parent.session_uuid, tostring(parent._port), failures, M.MAX_PROBE_RETRIES))
+            hub.probe_preview_dns(connector.session_uuid, parent.session_uuid, url, hostname, 15.0)
+            return true
+        end
+        log.info(string.format("Hosted preview for %s not reachable after %d probe(s) (port %s)",
+            parent.session_uuid, failures, tostring(parent._port)))
         parent:update({
             hosted_preview = preview_state_for(parent, {
                 status = "error",
@@ -641,8 +641,10 @@ function Hub:delete_agent(agent_id, delete_worktree)
         end
 
         local agents_handler = require("handlers.agents")
-        local deleted = agents_handler.handle_delete_agent(resolved_id, delete_worktree or false)
-        if deleted then
+        local deleted, kept_err = agents_handler.handle_delete_agent(resolved_id, delete_worktree or false)
+        if deleted and kept_err then
+            return string.format("Agent deleted: %s (%s)", resolved_id, kept_err)
+        elseif deleted then
             return "Agent deleted: " .. resolved_id
         else
             return "Agent not found: " .. resolved_id
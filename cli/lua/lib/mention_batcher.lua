@@ -0,0 +1,143 @@
+-- Per-agent coalescing of mention notifications.
+--
+-- Each mention for an existing agent is delivered as PTY input (text plus
+-- submit). A burst of mentions would otherwise queue several submits back to
+-- back, which can interleave in the agent's input box and submit partial
+-- messages. Mentions arriving within a short window are collected here and
+-- delivered as a single block once the window passes without a new mention.
+-- A steady stream that never goes quiet is still delivered once the first
+-- queued mention has waited MAX_WAIT_FACTOR windows.
+--
+-- Both mention paths go through `notify`: create_agent commands for an
+-- existing workspace (handlers/agents.lua) and the GitHub plugin.
+--
+-- Config (config.json):
+--   mention_debounce_secs  number  quiet window before delivery (default 1.5;
+--                                  0 delivers every mention immediately)
+
+local state = require("hub.state")
+local Agent = require("lib.agent")
+
+local M = {}
+
+M.DEFAULT_WINDOW_SECS = 1.5
+
+--- Longest a queued mention waits, in windows, before it is delivered.
+M.MAX_WAIT_FACTOR = 5
+
+-- Pending mention bodies keyed by session_uuid (persistent across reloads).
+local pending = state.get("mention_batcher.pending", {})
+
+-- Max-wait timer ids keyed by session_uuid (persistent across reloads).
+local deadlines = state.get("mention_batcher.deadlines", {})
+
+local function timer_id(session_uuid)
+    return "mention_debounce:" .. session_uuid
+end
+
+--- Resolve the debounce window from config.json.
+-- @return number seconds (0 disables coalescing)
+function M.window_secs()
+    if type(config) == "table" and type(config.get) == "function" then
+        local ok, value = pcall(config.get, "mention_debounce_secs")
+        if ok and type(value) == "number" and value >= 0 then
+            return value
+        end
+    end
+    return M.DEFAULT_WINDOW_SECS
+end
+
+--- Queue a mention body for an agent.
+-- Bodies are delivered in arrival order; an exact repeat of a body already
+-- waiting in the window is dropped.
+-- @param session_uuid string
+-- @param body string
+-- @param deliver function(session_uuid, bodies) called once per window
+function M.enqueue(session_uuid, body, deliver)
+    local window = M.window_secs()
+    if window <= 0 then
+        deliver(session_uuid, { body })
+        return
+    end
+
+    local queue = pending[session_uuid]
+    if not queue then
+        queue = {}
+        pending[session_uuid] = queue
+        deadlines[session_uuid] = timer.after(window * M.MAX_WAIT_FACTOR, function()
+            deadlines[session_uuid] = nil
+            M.flush(session_uuid, deliver)
+        end)
+    end
+    for _, queued in ipairs(queue) do
+        if queued == body then
+            log.info(string.format("Dropping duplicate mention for %s (same text already pending)",
+                session_uuid))
+            return
+        end
+    end
+    queue[#queue + 1] = body
+
+    timer.after_idle(timer_id(session_uuid), window, function()
+        M.flush(session_uuid, deliver)
+    end)
+end
+
+--- Deliver any pending mentions for an agent now.
+-- @param session_uuid string
+-- @param deliver function(session_uuid, bodies)
+function M.flush(session_uuid, deliver)
+    local queue = pending[session_uuid]
+    pending[session_uuid] = nil
+    timer.cancel(timer_id(session_uuid))
+    if deadlines[session_uuid] then
+        timer.cancel(deadlines[session_uuid])
+        deadlines[session_uuid] = nil
+    end
+    if not queue or #queue == 0 then return end
+    if #queue > 1 then
+        log.info(string.format("Coalesced %d mentions for %s", #queue, session_uuid))
+    end
+    deliver(session_uuid, queue)
+end
+
+--- Format the notification for coalesced mentions.
+-- @param bodies string[] Mention bodies in arrival order
+-- @return string
+function M.format(bodies)
+    return string.format(
+        "=== NEW MENTION (automated notification) ===\n\n%s\n\n==================",
+        table.concat(bodies, "\n\n---\n\n")
+    )
+end
+
+--- Deliver coalesced mentions to an agent via PTY input.
+local function deliver(session_uuid, bodies)
+    local agent = Agent.get(session_uuid)
+    if not agent then
+        log.debug("Dropping notification for closed agent: " .. session_uuid)
+        return
+    end
+    if agent.session then
+        agent.session:send_message(M.format(bodies))
+        log.info(string.format("Sent notification to existing agent: %s (%d mention(s))",
+            agent.session_uuid, #bodies))
+    else
+        log.warn("Cannot notify agent (no session): " .. agent.session_uuid)
+    end
+end
+
+--- Notify an existing agent of a mention, coalescing within the window.
+-- @param agent table Agent to notify
+-- @param body string Mention text
+function M.notify(agent, body)
+    M.enqueue(agent.session_uuid, body, deliver)
+end
+
+--- Number of mentions waiting for an agent.
+function M.pending_count(session_uuid)
+    local queue = pending[session_uuid]
+    return queue and #queue or 0
+end
+
+return M
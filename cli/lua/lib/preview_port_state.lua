@@ -0,0 +1,105 @@
+-- Per-port preview reconnection state.
+--
+-- Tracks how many times in a row each forwarded port's hosted preview has
+-- failed its reachability probe; lib/hosted_preview.lua re-probes until the
+-- count passes MAX_PROBE_RETRIES. Entries live in a bounded LRU (persisted via hub.state) so a
+-- long-running hub with heavy agent churn cannot accumulate dead state.
+--
+-- Session owns the port lifecycle: it clears an entry when a port is released
+-- on close and again when a port is reserved, so a reused port always starts
+-- fresh instead of inheriting another agent's retry history.
+
+local state = require("hub.state")
+
+local M = {}
+
+--- Maximum number of ports tracked at once; least recently used is evicted.
+M.CAPACITY = 64
+
+local lru = state.get("preview_port_state", {
+    entries = {},
+    order = {},
+})
+
+local function key_for(port)
+    local n = tonumber(port)
+    if not n then return nil end
+    return tostring(math.floor(n))
+end
+
+local function remove_from_order(key)
+    for i = #lru.order, 1, -1 do
+        if lru.order[i] == key then
+            table.remove(lru.order, i)
+            return
+        end
+    end
+end
+
+local function mark_used(key)
+    remove_from_order(key)
+    lru.order[#lru.order + 1] = key
+    while #lru.order > M.CAPACITY do
+        local evicted = table.remove(lru.order, 1)
+        lru.entries[evicted] = nil
+    end
+end
+
+--- Get (creating if needed) the state entry for a port.
+-- @param port number
+-- @return table|nil { retries, updated_at }
+function M.touch(port)
+    local key = key_for(port)
+    if not key then return nil end
+    local entry = lru.entries[key]
+    if not entry then
+        entry = { retries = 0, updated_at = os.time() }
+        lru.entries[key] = entry
+    end
+    mark_used(key)
+    return entry
+end
+
+--- Read the state entry for a port without creating or reordering it.
+-- @param port number
+-- @return table|nil
+function M.get(port)
+    local key = key_for(port)
+    return key and lru.entries[key] or nil
+end
+
+--- Record a reachable preview and reset the retry counter.
+-- @param port number
+function M.record_success(port)
+    local entry = M.touch(port)
+    if not entry then return nil end
+    entry.retries = 0
+    entry.updated_at = os.time()
+    return entry
+end
+
+--- Record a failed preview probe, bumping the retry counter.
+-- @param port number
+function M.record_failure(port)
+    local entry = M.touch(port)
+    if not entry then return nil end
+    entry.retries = entry.retries + 1
+    entry.updated_at = os.time()
+    return entry
+end
+
+--- Drop all state for a port (port freed or reassigned).
+-- @param port number
+function M.clear(port)
+    local key = key_for(port)
+    if not key then return end
+    lru.entries[key] = nil
+    remove_from_order(key)
+end
+
+--- Number of ports currently tracked.
+function M.count()
+    return #lru.order
+end
+
+return M
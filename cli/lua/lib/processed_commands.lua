@@ -0,0 +1,110 @@
+-- Recently processed server commands.
+--
+-- The server replays every command that isn't settled when the hub
+-- subscribes, so a hub that restarts between routing a command and the ack
+-- reaching the server would act on it twice (a second agent for the same
+-- issue, a repeated cleanup). Command ids are recorded here before routing
+-- and checked on arrival; a repeat is acked again but not routed.
+--
+-- Used for HubCommandChannel commands and for Github::EventsChannel
+-- messages (the GitHub plugin). Their ids come from different tables, so the
+-- plugin passes a scope ("github") that keeps the two apart.
+--
+-- Ids live in a bounded FIFO persisted to `{data_dir}/processed_commands.json`
+-- so they survive restarts. Without a data dir the set is kept in memory.
+
+local state = require("hub.state")
+
+local M = {}
+
+--- Maximum number of ids remembered; the oldest is dropped first.
+M.CAPACITY = 256
+
+--- File name under `config.data_dir()`.
+M.FILE = "processed_commands.json"
+
+local cache = state.get("processed_commands", { loaded = false, ids = {}, set = {} })
+
+local function file_path()
+    if type(config) ~= "table" or type(config.data_dir) ~= "function" then
+        return nil
+    end
+    local ok, dir = pcall(config.data_dir)
+    if not ok or type(dir) ~= "string" or dir == "" then
+        return nil
+    end
+    return dir .. "/" .. M.FILE
+end
+
+local function key_for(id, scope)
+    if id == nil then return nil end
+    if scope then
+        return scope .. ":" .. tostring(id)
+    end
+    return tostring(id)
+end
+
+local function ensure_loaded()
+    if cache.loaded then return end
+    cache.loaded = true
+    local path = file_path()
+    if not path or not fs.exists(path) then return end
+    local content = fs.read(path)
+    local ok, ids = pcall(json.decode, content or "")
+    if not ok or type(ids) ~= "table" then
+        log.warn("Ignoring unreadable " .. path)
+        return
+    end
+    for _, id in ipairs(ids) do
+        local key = key_for(id)
+        if key and not cache.set[key] then
+            cache.ids[#cache.ids + 1] = key
+            cache.set[key] = true
+        end
+    end
+end
+
+--- Write the id list via a temp file and rename, so a crash mid-write
+-- leaves the previous list rather than a truncated one.
+local function persist()
+    local path = file_path()
+    if not path then return end
+    local tmp = path .. ".tmp"
+    local ok, err = fs.write(tmp, json.encode(cache.ids))
+    if ok then
+        ok, err = fs.rename(tmp, path)
+    end
+    if not ok then
+        log.warn(string.format("Failed to write %s: %s", path, tostring(err)))
+        pcall(fs.delete, tmp)
+    end
+end
+
+--- Whether a command id has already been processed.
+-- @param id number|string|nil Command id (nil is never seen)
+-- @param scope string|nil Id namespace (nil for HubCommandChannel)
+-- @return boolean
+function M.seen(id, scope)
+    local key = key_for(id, scope)
+    if not key then return false end
+    ensure_loaded()
+    return cache.set[key] == true
+end
+
+--- Remember a command id as processed.
+-- @param id number|string|nil Command id (nil is ignored)
+-- @param scope string|nil Id namespace (nil for HubCommandChannel)
+function M.record(id, scope)
+    local key = key_for(id, scope)
+    if not key then return end
+    ensure_loaded()
+    if cache.set[key] then return end
+    cache.ids[#cache.ids + 1] = key
+    cache.set[key] = true
+    while #cache.ids > M.CAPACITY do
+        cache.set[table.remove(cache.ids, 1)] = nil
+    end
+    persist()
+end
+
+return M
@@ -0,0 +1,119 @@
+-- Prompt length limit.
+--
+-- A huge GitHub comment or pasted log can overwhelm an agent or blow its
+-- context window. Task prompts and mention notifications pass through
+-- `apply`, which cuts over-long text down to `max_prompt_chars` characters
+-- and says so in the text itself.
+--
+-- Config keys (config.json):
+--   max_prompt_chars   integer  characters kept from the original text
+--                               (unset or 0: no limit)
+--   prompt_truncation  string   "head" keeps the beginning, "tail" the end,
+--                               "head_tail" (default) both ends around an
+--                               elided middle
+
+local M = {}
+
+M.DEFAULT_STRATEGY = "head_tail"
+
+local STRATEGIES = { head = true, tail = true, head_tail = true }
+
+-- One UTF-8 encoded character (lead byte plus continuation bytes).
+local CHAR_PATTERN = "[%z\1-\127\194-\244][\128-\191]*"
+
+local function config_value(key)
+    if type(config) ~= "table" or type(config.get) ~= "function" then
+        return nil
+    end
+    local ok, value = pcall(config.get, key)
+    if not ok then
+        return nil
+    end
+    return value
+end
+
+local function char_count(text)
+    local _, count = text:gsub(CHAR_PATTERN, "")
+    return count
+end
+
+--- Byte index just past the first `n` characters of `text`.
+local function byte_end(text, n)
+    if n <= 0 then
+        return 0
+    end
+    local seen = 0
+    for pos in text:gmatch("()" .. CHAR_PATTERN) do
+        if seen == n then
+            return pos - 1
+        end
+        seen = seen + 1
+    end
+    return #text
+end
+
+local function head(text, n)
+    return text:sub(1, byte_end(text, n))
+end
+
+local function tail(text, n, total)
+    return text:sub(byte_end(text, total - n) + 1)
+end
+
+--- Resolve the configured limit and strategy.
+-- @return number|nil limit (nil when unlimited)
+-- @return string strategy
+function M.settings()
+    local limit = tonumber(config_value("max_prompt_chars"))
+    if not limit or limit <= 0 then
+        limit = nil
+    else
+        limit = math.floor(limit)
+    end
+
+    local strategy = config_value("prompt_truncation")
+    if not STRATEGIES[strategy] then
+        strategy = M.DEFAULT_STRATEGY
+    end
+    return limit, strategy
+end
+
+--- Truncate text that exceeds the limit.
+-- @param text string|nil Prompt or mention body
+-- @param limit number|nil Characters to keep (defaults to config)
+-- @param strategy string|nil "head", "tail" or "head_tail" (defaults to config)
+-- @return string|nil Text, truncated with a note when over the limit
+-- @return boolean Whether it was truncated
+function M.apply(text, limit, strategy)
+    if type(text) ~= "string" then
+        return text, false
+    end
+    if limit == nil then
+        local configured_limit, configured_strategy = M.settings()
+        limit = configured_limit
+        strategy = strategy or configured_strategy
+    end
+    strategy = STRATEGIES[strategy] and strategy or M.DEFAULT_STRATEGY
+    if not limit or #text <= limit then
+        return text, false
+    end
+
+    local total = char_count(text)
+    if total <= limit then
+        return text, false
+    end
+
+    local dropped = total - limit
+    local note = string.format("[... truncated %d of %d characters ...]", dropped, total)
+    if strategy == "head" then
+        return head(text, limit) .. "\n\n" .. note, true
+    elseif strategy == "tail" then
+        return note .. "\n\n" .. tail(text, limit, total), true
+    end
+
+    local head_chars = math.ceil(limit / 2)
+    return head(text, head_chars) .. "\n\n" .. note .. "\n\n"
+        .. tail(text, limit - head_chars, total), true
+end
+
+return M
@@ -22,29 +22,53 @@
 local state = require("hub.state")
 local hooks = require("hub.hooks")
 local TargetContext = require("lib.target_context")
+local PreviewPortState = require("lib.preview_port_state")
 
 local Session = state.class("Session")
 
 -- Session registry keyed by session_uuid (persistent across reloads)
 local sessions = state.get("agent_registry", {})
 
--- Reserve forwarded ports from a high, non-common range and probe localhost
--- availability before assigning them to accessories.
+-- Reserve forwarded ports from a high, non-common range (or
+-- `tunnel_port_range` in config.json) and hold them bound until the session
+-- spawns, so two quick spawns can't race for the same port.
 local FORWARD_PORT_MIN = 46000
 local FORWARD_PORT_MAX = 61999
+-- Restart reuses the session UUID, so the replacement is only spawned once
+-- the old session process has exited and cleaned up its socket and PID
+-- file. Poll for that on a timer rather than blocking the hub.
+local RESTART_POLL_SECS = 0.05
+local RESTART_EXIT_TIMEOUT_SECS = 3
+
 local port_state = state.get("agent_port_state", {
     next_port = FORWARD_PORT_MIN,
     reserved = {},
 })
 
-local function normalize_port_state()
+--- Inclusive forwarded-port range: `tunnel_port_range` if valid, else the default.
+local function forward_port_range()
+    local ok, range = pcall(config.get, "tunnel_port_range")
+    if ok and type(range) == "table" then
+        local min, max = tonumber(range[1]), tonumber(range[2])
+        if min and max and min >= 1 and min <= max and max <= 65535 then
+            return min, max
+        end
+        log.warn("Ignoring invalid tunnel_port_range; expected [first, last]")
+    end
+    return FORWARD_PORT_MIN, FORWARD_PORT_MAX
+end
+
+local function normalize_port_state(min, max)
+    if not min then
+        min, max = forward_port_range()
+    end
     if type(port_state.reserved) ~= "table" then
         port_state.reserved = {}
     end
     if type(port_state.next_port) ~= "number"
-        or port_state.next_port < FORWARD_PORT_MIN
-        or port_state.next_port > FORWARD_PORT_MAX then
-        port_state.next_port = FORWARD_PORT_MIN
+        or port_state.next_port < min
+        or port_state.next_port > max then
+        port_state.next_port = min
     end
 end
 
@@ -72,27 +96,31 @@ local function collect_reserved_ports()
 end
 
 local function reserve_forward_port()
-    normalize_port_state()
+    local min, max = forward_port_range()
+    normalize_port_state(min, max)
     local start = port_state.next_port
     local excluded = collect_reserved_ports()
-    local port, err = config.find_available_port(start, FORWARD_PORT_MAX, excluded)
-    if not port and start > FORWARD_PORT_MIN then
-        port, err = config.find_available_port(FORWARD_PORT_MIN, start - 1, excluded)
+    local port = config.reserve_port(start, max, excluded)
+    if not port and start > min then
+        port = config.reserve_port(min, start - 1, excluded)
     end
     if not port then
-        return nil, err or string.format(
-            "No available localhost port in preferred range %d-%d",
-            FORWARD_PORT_MIN, FORWARD_PORT_MAX)
+        return nil, string.format(
+            "No available localhost port in range %d-%d", min, max)
     end
-    port_state.next_port = (port < FORWARD_PORT_MAX) and (port + 1) or FORWARD_PORT_MIN
+    port_state.next_port = (port < max) and (port + 1) or min
     port_state.reserved[tostring(port)] = true
+    -- A reused port must not inherit a previous agent's preview retries.
+    PreviewPortState.clear(port)
     return port
 end
 
 local function release_forward_port(port)
     normalize_port_state()
     if type(port) == "number" then
+        config.release_port(port)
         port_state.reserved[tostring(port)] = nil
+        PreviewPortState.clear(port)
     end
 end
 
@@ -137,6 +165,13 @@ local function generate_session_uuid()
         math.random(0, 0xFFFFFFFF))
 end
 
+--- Quote a string as a single POSIX shell word.
+-- @param value string
+-- @return string
+local function shell_quote(value)
+    return "'" .. tostring(value):gsub("'", "'\\''") .. "'"
+end
+
 --- Default display name for anonymous orchestration workspaces.
 -- Keeps workspace identity stable via workspace_id while making UI labels readable.
 -- @param branch_name string|nil
@@ -168,6 +203,7 @@ end
 --                              { name, command, init_script, definition_dir, notifications, forward_port }
 --   prompt          string   (optional)  task description
 --   metadata        table    (optional)  plugin key-value store (e.g., issue_number, invocation_url)
+--   pinned          boolean  (optional)  exempt from idle auto-close (also read from metadata.pinned)
 --   workspace       string   (optional)  workspace name (e.g. "owner/repo#42")
 --   workspace_id    string   (optional)  pre-resolved workspace ID
 --   workspace_expect_new boolean (optional) reject reusing an active workspace with same name
@@ -175,6 +211,7 @@ end
 --   env             table    (optional)  base environment variables
 --   dims            table    (optional)  { rows = 24, cols = 80 }
 --   agent_name      string   (optional)  config agent name (e.g., "claude")
+--   worktree_reused boolean  (optional)  true if spawned into an existing worktree
 --   profile_name    string   (optional)  DEPRECATED alias for agent_name
 --
 -- @param self The instance (metatable already set by subclass)
@@ -203,6 +240,9 @@ function Session._init(self, config)
     if config.invocation_url and not metadata.invocation_url then
         metadata.invocation_url = config.invocation_url
     end
+    -- Pinning may arrive via creation metadata; it is a session field, not plugin data.
+    local pinned = config.pinned == true or metadata.pinned == true
+    metadata.pinned = nil
 
     local target = TargetContext.resolve({
         explicit = {
@@ -241,13 +281,16 @@ function Session._init(self, config)
     self.agent_name = config.agent_name or config.profile_name
     self.profile_name = config.agent_name or config.profile_name  -- backward compat alias
     self.created_at = os.time()
+    self.worktree_reused = config.worktree_reused  -- nil when no worktree applies
     self.status = "running"
     self.title = nil          -- window title from OSC 0/2 (set by pty_title_changed hook)
     self.cwd = nil            -- current working directory from OSC 7 (set by pty_cwd_changed hook)
     self.notification = false -- true when OSC notification fired, cleared by client
     self.is_idle = true       -- idle until first PTY output (managed by pty_output hook)
+    self.pinned = pinned      -- exempt from idle auto-close (handlers/idle_reaper.lua)
     self.session = nil        -- single PtySessionHandle
     self._session_config = session_config  -- original session config from creation
+    self._base_env = config.env               -- base env, rebuilt on restart
     self.session_dir = session_config.definition_dir
 
     local key = self.session_uuid
@@ -370,7 +413,35 @@ function Session._init(self, config)
         cols = config.dims.cols or 80
     end
 
-    -- Spawn the single PTY session
+    self:_spawn_process(env, rows, cols)
+
+    self:_sync_session_manifest()
+    log.info(string.format("Session %s: registered (uuid=%s)", key, session_uuid))
+
+    -- Register in session registry (keyed by session_uuid)
+    sessions[session_uuid] = self
+    sync_manifest_workspaces()
+
+    -- Notify observers
+    hooks.notify("after_agent_create", self)
+
+    log.info(string.format("Session created: %s (uuid=%s, type=%s)", key, session_uuid, session_type))
+end
+
+--- Spawn the session's PTY process and register it with HandleCache.
+-- Shared by creation and restart. The session must already have its
+-- identity, worktree, and `_session_config` set. A forwarded port already
+-- held by the session is reused; otherwise one is reserved.
+-- @param env table Environment from build_env()
+-- @param rows number Terminal rows
+-- @param cols number Terminal cols
+function Session:_spawn_process(env, rows, cols)
+    local key = self.session_uuid
+    local session_uuid = self.session_uuid
+    local session_type = self.session_type
+    local session_name = self.session_name
+    local session_config = self._session_config
+
     -- Shallow-copy env for session-specific overrides
     local session_env = {}
     for k, v in pairs(env) do
@@ -378,8 +449,8 @@ function Session._init(self, config)
     end
 
     local spawn_config = {
-        worktree_path = config.worktree_path,
-        cwd = config.worktree_path,
+        worktree_path = self.worktree_path,
+        cwd = self.worktree_path,
         command = session_config.command or "bash",
         args = type(session_config.args) == "table" and session_config.args or {},
         env = session_env,
@@ -396,7 +467,7 @@ function Session._init(self, config)
     if session_config.init_script then
         if fs.exists(session_config.init_script) then
             spawn_config.init_commands[#spawn_config.init_commands + 1] =
-                "source " .. session_config.init_script
+                "source " .. shell_quote(session_config.init_script)
         else
             log.debug(string.format("Init script not found: %s", session_config.init_script))
         end
@@ -410,44 +481,52 @@ function Session._init(self, config)
     end
 
     -- Allocate a high, currently bindable port for forwarded sessions.
-    local port = nil
-    if session_config.forward_port then
+    local port = self._port
+    local reserved_here = false
+    if session_config.forward_port and not port then
         local port_err
         port, port_err = reserve_forward_port()
         if not port then
             error(string.format("Failed to allocate forwarded port: %s", tostring(port_err)))
         end
+        reserved_here = true
+    end
+    if port then
         spawn_config.port = port
         session_env.PORT = tostring(port)
     end
 
     -- Interceptor: plugins can inspect context or block spawn (return nil)
     local spawn_ctx = {
-        worktree_path = config.worktree_path,
-        branch = config.branch_name,
-        session_uuid = key,
+        worktree_path = self.worktree_path,
+        branch = self.branch_name,
         session_uuid = session_uuid,
         session_type = session_type,
         session_name = session_name,
-        repo = config.repo,
+        repo = self.repo,
         target_id = self.target_id,
         target_path = self.target_path,
         target_repo = self.target_repo,
         workspace_id = self._workspace_id,
         workspace_name = self._workspace_name,
-        metadata = metadata,
+        metadata = self.metadata,
     }
     local spawn_result = hooks.call("before_pty_spawn", spawn_ctx)
     if spawn_result == nil then
-        if port then
+        if reserved_here then
             release_forward_port(port)
         end
         error(string.format("PTY spawn blocked by interceptor for %s", key))
     end
 
+    -- Hand the held port over: the agent binds it once it starts.
+    if port then
+        config.release_port(port)
+    end
+
     local ok, handle = pcall(hub.spawn_session, spawn_config, session_uuid)
     if not ok or not handle then
-        if port then
+        if reserved_here then
             release_forward_port(port)
         end
         error(string.format(
@@ -474,18 +553,6 @@ function Session._init(self, config)
     else
         log.error(string.format("Session %s: failed to register: %s", key, tostring(reg_index)))
     end
-
-    self:_sync_session_manifest()
-    log.info(string.format("Session %s: registered (uuid=%s)", key, session_uuid))
-
-    -- Register in session registry (keyed by session_uuid)
-    sessions[session_uuid] = self
-    sync_manifest_workspaces()
-
-    -- Notify observers
-    hooks.notify("after_agent_create", self)
-
-    log.info(string.format("Session created: %s (uuid=%s, type=%s)", key, session_uuid, session_type))
 end
 
 --- Initialize a session from a persisted manifest during session recovery.
@@ -529,8 +596,10 @@ function Session._init_recovered(self, config)
     self.cwd             = config.cwd
     self.label           = config.label
     self.task            = config.task
+    self.completion      = config.completion
     self.notification    = false
     self.is_idle         = true
+    self.pinned          = config.pinned == true
     self.session         = config.handle
     self._session_config = nil
     self._port           = nil
@@ -713,6 +782,7 @@ function Session:_sync_session_manifest()
     manifest.port           = nil
     manifest.notification   = nil
     manifest.is_idle        = nil
+    manifest.idle_seconds   = nil
 
     log.info(string.format("Session %s: writing manifest to %s/workspaces/%s/sessions/%s/manifest.json",
         self.session_uuid, tostring(self._data_dir), tostring(self._workspace_id), self.session_uuid))
@@ -888,9 +958,120 @@ function Session:move_to_workspace(opts)
     }, nil
 end
 
+--- Re-run the latest initialization script by restarting the session.
+-- Used after the agent's `initialization` file changes so existing sessions
+-- pick up new env/MCP setup without being recreated. Typing `source ...`
+-- into the PTY would reach whatever runs in the foreground, which for an
+-- agent session is the agent CLI (an idle one would take it as a chat
+-- message), so the session is restarted instead: the fresh shell sources
+-- the script before starting the agent. The worktree and any conversation
+-- state are left untouched; see `Session:restart`.
+-- @param session_config table Freshly resolved session config
+-- @param opts table|nil { force = boolean } force=true skips the busy guard
+-- @param on_done function|nil Passed to `Session:restart`
+-- @return boolean ok Whether the restart was started
+-- @return string|nil error message
+function Session:reinit(session_config, opts, on_done)
+    opts = opts or {}
+
+    local init_script = session_config and session_config.init_script
+    if not init_script or not fs.exists(init_script) then
+        return false, string.format("init script not found: %s", tostring(init_script))
+    end
+    if not self.is_idle and not opts.force then
+        return false, "session is busy; retry when idle or pass force"
+    end
+
+    log.info(string.format("Session %s: restarting to re-run init script %s",
+        self.session_uuid, init_script))
+    return self:restart(session_config, on_done)
+end
+
+--- Kill the session's process and spawn a fresh one in place.
+-- For recovering a wedged agent without losing its task: the session UUID,
+-- creation time, worktree, and prompt are kept, so the agent keeps its list
+-- position and can resume from the conversation state in its worktree. The
+-- init script is sourced again in the new shell.
+--
+-- The old process is killed right away; the respawn waits (on a timer) for
+-- it to exit, so the outcome is reported through `on_done`. A failed
+-- respawn leaves the session with status "failed".
+-- @param session_config table|nil Freshly resolved session config; defaults
+--   to the one the session was created with
+-- @param on_done function|nil Called as on_done(ok, err) once the new
+--   process is running or the restart has failed
+-- @return boolean ok Whether the restart was started
+-- @return string|nil error message
+function Session:restart(session_config, on_done)
+    session_config = session_config or self._session_config
+    if not session_config then
+        return false, "no session config to restart with"
+    end
+    on_done = on_done or function() end
+    local key = self.session_uuid
+
+    local rows, cols = 24, 80
+    if self.session then
+        local ok_dims, r, c = pcall(function() return self.session:dimensions() end)
+        if ok_dims and r and c then
+            rows, cols = r, c
+        end
+
+        pcall(hub.unregister_session, key)
+        local ok, err = pcall(function() self.session:kill() end)
+        if not ok then
+            log.warn(string.format("Session %s: error killing PTY for restart: %s", key, tostring(err)))
+        end
+        self.session = nil
+    end
+
+    self._session_config = session_config
+
+    local function fail(err)
+        log.error(string.format("Session %s: restart failed: %s", key, tostring(err)))
+        self:update({ status = "failed" })
+        on_done(false, tostring(err))
+    end
+
+    local polls_left = math.ceil(RESTART_EXIT_TIMEOUT_SECS / RESTART_POLL_SECS)
+    local function respawn()
+        if self.status == "closed" then
+            on_done(false, "session closed during restart")
+            return
+        end
+        if hub.session_socket_exists(key) then
+            if polls_left <= 0 then
+                return fail("previous session process is still running")
+            end
+            polls_left = polls_left - 1
+            timer.after(RESTART_POLL_SECS, respawn)
+            return
+        end
+
+        local ok, err = pcall(self._spawn_process, self, self:build_env(self._base_env), rows, cols)
+        if not ok then
+            return fail(err)
+        end
+
+        self:update({ status = "running", is_idle = true })
+        self:_sync_session_manifest()
+        if self._data_dir and self._workspace_id then
+            require("lib.workspace_store").append_event(self._data_dir, self._workspace_id, key, "restarted")
+        end
+
+        log.info(string.format("Session %s: restarted in %s", key, tostring(self.worktree_path)))
+        on_done(true, nil)
+    end
+
+    respawn()
+    return true, nil
+end
+
 --- Close the session and clean up resources.
 -- @param delete_worktree boolean Whether to queue worktree deletion
-function Session:close(delete_worktree)
+-- @param cleanup_policy string|nil Remove the worktree via worktree.cleanup
+--   with this policy ("delete" or "archive") instead of worktree.delete
+function Session:close(delete_worktree, cleanup_policy)
     local key = self.session_uuid
 
     pcall(function()
@@ -946,9 +1127,30 @@ function Session:close(delete_worktree)
         end
     end
 
-    -- Queue worktree deletion if requested
+    -- Never remove a worktree another live session is still running in
+    if delete_worktree then
+        local others = require("lib.session_close_policy").other_active_sessions(self, Session.list())
+        if #others > 0 then
+            log.warn(string.format("Session %s: keeping worktree %s, %d other session(s) still use it",
+                key, tostring(self.worktree_path), #others))
+            delete_worktree = false
+        end
+    end
+
+    -- Queue worktree deletion if requested. The repo's teardown script runs
+    -- with the same env the session was spawned with.
     if delete_worktree then
-        local ok3, err3 = pcall(worktree.delete, self.worktree_path, self.branch_name)
+        local env_ok, env = pcall(self.build_env, self, self._base_env)
+        if not env_ok then
+            log.warn(string.format("Session %s: no teardown env: %s", key, tostring(env)))
+            env = nil
+        end
+        local ok3, err3
+        if cleanup_policy then
+            ok3, err3 = pcall(worktree.cleanup, self.worktree_path, self.branch_name, cleanup_policy, env)
+        else
+            ok3, err3 = pcall(worktree.delete, self.worktree_path, self.branch_name, env)
+        end
         if not ok3 then
             log.warn(string.format("Session %s: failed to delete worktree: %s",
                 key, tostring(err3)))
@@ -1041,7 +1243,16 @@ function Session:build_env(base_env)
         end
     end
     if self.prompt and self.prompt ~= "" then
-        env.BOTSTER_PROMPT = self.prompt
+        if self.session_type == "agent" then
+            env.BOTSTER_PROMPT = require("lib.agent_preamble").apply(self.prompt, self.agent_name, {
+                worktree_path = self.worktree_path,
+                repo = self.repo,
+                branch = self.branch_name,
+                issue_number = self.metadata and self.metadata.issue_number,
+            })
+        else
+            env.BOTSTER_PROMPT = self.prompt
+        end
     end
     -- Fire filter hook for customization
     env = hooks.call("filter_agent_env", env, self) or env
@@ -1059,6 +1270,26 @@ function Session:_resolve_target_name()
     return nil
 end
 
+--- Epoch seconds of the session's last PTY output.
+-- Falls back to the creation time before the first output.
+-- @return number
+function Session:last_activity()
+    local handle = self.session
+    if handle then
+        local ok, ms = pcall(function() return handle:last_output_at() end)
+        if ok and type(ms) == "number" and ms > 0 then
+            return math.floor(ms / 1000)
+        end
+    end
+    return self.created_at or os.time()
+end
+
+--- Seconds since the session last produced output.
+-- @return number
+function Session:idle_seconds()
+    return math.max(0, os.time() - self:last_activity())
+end
+
 --- Get session metadata for clients.
 -- Returns a serializable table of session info.
 -- @return table Session info
@@ -1099,7 +1330,9 @@ function Session:info()
         worktree_path = self.worktree_path,
         session_dir = self.session_dir,
         in_worktree = self._is_worktree or false,
+        worktree_reused = self.worktree_reused,
         status = self.status,
+        completion = self.completion,
         notification = self.notification or false,
         port = port,
         hosted_preview = self.hosted_preview,
@@ -1108,6 +1341,8 @@ function Session:info()
         label = self.label,
         task = self.task,
         is_idle = self.is_idle or false,
+        idle_seconds = self:idle_seconds(),
+        pinned = self.pinned or false,
     }
 end
 
@@ -168,7 +168,8 @@ function M.find_by_repo(repo)
         if admitted.enabled ~= false then
             local inspection = inspect_target(admitted.path)
             local repo_name = inspection and normalize_repo(inspection.repo_name) or nil
-            if repo_name == normalized_repo then
+            -- GitHub repo names are case-insensitive (Owner/Repo == owner/repo).
+            if repo_name and repo_name:lower() == normalized_repo:lower() then
                 matches[#matches + 1] = {
                     target_id = admitted.id,
                     target_path = admitted.path,
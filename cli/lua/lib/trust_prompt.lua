@@ -0,0 +1,123 @@
+-- Trust prompt detection for freshly spawned agents.
+--
+-- Agent CLIs sometimes open with a one-time "do you trust this folder?"
+-- prompt. Nothing in the session list shows it, so the agent silently stalls
+-- right after spawn. This module scans a session's early output for that
+-- prompt and either answers it (config `auto_trust = true`) or badges the
+-- session as needing attention.
+--
+-- Config keys (config.json):
+--   auto_trust              boolean  answer the prompt automatically (default false)
+--   trust_prompt_patterns   string[] case-insensitive substrings to match
+--                                    (default DEFAULT_PATTERNS)
+--   trust_prompt_response   string   bytes written when auto-trusting (default "\r",
+--                                    which accepts the highlighted "Yes" option)
+
+local M = {}
+
+M.DEFAULT_PATTERNS = {
+    "do you trust the files in this folder",
+    "do you trust the contents of this directory",
+    "do you trust this folder",
+}
+
+--- Only output within this many seconds of spawn is scanned.
+M.WINDOW_SECS = 60
+
+--- Bytes of trailing output kept per session so prompts split across chunks still match.
+local TAIL_BYTES = 512
+
+local function config_value(key)
+    if type(config) ~= "table" or type(config.get) ~= "function" then
+        return nil
+    end
+    local ok, value = pcall(config.get, key)
+    if ok then return value end
+    return nil
+end
+
+--- Resolve the effective policy from config.json, filling defaults.
+-- @return table { auto_trust, patterns, response }
+function M.policy()
+    local patterns = config_value("trust_prompt_patterns")
+    if type(patterns) ~= "table" or #patterns == 0 then
+        patterns = M.DEFAULT_PATTERNS
+    end
+    local response = config_value("trust_prompt_response")
+    if type(response) ~= "string" or response == "" then
+        response = "\r"
+    end
+    return {
+        auto_trust = config_value("auto_trust") == true,
+        patterns = patterns,
+        response = response,
+    }
+end
+
+--- Strip escape sequences and collapse whitespace so TUI-drawn prompts match.
+local function normalize(text)
+    text = text:gsub("\27%[[%d;?]*[%a@]", " ")
+    text = text:gsub("\27%][^\7\27]*\7", " ")
+    text = text:gsub("\27.", " ")
+    text = text:gsub("%s+", " ")
+    return text:lower()
+end
+
+--- Scan a chunk of PTY output for a trust prompt and act on it.
+-- Runs at most once per session and only within WINDOW_SECS of spawn.
+-- @param session table    Session (needs session_uuid, created_at, session, update)
+-- @param data string      Raw PTY output chunk
+-- @param policy table|nil Override for M.policy() (tests)
+-- @return string|nil "auto_trusted" or "needs_attention" when the prompt was handled
+function M.scan(session, data, policy)
+    if type(session) ~= "table" or type(data) ~= "string" or session._trust_prompt_handled then
+        return nil
+    end
+    if session.created_at and os.time() - session.created_at > M.WINDOW_SECS then
+        return nil
+    end
+
+    local tail = (session._trust_prompt_tail or "") .. data
+    if #tail > TAIL_BYTES then
+        tail = tail:sub(-TAIL_BYTES)
+    end
+    session._trust_prompt_tail = tail
+
+    policy = policy or M.policy()
+    local haystack = normalize(tail)
+    local matched
+    for _, pattern in ipairs(policy.patterns) do
+        if type(pattern) == "string" and haystack:find(pattern:lower(), 1, true) then
+            matched = pattern
+            break
+        end
+    end
+    if not matched then
+        return nil
+    end
+
+    session._trust_prompt_handled = true
+    session._trust_prompt_tail = nil
+
+    if policy.auto_trust and session.session then
+        local ok, err = pcall(function() session.session:write(policy.response) end)
+        if ok then
+            log.info(string.format("Session %s: trust prompt detected (%q), auto-trusted per config",
+                session.session_uuid, matched))
+            return "auto_trusted"
+        end
+        log.warn(string.format("Session %s: failed to answer trust prompt: %s",
+            session.session_uuid, tostring(err)))
+    end
+
+    log.info(string.format("Session %s: trust prompt detected (%q), waiting for user",
+        session.session_uuid, matched))
+    if type(session.update) == "function" then
+        session:update({ notification = true })
+    else
+        session.notification = true
+    end
+    return "needs_attention"
+end
+
+return M
@@ -254,6 +254,23 @@ function M.on_action(action, context)
         return { set_mode_ops("close_agent_confirm") }
       end
       return { set_mode_ops(base_mode(context)) }
+    elseif selected == "restart_agent" then
+      if context.selected_agent then
+        return { set_mode_ops("restart_agent_confirm") }
+      end
+      return { set_mode_ops(base_mode(context)) }
+    elseif selected == "toggle_pin" then
+      local agent = agent_by_id(context.selected_agent)
+      if agent then
+        return {
+          { op = "send_msg", data = {
+            subscriptionId = "tui_hub",
+            data = { type = "update_session", agent_id = context.selected_agent, pinned = not agent.pinned },
+          }},
+          set_mode_ops(base_mode(context)),
+        }
+      end
+      return { set_mode_ops(base_mode(context)) }
     elseif selected == "show_connection_code" then
       return {
         set_mode_ops("connection_code"),
@@ -707,6 +724,20 @@ function M.on_action(action, context)
     return { set_mode_ops(base_mode(context)) }
   end
 
+  -- === Confirm restart agent ===
+  if action == "confirm_restart" and _tui_state.mode == "restart_agent_confirm" then
+    if context.selected_agent then
+      return {
+        { op = "send_msg", data = {
+          subscriptionId = "tui_hub",
+          data = { type = "restart_agent", agent_id = context.selected_agent },
+        }},
+        set_mode_ops(base_mode(context)),
+      }
+    end
+    return { set_mode_ops(base_mode(context)) }
+  end
+
   -- === Connection code actions ===
   if action == "regenerate_connection_code" then
     _tui_state.pending_fields.connection_code = nil
@@ -290,9 +290,9 @@ function M.on_hub_event(event_type, event_data, context)
     if not agent_id or not status then return nil end
 
     -- Update creation progress display
-    if status == "creating_worktree" then
+    if status == "creating_worktree" or status == "reusing_worktree" then
       _tui_state.pending_fields.creating_agent_id = agent_id
-      _tui_state.pending_fields.creating_agent_stage = "creating_worktree"
+      _tui_state.pending_fields.creating_agent_stage = status
     elseif status == "spawning_ptys" then
       _tui_state.pending_fields.creating_agent_id = agent_id
       _tui_state.pending_fields.creating_agent_stage = "spawning_agent"
@@ -112,6 +112,14 @@ M.close_agent_confirm = {
   ["d"]      = "confirm_close_delete",
 }
 
+M.restart_agent_confirm = {
+  ["escape"] = "close_modal",
+  ["n"]      = "close_modal",
+  ["q"]      = "close_modal",
+  ["y"]      = "confirm_restart",
+  ["enter"]  = "confirm_restart",
+}
+
 M.connection_code = {
   ["escape"] = "close_modal",
   ["q"]      = "close_modal",
@@ -189,7 +197,7 @@ function M.handle_key(key, mode, context)
     return nil
   end
 
-  if mode == "close_agent_confirm" or mode == "connection_code" or mode == "error" or mode == "restarting" then
+  if mode == "close_agent_confirm" or mode == "restart_agent_confirm" or mode == "connection_code" or mode == "error" or mode == "restarting" then
     return nil
   end
 
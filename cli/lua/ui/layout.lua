@@ -57,6 +57,7 @@ local WORKSPACE_STATUS_ICON = {
   running           = { text = "●", style = { fg = "green" } },
   failed            = { text = "●", style = { fg = "red" } },
   creating_worktree = { text = "○", style = { fg = "yellow" } },
+  reusing_worktree  = { text = "○", style = { fg = "yellow" } },
   spawning_ptys     = { text = "○", style = { fg = "yellow" } },
   stopping          = { text = "○", style = { fg = "yellow" } },
   removing_worktree = { text = "○", style = { fg = "yellow" } },
@@ -132,6 +133,11 @@ local function session_activity_icon(agent)
     spans[#spans + 1] = { text = "✺ ", style = { fg = "green" } }
   end
 
+  -- Pinned agents are exempt from idle auto-close; mark them so it's visible.
+  if agent.pinned then
+    spans[#spans + 1] = { text = "⚲ ", style = { fg = "cyan" } }
+  end
+
   if #spans == 0 then return nil end
   return spans
 end
@@ -160,6 +166,7 @@ local function build_list_items()
       -- In-progress agent creation indicator
       local stages = {
         creating_worktree = "Creating worktree...",
+        reusing_worktree  = "Reusing existing worktree...",
         copying_config    = "Copying config...",
         spawning_agent    = "Starting agent...",
         spawning          = "Starting agent...",
@@ -245,6 +252,7 @@ local function build_agent_items(state)
   if creating then
     local stages = {
       creating_worktree = "Creating worktree...",
+      reusing_worktree = "Reusing existing worktree...",
       copying_config = "Copying config...",
       spawning_agent = "Starting agent...",
       spawning = "Starting agent...",
@@ -300,6 +308,10 @@ local function build_menu_items(state)
   if sa then
     table.insert(items, { text = "── Agent ──", header = true })
     table.insert(items, { text = "Close Agent", action = "close_agent" })
+    table.insert(items, { text = "Restart Agent", action = "restart_agent" })
+    if sa.session_type ~= "accessory" then
+      table.insert(items, { text = sa.pinned and "Unpin Agent" or "Pin Agent", action = "toggle_pin" })
+    end
   end
 
   -- Hub section (always shown)
@@ -724,6 +736,23 @@ function render_overlay(state)
         props = { lines = lines },
       },
     }
+  elseif _tui_state.mode == "restart_agent_confirm" then
+    return {
+      type = "centered", width = 50, height = 20,
+      child = {
+        type = "paragraph",
+        block = { title = " Confirm Restart ", borders = "all" },
+        props = { lines = {
+          "Restart selected agent?",
+          "",
+          "Kills its shell and starts a fresh one in the",
+          "same worktree. The prompt and worktree are kept.",
+          "",
+          "Y - Restart agent",
+          "N/Esc - Cancel",
+        } },
+      },
+    }
   elseif _tui_state.mode == "connection_code" then
     -- Responsive sizing: fit QR code + header/footer/border, min 60x70%
     local need_w = (state.qr_width or 75) + 4
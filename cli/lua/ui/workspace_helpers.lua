@@ -10,13 +10,14 @@ M.WORKSPACE_STATUS_PRIORITY = {
 }
 
 local function classify_agent_status(status)
-  if status == "active" or status == "running" or status == "spawning_ptys" or status == "creating_worktree" then
+  if status == "active" or status == "running" or status == "spawning_ptys" or status == "creating_worktree"
+      or status == "reusing_worktree" then
     return "active"
   end
   if status == "orphaned" then
     return "orphaned"
   end
-  if status == "closed" or status == "deleted" or status == "exited" then
+  if status == "closed" or status == "deleted" or status == "exited" or status == "failed" then
     return "closed"
   end
   if status == "suspended" then
@@ -14,6 +14,10 @@
 //! (permission prompt, multi-choice, etc.) and delivery is retried on
 //! the next output event.
 //!
+//! The message is pasted and Enter is only sent once the app has redrawn
+//! in response to the paste (or a short timeout passes), so the
+//! submit can't race ahead of the app ingesting the text.
+//!
 //! # Human Activity Detection
 //!
 //! Delivery is deferred when a human is actively typing (determined by
@@ -57,6 +61,12 @@ const PASTE_END: &[u8] = b"\x1b[201~";
 /// Maximum time to wait for probe echo before retrying.
 const PROBE_TIMEOUT: Duration = Duration::from_millis(200);
 
+/// Longest wait for the app to react to a paste before submitting anyway.
+const PASTE_ECHO_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Output quiet period that marks the app as done redrawing after a paste.
+const PASTE_SETTLE: Duration = Duration::from_millis(50);
+
 /// Minimum interval since last human input before attempting delivery.
 const HUMAN_ACTIVITY_COOLDOWN: Duration = Duration::from_secs(2);
 
@@ -152,6 +162,45 @@ fn contains_probe_echo(data: &[u8]) -> bool {
     data.windows(PROBE.len()).any(|w| w == PROBE)
 }
 
+/// Outcome of waiting for the app to react to a paste.
+#[derive(Debug, PartialEq, Eq)]
+enum PasteEcho {
+    /// Output arrived and then went quiet.
+    Echoed,
+    /// Nothing arrived within [`PASTE_ECHO_TIMEOUT`].
+    TimedOut,
+    /// The PTY broadcast channel closed.
+    Closed,
+}
+
+/// Wait for PTY output following a paste, then for it to settle.
+///
+/// Returns as soon as output has been quiet for [`PASTE_SETTLE`] after the
+/// first event, and never waits longer than `timeout` in total.
+async fn wait_for_paste_echo(
+    rx: &mut broadcast::Receiver<PtyEvent>,
+    timeout: Duration,
+) -> PasteEcho {
+    let deadline = tokio::time::Instant::now() + timeout;
+    let mut echoed = false;
+    loop {
+        let wait_until = if echoed {
+            (tokio::time::Instant::now() + PASTE_SETTLE).min(deadline)
+        } else {
+            deadline
+        };
+        match tokio::time::timeout_at(wait_until, rx.recv()).await {
+            Ok(Ok(PtyEvent::Output(_))) | Ok(Err(broadcast::error::RecvError::Lagged(_))) => {
+                echoed = true;
+            }
+            Ok(Ok(_)) => {}
+            Ok(Err(broadcast::error::RecvError::Closed)) => return PasteEcho::Closed,
+            Err(_elapsed) if echoed => return PasteEcho::Echoed,
+            Err(_elapsed) => return PasteEcho::TimedOut,
+        }
+    }
+}
+
 /// Spawn the delivery task for a PTY session.
 ///
 /// The task runs until the PTY broadcast channel closes (process exit).
@@ -317,9 +366,9 @@ async fn attempt_delivery(
                 // Three-phase delivery with kitty-aware key encodings:
                 //   1. Erase probe (backspace × 2)
                 //   2. Paste message (bracketed paste)
-                //   3. Submit (Enter)
-                // Each phase is a separate write with a small delay so the
-                // app processes each action before receiving the next.
+                //   3. Submit (Enter) once the app has redrawn the paste
+                // Each phase is a separate write so the app processes each
+                // action before receiving the next.
                 let kitty = kitty_enabled.load(Ordering::Relaxed);
                 let bs: &[u8] = if kitty {
                     BACKSPACE_KITTY
@@ -338,6 +387,9 @@ async fn attempt_delivery(
                 }
 
                 // Phase 2: Deliver message as bracketed paste.
+                // Drop output from the probe and backspaces first so only
+                // the app's reaction to the paste counts as its echo.
+                while let Ok(_) | Err(broadcast::error::TryRecvError::Lagged(_)) = rx.try_recv() {}
                 let mut paste = Vec::new();
                 paste.extend_from_slice(PASTE_START);
                 paste.extend_from_slice(msg.as_bytes());
@@ -345,7 +397,13 @@ async fn attempt_delivery(
                 if !write_to_pty(shared_state, &paste) {
                     return DeliveryResult::PtyUnavailable;
                 }
-                tokio::time::sleep(Duration::from_millis(200)).await;
+                match wait_for_paste_echo(&mut rx, PASTE_ECHO_TIMEOUT).await {
+                    PasteEcho::Echoed => {}
+                    PasteEcho::TimedOut => {
+                        log::debug!("[MessageDelivery] No paste echo, submitting anyway");
+                    }
+                    PasteEcho::Closed => return DeliveryResult::ChannelClosed,
+                }
 
                 // Phase 3: Submit with Enter.
                 // Always use legacy \r — even with kitty DISAMBIGUATE_ESCAPE_CODES,
@@ -445,6 +503,104 @@ mod tests {
         assert!(!is_human_active(&ts));
     }
 
+    /// Fake PTY writer: records every write and plays the app's echoes back
+    /// on the broadcast channel — the probe immediately, a paste after
+    /// `paste_echo_delay`.
+    struct EchoingWriter {
+        events: broadcast::Sender<PtyEvent>,
+        log: Arc<Mutex<Vec<&'static str>>>,
+        paste_echo_delay: Duration,
+    }
+
+    impl std::io::Write for EchoingWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            let label = if buf == PROBE {
+                let _ = self.events.send(PtyEvent::Output(PROBE.to_vec()));
+                "probe"
+            } else if buf.starts_with(PASTE_START) {
+                let events = self.events.clone();
+                let log = Arc::clone(&self.log);
+                let delay = self.paste_echo_delay;
+                std::thread::spawn(move || {
+                    std::thread::sleep(delay);
+                    log.lock().unwrap().push("paste echo");
+                    let _ = events.send(PtyEvent::Output(b"[Pasted text]".to_vec()));
+                });
+                "paste"
+            } else if buf == ENTER {
+                "enter"
+            } else {
+                "other"
+            };
+            self.log.lock().unwrap().push(label);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_submit_waits_for_paste_echo() {
+        let (event_tx, _keepalive) = broadcast::channel(64);
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let shared_state = Arc::new(Mutex::new(SharedPtyState {
+            master_pty: None,
+            writer: Some(Box::new(EchoingWriter {
+                events: event_tx.clone(),
+                log: Arc::clone(&log),
+                // Longer than the fixed sleep this replaced.
+                paste_echo_delay: Duration::from_millis(400),
+            })),
+            dimensions: (24, 80),
+            last_human_input_ms: Arc::new(std::sync::atomic::AtomicI64::new(0)),
+        }));
+        let delivery = Arc::new(MessageDeliveryState::new());
+        delivery.enqueue("hello".to_string());
+
+        let result = attempt_delivery(
+            &delivery,
+            &shared_state,
+            &event_tx,
+            &std::sync::atomic::AtomicI64::new(0),
+            &AtomicBool::new(false),
+        )
+        .await;
+
+        assert!(matches!(result, DeliveryResult::Delivered(ref m) if m == "hello"));
+        let log = log.lock().unwrap().clone();
+        let echo = log.iter().position(|e| *e == "paste echo");
+        let enter = log.iter().position(|e| *e == "enter");
+        assert!(
+            echo.is_some() && enter > echo,
+            "enter must follow the paste echo: {log:?}"
+        );
+        assert_eq!(log.iter().filter(|e| **e == "enter").count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_paste_echo_wait_is_bounded() {
+        let (event_tx, mut rx) = broadcast::channel::<PtyEvent>(8);
+        let started = tokio::time::Instant::now();
+        assert_eq!(
+            wait_for_paste_echo(&mut rx, Duration::from_millis(100)).await,
+            PasteEcho::TimedOut
+        );
+        assert!(started.elapsed() >= Duration::from_millis(100));
+
+        event_tx.send(PtyEvent::Output(b"x".to_vec())).unwrap();
+        assert_eq!(
+            wait_for_paste_echo(&mut rx, Duration::from_secs(5)).await,
+            PasteEcho::Echoed
+        );
+        drop(event_tx);
+        assert_eq!(
+            wait_for_paste_echo(&mut rx, Duration::from_secs(5)).await,
+            PasteEcho::Closed
+        );
+    }
+
     #[test]
     fn test_message_queue() {
         let state = MessageDeliveryState::new();
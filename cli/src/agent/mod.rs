@@ -43,6 +43,11 @@ use std::{path::PathBuf, time::Duration};
 /// Agent metadata (repo, issue, status, etc.) is managed by Lua.
 /// This struct provides PTY infrastructure for tests. In production,
 /// PTY sessions are created directly and registered via HandleCache.
+///
+/// There is no screen accessor here: the session process owns the terminal
+/// parser. Plain screen text comes from its `FRAME_GET_SCREEN` RPC
+/// ([`crate::terminal::TerminalParser::contents`]), exposed to Lua as
+/// `session:get_screen()`.
 pub struct Agent {
     /// Unique identifier for this agent instance.
     pub id: uuid::Uuid,
@@ -184,11 +189,11 @@ impl Agent {
 
     /// Generate a unique agent ID for this agent.
     ///
-    /// Format: `{repo-safe}-{branch-name}` (slashes replaced with dashes).
+    /// Same as the worktree directory name, see
+    /// [`crate::git::worktree_dir_name`].
     #[must_use]
     pub fn agent_id(&self) -> String {
-        let repo_safe = self.repo.replace('/', "-");
-        format!("{}-{}", repo_safe, self.branch_name.replace('/', "-"))
+        crate::git::worktree_dir_name(&self.repo, &self.branch_name)
     }
 
     // =========================================================================
@@ -209,8 +214,8 @@ impl Drop for Agent {
             "Agent {} dropping - cleaning up PTY sessions",
             self.agent_id()
         );
-        // PTY child processes are killed by PtySession's Drop
-        // which is called when Agent is dropped
+        // PtySession's Drop terminates the child's process group (SIGTERM,
+        // then SIGKILL after a grace period) and reaps it in the background.
     }
 }
 
@@ -247,6 +252,19 @@ mod tests {
         assert_eq!(agent.agent_id(), "owner-repo-botster-issue-42");
     }
 
+    #[test]
+    fn test_agent_id_normalizes_repo_case() {
+        let temp_dir = TempDir::new().unwrap();
+        let agent = Agent::new(
+            uuid::Uuid::new_v4(),
+            "Owner/Repo".to_string(),
+            "botster-issue-42".to_string(),
+            temp_dir.path().to_path_buf(),
+        );
+
+        assert_eq!(agent.agent_id(), "owner-repo-botster-issue-42");
+    }
+
     // test_scrollback_snapshot removed — session process owns snapshot generation.
 
     #[test]
@@ -292,4 +310,76 @@ mod tests {
         assert_eq!(info.rows, 24);
         assert_eq!(info.cols, 80);
     }
+
+    /// Whether a process with `pid` still exists (zombies count as gone).
+    fn process_alive(pid: libc::pid_t) -> bool {
+        let stat = std::fs::read_to_string(format!("/proc/{pid}/stat"));
+        match stat {
+            Ok(stat) => !stat.contains(") Z "),
+            // No procfs (macOS): fall back to signal 0.
+            // SAFETY: signal 0 only checks that the PID exists.
+            Err(_) => unsafe { libc::kill(pid, 0) == 0 },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_drop_terminates_child_process_group() {
+        use std::collections::HashMap;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let pid_file = temp_dir.path().join("grandchild.pid");
+        let mut agent = Agent::new(
+            uuid::Uuid::new_v4(),
+            "test/repo".to_string(),
+            "issue-1".to_string(),
+            temp_dir.path().to_path_buf(),
+        );
+        agent
+            .pty
+            .spawn(pty::PtySpawnConfig {
+                worktree_path: temp_dir.path().to_path_buf(),
+                command: "sh".to_string(),
+                args: vec![
+                    "-c".to_string(),
+                    format!("sleep 300 & echo $! > {}; wait", pid_file.display()),
+                ],
+                env: HashMap::new(),
+                init_commands: vec![],
+                detect_notifications: false,
+                port: None,
+                context: String::new(),
+            })
+            .unwrap();
+
+        let child_pid = libc::pid_t::try_from(agent.pty.get_child_pid().unwrap()).unwrap();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        let grandchild_pid = loop {
+            if let Some(pid) = std::fs::read_to_string(&pid_file)
+                .ok()
+                .and_then(|s| s.trim().parse::<libc::pid_t>().ok())
+            {
+                break pid;
+            }
+            assert!(std::time::Instant::now() < deadline, "child never started");
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        };
+        assert!(process_alive(child_pid));
+        assert!(process_alive(grandchild_pid));
+
+        drop(agent);
+
+        // Reaping happens on a background thread; the grandchild is
+        // reparented to init, which reaps it shortly after.
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(2);
+        while (process_alive(child_pid) || process_alive(grandchild_pid))
+            && std::time::Instant::now() < deadline
+        {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        assert!(!process_alive(child_pid), "PTY child should be reaped");
+        assert!(
+            !process_alive(grandchild_pid),
+            "grandchild in the PTY's process group should be killed"
+        );
+    }
 }
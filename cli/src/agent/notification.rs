@@ -51,29 +51,95 @@ pub enum AgentNotification {
 
 /// Agent execution status.
 ///
-/// Tracks the lifecycle state of an agent from initialization through
-/// completion or failure.
+/// Tracks the lifecycle state of an agent from initialization until its
+/// process ends, distinguishing a clean exit from a crash.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum AgentStatus {
     /// Agent is starting up.
     Initializing,
     /// Agent is actively running.
     Running,
-    /// Agent completed successfully.
-    Finished,
-    /// Agent failed with an error message.
-    Failed(String),
+    /// Agent process exited on its own with this exit code.
+    Exited {
+        /// Process exit code.
+        code: i32,
+    },
+    /// Agent process was terminated by a signal.
+    Failed {
+        /// Signal number, if it could be determined.
+        signal: Option<i32>,
+    },
     /// Agent was manually terminated.
     Killed,
 }
 
+impl AgentStatus {
+    /// Status for a child process that ended with `status`.
+    #[must_use]
+    pub fn from_exit_status(status: &portable_pty::ExitStatus) -> Self {
+        match status.signal() {
+            Some(name) => Self::Failed {
+                signal: signal_number(name),
+            },
+            None => Self::Exited {
+                code: i32::try_from(status.exit_code()).unwrap_or(i32::MAX),
+            },
+        }
+    }
+}
+
+/// `strsignal(3)` descriptions, as glibc and the BSDs/macOS word them.
+const SIGNAL_NAMES: &[(libc::c_int, &str)] = &[
+    (libc::SIGHUP, "Hangup"),
+    (libc::SIGINT, "Interrupt"),
+    (libc::SIGQUIT, "Quit"),
+    (libc::SIGILL, "Illegal instruction"),
+    (libc::SIGTRAP, "Trace/breakpoint trap"),
+    (libc::SIGABRT, "Aborted"),
+    (libc::SIGABRT, "Abort trap"),
+    (libc::SIGBUS, "Bus error"),
+    (libc::SIGFPE, "Floating point exception"),
+    (libc::SIGFPE, "Floating-point exception"),
+    (libc::SIGKILL, "Killed"),
+    (libc::SIGUSR1, "User defined signal 1"),
+    (libc::SIGSEGV, "Segmentation fault"),
+    (libc::SIGUSR2, "User defined signal 2"),
+    (libc::SIGPIPE, "Broken pipe"),
+    (libc::SIGALRM, "Alarm clock"),
+    (libc::SIGTERM, "Terminated"),
+    (libc::SIGXCPU, "CPU time limit exceeded"),
+    (libc::SIGXFSZ, "File size limit exceeded"),
+    (libc::SIGSYS, "Bad system call"),
+];
+
+/// Map a signal description back to its number.
+///
+/// `portable_pty` reports signals as `strsignal(3)` text (or `"Signal N"`
+/// when the platform has no description). `strsignal` itself is not
+/// thread-safe, so the common descriptions are matched from a fixed table.
+fn signal_number(name: &str) -> Option<i32> {
+    if let Some(n) = name
+        .strip_prefix("Signal ")
+        .or_else(|| name.strip_prefix("Unknown signal "))
+    {
+        return n.trim().parse().ok();
+    }
+    SIGNAL_NAMES
+        .iter()
+        .find(|(_, desc)| *desc == name)
+        .map(|&(signal, _)| signal)
+}
+
 impl std::fmt::Display for AgentStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             AgentStatus::Initializing => write!(f, "initializing"),
             AgentStatus::Running => write!(f, "running"),
-            AgentStatus::Finished => write!(f, "finished"),
-            AgentStatus::Failed(e) => write!(f, "failed: {}", e),
+            AgentStatus::Exited { code } => write!(f, "exited ({code})"),
+            AgentStatus::Failed {
+                signal: Some(signal),
+            } => write!(f, "failed (signal {signal})"),
+            AgentStatus::Failed { signal: None } => write!(f, "failed"),
             AgentStatus::Killed => write!(f, "killed"),
         }
     }
@@ -262,11 +328,35 @@ mod tests {
     fn test_agent_status_display() {
         assert_eq!(format!("{}", AgentStatus::Initializing), "initializing");
         assert_eq!(format!("{}", AgentStatus::Running), "running");
-        assert_eq!(format!("{}", AgentStatus::Finished), "finished");
+        assert_eq!(format!("{}", AgentStatus::Exited { code: 3 }), "exited (3)");
         assert_eq!(
-            format!("{}", AgentStatus::Failed("error".to_string())),
-            "failed: error"
+            format!("{}", AgentStatus::Failed { signal: Some(9) }),
+            "failed (signal 9)"
         );
         assert_eq!(format!("{}", AgentStatus::Killed), "killed");
     }
+
+    #[test]
+    fn test_agent_status_from_exit_status() {
+        use portable_pty::ExitStatus;
+
+        assert_eq!(
+            AgentStatus::from_exit_status(&ExitStatus::with_exit_code(3)),
+            AgentStatus::Exited { code: 3 }
+        );
+        assert_eq!(
+            AgentStatus::from_exit_status(&ExitStatus::with_signal("Signal 9")),
+            AgentStatus::Failed { signal: Some(9) }
+        );
+        assert_eq!(
+            AgentStatus::from_exit_status(&ExitStatus::with_signal("Terminated")),
+            AgentStatus::Failed {
+                signal: Some(libc::SIGTERM)
+            }
+        );
+        assert_eq!(
+            AgentStatus::from_exit_status(&ExitStatus::with_signal("Not a signal")),
+            AgentStatus::Failed { signal: None }
+        );
+    }
 }
@@ -121,6 +121,8 @@ pub enum PtyEvent {
     ProcessExited {
         /// Exit code if available (None if killed by signal).
         exit_code: Option<i32>,
+        /// Signal that killed the process, if known.
+        signal: Option<i32>,
     },
 
     /// OSC notification detected in PTY output.
@@ -182,7 +184,19 @@ impl PtyEvent {
     /// Create a process exited event.
     #[must_use]
     pub fn process_exited(exit_code: Option<i32>) -> Self {
-        Self::ProcessExited { exit_code }
+        Self::ProcessExited {
+            exit_code,
+            signal: None,
+        }
+    }
+
+    /// Create a process exited event for a process killed by `signal`.
+    #[must_use]
+    pub fn process_signalled(signal: i32) -> Self {
+        Self::ProcessExited {
+            exit_code: None,
+            signal: Some(signal),
+        }
     }
 
     /// Create a notification event.
@@ -314,8 +328,9 @@ mod tests {
         let event = PtyEvent::process_exited(Some(0));
         assert!(event.is_process_exited());
         match event {
-            PtyEvent::ProcessExited { exit_code } => {
+            PtyEvent::ProcessExited { exit_code, signal } => {
                 assert_eq!(exit_code, Some(0));
+                assert!(signal.is_none());
             }
             _ => panic!("Expected ProcessExited variant"),
         }
@@ -326,8 +341,21 @@ mod tests {
         let event = PtyEvent::process_exited(None);
         assert!(event.is_process_exited());
         match event {
-            PtyEvent::ProcessExited { exit_code } => {
+            PtyEvent::ProcessExited { exit_code, .. } => {
+                assert!(exit_code.is_none());
+            }
+            _ => panic!("Expected ProcessExited variant"),
+        }
+    }
+
+    #[test]
+    fn test_pty_event_process_signalled() {
+        let event = PtyEvent::process_signalled(9);
+        assert!(event.is_process_exited());
+        match event {
+            PtyEvent::ProcessExited { exit_code, signal } => {
                 assert!(exit_code.is_none());
+                assert_eq!(signal, Some(9));
             }
             _ => panic!("Expected ProcessExited variant"),
         }
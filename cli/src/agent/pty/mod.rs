@@ -42,7 +42,7 @@ pub use events::{PromptMark, PtyEvent};
 
 pub use super::spawn::PtySpawnConfig;
 
-use anyhow::{Context, Result};
+use anyhow::Result;
 use portable_pty::{Child, MasterPty, PtySize};
 use std::{
     io::Write,
@@ -63,6 +63,10 @@ const PTY_COMMAND_CHANNEL_CAPACITY: usize = 64;
 /// start missing events. Set high enough to handle bursts of output.
 const BROADCAST_CHANNEL_CAPACITY: usize = 1024;
 
+/// How long the child's process group gets to exit after SIGTERM before
+/// [`PtySession::kill_child`] escalates to SIGKILL.
+const CHILD_KILL_GRACE: std::time::Duration = std::time::Duration::from_millis(500);
+
 /// Shared mutable state for PTY command processing.
 ///
 /// This struct holds state that needs concurrent access from both the
@@ -343,7 +347,9 @@ impl PtySession {
     ///
     /// # Errors
     ///
-    /// Returns an error if PTY creation, command spawn, or writer setup fails.
+    /// Returns an [`AgentSpawnError`](spawn::AgentSpawnError) if the
+    /// worktree, command or a sourced init script is missing, or if PTY
+    /// creation, command spawn, or writer setup fails.
     ///
     /// # Example
     ///
@@ -360,7 +366,16 @@ impl PtySession {
     ///     context: String::new(),
     /// })?;
     /// ```
-    pub fn spawn(&mut self, config: PtySpawnConfig) -> Result<()> {
+    pub fn spawn(&mut self, config: PtySpawnConfig) -> Result<(), spawn::AgentSpawnError> {
+        use spawn::AgentSpawnError;
+
+        spawn::check_spawn(
+            spawn::program_name(&config.command, &config.args),
+            &config.worktree_path,
+            &config.env,
+            &config.init_commands,
+        )?;
+
         // Set port if provided
         if let Some(port) = config.port {
             self.set_port(port);
@@ -368,7 +383,8 @@ impl PtySession {
 
         // Open PTY pair with current dimensions
         let (rows, cols) = self.dimensions();
-        let pair = spawn::open_pty(rows, cols)?;
+        let pair = spawn::open_pty(rows, cols)
+            .map_err(|e| AgentSpawnError::PtyAllocationFailed(format!("{e:#}")))?;
 
         // Build and spawn command
         let cmd = spawn::build_command(
@@ -380,14 +396,18 @@ impl PtySession {
         let child = pair
             .slave
             .spawn_command(cmd)
-            .context("Failed to spawn command")?;
+            .map_err(|e| AgentSpawnError::SpawnFailed(e.to_string()))?;
 
         // Track notification detection flag
         self.detect_notifications = config.detect_notifications;
 
         // Configure PTY with spawned resources
         self.set_child(child);
-        self.set_writer(pair.master.take_writer()?);
+        let writer = pair
+            .master
+            .take_writer()
+            .map_err(|e| AgentSpawnError::PtyAllocationFailed(e.to_string()))?;
+        self.set_writer(writer);
 
         // The session process is the sole reader of the PTY master FD.
         // Output is forwarded to the hub over the session socket.
@@ -623,11 +643,22 @@ impl PtySession {
         self.child = Some(child);
     }
 
-    /// Kill the child process if running.
+    /// Kill the child process and everything it started, then reap it.
     ///
     /// This is automatically called on drop, but can be called manually
     /// for explicit cleanup.
     ///
+    /// The child is a session leader (portable-pty calls `setsid()`), so its
+    /// PID is also its process group ID. The whole group gets SIGTERM, and
+    /// SIGKILL only if something is still running after [`CHILD_KILL_GRACE`],
+    /// so the agent CLI started by `bash` can shut down cleanly and is never
+    /// orphaned. The group is only signalled while the leader is still our
+    /// unreaped child: once reaped, its PID (and so the group ID) may belong
+    /// to an unrelated process.
+    ///
+    /// The grace period and reaping run on a background thread so the
+    /// caller (usually the hub's event loop) never blocks on a slow child.
+    ///
     /// # macOS PTY session leader behavior
     ///
     /// On macOS, bash is the PTY session leader (it opened the slave as its
@@ -642,27 +673,80 @@ impl PtySession {
     /// on the child's death), `read()` on the master returns `EIO`, the thread
     /// exits its loop, and the clone is dropped.
     pub fn kill_child(&mut self) {
-        if let Some(mut child) = self.child.take() {
-            log::info!("Killing PTY child process");
-            if let Err(e) = child.kill() {
-                log::warn!("Failed to kill PTY child: {e}");
+        let Some(mut child) = self.child.take() else {
+            return;
+        };
+
+        // `try_wait` reaps the leader if it already exited; in that case
+        // its PID is free for reuse and the group must not be signalled.
+        let mut status = match child.try_wait() {
+            Ok(status) => status,
+            Err(e) => {
+                log::warn!("Failed to check PTY child before killing it: {e}");
+                None
             }
+        };
+        let pgid = if status.is_none() {
+            child
+                .process_id()
+                .and_then(|pid| libc::pid_t::try_from(pid).ok())
+        } else {
+            None
+        };
+        log::info!("Killing PTY child process (pgid={pgid:?})");
+        if let Some(pgid) = pgid {
+            // SAFETY: the leader is our unreaped child, so `pgid` is still
+            // its group; killpg only sends a signal.
+            unsafe {
+                libc::killpg(pgid, libc::SIGTERM);
+            }
+        }
+
+        // Release both master-PTY FD references before waiting.
+        // See doc-comment above for why this is required on macOS.
+        // Closing the master also hangs up the terminal (SIGHUP), which
+        // interactive shells honor even though they ignore SIGTERM.
+        {
+            let mut state = self
+                .shared_state
+                .lock()
+                .expect("shared_state lock poisoned");
+            drop(state.master_pty.take());
+            drop(state.writer.take());
+        }
 
-            // Release both master-PTY FD references before waiting.
-            // See doc-comment above for why this is required on macOS.
-            {
-                let mut state = self
-                    .shared_state
-                    .lock()
-                    .expect("shared_state lock poisoned");
-                drop(state.master_pty.take());
-                drop(state.writer.take());
+        let reap_child = move || {
+            let mut reap = || {
+                if status.is_none() {
+                    status = child.try_wait().ok().flatten();
+                }
+            };
+            match pgid {
+                // Waits for the whole group, not just the leader: descendants
+                // may still hold it open.
+                Some(pgid) => {
+                    crate::process::wait_process_group(pgid, CHILD_KILL_GRACE, reap);
+                }
+                None => reap(),
             }
 
             // Wait for process to exit to prevent zombies.
-            // With master FDs released, macOS can finalize the PTY session
-            // and report the exit status immediately.
-            let _ = child.wait();
+            let status = match status {
+                Some(status) => Ok(status),
+                None => child.wait(),
+            };
+            match status {
+                Ok(status) if status.success() => log::info!("PTY child exited cleanly"),
+                Ok(status) => log::info!("PTY child terminated ({status})"),
+                Err(e) => log::warn!("Failed to reap PTY child: {e}"),
+            }
+        };
+
+        if let Err(e) = std::thread::Builder::new()
+            .name("pty-reaper".to_string())
+            .spawn(reap_child)
+        {
+            log::warn!("Failed to spawn PTY reaper thread: {e}");
         }
     }
 
@@ -1136,6 +1220,42 @@ mod tests {
         assert_eq!(session.port(), Some(8080));
     }
 
+    #[tokio::test]
+    async fn test_spawn_reports_missing_worktree_and_shell() {
+        use std::collections::HashMap;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config = |worktree_path: std::path::PathBuf, command: &str| PtySpawnConfig {
+            worktree_path,
+            command: command.to_string(),
+            args: vec![],
+            env: HashMap::new(),
+            init_commands: vec![],
+            detect_notifications: false,
+            port: None,
+            context: String::new(),
+        };
+
+        let mut session = PtySession::new(24, 80);
+        let err = session
+            .spawn(config(temp_dir.path().join("gone"), "bash"))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            spawn::AgentSpawnError::WorktreePathMissing(_)
+        ));
+        assert!(!session.is_spawned());
+
+        let err = session
+            .spawn(config(
+                temp_dir.path().to_path_buf(),
+                "botster-no-such-shell",
+            ))
+            .unwrap_err();
+        assert!(matches!(err, spawn::AgentSpawnError::ShellNotFound(_)));
+        assert!(!session.is_spawned());
+    }
+
     #[test]
     fn test_spawn_command_processor_outside_tokio_runtime() {
         let mut session = PtySession::new(24, 80);
@@ -16,7 +16,7 @@
 // Rust guideline compliant 2026-02
 
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use portable_pty::{native_pty_system, CommandBuilder, PtyPair, PtySize};
@@ -72,6 +72,140 @@ pub struct PtySpawnConfig {
     pub context: String,
 }
 
+/// Why a PTY process could not be started.
+///
+/// The `Display` text is user-facing: it reaches the browser through the
+/// `create_agent` failure notification, so each variant says what to fix.
+#[derive(Debug)]
+pub enum AgentSpawnError {
+    /// The working directory does not exist.
+    WorktreePathMissing(PathBuf),
+    /// The command is neither an existing path nor found on `PATH`.
+    ShellNotFound(String),
+    /// The pseudo-terminal could not be opened or its writer taken.
+    PtyAllocationFailed(String),
+    /// An init command sources a script that does not exist.
+    InitScriptMissing(PathBuf),
+    /// The command exists but could not be started.
+    SpawnFailed(String),
+}
+
+impl std::fmt::Display for AgentSpawnError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::WorktreePathMissing(path) => {
+                write!(f, "Worktree path does not exist: {}", path.display())
+            }
+            Self::ShellNotFound(command) => write!(f, "Command not found: {command}"),
+            Self::PtyAllocationFailed(msg) => write!(f, "PTY allocation failed: {msg}"),
+            Self::InitScriptMissing(path) => {
+                write!(f, "Init script not found: {}", path.display())
+            }
+            Self::SpawnFailed(msg) => write!(f, "Failed to spawn command: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for AgentSpawnError {}
+
+/// Check the parts of a spawn that commonly fail before forking.
+///
+/// Catches a missing working directory, a `program` that cannot be resolved,
+/// and `source <script>` init commands whose script is missing. Init
+/// commands that are not a single plain `source`/`.` of a path are not
+/// inspected.
+///
+/// # Errors
+///
+/// Returns the first [`AgentSpawnError`] found.
+#[allow(
+    clippy::implicit_hasher,
+    reason = "internal API doesn't need hasher generalization"
+)]
+pub fn check_spawn(
+    program: &str,
+    cwd: &Path,
+    env_vars: &HashMap<String, String>,
+    init_commands: &[String],
+) -> Result<(), AgentSpawnError> {
+    if !cwd.is_dir() {
+        return Err(AgentSpawnError::WorktreePathMissing(cwd.to_path_buf()));
+    }
+
+    if !command_exists(program, cwd, env_vars) {
+        return Err(AgentSpawnError::ShellNotFound(program.to_string()));
+    }
+
+    for script in init_commands.iter().filter_map(|cmd| sourced_script(cmd)) {
+        let path = cwd.join(script);
+        if !path.is_file() {
+            return Err(AgentSpawnError::InitScriptMissing(path));
+        }
+    }
+
+    Ok(())
+}
+
+/// The program [`build_command`] runs: the first word of `command_str` when
+/// there are no separate `args`, otherwise `command_str` itself.
+#[must_use]
+pub fn program_name<'a>(command_str: &'a str, args: &[String]) -> &'a str {
+    if args.is_empty() {
+        command_str.split_whitespace().next().unwrap_or("")
+    } else {
+        command_str
+    }
+}
+
+/// Whether `program` resolves like a shell would: as a path when it contains
+/// a slash, otherwise through `PATH` (plus the running binary's directory,
+/// which [`build_command`] prepends).
+fn command_exists(program: &str, cwd: &Path, env_vars: &HashMap<String, String>) -> bool {
+    if program.is_empty() {
+        return false;
+    }
+    if program.contains('/') {
+        return cwd.join(program).is_file();
+    }
+
+    let path_var = env_vars
+        .get("PATH")
+        .cloned()
+        .or_else(|| std::env::var("PATH").ok())
+        .unwrap_or_default();
+    let exe_dir = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf));
+
+    exe_dir
+        .into_iter()
+        .chain(std::env::split_paths(&path_var))
+        .any(|dir| dir.join(program).is_file())
+}
+
+/// The script path of a `source <path>` or `. <path>` command whose path is
+/// a plain word or one single-quoted word (as the Lua session layer quotes
+/// it, with `'\''` for embedded quotes).
+fn sourced_script(command: &str) -> Option<String> {
+    let command = command.trim();
+    let script = command
+        .strip_prefix("source ")
+        .or_else(|| command.strip_prefix(". "))?
+        .trim();
+    if let Some(quoted) = script.strip_prefix('\'') {
+        let parts: Vec<&str> = quoted.strip_suffix('\'')?.split(r"'\''").collect();
+        if parts.iter().any(|part| part.contains('\'')) {
+            return None;
+        }
+        let path = parts.join("'");
+        return (!path.is_empty()).then_some(path);
+    }
+    let plain = !script.is_empty()
+        && !script.starts_with('~')
+        && !script.contains(|c: char| c.is_whitespace() || "$`;&|<>\"'".contains(c));
+    plain.then(|| script.to_string())
+}
+
 /// Open a new PTY pair with the given dimensions.
 pub fn open_pty(rows: u16, cols: u16) -> Result<PtyPair> {
     let pty_system = native_pty_system();
@@ -316,6 +450,59 @@ mod tests {
         let _ = cmd;
     }
 
+    #[test]
+    fn test_check_spawn_missing_worktree() {
+        let missing = std::env::temp_dir().join("botster-no-such-worktree-7f3a");
+        let err = check_spawn("bash", &missing, &HashMap::new(), &[]).unwrap_err();
+
+        assert!(matches!(err, AgentSpawnError::WorktreePathMissing(ref p) if *p == missing));
+        assert!(err
+            .to_string()
+            .starts_with("Worktree path does not exist: "));
+    }
+
+    #[test]
+    fn test_check_spawn_bogus_command() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let program = program_name("botster-no-such-shell --login", &[]);
+        let err = check_spawn(program, temp_dir.path(), &HashMap::new(), &[]).unwrap_err();
+
+        assert!(
+            matches!(err, AgentSpawnError::ShellNotFound(ref c) if c == "botster-no-such-shell")
+        );
+        assert_eq!(err.to_string(), "Command not found: botster-no-such-shell");
+    }
+
+    #[test]
+    fn test_check_spawn_command_with_inline_args() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let env = HashMap::new();
+
+        assert!(check_spawn(program_name("sh -l", &[]), temp_dir.path(), &env, &[]).is_ok());
+        assert!(check_spawn("sh -l", temp_dir.path(), &env, &[]).is_err());
+    }
+
+    #[test]
+    fn test_check_spawn_missing_init_script() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("present.sh"), "").unwrap();
+        let env = HashMap::new();
+        let check = |cmd: &str| check_spawn("sh", temp_dir.path(), &env, &[cmd.to_string()]);
+
+        assert!(check("source present.sh").is_ok());
+        assert!(check("source 'present.sh'").is_ok());
+        assert!(check("echo not a script").is_ok());
+        assert!(check("source $HOME/.profile").is_ok(), "not inspected");
+        assert!(matches!(
+            check(". missing.sh").unwrap_err(),
+            AgentSpawnError::InitScriptMissing(p) if p == temp_dir.path().join("missing.sh")
+        ));
+        assert!(matches!(
+            check("source 'it'\\''s missing.sh'").unwrap_err(),
+            AgentSpawnError::InitScriptMissing(p) if p == temp_dir.path().join("it's missing.sh")
+        ));
+    }
+
     #[test]
     fn test_build_command_with_args() {
         use std::collections::HashMap;
@@ -5,4 +5,7 @@
 pub mod ui;
 
 // Re-export commonly used types
-pub use ui::{buffer_to_ansi, centered_rect};
+pub use ui::{
+    buffer_to_ansi, buffer_to_ansi_diff, centered_rect, render_too_small, terminal_too_small,
+    ColorDepth,
+};
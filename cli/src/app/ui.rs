@@ -7,17 +7,23 @@
 //!
 //! The TUI uses ratatui for rendering. When streaming to browsers via WebRTC,
 //! the rendered buffer is converted to ANSI escape sequences using
-//! [`buffer_to_ansi`].
+//! [`buffer_to_ansi`], or [`buffer_to_ansi_diff`] to send only the cells that
+//! changed since the previous frame.
 //!
 //! Modal dialogs are positioned using [`centered_rect`] which calculates
 //! a centered rectangle within a parent area.
+//!
+//! Viewports below [`MIN_TERMINAL_COLS`] x [`MIN_TERMINAL_ROWS`] get a
+//! [`render_too_small`] notice instead of the full layout.
 
 // Rust guideline compliant 2026-02
 
 use ratatui::{
-    buffer::Buffer,
-    layout::{Constraint, Direction, Layout, Rect},
+    buffer::{Buffer, Cell, CellWidth},
+    layout::{Alignment, Constraint, Direction, Layout, Position, Rect},
     style::{Color, Modifier},
+    widgets::{Paragraph, Wrap},
+    Frame,
 };
 use std::fmt::Write;
 
@@ -65,6 +71,39 @@ pub fn centered_rect(percent_x: u16, percent_y: u16, parent: Rect) -> Rect {
         .split(popup_layout[1])[1]
 }
 
+/// Minimum terminal width (columns) for the full TUI layout.
+pub const MIN_TERMINAL_COLS: u16 = 40;
+
+/// Minimum terminal height (rows) for the full TUI layout.
+pub const MIN_TERMINAL_ROWS: u16 = 10;
+
+/// Returns true when `area` is too small for the full layout.
+///
+/// Below this size percentage layouts and [`centered_rect`] collapse to
+/// zero-width chunks, so callers render [`render_too_small`] instead.
+pub fn terminal_too_small(area: Rect) -> bool {
+    area.width < MIN_TERMINAL_COLS || area.height < MIN_TERMINAL_ROWS
+}
+
+/// Renders a "terminal too small" notice filling the frame.
+///
+/// Used in place of the full layout when [`terminal_too_small`] is true.
+/// The notice wraps, so it stays readable down to a single cell.
+pub fn render_too_small(f: &mut Frame) {
+    let area = f.area();
+    if area.width == 0 || area.height == 0 {
+        return;
+    }
+    let message = format!(
+        "Terminal too small (need {MIN_TERMINAL_COLS}x{MIN_TERMINAL_ROWS}, have {}x{})",
+        area.width, area.height
+    );
+    let notice = Paragraph::new(message)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    f.render_widget(notice, area);
+}
+
 /// Converts a ratatui Buffer to ANSI escape sequences.
 ///
 /// Used for streaming the TUI to browsers via WebRTC. The output string
@@ -77,10 +116,16 @@ pub fn centered_rect(percent_x: u16, percent_y: u16, parent: Rect) -> Rect {
 /// * `height` - Buffer height
 /// * `clip_width` - Optional clipping width (for browser dimensions)
 /// * `clip_height` - Optional clipping height (for browser dimensions)
+/// * `cursor` - Where the source wants the cursor, or `None` if it is hidden
+/// * `depth` - Color palette of the receiving terminal; RGB colors are
+///   downgraded to the nearest palette entry below [`ColorDepth::TrueColor`]
 ///
 /// # Returns
 ///
 /// A string containing ANSI escape sequences representing the buffer contents.
+/// The frame ends with the cursor state: `CSI ?25h` followed by a `CUP` to
+/// `cursor` when it is visible and inside the output area, otherwise
+/// `CSI ?25l`.
 ///
 /// # Performance
 ///
@@ -92,6 +137,8 @@ pub fn buffer_to_ansi(
     height: u16,
     clip_width: Option<u16>,
     clip_height: Option<u16>,
+    cursor: Option<Position>,
+    depth: ColorDepth,
 ) -> String {
     let out_width = clip_width.unwrap_or(width).min(width);
     let out_height = clip_height.unwrap_or(height).min(height);
@@ -101,49 +148,252 @@ pub fn buffer_to_ansi(
     // Reset and clear screen, move cursor to home
     output.push_str("\x1b[0m\x1b[H\x1b[2J");
 
-    let mut last_fg = Color::Reset;
-    let mut last_bg = Color::Reset;
-    let mut last_modifiers = Modifier::empty();
+    let mut style = StyleState::new(depth);
 
     for y in 0..out_height {
         // Move cursor to start of line
         write!(output, "\x1b[{};1H", y + 1).expect("string write is infallible");
 
-        for x in 0..out_width {
+        let mut x = 0;
+        while x < out_width {
             let Some(cell) = buffer.cell((x, y)) else {
                 output.push(' ');
+                x += 1;
                 continue;
             };
 
-            let fg = cell.fg;
-            let bg = cell.bg;
-            let modifiers = cell.modifier;
+            style.apply(&mut output, cell);
+            let cell_width = cell.cell_width().max(1);
+            if x + cell_width > out_width {
+                // A wide glyph cut off by the clip edge would overflow the line
+                output.push(' ');
+            } else {
+                output.push_str(cell.symbol());
+            }
+            // Wide glyphs already cover their continuation cells
+            x += cell_width;
+        }
+    }
+
+    finish_frame(&mut output, cursor, out_width, out_height);
+    output
+}
+
+/// Converts only the cells of `curr` that differ from `prev` to ANSI.
+///
+/// Emits a cursor move before each run of changed cells and style changes as
+/// needed, so a frame where one character changed costs a few bytes instead
+/// of a full repaint. Falls back to [`buffer_to_ansi`] when the buffer
+/// dimensions changed, since the receiving terminal's contents no longer
+/// line up with `prev`.
+///
+/// The frame ends with the same cursor state as [`buffer_to_ansi`], and colors
+/// are downgraded to `depth` the same way.
+pub fn buffer_to_ansi_diff(
+    prev: &Buffer,
+    curr: &Buffer,
+    width: u16,
+    height: u16,
+    cursor: Option<Position>,
+    depth: ColorDepth,
+) -> String {
+    if prev.area != curr.area {
+        return buffer_to_ansi(curr, width, height, None, None, cursor, depth);
+    }
+
+    let mut output = String::new();
+    output.push_str("\x1b[0m");
+
+    let mut style = StyleState::new(depth);
+    // Where the terminal cursor sits after the last emitted cell
+    let mut pen: Option<(u16, u16)> = None;
 
-            // Only emit style changes when attributes differ
-            if fg != last_fg || bg != last_bg || modifiers != last_modifiers {
-                output.push_str("\x1b[0m"); // Reset first
+    for y in 0..height {
+        let mut x = 0;
+        while x < width {
+            let Some(cell) = curr.cell((x, y)) else {
+                x += 1;
+                continue;
+            };
+            // Wide glyphs already cover their continuation cells
+            let cell_width = cell.cell_width().max(1);
+            if prev.cell((x, y)) != Some(cell) {
+                if pen != Some((x, y)) {
+                    write!(output, "\x1b[{};{}H", y + 1, x + 1)
+                        .expect("string write is infallible");
+                }
+                style.apply(&mut output, cell);
+                output.push_str(cell.symbol());
+                pen = Some((x + cell_width, y));
+            }
+            x += cell_width;
+        }
+    }
 
-                // Apply modifiers
-                apply_modifiers(&mut output, modifiers);
+    finish_frame(&mut output, cursor, width, height);
+    output
+}
 
-                // Apply colors
-                apply_foreground_color(&mut output, fg);
-                apply_background_color(&mut output, bg);
+/// Color palette supported by the terminal receiving ANSI output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorDepth {
+    /// 24-bit RGB (`CSI 38;2;r;g;b m`).
+    #[default]
+    TrueColor,
+    /// The xterm 256-color palette (`CSI 38;5;n m`).
+    Indexed256,
+    /// The 16 standard ANSI colors.
+    Ansi16,
+}
 
-                last_fg = fg;
-                last_bg = bg;
-                last_modifiers = modifiers;
+/// RGB values of the 16 ANSI colors, in palette index order (xterm defaults).
+const ANSI16_PALETTE: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// Channel values of the 6x6x6 color cube (palette indices 16-231).
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl ColorDepth {
+    /// Maps `color` onto this palette. Named colors pass through unchanged.
+    #[must_use]
+    pub fn downgrade(self, color: Color) -> Color {
+        match (self, color) {
+            (Self::Indexed256, Color::Rgb(r, g, b)) => Color::Indexed(rgb_to_indexed(r, g, b)),
+            (Self::Ansi16, Color::Rgb(r, g, b)) => rgb_to_ansi16(r, g, b),
+            (Self::Ansi16, Color::Indexed(i)) => {
+                let (r, g, b) = indexed_to_rgb(i);
+                rgb_to_ansi16(r, g, b)
             }
+            _ => color,
+        }
+    }
+}
+
+/// Squared euclidean distance between two RGB colors.
+fn rgb_distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (i32::from(a) - i32::from(b)).unsigned_abs().pow(2);
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+/// Nearest xterm 256-color index, choosing between the color cube and the
+/// grayscale ramp.
+fn rgb_to_indexed(r: u8, g: u8, b: u8) -> u8 {
+    let cube_index = |v: u8| match v {
+        0..=47 => 0,
+        48..=114 => 1,
+        _ => (v - 35) / 40,
+    };
+    let (ri, gi, bi) = (cube_index(r), cube_index(g), cube_index(b));
+    let cube_rgb = (
+        CUBE_LEVELS[usize::from(ri)],
+        CUBE_LEVELS[usize::from(gi)],
+        CUBE_LEVELS[usize::from(bi)],
+    );
+
+    // Grayscale ramp 232-255 covers 8, 18, ..., 238
+    let average = (u16::from(r) + u16::from(g) + u16::from(b)) / 3;
+    let gray_index = (average.saturating_sub(3) / 10).min(23) as u8;
+    let gray_level = 8 + 10 * gray_index;
+
+    if rgb_distance((gray_level, gray_level, gray_level), (r, g, b))
+        < rgb_distance(cube_rgb, (r, g, b))
+    {
+        232 + gray_index
+    } else {
+        16 + 36 * ri + 6 * gi + bi
+    }
+}
+
+/// RGB value of an xterm 256-color index.
+fn indexed_to_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI16_PALETTE[usize::from(index)].1,
+        16..=231 => {
+            let i = index - 16;
+            (
+                CUBE_LEVELS[usize::from(i / 36)],
+                CUBE_LEVELS[usize::from(i / 6 % 6)],
+                CUBE_LEVELS[usize::from(i % 6)],
+            )
+        }
+        _ => {
+            let level = 8 + 10 * (index - 232);
+            (level, level, level)
+        }
+    }
+}
+
+/// Nearest of the 16 ANSI colors.
+fn rgb_to_ansi16(r: u8, g: u8, b: u8) -> Color {
+    ANSI16_PALETTE
+        .iter()
+        .min_by_key(|(_, rgb)| rgb_distance(*rgb, (r, g, b)))
+        .map_or(Color::Reset, |(color, _)| *color)
+}
+
+/// Tracks the SGR attributes last written so unchanged styles aren't re-sent.
+struct StyleState {
+    depth: ColorDepth,
+    fg: Color,
+    bg: Color,
+    modifiers: Modifier,
+}
+
+impl StyleState {
+    fn new(depth: ColorDepth) -> Self {
+        Self {
+            depth,
+            fg: Color::Reset,
+            bg: Color::Reset,
+            modifiers: Modifier::empty(),
+        }
+    }
 
-            // Write the character
-            output.push_str(cell.symbol());
+    /// Emits the style of `cell` if it differs from the last one written.
+    fn apply(&mut self, output: &mut String, cell: &Cell) {
+        if cell.fg == self.fg && cell.bg == self.bg && cell.modifier == self.modifiers {
+            return;
         }
+
+        output.push_str("\x1b[0m"); // Reset first
+        apply_modifiers(output, cell.modifier);
+        apply_foreground_color(output, self.depth.downgrade(cell.fg));
+        apply_background_color(output, self.depth.downgrade(cell.bg));
+
+        self.fg = cell.fg;
+        self.bg = cell.bg;
+        self.modifiers = cell.modifier;
     }
+}
 
-    // Reset at end
+/// Resets attributes and leaves the cursor where the source put it (e.g. an
+/// input field), or hides it when it is absent or outside the output area.
+fn finish_frame(output: &mut String, cursor: Option<Position>, width: u16, height: u16) {
     output.push_str("\x1b[0m");
 
-    output
+    match cursor {
+        Some(pos) if pos.x < width && pos.y < height => {
+            write!(output, "\x1b[?25h\x1b[{};{}H", pos.y + 1, pos.x + 1)
+                .expect("string write is infallible");
+        }
+        _ => output.push_str("\x1b[?25l"),
+    }
 }
 
 /// Applies text modifiers to the output string.
@@ -226,6 +476,7 @@ fn apply_background_color(output: &mut String, color: Color) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ratatui::style::Style;
 
     #[test]
     fn test_centered_rect_50_percent() {
@@ -251,7 +502,7 @@ mod tests {
     #[test]
     fn test_buffer_to_ansi_empty() {
         let buffer = Buffer::empty(Rect::new(0, 0, 10, 5));
-        let result = buffer_to_ansi(&buffer, 10, 5, None, None);
+        let result = buffer_to_ansi(&buffer, 10, 5, None, None, None, ColorDepth::TrueColor);
 
         // Should contain reset and cursor positioning
         assert!(result.contains("\x1b[0m"));
@@ -261,7 +512,15 @@ mod tests {
     #[test]
     fn test_buffer_to_ansi_with_clipping() {
         let buffer = Buffer::empty(Rect::new(0, 0, 100, 50));
-        let result = buffer_to_ansi(&buffer, 100, 50, Some(10), Some(5));
+        let result = buffer_to_ansi(
+            &buffer,
+            100,
+            50,
+            Some(10),
+            Some(5),
+            None,
+            ColorDepth::TrueColor,
+        );
 
         // Should only have 5 lines of output
         let line_count = result.matches("\x1b[").count();
@@ -269,6 +528,194 @@ mod tests {
         assert!(line_count > 0);
     }
 
+    #[test]
+    fn test_buffer_to_ansi_ends_with_cursor_position() {
+        let buffer = Buffer::empty(Rect::new(0, 0, 20, 10));
+        let result = buffer_to_ansi(
+            &buffer,
+            20,
+            10,
+            None,
+            None,
+            Some(Position::new(4, 2)),
+            ColorDepth::TrueColor,
+        );
+
+        assert!(result.ends_with("\x1b[?25h\x1b[3;5H"), "got {result:?}");
+    }
+
+    #[test]
+    fn test_buffer_to_ansi_hides_cursor() {
+        let buffer = Buffer::empty(Rect::new(0, 0, 20, 10));
+
+        let hidden = buffer_to_ansi(&buffer, 20, 10, None, None, None, ColorDepth::TrueColor);
+        assert!(hidden.ends_with("\x1b[?25l"));
+
+        // A cursor outside the clipped area can't be shown either
+        let clipped = buffer_to_ansi(
+            &buffer,
+            20,
+            10,
+            Some(5),
+            Some(5),
+            Some(Position::new(8, 1)),
+            ColorDepth::TrueColor,
+        );
+        assert!(clipped.ends_with("\x1b[?25l"));
+    }
+
+    fn filled_buffer(width: u16, height: u16) -> Buffer {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, width, height));
+        for y in 0..height {
+            buffer.set_string(0, y, "x".repeat(usize::from(width)), Style::default());
+        }
+        buffer
+    }
+
+    #[test]
+    fn test_buffer_to_ansi_diff_one_cell_is_smaller_than_full() {
+        let prev = filled_buffer(80, 24);
+        let mut curr = prev.clone();
+        curr.set_string(10, 5, "y", Style::default().fg(Color::Red));
+
+        let full = buffer_to_ansi(&curr, 80, 24, None, None, None, ColorDepth::TrueColor);
+        let diff = buffer_to_ansi_diff(&prev, &curr, 80, 24, None, ColorDepth::TrueColor);
+
+        assert!(
+            diff.len() * 20 < full.len(),
+            "diff {} vs full {}",
+            diff.len(),
+            full.len()
+        );
+        assert!(diff.contains("\x1b[6;11H"), "got {diff:?}");
+        assert!(diff.contains("\x1b[31my"), "got {diff:?}");
+        assert!(!diff.contains("\x1b[2J"));
+    }
+
+    #[test]
+    fn test_buffer_to_ansi_diff_unchanged_frame_only_sets_cursor() {
+        let buffer = filled_buffer(20, 10);
+        let diff = buffer_to_ansi_diff(
+            &buffer,
+            &buffer,
+            20,
+            10,
+            Some(Position::new(4, 2)),
+            ColorDepth::TrueColor,
+        );
+
+        assert_eq!(diff, "\x1b[0m\x1b[0m\x1b[?25h\x1b[3;5H");
+    }
+
+    #[test]
+    fn test_buffer_to_ansi_diff_repaints_on_resize() {
+        let prev = filled_buffer(20, 10);
+        let curr = filled_buffer(30, 10);
+
+        let diff = buffer_to_ansi_diff(&prev, &curr, 30, 10, None, ColorDepth::TrueColor);
+        assert_eq!(
+            diff,
+            buffer_to_ansi(&curr, 30, 10, None, None, None, ColorDepth::TrueColor)
+        );
+    }
+
+    #[test]
+    fn test_buffer_to_ansi_wide_character_keeps_alignment() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 6, 1));
+        buffer.set_string(0, 0, "a日b", Style::default());
+
+        let result = buffer_to_ansi(&buffer, 6, 1, None, None, None, ColorDepth::TrueColor);
+        assert!(result.contains("\x1b[1;1Ha日b  \x1b[0m"), "got {result:?}");
+        assert!(!result.contains("日 b"), "got {result:?}");
+    }
+
+    #[test]
+    fn test_buffer_to_ansi_clips_wide_character_at_edge() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 6, 1));
+        buffer.set_string(0, 0, "ab日", Style::default());
+
+        let result = buffer_to_ansi(&buffer, 6, 1, Some(3), None, None, ColorDepth::TrueColor);
+        assert!(result.contains("\x1b[1;1Hab \x1b[0m"), "got {result:?}");
+    }
+
+    #[test]
+    fn test_buffer_to_ansi_diff_wide_character_skips_continuation() {
+        let prev = filled_buffer(6, 1);
+        let mut curr = prev.clone();
+        curr.set_string(1, 0, "日", Style::default());
+
+        let diff = buffer_to_ansi_diff(&prev, &curr, 6, 1, None, ColorDepth::TrueColor);
+        assert!(diff.contains("\x1b[1;2H日\x1b[0m"), "got {diff:?}");
+    }
+
+    #[test]
+    fn test_color_depth_indexed256_maps_rgb() {
+        let depth = ColorDepth::Indexed256;
+        assert_eq!(depth.downgrade(Color::Rgb(255, 0, 0)), Color::Indexed(196));
+        assert_eq!(
+            depth.downgrade(Color::Rgb(95, 135, 175)),
+            Color::Indexed(67)
+        );
+        // Neutral grays land on the grayscale ramp
+        assert_eq!(
+            depth.downgrade(Color::Rgb(128, 128, 128)),
+            Color::Indexed(244)
+        );
+        assert_eq!(depth.downgrade(Color::Red), Color::Red);
+    }
+
+    #[test]
+    fn test_color_depth_ansi16_maps_rgb_and_indexed() {
+        let depth = ColorDepth::Ansi16;
+        assert_eq!(depth.downgrade(Color::Rgb(250, 10, 10)), Color::LightRed);
+        assert_eq!(depth.downgrade(Color::Rgb(0, 0, 0)), Color::Black);
+        assert_eq!(depth.downgrade(Color::Indexed(46)), Color::LightGreen);
+    }
+
+    #[test]
+    fn test_buffer_to_ansi_downgrades_color() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 4, 1));
+        buffer.set_string(0, 0, "x", Style::default().fg(Color::Rgb(255, 0, 0)));
+
+        let truecolor = buffer_to_ansi(&buffer, 4, 1, None, None, None, ColorDepth::default());
+        assert!(truecolor.contains("\x1b[38;2;255;0;0m"));
+
+        let indexed = buffer_to_ansi(&buffer, 4, 1, None, None, None, ColorDepth::Indexed256);
+        assert!(indexed.contains("\x1b[38;5;196m"));
+        assert!(!indexed.contains("38;2;"));
+    }
+
+    #[test]
+    fn test_terminal_too_small_threshold() {
+        assert!(terminal_too_small(Rect::new(0, 0, 10, 5)));
+        assert!(terminal_too_small(Rect::new(0, 0, 39, 40)));
+        assert!(terminal_too_small(Rect::new(0, 0, 80, 9)));
+        assert!(!terminal_too_small(Rect::new(0, 0, 40, 10)));
+    }
+
+    #[test]
+    fn test_render_too_small_at_10x5() {
+        let backend = ratatui::backend::TestBackend::new(10, 5);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        terminal.draw(render_too_small).unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let text: String = buffer.content().iter().map(|c| c.symbol()).collect();
+        assert!(text.contains("Terminal"), "got {text:?}");
+        assert!(text.contains("small"), "got {text:?}");
+        assert!(
+            buffer_to_ansi(buffer, 10, 5, None, None, None, ColorDepth::TrueColor)
+                .contains("Terminal")
+        );
+    }
+
+    #[test]
+    fn test_render_too_small_handles_empty_area() {
+        let backend = ratatui::backend::TestBackend::new(0, 0);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        terminal.draw(render_too_small).unwrap();
+    }
+
     #[test]
     fn test_apply_modifiers() {
         let mut output = String::new();
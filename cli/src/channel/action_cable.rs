@@ -1357,6 +1357,7 @@ impl Channel for ActionCableChannel {
                 "Already connected".to_string(),
             ));
         }
+        config.ensure_e2e(self.crypto_service.is_some())?;
 
         // Create channels
         let (send_tx, send_rx) = mpsc::channel::<OutgoingMessage>(100);
@@ -16,10 +16,19 @@
 //! let decompressed = maybe_decompress(&compressed)?;
 //! ```
 //!
+//! # Codec Negotiation
+//!
+//! WebRTC peers advertise the codecs they can decode when they send their
+//! offer (e.g. `["deflate", "zstd"]`). [`negotiate_codec`] picks the hub's
+//! most preferred codec that the peer also supports, and PTY frames carry
+//! the chosen [`CompressionCodec`] in their flags byte (see
+//! [`CompressionCodec::frame_flags`]). Peers that advertise nothing get
+//! gzip, matching the behavior of browsers that predate negotiation.
+//!
 //! Rust guideline compliant 2025-01
 
-use flate2::read::GzDecoder;
-use flate2::write::GzEncoder;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use flate2::write::{GzEncoder, ZlibEncoder};
 use flate2::Compression;
 use std::io::{Read, Write};
 
@@ -172,10 +181,205 @@ pub fn should_compress_response(
     !already_compressed
 }
 
+/// Compression codec negotiated with a single peer.
+///
+/// Codec ids are carried in the high nibble of the PTY frame flags byte.
+/// Gzip keeps id `0` so frames from pre-negotiation hubs (flags `0x01`)
+/// still decode as gzip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CompressionCodec {
+    /// Never compress; frames are always sent raw.
+    None,
+    /// Gzip (RFC 1952). Legacy default for peers that do not negotiate.
+    #[default]
+    Gzip,
+    /// Zlib-wrapped deflate (RFC 1950), i.e. `DecompressionStream("deflate")`.
+    Deflate,
+}
+
+/// Codecs the hub can encode, most preferred first.
+///
+/// Advertised peer codecs the hub does not implement (e.g. `zstd`) are
+/// ignored during negotiation.
+pub const HUB_CODEC_PREFERENCE: &[CompressionCodec] =
+    &[CompressionCodec::Deflate, CompressionCodec::Gzip];
+
+impl CompressionCodec {
+    /// Parse a codec name as advertised by a peer.
+    ///
+    /// Returns `None` for codecs the hub does not implement.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "none" | "identity" => Some(Self::None),
+            "gzip" => Some(Self::Gzip),
+            "deflate" => Some(Self::Deflate),
+            _ => None,
+        }
+    }
+
+    /// Wire name of this codec.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Gzip => "gzip",
+            Self::Deflate => "deflate",
+        }
+    }
+
+    /// Codec id carried in the high nibble of the frame flags byte.
+    #[must_use]
+    pub fn id(self) -> u8 {
+        match self {
+            Self::Gzip => 0,
+            Self::None => 1,
+            Self::Deflate => 2,
+        }
+    }
+
+    /// Inverse of [`CompressionCodec::id`].
+    #[must_use]
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Self::Gzip),
+            1 => Some(Self::None),
+            2 => Some(Self::Deflate),
+            _ => None,
+        }
+    }
+
+    /// Flags byte for a PTY frame compressed with this codec.
+    ///
+    /// Bit 0 marks the payload as compressed; the high nibble is the codec id.
+    #[must_use]
+    pub fn frame_flags(self) -> u8 {
+        (self.id() << 4) | 0x01
+    }
+
+    /// Compress `data` with this codec, ignoring any size threshold.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ChannelError::CompressionError` if encoding fails.
+    pub fn compress(self, data: &[u8]) -> Result<Vec<u8>, ChannelError> {
+        let encoded = match self {
+            Self::None => return Ok(data.to_vec()),
+            Self::Gzip => {
+                let mut encoder =
+                    GzEncoder::new(Vec::with_capacity(data.len() / 2), Compression::fast());
+                encoder.write_all(data).and_then(|()| encoder.finish())
+            }
+            Self::Deflate => {
+                let mut encoder =
+                    ZlibEncoder::new(Vec::with_capacity(data.len() / 2), Compression::fast());
+                encoder.write_all(data).and_then(|()| encoder.finish())
+            }
+        };
+        encoded.map_err(|e| ChannelError::CompressionError(format!("{} failed: {e}", self.name())))
+    }
+
+    /// Decompress `data` that was produced by [`CompressionCodec::compress`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `ChannelError::CompressionError` if decoding fails.
+    pub fn decompress(self, data: &[u8]) -> Result<Vec<u8>, ChannelError> {
+        let mut decompressed = Vec::new();
+        let result = match self {
+            Self::None => return Ok(data.to_vec()),
+            Self::Gzip => GzDecoder::new(data).read_to_end(&mut decompressed),
+            Self::Deflate => ZlibDecoder::new(data).read_to_end(&mut decompressed),
+        };
+        result.map_err(|e| {
+            ChannelError::CompressionError(format!("{} decompress failed: {e}", self.name()))
+        })?;
+        Ok(decompressed)
+    }
+}
+
+/// Pick the best codec supported by both the hub and a peer.
+///
+/// `advertised` is the peer's codec list from its offer. `None` means the
+/// peer did not advertise at all and gets the legacy gzip codec. When the
+/// peer advertised codecs but none match, compression is disabled.
+#[must_use]
+pub fn negotiate_codec(advertised: Option<&[String]>) -> CompressionCodec {
+    let Some(advertised) = advertised else {
+        return CompressionCodec::Gzip;
+    };
+
+    let peer: Vec<CompressionCodec> = advertised
+        .iter()
+        .filter_map(|name| CompressionCodec::from_name(name))
+        .collect();
+
+    HUB_CODEC_PREFERENCE
+        .iter()
+        .copied()
+        .find(|codec| peer.contains(codec))
+        .unwrap_or(CompressionCodec::None)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| (*s).to_string()).collect()
+    }
+
+    #[test]
+    fn test_negotiate_prefers_deflate() {
+        let advertised = names(&["gzip", "deflate", "zstd"]);
+        assert_eq!(
+            negotiate_codec(Some(&advertised)),
+            CompressionCodec::Deflate
+        );
+    }
+
+    #[test]
+    fn test_negotiate_without_advertisement_is_gzip() {
+        assert_eq!(negotiate_codec(None), CompressionCodec::Gzip);
+    }
+
+    #[test]
+    fn test_negotiate_no_mutual_codec_is_uncompressed() {
+        let advertised = names(&["zstd", "br"]);
+        assert_eq!(negotiate_codec(Some(&advertised)), CompressionCodec::None);
+        assert_eq!(negotiate_codec(Some(&[])), CompressionCodec::None);
+    }
+
+    #[test]
+    fn test_codec_roundtrip() {
+        let data: Vec<u8> = (0..10000).map(|i| (i % 256) as u8).collect();
+        for codec in [
+            CompressionCodec::None,
+            CompressionCodec::Gzip,
+            CompressionCodec::Deflate,
+        ] {
+            let encoded = codec.compress(&data).expect("compress");
+            assert_eq!(codec.decompress(&encoded).expect("decompress"), data);
+        }
+    }
+
+    #[test]
+    fn test_codec_frame_flags() {
+        // Gzip keeps the legacy 0x01 flag so old browsers still decode it.
+        assert_eq!(CompressionCodec::Gzip.frame_flags(), 0x01);
+        assert_eq!(CompressionCodec::Deflate.frame_flags(), 0x21);
+        for codec in [
+            CompressionCodec::None,
+            CompressionCodec::Gzip,
+            CompressionCodec::Deflate,
+        ] {
+            assert_eq!(
+                CompressionCodec::from_id(codec.frame_flags() >> 4),
+                Some(codec)
+            );
+        }
+    }
+
     #[test]
     fn test_roundtrip_uncompressed() {
         let data = b"hello world";
@@ -65,6 +65,9 @@ pub struct ChannelConfig {
     /// When set, subscribes to `hub:{hub_id}:browser:{identity}` instead of CLI stream.
     pub browser_identity: Option<String>,
     /// Whether to encrypt messages using E2E encryption.
+    ///
+    /// When set, `connect` fails with [`ChannelError::EncryptionError`] if the
+    /// channel has no crypto service, rather than relaying plaintext.
     pub encrypt: bool,
     /// Compression threshold in bytes. None disables compression.
     /// Payloads exceeding this size are gzip-compressed.
@@ -75,6 +78,36 @@ pub struct ChannelConfig {
     pub cli_subscription: bool,
 }
 
+impl ChannelConfig {
+    /// Fail closed when this channel must be end-to-end encrypted but has
+    /// no crypto service, and log which kind of channel is being set up.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ChannelError::EncryptionError`] if `encrypt` is set and
+    /// `has_crypto` is false.
+    pub fn ensure_e2e(&self, has_crypto: bool) -> Result<(), ChannelError> {
+        match (self.encrypt, has_crypto) {
+            (true, false) => Err(ChannelError::EncryptionError(format!(
+                "{} requires end-to-end encryption but no crypto service is available; \
+                 refusing to relay plaintext",
+                self.channel_name
+            ))),
+            (true, true) => {
+                log::info!("[{}] End-to-end encrypted", self.channel_name);
+                Ok(())
+            }
+            (false, _) => {
+                log::info!(
+                    "[{}] Not end-to-end encrypted (transport TLS only)",
+                    self.channel_name
+                );
+                Ok(())
+            }
+        }
+    }
+}
+
 /// Connection state for a channel.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ConnectionState {
@@ -276,6 +309,8 @@ impl SharedConnectionState {
 pub use action_cable::{
     ActionCableChannel, ActionCableChannelBuilder, ChannelReceiverHandle, ChannelSenderHandle,
 };
-pub use compression::{maybe_compress, maybe_decompress, should_compress_response};
+pub use compression::{
+    maybe_compress, maybe_decompress, negotiate_codec, should_compress_response, CompressionCodec,
+};
 pub use reliable::{ReliableMessage, ReliableReceiver, ReliableSender, ReliableSession};
-pub use webrtc::{WebRtcChannel, WebRtcChannelBuilder, WebRtcConfig, WebRtcSender};
+pub use webrtc::{WebRtcChannel, WebRtcChannelBuilder, WebRtcConfig, WebRtcError, WebRtcSender};
@@ -19,12 +19,30 @@
 //! - No custom reliable delivery needed (SCTP provides it natively)
 //! - Peer-to-peer when possible, TURN relay as fallback
 //! - Signaling (offer/answer/ICE) via ActionCable, E2E encrypted
+//!
+//! # Delivery Mode
+//!
+//! The browser opens a single `relay` channel with `ordered: true` and the
+//! default (unlimited) retransmits, and every frame type rides on it. None
+//! of them tolerate loss or reordering:
+//!
+//! - PTY output is an incremental VT byte stream, not a full screen. A
+//!   dropped frame leaves the browser terminal wrong until the next
+//!   resnapshot, and snapshots themselves are followed by deltas that
+//!   assume they arrived.
+//! - Stream multiplexer and file frames carry TCP and file bytes.
+//! - Input and control messages must arrive once and in order.
+//!
+//! An unordered or `maxRetransmits: 0` screen channel only pays off once
+//! there is a frame type the browser can apply independently of the ones
+//! before it (a self-contained screen state). Until then, keep one reliable
+//! ordered channel.
 
 use async_trait::async_trait;
 use mdns_sd::{HostnameResolutionEvent, ScopedIp, ServiceDaemon};
 use std::collections::{HashMap, HashSet};
 use std::net::IpAddr;
-use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU8, Ordering};
 use std::sync::Arc;
 use std::sync::OnceLock;
 use std::time::{Duration, Instant};
@@ -37,6 +55,7 @@ use rustrtc::{
     IceServer, PeerConnection, PeerConnectionEvent, RtcConfiguration, SdpType, SessionDescription,
 };
 
+use crate::hub::events::WebRtcPeerState;
 use crate::relay::crypto_service::CryptoService;
 use crate::relay::olm_crypto::{
     CONTENT_FILE, CONTENT_FILE_CHUNK, CONTENT_MSG, CONTENT_PTY, CONTENT_STREAM,
@@ -96,6 +115,39 @@ pub struct PtyInputIncoming {
     pub browser_identity: String,
 }
 
+impl PtyInputIncoming {
+    /// Whether this input is plain typed text (no control bytes or escape
+    /// sequences), and so can be merged with neighbouring text.
+    fn is_plain_text(&self) -> bool {
+        !self.data.is_empty()
+            && self.data.iter().all(|&b| b >= 0x20 && b != 0x7f)
+            && std::str::from_utf8(&self.data).is_ok()
+    }
+
+    /// Merge runs of plain-text inputs into single writes, preserving order.
+    ///
+    /// Only consecutive inputs from the same browser to the same session are
+    /// merged. Control keys (Ctrl+C, Enter, arrows) and focus events always
+    /// stay as their own input, so they split a run rather than joining it.
+    pub fn coalesce(inputs: impl IntoIterator<Item = Self>) -> Vec<Self> {
+        let mut merged: Vec<Self> = Vec::new();
+        for input in inputs {
+            if let Some(last) = merged.last_mut() {
+                if last.is_plain_text()
+                    && input.is_plain_text()
+                    && last.session_uuid == input.session_uuid
+                    && last.browser_identity == input.browser_identity
+                {
+                    last.data.extend_from_slice(&input.data);
+                    continue;
+                }
+            }
+            merged.push(input);
+        }
+        merged
+    }
+}
+
 /// Incoming file from browser via binary DataChannel frame.
 ///
 /// Parsed from `CONTENT_FILE`. The browser sends image/file data
@@ -132,43 +184,64 @@ struct FileChunkAssembly {
     data: Vec<u8>,
 }
 
-use super::compression::maybe_compress;
+use super::compression::CompressionCodec;
 use super::{
     Channel, ChannelConfig, ChannelError, ConnectionState, IncomingMessage, PeerId,
     SharedConnectionState,
 };
 
 /// Prepare a PTY payload for wire transmission, honoring an optional compression
-/// threshold and passing pre-gzipped payloads through unchanged.
+/// threshold and the codec negotiated with the peer.
+///
+/// Returns the payload and the frame flags byte (`0x00` when sent raw,
+/// otherwise [`CompressionCodec::frame_flags`]).
 ///
 /// Callers (e.g. `queue_webrtc_terminal_snapshot`) may pre-compress very large
 /// blobs — the raw snapshot can exceed SCTP's negotiated max message size, and
 /// compressing inside `send_pty_raw` happens too late to save it. Pre-gzipped
 /// data starts with the gzip magic bytes `1f 8b`; routing prefixes for live
 /// output (`0x01`) and snapshots (`0x02`) are disjoint from that magic, so a
-/// byte-sniff is unambiguous.
+/// byte-sniff is unambiguous. Pre-gzipped payloads pass through unchanged for
+/// gzip peers and are transcoded for peers that negotiated another codec.
 fn pty_payload_with_compression<'a>(
     data: &'a [u8],
     threshold: Option<usize>,
-) -> Result<(std::borrow::Cow<'a, [u8]>, bool), ChannelError> {
-    // Pre-gzipped: pass through as compressed, skip re-gzip.
+    codec: CompressionCodec,
+) -> Result<(std::borrow::Cow<'a, [u8]>, u8), ChannelError> {
     if data.len() >= 2 && data[0] == 0x1f && data[1] == 0x8b {
-        return Ok((std::borrow::Cow::Borrowed(data), true));
+        // Pre-gzipped: pass through as compressed, skip re-gzip.
+        if codec == CompressionCodec::Gzip {
+            return Ok((std::borrow::Cow::Borrowed(data), codec.frame_flags()));
+        }
+        let raw = CompressionCodec::Gzip.decompress(data)?;
+        if codec == CompressionCodec::None {
+            return Ok((std::borrow::Cow::Owned(raw), 0x00));
+        }
+        let encoded = codec.compress(&raw)?;
+        return Ok((std::borrow::Cow::Owned(encoded), codec.frame_flags()));
     }
 
     let Some(threshold) = threshold else {
-        return Ok((std::borrow::Cow::Borrowed(data), false));
+        return Ok((std::borrow::Cow::Borrowed(data), 0x00));
     };
+    if codec == CompressionCodec::None || data.len() < threshold {
+        return Ok((std::borrow::Cow::Borrowed(data), 0x00));
+    }
 
-    let compressed = maybe_compress(data, Some(threshold))
-        .map_err(|e| ChannelError::CompressionError(e.to_string()))?;
-    if compressed[0] == 0x1f {
-        Ok((std::borrow::Cow::Owned(compressed[1..].to_vec()), true))
+    // Only use compressed if actually smaller.
+    let compressed = codec.compress(data)?;
+    if compressed.len() < data.len() {
+        Ok((std::borrow::Cow::Owned(compressed), codec.frame_flags()))
     } else {
-        Ok((std::borrow::Cow::Borrowed(data), false))
+        Ok((std::borrow::Cow::Borrowed(data), 0x00))
     }
 }
 
+/// Load a [`CompressionCodec`] stored by id, defaulting to gzip.
+fn decode_codec(cell: &AtomicU8) -> CompressionCodec {
+    CompressionCodec::from_id(cell.load(Ordering::Relaxed)).unwrap_or_default()
+}
+
 /// Internal message for the receive queue.
 #[derive(Debug)]
 pub(crate) struct RawIncoming {
@@ -191,6 +264,78 @@ pub enum OutgoingSignal {
     },
 }
 
+/// Failure while negotiating a WebRTC connection with a browser.
+///
+/// Returned by the signaling entry points ([`WebRtcChannel::handle_sdp_offer`],
+/// [`WebRtcChannel::handle_ice_candidate`]) so callers can tell a bad offer
+/// apart from a missing or closed connection. [`Self::user_message`] is what
+/// the browser is shown; `Display` keeps the technical detail for logs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebRtcError {
+    /// The hub has no WebRTC channel for this browser.
+    SessionNotFound,
+    /// The offer or ICE candidate could not be parsed.
+    InvalidSdp(String),
+    /// No peer connection exists yet (offer not handled).
+    NotConnected,
+    /// The channel was closed.
+    ChannelClosed,
+    /// Negotiation failed (ICE config, descriptions, candidates).
+    Signaling(String),
+}
+
+impl WebRtcError {
+    /// Stable machine-readable code sent to the browser.
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::SessionNotFound => "session_not_found",
+            Self::InvalidSdp(_) => "invalid_sdp",
+            Self::NotConnected => "not_connected",
+            Self::ChannelClosed => "channel_closed",
+            Self::Signaling(_) => "signaling_failed",
+        }
+    }
+
+    /// Human-readable explanation for the browser.
+    #[must_use]
+    pub fn user_message(&self) -> &'static str {
+        match self {
+            Self::SessionNotFound => {
+                "The hub has no connection for this browser. Reconnecting should fix this."
+            }
+            Self::InvalidSdp(_) => "The hub could not read the connection offer from this browser.",
+            Self::NotConnected => "The hub has not finished setting up this connection yet.",
+            Self::ChannelClosed => "The connection to the hub was closed.",
+            Self::Signaling(_) => "The hub could not establish a direct connection. It will retry.",
+        }
+    }
+}
+
+impl std::fmt::Display for WebRtcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SessionNotFound => write!(f, "No WebRTC session for browser"),
+            Self::InvalidSdp(msg) => write!(f, "Invalid SDP: {msg}"),
+            Self::NotConnected => write!(f, "No peer connection"),
+            Self::ChannelClosed => write!(f, "WebRTC channel closed"),
+            Self::Signaling(msg) => write!(f, "Signaling failed: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for WebRtcError {}
+
+impl From<ChannelError> for WebRtcError {
+    fn from(error: ChannelError) -> Self {
+        match error {
+            ChannelError::Closed => Self::ChannelClosed,
+            ChannelError::NoSession(_) => Self::SessionNotFound,
+            other => Self::Signaling(other.to_string()),
+        }
+    }
+}
+
 /// Configuration for WebRTC signaling.
 #[derive(Clone, Debug)]
 pub struct WebRtcConfig {
@@ -214,6 +359,7 @@ pub struct WebRtcChannelBuilder {
     pty_input_tx: Option<mpsc::Sender<PtyInputIncoming>>,
     file_input_tx: Option<mpsc::Sender<FileInputIncoming>>,
     hub_event_tx: Option<crate::hub::events::HubEventTx>,
+    ice_servers: Vec<crate::config::IceServerConfig>,
 }
 
 impl std::fmt::Debug for WebRtcChannelBuilder {
@@ -226,6 +372,7 @@ impl std::fmt::Debug for WebRtcChannelBuilder {
             .field("pty_input_tx", &self.pty_input_tx.is_some())
             .field("file_input_tx", &self.file_input_tx.is_some())
             .field("hub_event_tx", &self.hub_event_tx.is_some())
+            .field("ice_servers", &self.ice_servers.len())
             .finish()
     }
 }
@@ -241,6 +388,7 @@ impl Default for WebRtcChannelBuilder {
             pty_input_tx: None,
             file_input_tx: None,
             hub_event_tx: None,
+            ice_servers: Vec::new(),
         }
     }
 }
@@ -308,6 +456,13 @@ impl WebRtcChannelBuilder {
         self
     }
 
+    /// Set extra STUN/TURN servers used alongside the server-provided ones.
+    #[must_use]
+    pub fn ice_servers(mut self, servers: Vec<crate::config::IceServerConfig>) -> Self {
+        self.ice_servers = servers;
+        self
+    }
+
     /// Build the channel.
     ///
     /// # Panics
@@ -324,6 +479,7 @@ impl WebRtcChannelBuilder {
             stream_frame_tx: self.stream_frame_tx,
             pty_input_tx: self.pty_input_tx,
             file_input_tx: self.file_input_tx,
+            configured_ice_servers: self.ice_servers,
             peer_connection: Arc::new(Mutex::new(None)),
             data_channel: Arc::new(Mutex::new(None)),
             data_channel_id: Arc::new(Mutex::new(None)),
@@ -334,6 +490,7 @@ impl WebRtcChannelBuilder {
             recv_tx: Arc::new(Mutex::new(None)),
             peer_olm_key: Arc::new(Mutex::new(None)),
             decrypt_failures: Arc::new(AtomicU32::new(0)),
+            compression_codec: Arc::new(AtomicU8::new(CompressionCodec::default().id())),
             dc_opened: Arc::new(AtomicBool::new(false)),
             hub_event_tx: self.hub_event_tx,
             close_complete_tx: close_tx,
@@ -361,6 +518,8 @@ pub struct WebRtcChannel {
     pty_input_tx: Option<mpsc::Sender<PtyInputIncoming>>,
     /// Sender for incoming file transfers from browser.
     file_input_tx: Option<mpsc::Sender<FileInputIncoming>>,
+    /// User-configured STUN/TURN servers, appended to the fetched ICE config.
+    configured_ice_servers: Vec<crate::config::IceServerConfig>,
     /// WebRTC peer connection (rustrtc — Clone wraps Arc internally).
     peer_connection: Arc<Mutex<Option<PeerConnection>>>,
     /// WebRTC data channel (set by event loop when browser creates it).
@@ -381,6 +540,8 @@ pub struct WebRtcChannel {
     peer_olm_key: Arc<Mutex<Option<String>>>,
     /// Consecutive decryption failure count for session health monitoring.
     decrypt_failures: Arc<AtomicU32>,
+    /// Negotiated [`CompressionCodec`] id for PTY frames to this peer.
+    compression_codec: Arc<AtomicU8>,
     /// Set to `true` when the DataChannel opens; consumed by `take_dc_opened()`.
     /// Kept as test-only fallback when `hub_event_tx` is None.
     dc_opened: Arc<AtomicBool>,
@@ -614,12 +775,17 @@ impl WebRtcChannel {
         }
     }
 
-    /// Create the WebRTC peer connection.
-    fn create_peer_connection(
-        &self,
-        ice_servers: Vec<IceServer>,
-    ) -> Result<PeerConnection, ChannelError> {
-        let config = RtcConfiguration {
+    /// Build the peer connection configuration from the fetched ICE servers
+    /// plus any configured in `ice_servers` in the user's config.
+    fn rtc_configuration(&self, mut ice_servers: Vec<IceServer>) -> RtcConfiguration {
+        ice_servers.extend(self.configured_ice_servers.iter().map(|server| IceServer {
+            urls: vec![server.urls.clone()],
+            username: server.username.clone(),
+            credential: server.credential.clone(),
+            credential_type: rustrtc::IceCredentialType::Password,
+        }));
+
+        RtcConfiguration {
             ice_servers,
             // Reduce STUN/TURN probe timeout from default 5s to 2s.
             // Failed probes (unreachable STUN/TURN servers) block ICE gathering
@@ -629,19 +795,33 @@ impl WebRtcChannel {
             // default 30s, so failed ICE connections are detected promptly.
             ice_connection_timeout: Duration::from_secs(15),
             ..Default::default()
-        };
+        }
+    }
 
-        Ok(PeerConnection::new(config))
+    /// Create the WebRTC peer connection.
+    fn create_peer_connection(
+        &self,
+        ice_servers: Vec<IceServer>,
+    ) -> Result<PeerConnection, ChannelError> {
+        Ok(PeerConnection::new(self.rtc_configuration(ice_servers)))
     }
 
     /// Handle incoming SDP offer from browser and create answer.
     ///
     /// Called when CLI receives an encrypted offer via ActionCable signal channel.
+    ///
+    /// # Errors
+    ///
+    /// [`WebRtcError::InvalidSdp`] if the offer does not parse (checked before
+    /// any network work), [`WebRtcError::Signaling`] if negotiation fails.
     pub async fn handle_sdp_offer(
         &self,
         sdp: &str,
         browser_identity: &str,
-    ) -> Result<String, ChannelError> {
+    ) -> Result<String, WebRtcError> {
+        let offer = SessionDescription::parse(SdpType::Offer, sdp)
+            .map_err(|e| WebRtcError::InvalidSdp(format!("offer: {e}")))?;
+
         // Check for existing connection
         let mut pc_guard = self.peer_connection.lock().await;
 
@@ -656,19 +836,17 @@ impl WebRtcChannel {
 
             log::info!("[WebRTC] Applying ICE restart offer on existing connection");
 
-            let offer = SessionDescription::parse(SdpType::Offer, sdp)
-                .map_err(|e| ChannelError::ConnectionFailed(format!("Invalid SDP offer: {e}")))?;
-
             pc.set_remote_description(offer).await.map_err(|e| {
-                ChannelError::ConnectionFailed(format!("Failed to set remote description: {e}"))
+                WebRtcError::Signaling(format!("Failed to set remote description: {e}"))
             })?;
 
-            let answer = pc.create_answer().await.map_err(|e| {
-                ChannelError::ConnectionFailed(format!("Failed to create answer: {e}"))
-            })?;
+            let answer = pc
+                .create_answer()
+                .await
+                .map_err(|e| WebRtcError::Signaling(format!("Failed to create answer: {e}")))?;
 
             pc.set_local_description(answer.clone()).map_err(|e| {
-                ChannelError::ConnectionFailed(format!("Failed to set local description: {e}"))
+                WebRtcError::Signaling(format!("Failed to set local description: {e}"))
             })?;
 
             let mut sdp = answer.to_sdp_string();
@@ -696,23 +874,19 @@ impl WebRtcChannel {
         // Create peer connection (sync — no MediaEngine/Registry/APIBuilder boilerplate)
         let pc = self.create_peer_connection(ice_servers)?;
 
-        // Parse and set remote description (offer from browser)
-        let offer = SessionDescription::parse(SdpType::Offer, sdp)
-            .map_err(|e| ChannelError::ConnectionFailed(format!("Invalid SDP offer: {e}")))?;
-
+        // Set remote description (offer from browser)
         pc.set_remote_description(offer).await.map_err(|e| {
-            ChannelError::ConnectionFailed(format!("Failed to set remote description: {e}"))
+            WebRtcError::Signaling(format!("Failed to set remote description: {e}"))
         })?;
 
         // Create and set local description (answer)
         let answer = pc
             .create_answer()
             .await
-            .map_err(|e| ChannelError::ConnectionFailed(format!("Failed to create answer: {e}")))?;
+            .map_err(|e| WebRtcError::Signaling(format!("Failed to create answer: {e}")))?;
 
-        pc.set_local_description(answer.clone()).map_err(|e| {
-            ChannelError::ConnectionFailed(format!("Failed to set local description: {e}"))
-        })?;
+        pc.set_local_description(answer.clone())
+            .map_err(|e| WebRtcError::Signaling(format!("Failed to set local description: {e}")))?;
 
         // Store the peer's Olm key for encrypt routing.
         {
@@ -976,12 +1150,29 @@ impl WebRtcChannel {
                     _ = peer_state_rx.changed() => {
                         let s = *peer_state_rx.borrow();
                         log::info!("[WebRTC] Connection state changed: {s:?}");
+                        let peer_state = match s {
+                            rustrtc::PeerConnectionState::Connected => {
+                                Some(WebRtcPeerState::Connected)
+                            }
+                            rustrtc::PeerConnectionState::Disconnected => {
+                                Some(WebRtcPeerState::Disconnected)
+                            }
+                            rustrtc::PeerConnectionState::Failed => Some(WebRtcPeerState::Failed),
+                            _ => None,
+                        };
                         match s {
                             rustrtc::PeerConnectionState::Connected => {
                                 state.set(ConnectionState::Connected).await;
+                                notify_peer_state(&hub_event_tx, &browser_id, peer_state);
                             }
                             rustrtc::PeerConnectionState::Disconnected
                             | rustrtc::PeerConnectionState::Failed => {
+                                if matches!(s, rustrtc::PeerConnectionState::Failed) {
+                                    log::warn!(
+                                        "[WebRTC] ICE failed for {}; if the browser can't reach this machine directly, add a TURN server to `ice_servers` in config.json",
+                                        &browser_id[..browser_id.len().min(8)]
+                                    );
+                                }
                                 state.set(ConnectionState::Disconnected).await;
                                 data_channel.lock().await.take();
                                 data_channel_id.lock().await.take();
@@ -990,6 +1181,7 @@ impl WebRtcChannel {
                                     pc.close();
                                 }
                                 let _ = close_complete.send(true);
+                                notify_peer_state(&hub_event_tx, &browser_id, peer_state);
                                 break;
                             }
                             rustrtc::PeerConnectionState::Closed => {
@@ -1028,16 +1220,20 @@ impl WebRtcChannel {
     }
 
     /// Handle incoming ICE candidate from browser.
+    ///
+    /// # Errors
+    ///
+    /// [`WebRtcError::NotConnected`] before an offer has been handled,
+    /// [`WebRtcError::InvalidSdp`] for an unparseable candidate, and
+    /// [`WebRtcError::Signaling`] if the peer connection rejects it.
     pub async fn handle_ice_candidate(
         &self,
         candidate: &str,
         _sdp_mid: Option<&str>,
         _sdp_mline_index: Option<u16>,
-    ) -> Result<(), ChannelError> {
+    ) -> Result<(), WebRtcError> {
         let pc_guard = self.peer_connection.lock().await;
-        let pc = pc_guard
-            .as_ref()
-            .ok_or_else(|| ChannelError::ConnectionFailed("No peer connection".to_string()))?;
+        let pc = pc_guard.as_ref().ok_or(WebRtcError::NotConnected)?;
 
         // Parse the candidate SDP string (browser sends "candidate:..." format)
         let sdp_str = candidate.trim_start_matches("candidate:");
@@ -1069,22 +1265,21 @@ impl WebRtcChannel {
                             parsed
                         }
                         Err(original_err) => {
-                            return Err(ChannelError::ConnectionFailed(format!(
-                                "Failed to parse ICE candidate: rewritten={parse_err}; original={original_err}"
+                            return Err(WebRtcError::InvalidSdp(format!(
+                                "ICE candidate: rewritten={parse_err}; original={original_err}"
                             )));
                         }
                     }
                 } else {
-                    return Err(ChannelError::ConnectionFailed(format!(
-                        "Failed to parse ICE candidate: {parse_err}"
+                    return Err(WebRtcError::InvalidSdp(format!(
+                        "ICE candidate: {parse_err}"
                     )));
                 }
             }
         };
 
-        pc.add_ice_candidate(ice_candidate).map_err(|e| {
-            ChannelError::ConnectionFailed(format!("Failed to add ICE candidate: {e}"))
-        })?;
+        pc.add_ice_candidate(ice_candidate)
+            .map_err(|e| WebRtcError::Signaling(format!("Failed to add ICE candidate: {e}")))?;
 
         Ok(())
     }
@@ -1229,7 +1424,11 @@ impl WebRtcChannel {
 
 #[cfg(test)]
 mod tests {
-    use super::WebRtcChannel;
+    use super::{
+        pty_payload_with_compression, CompressionCodec, PtyInputIncoming, WebRtcChannel,
+        WebRtcError,
+    };
+    use crate::channel::{Channel, ChannelConfig, ChannelError};
     use mdns_sd::ScopedIp;
     use std::collections::HashSet;
     use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
@@ -1257,6 +1456,156 @@ mod tests {
         );
     }
 
+    fn key(session_uuid: &str, data: &[u8]) -> PtyInputIncoming {
+        PtyInputIncoming {
+            session_uuid: session_uuid.to_string(),
+            data: data.to_vec(),
+            browser_identity: "browser-a".to_string(),
+        }
+    }
+
+    #[test]
+    fn coalesce_merges_plain_characters_and_splits_on_ctrl_c() {
+        let inputs = [b"l", b"s", b" ", b"-", b"\x03", b"p", b"w"]
+            .into_iter()
+            .map(|data| key("sess-1", data));
+
+        let merged: Vec<Vec<u8>> = PtyInputIncoming::coalesce(inputs)
+            .into_iter()
+            .map(|input| input.data)
+            .collect();
+
+        assert_eq!(
+            merged,
+            vec![b"ls -".to_vec(), b"\x03".to_vec(), b"pw".to_vec()]
+        );
+    }
+
+    #[test]
+    fn coalesce_keeps_escape_sequences_and_other_sessions_separate() {
+        let mut other_browser = key("sess-1", b"z");
+        other_browser.browser_identity = "browser-b".to_string();
+        let inputs = vec![
+            key("sess-1", b"\x1b[I"),
+            key("sess-1", b"a"),
+            key("sess-1", b"\x1b[A"),
+            key("sess-1", "é".as_bytes()),
+            key("sess-2", b"b"),
+            other_browser,
+            key("sess-1", b"c"),
+            key("sess-1", b"\r"),
+        ];
+
+        let merged: Vec<(String, Vec<u8>)> = PtyInputIncoming::coalesce(inputs)
+            .into_iter()
+            .map(|input| (input.session_uuid, input.data))
+            .collect();
+
+        assert_eq!(merged.len(), 8, "nothing here should merge: {merged:?}");
+    }
+
+    #[test]
+    fn peer_state_change_is_reported_to_hub() {
+        use crate::hub::events::{HubEvent, WebRtcPeerState};
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let hub_event_tx = Some(tx.into());
+
+        super::notify_peer_state(&hub_event_tx, "olmkey:tab", Some(WebRtcPeerState::Failed));
+        match rx.try_recv() {
+            Ok(HubEvent::WebRtcConnectionChanged {
+                browser_identity,
+                state,
+            }) => {
+                assert_eq!(browser_identity, "olmkey:tab");
+                assert_eq!(state, WebRtcPeerState::Failed);
+            }
+            other => panic!("expected WebRtcConnectionChanged, got {other:?}"),
+        }
+
+        // States the Hub doesn't act on (e.g. Connecting) aren't reported
+        super::notify_peer_state(&hub_event_tx, "olmkey:tab", None);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn configured_turn_server_is_added_to_rtc_configuration() {
+        let channel = WebRtcChannel::builder()
+            .server_url("https://example.com")
+            .api_key("btstr_test")
+            .ice_servers(vec![crate::config::IceServerConfig {
+                urls: "turn:turn.example.com:3478".to_string(),
+                username: Some("user".to_string()),
+                credential: Some("secret".to_string()),
+            }])
+            .build();
+
+        let fetched = vec![rustrtc::IceServer {
+            urls: vec!["stun:stun.example.com:3478".to_string()],
+            username: None,
+            credential: None,
+            credential_type: rustrtc::IceCredentialType::Password,
+        }];
+        let config = channel.rtc_configuration(fetched);
+
+        assert_eq!(config.ice_servers.len(), 2);
+        assert_eq!(config.ice_servers[0].urls, ["stun:stun.example.com:3478"]);
+        let turn = &config.ice_servers[1];
+        assert_eq!(turn.urls, ["turn:turn.example.com:3478"]);
+        assert_eq!(turn.username.as_deref(), Some("user"));
+        assert_eq!(turn.credential.as_deref(), Some("secret"));
+    }
+
+    #[tokio::test]
+    async fn invalid_sdp_offer_is_rejected_before_negotiation() {
+        let channel = WebRtcChannel::builder().build();
+        let result = channel
+            .handle_sdp_offer("not-an-sdp-offer", "olmkey:tab")
+            .await;
+        assert!(
+            matches!(result, Err(WebRtcError::InvalidSdp(_))),
+            "expected InvalidSdp, got {result:?}"
+        );
+        assert_eq!(WebRtcError::InvalidSdp(String::new()).code(), "invalid_sdp");
+    }
+
+    #[tokio::test]
+    async fn encrypted_relay_without_crypto_service_fails_closed() {
+        let mut channel = WebRtcChannel::builder().build();
+        let result = channel
+            .connect(ChannelConfig {
+                channel_name: "WebRtcChannel".to_string(),
+                hub_id: "hub-1".to_string(),
+                browser_identity: Some("olmkey:tab".to_string()),
+                encrypt: true,
+                compression_threshold: Some(4096),
+                cli_subscription: false,
+            })
+            .await;
+
+        assert!(
+            matches!(result, Err(ChannelError::EncryptionError(_))),
+            "expected EncryptionError, got {result:?}"
+        );
+        assert!(
+            channel.config.lock().await.is_none(),
+            "a refused channel must not be configured for sending"
+        );
+    }
+
+    #[tokio::test]
+    async fn ice_candidate_before_offer_is_not_connected() {
+        let channel = WebRtcChannel::builder().build();
+        let result = channel
+            .handle_ice_candidate(
+                "candidate:1 1 udp 2122260223 192.168.1.2 54321 typ host",
+                Some("0"),
+                Some(0),
+            )
+            .await;
+        assert_eq!(result, Err(WebRtcError::NotConnected));
+    }
+
     #[test]
     fn mdns_hostname_candidates_are_detected() {
         let candidate =
@@ -1287,11 +1636,56 @@ mod tests {
             Some(IpAddr::V4(Ipv4Addr::LOCALHOST))
         );
     }
+
+    #[test]
+    fn deflate_only_peer_receives_deflate_frames() {
+        let advertised = vec!["deflate".to_string()];
+        let codec = crate::channel::negotiate_codec(Some(&advertised));
+        assert_eq!(codec, CompressionCodec::Deflate);
+
+        let data = "hello terminal ".repeat(1000).into_bytes();
+        let (payload, flags) =
+            pty_payload_with_compression(&data, Some(4096), codec).expect("compress");
+        assert_eq!(flags, CompressionCodec::Deflate.frame_flags());
+        assert_eq!(CompressionCodec::from_id(flags >> 4), Some(codec));
+        assert_eq!(codec.decompress(&payload).expect("inflate"), data);
+    }
+
+    #[test]
+    fn pre_gzipped_payload_is_transcoded_for_deflate_peer() {
+        let data = "snapshot ".repeat(1000).into_bytes();
+        let gzipped = CompressionCodec::Gzip.compress(&data).expect("gzip");
+
+        let (payload, flags) =
+            pty_payload_with_compression(&gzipped, Some(4096), CompressionCodec::Gzip)
+                .expect("passthrough");
+        assert_eq!(flags, 0x01);
+        assert_eq!(payload.as_ref(), gzipped.as_slice());
+
+        let (payload, flags) =
+            pty_payload_with_compression(&gzipped, Some(4096), CompressionCodec::Deflate)
+                .expect("transcode");
+        assert_eq!(flags, CompressionCodec::Deflate.frame_flags());
+        let inflated = CompressionCodec::Deflate
+            .decompress(&payload)
+            .expect("inflate");
+        assert_eq!(inflated, data);
+    }
+
+    #[test]
+    fn uncompressed_peer_gets_raw_frames() {
+        let data = "hello terminal ".repeat(1000).into_bytes();
+        let (payload, flags) =
+            pty_payload_with_compression(&data, Some(4096), CompressionCodec::None).expect("raw");
+        assert_eq!(flags, 0x00);
+        assert_eq!(payload.as_ref(), data.as_slice());
+    }
 }
 
 #[async_trait]
 impl Channel for WebRtcChannel {
     async fn connect(&mut self, config: ChannelConfig) -> Result<(), ChannelError> {
+        config.ensure_e2e(self.crypto_service.is_some())?;
         self.state.set(ConnectionState::Connecting).await;
 
         // Store config
@@ -1452,6 +1846,19 @@ impl WebRtcChannel {
         self.decrypt_failures.store(0, Ordering::Relaxed);
     }
 
+    /// Set the compression codec negotiated with this peer.
+    ///
+    /// Applies to PTY frames sent after the call, including via existing
+    /// [`WebRtcSender`] handles.
+    pub fn set_compression_codec(&self, codec: CompressionCodec) {
+        self.compression_codec.store(codec.id(), Ordering::Relaxed);
+    }
+
+    /// Compression codec currently used for PTY frames to this peer.
+    pub fn compression_codec(&self) -> CompressionCodec {
+        decode_codec(&self.compression_codec)
+    }
+
     /// Returns `true` exactly once after the DataChannel opens.
     ///
     /// Polled by the tick loop to fire `on_peer_connected` at the right time
@@ -1508,18 +1915,18 @@ impl WebRtcChannel {
             .as_ref()
             .ok_or_else(|| ChannelError::EncryptionError("No crypto service".into()))?;
 
-        // Compress raw bytes (gzip is very effective on terminal output)
+        // Compress raw bytes with the codec negotiated for this peer
         let config_guard = self.config.lock().await;
         let threshold = config_guard.as_ref().and_then(|c| c.compression_threshold);
         drop(config_guard);
 
-        let (payload, was_compressed) = pty_payload_with_compression(data, threshold)?;
+        let codec = decode_codec(&self.compression_codec);
+        let (payload, flags) = pty_payload_with_compression(data, threshold, codec)?;
 
         let peer_key = self.get_peer_olm_key().await?;
 
         // Build binary inner content: [CONTENT_PTY][flags][sub_id_len][sub_id][payload]
         let sub_bytes = subscription_id.as_bytes();
-        let flags: u8 = if was_compressed { 0x01 } else { 0x00 };
         let mut plaintext = Vec::with_capacity(3 + sub_bytes.len() + payload.len());
         plaintext.push(CONTENT_PTY);
         plaintext.push(flags);
@@ -1636,6 +2043,8 @@ pub struct WebRtcSender {
     config: Arc<Mutex<Option<ChannelConfig>>>,
     /// Peer's Olm identity key for encryption.
     peer_olm_key: Arc<Mutex<Option<String>>>,
+    /// Negotiated [`CompressionCodec`] id for PTY frames to this peer.
+    compression_codec: Arc<AtomicU8>,
 }
 
 impl std::fmt::Debug for WebRtcSender {
@@ -1676,12 +2085,12 @@ impl WebRtcSender {
         let threshold = config_guard.as_ref().and_then(|c| c.compression_threshold);
         drop(config_guard);
 
-        let (payload, was_compressed) = pty_payload_with_compression(data, threshold)?;
+        let codec = decode_codec(&self.compression_codec);
+        let (payload, flags) = pty_payload_with_compression(data, threshold, codec)?;
 
         let peer_key = self.get_peer_olm_key().await?;
 
         let sub_bytes = subscription_id.as_bytes();
-        let flags: u8 = if was_compressed { 0x01 } else { 0x00 };
         let mut plaintext = Vec::with_capacity(3 + sub_bytes.len() + payload.len());
         plaintext.push(CONTENT_PTY);
         plaintext.push(flags);
@@ -1831,6 +2240,7 @@ impl WebRtcChannel {
             crypto_service: self.crypto_service.clone(),
             config: Arc::clone(&self.config),
             peer_olm_key: Arc::clone(&self.peer_olm_key),
+            compression_codec: Arc::clone(&self.compression_codec),
         }
     }
 }
@@ -2194,6 +2604,20 @@ fn parse_pty_input_sub_id(
     })
 }
 
+/// Report a peer connection state change to the Hub, if it is listening.
+fn notify_peer_state(
+    hub_event_tx: &Option<crate::hub::events::HubEventTx>,
+    browser_identity: &str,
+    state: Option<WebRtcPeerState>,
+) {
+    if let (Some(tx), Some(state)) = (hub_event_tx, state) {
+        let _ = tx.send(crate::hub::events::HubEvent::WebRtcConnectionChanged {
+            browser_identity: browser_identity.to_string(),
+            state,
+        });
+    }
+}
+
 fn notify_ingress_backpressure(
     hub_event_tx: &Option<crate::hub::events::HubEventTx>,
     browser_identity: &str,
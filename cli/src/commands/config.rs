@@ -0,0 +1,274 @@
+//! `botster config` subcommand.
+//!
+//! Reads values from the loaded [`Config`] using the same dot-notation paths
+//! as [`super::json`]. The config is serialized to JSON first, so the keys
+//! are exactly the ones written to `config.json`. Fields left out of that
+//! JSON because they are unset (empty lists, `None`) still resolve, to
+//! `null`. A small set of top-level keys can also be changed (see
+//! [`crate::config::SETTABLE_KEYS`]).
+//!
+//! # Examples
+//!
+//! ```bash
+//! # Print the whole config
+//! botster config
+//!
+//! # Print a single value
+//! botster config poll_interval
+//! botster config profiles.codex.max_concurrent
+//!
+//! # Change a value
+//! botster config poll_interval 10
+//! ```
+
+use anyhow::Result;
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde_json::Value;
+
+use crate::config::{Config, IceServerConfig, ProfileLimits, RepoOverride};
+
+/// Key fragments whose values are never printed.
+const SECRET_KEY_MARKERS: &[&str] = &["token", "secret", "password", "credential", "api_key"];
+
+/// Placeholder printed instead of a secret value.
+const MASK: &str = "********";
+
+/// Prints the whole config as pretty JSON with secrets masked.
+///
+/// # Errors
+///
+/// Returns an error if the config cannot be serialized.
+pub fn show(config: &Config) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(&masked_json(config)?)?);
+    Ok(())
+}
+
+/// Prints the value at `key_path`.
+///
+/// Strings print bare so the output can be used in scripts; everything else
+/// prints as pretty JSON.
+///
+/// # Errors
+///
+/// Returns an error if no config key matches `key_path`.
+pub fn get(config: &Config, key_path: &str) -> Result<()> {
+    match resolve(config, key_path)? {
+        Value::String(s) => println!("{s}"),
+        value => println!("{}", serde_json::to_string_pretty(&value)?),
+    }
+    Ok(())
+}
+
+/// Sets `key` to `value` and writes it to `config.json`.
+///
+/// Only that key is rewritten; the rest of the file, including keys from
+/// Lua `config.set` or plugins and any environment overrides, is left as
+/// is. `api_key` goes to the keyring.
+///
+/// # Errors
+///
+/// Returns an error if the key is unknown, the value is invalid, or saving
+/// fails.
+pub fn set(key: &str, value: &str) -> Result<()> {
+    // Parses and validates `value` into its typed form.
+    let mut config = Config::default();
+    config.set(key, value)?;
+    if key == "api_key" {
+        config.save_token(value)?;
+        println!("Saved api_key to the keyring");
+    } else {
+        config.save_key(key)?;
+        println!("Set {key} = {value}");
+    }
+    Ok(())
+}
+
+/// Looks up `key_path` in the masked JSON form of `config`, with unset
+/// fields filled in as `null`.
+fn resolve(config: &Config, key_path: &str) -> Result<Value> {
+    let mut root = masked_json(config)?;
+    fill_known_fields::<Config>(&mut root);
+    for (key, fill) in [
+        (
+            "profiles",
+            fill_known_fields::<ProfileLimits> as fn(&mut Value),
+        ),
+        ("repos", fill_known_fields::<RepoOverride>),
+    ] {
+        if let Some(Value::Object(entries)) = root.get_mut(key) {
+            entries.values_mut().for_each(fill);
+        }
+    }
+    if let Some(Value::Array(servers)) = root.get_mut("ice_servers") {
+        servers
+            .iter_mut()
+            .for_each(fill_known_fields::<IceServerConfig>);
+    }
+    super::json::lookup(&root, key_path)
+        .cloned()
+        .map_err(|_| anyhow::anyhow!("No such config key: '{key_path}'"))
+}
+
+/// Serializes `config` with every secret value replaced by [`MASK`].
+fn masked_json(config: &Config) -> Result<Value> {
+    let mut value = serde_json::to_value(config)?;
+    mask_secrets(&mut value);
+    Ok(value)
+}
+
+fn mask_secrets(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                if is_secret_key(key) && !child.is_null() {
+                    *child = Value::String(MASK.to_string());
+                } else {
+                    mask_secrets(child);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(mask_secrets),
+        _ => {}
+    }
+}
+
+/// Adds a `null` entry to the object `value` for every field of `T` that
+/// serialization skipped.
+fn fill_known_fields<'de, T: Deserialize<'de>>(value: &mut Value) {
+    if let Value::Object(map) = value {
+        for field in struct_fields::<T>() {
+            map.entry(*field).or_insert(Value::Null);
+        }
+    }
+}
+
+/// Field names serde deserializes `T` from (fields marked `skip` are not
+/// included). Found by asking `T` to deserialize from a deserializer that
+/// only records the field list.
+fn struct_fields<'de, T: Deserialize<'de>>() -> &'static [&'static str] {
+    struct FieldNames<'a>(&'a mut &'static [&'static str]);
+
+    impl<'de> Deserializer<'de> for FieldNames<'_> {
+        type Error = de::value::Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+            Err(de::Error::custom("not a struct"))
+        }
+
+        fn deserialize_struct<V: Visitor<'de>>(
+            self,
+            _name: &'static str,
+            fields: &'static [&'static str],
+            _visitor: V,
+        ) -> Result<V::Value, Self::Error> {
+            *self.0 = fields;
+            Err(de::Error::custom("field names recorded"))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map enum identifier ignored_any
+        }
+    }
+
+    let mut fields: &'static [&'static str] = &[];
+    let _ = T::deserialize(FieldNames(&mut fields));
+    fields
+}
+
+fn is_secret_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    SECRET_KEY_MARKERS.iter().any(|marker| key.contains(marker))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_top_level_key() {
+        let config = Config {
+            poll_interval: 7,
+            ..Config::default()
+        };
+
+        assert_eq!(resolve(&config, "poll_interval").unwrap(), 7);
+    }
+
+    #[test]
+    fn test_get_nested_key() {
+        let mut config = Config::default();
+        config.profiles.insert(
+            "codex".to_string(),
+            ProfileLimits {
+                max_concurrent: Some(2),
+                ..ProfileLimits::default()
+            },
+        );
+
+        assert_eq!(
+            resolve(&config, "profiles.codex.max_concurrent").unwrap(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_get_unset_key_skipped_when_serializing() {
+        let mut config = Config::default();
+        config
+            .profiles
+            .insert("codex".to_string(), ProfileLimits::default());
+
+        assert_eq!(resolve(&config, "branch_template").unwrap(), Value::Null);
+        assert_eq!(resolve(&config, "allowed_repos").unwrap(), Value::Null);
+        assert_eq!(
+            resolve(&config, "profiles.codex.max_concurrent").unwrap(),
+            Value::Null
+        );
+        assert!(
+            resolve(&config, "token").is_err(),
+            "fields never serialized are not config keys"
+        );
+    }
+
+    #[test]
+    fn test_get_missing_key() {
+        let err = resolve(&Config::default(), "profiles.nope").unwrap_err();
+        assert_eq!(err.to_string(), "No such config key: 'profiles.nope'");
+    }
+
+    #[test]
+    fn test_ice_server_credentials_are_masked() {
+        let config = Config {
+            ice_servers: vec![IceServerConfig {
+                urls: "turn:turn.example.com:3478".to_string(),
+                username: Some("botster".to_string()),
+                credential: Some("hunter2".to_string()),
+            }],
+            ..Config::default()
+        };
+
+        let servers = resolve(&config, "ice_servers").unwrap();
+        assert_eq!(servers[0]["urls"], "turn:turn.example.com:3478");
+        assert_eq!(servers[0]["username"], "botster");
+        assert_eq!(servers[0]["credential"], MASK);
+        assert!(!masked_json(&config)
+            .unwrap()
+            .to_string()
+            .contains("hunter2"));
+    }
+
+    #[test]
+    fn test_secret_values_are_masked() {
+        let mut value = serde_json::json!({
+            "server_url": "https://trybotster.com",
+            "nested": { "api_key": "btstr_abc", "refresh_token": null },
+        });
+        mask_secrets(&mut value);
+
+        assert_eq!(value["server_url"], "https://trybotster.com");
+        assert_eq!(value["nested"]["api_key"], MASK);
+        assert!(value["nested"]["refresh_token"].is_null());
+    }
+}
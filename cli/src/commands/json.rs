@@ -8,6 +8,9 @@
 //!
 //! Keys are specified using dot notation: `projects.myproject.hasTrust`
 //!
+//! A numeric segment indexes into an array: `servers.0.name` is the `name` of
+//! the first entry in `servers`.
+//!
 //! # Examples
 //!
 //! ```bash
@@ -17,13 +20,17 @@
 //! # Set a value (creates intermediate objects if needed)
 //! botster json-set ~/.config/claude/projects.json "projects.myproject.hasTrust" "true"
 //!
+//! # Set a value, creating the file if it doesn't exist yet
+//! botster json-set --create ~/.config/claude/projects.json "projects.myproject.hasTrust" "true"
+//!
 //! # Delete a key
 //! botster json-delete ~/.config/claude/projects.json "projects.myproject.hasTrust"
 //! ```
 
 use anyhow::{Context, Result};
 use std::fs;
-use std::path::Path;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 /// Reads a value from a JSON file using dot-notation path.
 ///
@@ -48,50 +55,129 @@ pub fn get(file_path: &str, key_path: &str) -> Result<()> {
     let content = fs::read_to_string(Path::new(path.as_ref()))
         .with_context(|| format!("Failed to read {}", file_path))?;
 
-    let mut value: serde_json::Value = serde_json::from_str(&content)
+    let root: serde_json::Value = serde_json::from_str(&content)
         .with_context(|| format!("Failed to parse {} as JSON", file_path))?;
 
-    // Navigate through the key path
+    let value = lookup(&root, key_path)?;
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+/// Resolves a dot-notation path against an in-memory JSON value.
+///
+/// # Errors
+///
+/// Returns an error naming the first key in the path that does not exist, or
+/// the first array index that is out of range.
+pub fn lookup<'a>(root: &'a serde_json::Value, key_path: &str) -> Result<&'a serde_json::Value> {
+    let mut value = root;
     for key in key_path.split('.') {
-        value = value
-            .get(key)
-            .with_context(|| format!("Key '{}' not found in path '{}'", key, key_path))?
-            .clone();
+        value = match (value, array_index(key)) {
+            (serde_json::Value::Array(arr), Some(index)) => arr
+                .get(index)
+                .with_context(|| index_out_of_range(index, arr.len(), key_path))?,
+            _ => value
+                .get(key)
+                .with_context(|| format!("Key '{}' not found in path '{}'", key, key_path))?,
+        };
     }
+    Ok(value)
+}
 
-    println!("{}", serde_json::to_string_pretty(&value)?);
-    Ok(())
+/// Parses a path segment as an array index.
+fn array_index(key: &str) -> Option<usize> {
+    key.parse().ok()
+}
+
+/// Error message for an array index past the end of an array.
+fn index_out_of_range(index: usize, len: usize, key_path: &str) -> String {
+    format!(
+        "Index {} out of range in path '{}' (array has {} elements)",
+        index, key_path, len
+    )
+}
+
+/// Returns the slot for `key` under `parent`, creating it if needed.
+///
+/// Missing object keys are inserted as `null`. An array index may point at
+/// an existing element or one past the end, which appends a `null`; anything
+/// further is an error rather than a gap of `null`s.
+fn slot_mut<'a>(
+    parent: &'a mut serde_json::Value,
+    key: &str,
+    key_path: &str,
+) -> Result<&'a mut serde_json::Value> {
+    match (parent, array_index(key)) {
+        (serde_json::Value::Object(obj), _) => Ok(obj
+            .entry(key.to_string())
+            .or_insert(serde_json::Value::Null)),
+        (serde_json::Value::Array(arr), Some(index)) => {
+            if index > arr.len() {
+                anyhow::bail!(index_out_of_range(index, arr.len(), key_path));
+            }
+            if index == arr.len() {
+                arr.push(serde_json::Value::Null);
+            }
+            Ok(&mut arr[index])
+        }
+        _ => anyhow::bail!(
+            "Cannot set key '{}' - parent is not an object or array",
+            key
+        ),
+    }
 }
 
 /// Sets a value in a JSON file using dot-notation path.
 ///
 /// Navigates to the specified location in the JSON structure and sets the value.
-/// Creates intermediate objects if they don't exist. The value is parsed as JSON
-/// first; if parsing fails, it's treated as a string.
+/// Creates intermediate objects if they don't exist, and appends to an array
+/// when indexing one past its end. The value is parsed as JSON first; if
+/// parsing fails, it's treated as a string.
+///
+/// With `create`, a missing file (and its parent directories) is created
+/// starting from an empty object. Without it, a missing file is an error so
+/// typos in the path aren't silently turned into new files.
 ///
 /// # Errors
 ///
 /// Returns an error if:
 /// - The file cannot be read or written
+/// - The file does not exist and `create` is false
 /// - The file contains invalid JSON
-/// - An intermediate key exists but is not an object
+/// - The root is not an object or array
+/// - An array index is more than one past the end of its array
 ///
 /// # Examples
 ///
 /// ```ignore
 /// // Set a boolean value
-/// json::set("config.json", "settings.enabled", "true")?;
+/// json::set("config.json", "settings.enabled", "true", false)?;
 ///
 /// // Set an object value
-/// json::set("config.json", "settings.options", r#"{"key": "value"}"#)?;
+/// json::set("config.json", "settings.options", r#"{"key": "value"}"#, false)?;
+///
+/// // Set a field on the first array element
+/// json::set("config.json", "servers.0.name", "primary", false)?;
+///
+/// // Initialize a fresh file
+/// json::set("new/config.json", "settings.enabled", "true", true)?;
 /// ```
-pub fn set(file_path: &str, key_path: &str, new_value: &str) -> Result<()> {
+pub fn set(file_path: &str, key_path: &str, new_value: &str, create: bool) -> Result<()> {
     let path = shellexpand::tilde(file_path);
-    let content = fs::read_to_string(Path::new(path.as_ref()))
-        .with_context(|| format!("Failed to read {}", file_path))?;
+    let path = Path::new(path.as_ref());
 
-    let mut root: serde_json::Value = serde_json::from_str(&content)
-        .with_context(|| format!("Failed to parse {} as JSON", file_path))?;
+    let mut root: serde_json::Value = if create && !path.exists() {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        serde_json::json!({})
+    } else {
+        let content =
+            fs::read_to_string(path).with_context(|| format!("Failed to read {}", file_path))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {} as JSON", file_path))?
+    };
 
     // Parse the new value as JSON, fall back to string if parsing fails
     let parsed_value: serde_json::Value = serde_json::from_str(new_value)
@@ -99,38 +185,25 @@ pub fn set(file_path: &str, key_path: &str, new_value: &str) -> Result<()> {
 
     // Split the path and navigate/create structure
     let keys: Vec<&str> = key_path.split('.').collect();
+    let (last, parents) = keys.split_last().expect("split yields at least one key");
     let mut current = &mut root;
 
-    for (i, key) in keys.iter().enumerate() {
-        if i == keys.len() - 1 {
-            // Last key - set the value
-            if let Some(obj) = current.as_object_mut() {
-                obj.insert(key.to_string(), parsed_value.clone());
-            } else {
-                anyhow::bail!("Cannot set key '{}' - parent is not an object", key);
-            }
-        } else {
-            // Navigate/create intermediate objects
-            if !current.is_object() {
-                anyhow::bail!("Cannot navigate through '{}' - not an object", key);
-            }
-
-            let obj = current.as_object_mut().expect("checked is_object() above");
-
-            // If key doesn't exist or exists but isn't an object, create/replace with empty object
-            if !obj.contains_key(*key) || !obj[*key].is_object() {
-                obj.insert(key.to_string(), serde_json::json!({}));
-            }
-            current = obj.get_mut(*key).expect("key was just inserted if missing");
+    for (i, key) in parents.iter().enumerate() {
+        let slot = slot_mut(current, key, key_path)?;
+        // Replace anything that can't hold the next key with an empty object,
+        // keeping arrays when the next key indexes into them
+        let keep = slot.is_object() || (slot.is_array() && array_index(keys[i + 1]).is_some());
+        if !keep {
+            *slot = serde_json::json!({});
         }
+        current = slot;
     }
+    // Last key - set the value
+    *slot_mut(current, last, key_path)? = parsed_value;
 
     // Write back to file with pretty formatting
-    fs::write(
-        Path::new(path.as_ref()),
-        serde_json::to_string_pretty(&root)?,
-    )
-    .with_context(|| format!("Failed to write {}", file_path))?;
+    write_atomic(path, &serde_json::to_string_pretty(&root)?)
+        .with_context(|| format!("Failed to write {}", file_path))?;
 
     Ok(())
 }
@@ -146,13 +219,17 @@ pub fn set(file_path: &str, key_path: &str, new_value: &str) -> Result<()> {
 /// - The file cannot be read or written
 /// - The file contains invalid JSON
 /// - Attempting to delete the root object
-/// - An intermediate key is not an object
+/// - An intermediate key is not an object or array
+/// - An array index in the path is out of range
 ///
 /// # Examples
 ///
 /// ```ignore
 /// // Delete a nested key
 /// json::delete("config.json", "settings.deprecated_option")?;
+///
+/// // Remove the second array element
+/// json::delete("config.json", "servers.1")?;
 /// ```
 pub fn delete(file_path: &str, key_path: &str) -> Result<()> {
     let path = shellexpand::tilde(file_path);
@@ -173,43 +250,119 @@ pub fn delete(file_path: &str, key_path: &str) -> Result<()> {
 
     // Navigate to the parent of the key we want to delete
     for (i, key) in keys.iter().enumerate() {
-        if i == keys.len() - 1 {
-            // Last key - delete it
-            if let Some(obj) = current.as_object_mut() {
-                obj.remove(*key);
-            } else {
-                anyhow::bail!("Cannot delete key '{}' - parent is not an object", key);
+        let is_last = i == keys.len() - 1;
+        match (current, array_index(key)) {
+            (serde_json::Value::Array(arr), Some(index)) => {
+                if index >= arr.len() {
+                    anyhow::bail!(index_out_of_range(index, arr.len(), key_path));
+                }
+                if is_last {
+                    arr.remove(index);
+                    break;
+                }
+                current = &mut arr[index];
             }
-        } else {
-            // Navigate to next level
-            if !current.is_object() {
-                anyhow::bail!("Cannot navigate through '{}' - not an object", key);
+            (serde_json::Value::Object(obj), _) => {
+                if is_last {
+                    obj.remove(*key);
+                    break;
+                }
+                match obj.get_mut(*key) {
+                    Some(next) => current = next,
+                    // Key doesn't exist, nothing to delete (idempotent)
+                    None => return Ok(()),
+                }
             }
-
-            let obj = current.as_object_mut().expect("checked is_object() above");
-            if !obj.contains_key(*key) {
-                // Key doesn't exist, nothing to delete (idempotent)
-                return Ok(());
+            _ if is_last => {
+                anyhow::bail!("Cannot delete key '{}' - parent is not an object", key)
             }
-
-            current = obj.get_mut(*key).expect("checked contains_key() above");
+            _ => anyhow::bail!("Cannot navigate through '{}' - not an object", key),
         }
     }
 
     // Write back to file with pretty formatting
-    fs::write(
+    write_atomic(
         Path::new(path.as_ref()),
-        serde_json::to_string_pretty(&root)?,
+        &serde_json::to_string_pretty(&root)?,
     )
     .with_context(|| format!("Failed to write {}", file_path))?;
 
     Ok(())
 }
 
+/// Replaces the contents of `path` without ever exposing a partial file.
+///
+/// Writes to a uniquely named temporary file in the same directory, then
+/// renames it over `path`, so readers see either the old or the new content.
+/// A symlinked `path` is resolved first so the link survives and its target
+/// is updated. The temporary file is created with the original file's
+/// permissions, so a private file is never readable by others mid-write.
+fn write_atomic(path: &Path, contents: &str) -> Result<()> {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let permissions = fs::metadata(&path).ok().map(|m| m.permissions());
+    let (mut file, tmp_path) = create_temp_sibling(&path, permissions.as_ref())?;
+
+    let result = (|| -> Result<()> {
+        if let Some(permissions) = permissions {
+            // The create mode was narrowed by the umask; restore it exactly.
+            file.set_permissions(permissions)?;
+        }
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, &path)?;
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+/// Creates a new, uniquely named `.<name>.tmp.<pid>.<n>` file next to `path`.
+///
+/// On Unix the file is created with `permissions`' mode (subject to the
+/// umask) rather than the default.
+fn create_temp_sibling(
+    path: &Path,
+    permissions: Option<&fs::Permissions>,
+) -> Result<(fs::File, PathBuf)> {
+    static COUNTER: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+
+    let file_name = path
+        .file_name()
+        .with_context(|| format!("Not a file path: {}", path.display()))?;
+
+    loop {
+        let n = COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let mut tmp_name = std::ffi::OsString::from(".");
+        tmp_name.push(file_name);
+        tmp_name.push(format!(".tmp.{}.{}", std::process::id(), n));
+        let tmp_path = path.with_file_name(tmp_name);
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+            if let Some(permissions) = permissions {
+                options.mode(permissions.mode() & 0o7777);
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = permissions;
+
+        match options.open(&tmp_path) {
+            Ok(file) => return Ok((file, tmp_path)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::io::Write;
     use tempfile::NamedTempFile;
 
     fn create_test_file(content: &str) -> NamedTempFile {
@@ -252,7 +405,7 @@ mod tests {
         let file = create_test_file(r#"{"name": "old"}"#);
         let path = file.path().to_str().unwrap();
 
-        set(path, "name", "\"new\"").unwrap();
+        set(path, "name", "\"new\"", false).unwrap();
 
         let content = fs::read_to_string(path).unwrap();
         let parsed: serde_json::Value = serde_json::from_str(&content).unwrap();
@@ -264,7 +417,7 @@ mod tests {
         let file = create_test_file(r#"{}"#);
         let path = file.path().to_str().unwrap();
 
-        set(path, "a.b.c", "\"deep\"").unwrap();
+        set(path, "a.b.c", "\"deep\"", false).unwrap();
 
         let content = fs::read_to_string(path).unwrap();
         let parsed: serde_json::Value = serde_json::from_str(&content).unwrap();
@@ -276,13 +429,127 @@ mod tests {
         let file = create_test_file(r#"{"enabled": false}"#);
         let path = file.path().to_str().unwrap();
 
-        set(path, "enabled", "true").unwrap();
+        set(path, "enabled", "true", false).unwrap();
 
         let content = fs::read_to_string(path).unwrap();
         let parsed: serde_json::Value = serde_json::from_str(&content).unwrap();
         assert_eq!(parsed["enabled"], true);
     }
 
+    #[test]
+    fn test_set_create_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("nested/dir/config.json");
+        let path = file.to_str().unwrap();
+
+        set(path, "settings.enabled", "true", true).unwrap();
+
+        let content = fs::read_to_string(path).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(parsed, serde_json::json!({"settings": {"enabled": true}}));
+    }
+
+    #[test]
+    fn test_set_missing_file_without_create_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("config.json");
+        let path = file.to_str().unwrap();
+
+        let result = set(path, "settings.enabled", "true", false);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("Failed to read"));
+        assert!(!file.exists());
+    }
+
+    #[test]
+    fn test_set_never_exposes_partial_file() {
+        let old_value = "a".repeat(1 << 20);
+        let new_value = "b".repeat(1 << 20);
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("state.json");
+        fs::write(&file, serde_json::json!({ "data": old_value }).to_string()).unwrap();
+        let path = file.to_str().unwrap().to_string();
+
+        let done = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let reader = {
+            let done = std::sync::Arc::clone(&done);
+            let file = file.clone();
+            let (old_value, new_value) = (old_value.clone(), new_value.clone());
+            std::thread::spawn(move || {
+                while !done.load(std::sync::atomic::Ordering::Relaxed) {
+                    let content = fs::read_to_string(&file).unwrap();
+                    let parsed: serde_json::Value = serde_json::from_str(&content)
+                        .expect("reader saw a partially written file");
+                    let data = parsed["data"].as_str().unwrap();
+                    assert!(data == old_value || data == new_value);
+                }
+            })
+        };
+
+        for i in 0..10 {
+            let value = if i % 2 == 0 { &new_value } else { &old_value };
+            set(&path, "data", value, false).unwrap();
+        }
+        done.store(true, std::sync::atomic::Ordering::Relaxed);
+        reader.join().unwrap();
+
+        // Only the target file remains; no temp files left behind
+        let entries: Vec<_> = fs::read_dir(dir.path()).unwrap().collect();
+        assert_eq!(entries.len(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_set_preserves_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let file = create_test_file(r#"{"name": "old"}"#);
+        let path = file.path().to_str().unwrap();
+        fs::set_permissions(path, fs::Permissions::from_mode(0o640)).unwrap();
+
+        set(path, "name", "\"new\"", false).unwrap();
+
+        let mode = fs::metadata(path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_set_keeps_symlink() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let target = dir.path().join("real.json");
+        let link = dir.path().join("link.json");
+        fs::write(&target, r#"{"name": "old"}"#).unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        set(link.to_str().unwrap(), "name", "\"new\"", false).unwrap();
+
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        let parsed: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&target).unwrap()).unwrap();
+        assert_eq!(parsed["name"], "new");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_temp_file_is_created_with_target_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("secrets.json");
+        let permissions = fs::Permissions::from_mode(0o600);
+
+        let (_file, tmp_path) = create_temp_sibling(&path, Some(&permissions)).unwrap();
+        let (_other, other_path) = create_temp_sibling(&path, Some(&permissions)).unwrap();
+
+        assert_ne!(tmp_path, other_path);
+        let mode = fs::metadata(&tmp_path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o077, 0, "not readable by group or others");
+    }
+
     #[test]
     fn test_delete_key() {
         let file = create_test_file(r#"{"keep": 1, "remove": 2}"#);
@@ -319,6 +586,86 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_lookup_array_element() {
+        let root = serde_json::json!({"projects": {"list": [{"name": "a"}, {"name": "b"}]}});
+
+        assert_eq!(lookup(&root, "projects.list.1.name").unwrap(), "b");
+    }
+
+    #[test]
+    fn test_get_array_index_out_of_range() {
+        let file = create_test_file(r#"{"list": [1, 2]}"#);
+        let path = file.path().to_str().unwrap();
+
+        let result = get(path, "list.2");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("out of range"));
+    }
+
+    #[test]
+    fn test_set_array_element() {
+        let file = create_test_file(r#"{"servers": [{"name": "old"}, {"name": "other"}]}"#);
+        let path = file.path().to_str().unwrap();
+
+        set(path, "servers.0.name", "\"new\"", false).unwrap();
+
+        let content = fs::read_to_string(path).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(parsed["servers"][0]["name"], "new");
+        assert_eq!(parsed["servers"][1]["name"], "other");
+    }
+
+    #[test]
+    fn test_set_array_index_at_end_appends() {
+        let file = create_test_file(r#"{"list": [1]}"#);
+        let path = file.path().to_str().unwrap();
+
+        set(path, "list.1", "2", false).unwrap();
+
+        let content = fs::read_to_string(path).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(parsed["list"], serde_json::json!([1, 2]));
+    }
+
+    #[test]
+    fn test_set_array_index_past_end_fails() {
+        let file = create_test_file(r#"{"list": [1]}"#);
+        let path = file.path().to_str().unwrap();
+
+        let max = format!("list.{}", usize::MAX);
+        for key in ["list.3", "list.4000000000", max.as_str()] {
+            let err = set(path, key, "4", false).unwrap_err();
+            assert!(err.to_string().contains("out of range"), "{key}: {err}");
+        }
+
+        let content = fs::read_to_string(path).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(parsed["list"], serde_json::json!([1]));
+    }
+
+    #[test]
+    fn test_delete_array_element() {
+        let file = create_test_file(r#"{"list": ["a", "b", "c"]}"#);
+        let path = file.path().to_str().unwrap();
+
+        delete(path, "list.1").unwrap();
+
+        let content = fs::read_to_string(path).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(parsed["list"], serde_json::json!(["a", "c"]));
+    }
+
+    #[test]
+    fn test_delete_array_index_out_of_range() {
+        let file = create_test_file(r#"{"list": [{"name": "a"}]}"#);
+        let path = file.path().to_str().unwrap();
+
+        let result = delete(path, "list.1.name");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("out of range"));
+    }
+
     #[test]
     fn test_delete_root_fails() {
         let file = create_test_file(r#"{"name": "test"}"#);
@@ -3,8 +3,10 @@
 //! This module contains the business logic for all CLI subcommands that don't
 //! involve the interactive TUI. Commands are organized into submodules by domain:
 //!
+//! - [`config`] - Read botster configuration values
 //! - [`json`] - JSON file manipulation (get, set, delete)
 //! - [`reset`] - Remove all botster data from the system
+//! - [`status`] - Report running hubs and their sessions
 //! - [`update`] - Self-update functionality
 //! - [`worktree`] - Git worktree management (list, delete)
 //!
@@ -16,14 +18,16 @@
 //! use botster::commands;
 //!
 //! commands::json::get(&file_path, &key_path)?;
-//! commands::worktree::list()?;
-//! commands::update::check()?;
+//! commands::worktree::list(false)?;
+//! commands::update::check(commands::update::UpdateChannel::Stable)?;
 //! commands::reset::run(false)?;
 //! ```
 
+pub mod config;
 pub mod context;
 pub mod json;
 pub mod reset;
+pub mod status;
 pub mod update;
 pub mod worktree;
 
@@ -0,0 +1,130 @@
+//! Status command - reports running hubs and their sessions.
+//!
+//! A running hub rewrites `{config_dir}/hubs/{hub_id}/status.json` on every
+//! cleanup tick (see [`crate::hub::daemon::HubStatus`]). This command reads
+//! those snapshots and prints the ones that are fresh and whose process is
+//! still alive, so a hub that crashed without cleaning up is reported as not
+//! running.
+
+use anyhow::Result;
+
+use crate::hub::daemon::{self, HubStatus};
+
+/// Run the status command.
+pub fn run() -> Result<()> {
+    print!("{}", format_statuses(&daemon::read_all_statuses()));
+    Ok(())
+}
+
+/// Formats the report for every live snapshot in `statuses`.
+///
+/// Snapshots whose PID is no longer running, or that have not been rewritten
+/// recently, are skipped. If none remain, the report is a single "No hub
+/// running" line.
+fn format_statuses(statuses: &[HubStatus]) -> String {
+    let live: Vec<&HubStatus> = statuses.iter().filter(|status| status.is_live()).collect();
+    if live.is_empty() {
+        return "No hub running\n".to_string();
+    }
+
+    let mut out = String::new();
+    for status in live {
+        out.push_str(&format_status(status));
+    }
+    out
+}
+
+/// Formats a single hub snapshot.
+fn format_status(status: &HubStatus) -> String {
+    let mut out = format!(
+        "Hub {} (pid {})\n",
+        &status.hub_id[..status.hub_id.len().min(8)],
+        status.pid
+    );
+    out.push_str(&format!(
+        "  Botster ID: {}\n",
+        status.botster_id.as_deref().unwrap_or("(not registered)")
+    ));
+    out.push_str(&format!("  Agents: {}\n", status.agent_count()));
+    for session in &status.sessions {
+        out.push_str(&format!(
+            "    {} [{}] {} - {}\n",
+            session.session_uuid, session.session_type, session.label, session.status
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hub::daemon::SessionStatus;
+
+    fn now_secs() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    fn write_synthetic_status(dir: &std::path::Path, pid: u32, updated_at: u64) -> HubStatus {
+        let status = HubStatus {
+            hub_id: "abcdef0123456789".to_string(),
+            botster_id: Some("hub-42".to_string()),
+            pid,
+            updated_at,
+            sessions: vec![
+                SessionStatus {
+                    session_uuid: "sess-1".to_string(),
+                    label: "issue-7".to_string(),
+                    session_type: "agent".to_string(),
+                    status: "running".to_string(),
+                },
+                SessionStatus {
+                    session_uuid: "sess-2".to_string(),
+                    label: "shell".to_string(),
+                    session_type: "accessory".to_string(),
+                    status: "disconnected".to_string(),
+                },
+            ],
+        };
+        let path = dir.join("status.json");
+        std::fs::write(&path, serde_json::to_string(&status).unwrap()).unwrap();
+        daemon::read_status_at(&path).expect("synthetic status should parse")
+    }
+
+    #[test]
+    fn test_format_statuses_reports_live_hub() {
+        let dir = tempfile::tempdir().unwrap();
+        let status = write_synthetic_status(dir.path(), std::process::id(), now_secs());
+
+        let report = format_statuses(&[status]);
+        assert!(report.starts_with("Hub abcdef01 (pid "));
+        assert!(report.contains("Botster ID: hub-42"));
+        assert!(report.contains("Agents: 1"));
+        assert!(report.contains("sess-1 [agent] issue-7 - running"));
+        assert!(report.contains("sess-2 [accessory] shell - disconnected"));
+    }
+
+    #[test]
+    fn test_format_statuses_ignores_stale_hub() {
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let dead_pid = child.id();
+        child.wait().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let status = write_synthetic_status(dir.path(), dead_pid, now_secs());
+
+        assert_eq!(format_statuses(&[status]), "No hub running\n");
+        assert_eq!(format_statuses(&[]), "No hub running\n");
+    }
+
+    #[test]
+    fn test_format_statuses_ignores_old_snapshot_with_reused_pid() {
+        let dir = tempfile::tempdir().unwrap();
+        let stale_at = now_secs() - daemon::STATUS_STALE_AFTER_SECS - 1;
+        let status = write_synthetic_status(dir.path(), std::process::id(), stale_at);
+
+        assert_eq!(format_statuses(&[status]), "No hub running\n");
+    }
+}
@@ -6,29 +6,40 @@
 //!
 //! # Security
 //!
-//! Downloads are verified using SHA256 checksums when available.
+//! Downloads must carry a minisign signature (`<binary>.minisig`) made with
+//! the release key compiled into botster; `--allow-unsigned` overrides this.
+//! They are also checked against SHA256 checksums when available. The
+//! previous binary is kept until the new one passes a `--version`
+//! self-check, and restored if it doesn't.
 //!
 //! # Examples
 //!
 //! ```bash
 //! # Check if updates are available
-//! botster update-check
+//! botster update --check
+//!
+//! # Include pre-releases
+//! botster update --check --channel beta
 //!
 //! # Download and install the latest version
 //! botster update
+//!
+//! # Install even if the release has no valid signature
+//! botster update --allow-unsigned
 //! ```
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use semver::Version;
 use serde_json::Value;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 /// The current version of botster, derived from Cargo.toml.
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-/// GitHub API URL for fetching the latest release information.
+/// GitHub API URL for listing releases, newest first.
 const GITHUB_RELEASES_API: &str =
-    "https://api.github.com/repos/Tonksthebear/trybotster/releases/latest";
+    "https://api.github.com/repos/Tonksthebear/trybotster/releases?per_page=100";
 
 /// Base URL for downloading release binaries.
 const GITHUB_RELEASES_DOWNLOAD: &str =
@@ -45,6 +56,39 @@ const BOOT_CHECK_TIMEOUT: Duration = Duration::from_secs(15);
 /// Prevents blocking the hub event loop on slow/unreachable GitHub API.
 const LUA_CHECK_TIMEOUT: Duration = Duration::from_secs(15);
 
+/// Minisign public key release binaries are signed with (the base64 line of
+/// `minisign.pub`), compiled in from `BOTSTER_UPDATE_PUBLIC_KEY`.
+///
+/// An empty value counts as no key: CI exports `""` when the repo variable
+/// is unset.
+const UPDATE_PUBLIC_KEY: Option<&str> = match option_env!("BOTSTER_UPDATE_PUBLIC_KEY") {
+    Some(key) if !key.is_empty() => Some(key),
+    _ => None,
+};
+
+/// How long a freshly installed binary gets to answer `--version`.
+const SELF_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Which releases an update may come from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum UpdateChannel {
+    /// Full releases only.
+    #[default]
+    Stable,
+    /// Full releases and pre-releases.
+    Beta,
+}
+
+impl UpdateChannel {
+    /// Returns the command-line spelling of the channel.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Stable => "stable",
+            Self::Beta => "beta",
+        }
+    }
+}
+
 /// Result of checking for updates.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum UpdateStatus {
@@ -83,13 +127,14 @@ pub fn check_on_boot() -> Result<()> {
         return Ok(());
     }
 
-    let latest_str = match fetch_latest_version_with_timeout(BOOT_CHECK_TIMEOUT) {
-        Ok(v) => v,
-        Err(e) => {
-            log::warn!("Boot update check failed: {e}");
-            return Ok(());
-        }
-    };
+    let latest_str =
+        match fetch_latest_version_with_timeout(BOOT_CHECK_TIMEOUT, UpdateChannel::Stable) {
+            Ok(v) => v,
+            Err(e) => {
+                log::warn!("Boot update check failed: {e}");
+                return Ok(());
+            }
+        };
 
     let current = match Version::parse(VERSION) {
         Ok(v) => v,
@@ -120,7 +165,7 @@ pub fn check_on_boot() -> Result<()> {
     let answer = input.trim().to_lowercase();
 
     if answer.is_empty() || answer == "y" || answer == "yes" {
-        install()?;
+        install(false, UpdateChannel::Stable)?;
         exec_restart()?;
     }
 
@@ -135,13 +180,14 @@ pub fn check_on_boot_headless() -> Result<()> {
         return Ok(());
     }
 
-    let latest_str = match fetch_latest_version_with_timeout(BOOT_CHECK_TIMEOUT) {
-        Ok(v) => v,
-        Err(e) => {
-            log::warn!("Boot update check failed: {e}");
-            return Ok(());
-        }
-    };
+    let latest_str =
+        match fetch_latest_version_with_timeout(BOOT_CHECK_TIMEOUT, UpdateChannel::Stable) {
+            Ok(v) => v,
+            Err(e) => {
+                log::warn!("Boot update check failed: {e}");
+                return Ok(());
+            }
+        };
 
     let current = Version::parse(VERSION).ok();
     let latest = Version::parse(&latest_str).ok();
@@ -201,12 +247,19 @@ pub fn exec_restart() -> Result<()> {
     }
 }
 
-/// Fetches the latest version string from GitHub with a custom timeout.
-fn fetch_latest_version_with_timeout(timeout: Duration) -> Result<String> {
+/// Fetches the latest version on `channel` from GitHub with a custom timeout.
+fn fetch_latest_version_with_timeout(timeout: Duration, channel: UpdateChannel) -> Result<String> {
     let client = reqwest::blocking::Client::builder()
         .timeout(timeout)
         .build()?;
+    fetch_latest_version_with(&client, channel)
+}
 
+/// Fetches the release list and picks the newest version on `channel`.
+fn fetch_latest_version_with(
+    client: &reqwest::blocking::Client,
+    channel: UpdateChannel,
+) -> Result<String> {
     let response = client
         .get(GITHUB_RELEASES_API)
         .header("User-Agent", USER_AGENT)
@@ -216,14 +269,32 @@ fn fetch_latest_version_with_timeout(timeout: Duration) -> Result<String> {
         anyhow::bail!("Failed to check for updates: {}", response.status());
     }
 
-    let release: Value = response.json()?;
-    let version = release["tag_name"]
-        .as_str()
-        .ok_or_else(|| anyhow::anyhow!("Invalid release data: missing tag_name"))?
-        .trim_start_matches('v')
-        .to_string();
+    let releases: Value = response.json()?;
+    let version = select_release(&releases, channel)
+        .ok_or_else(|| anyhow::anyhow!("No {} release found", channel.as_str()))?;
+
+    Ok(version.to_string())
+}
 
-    Ok(version)
+/// Picks the newest release on `channel` from a GitHub releases list.
+///
+/// Drafts and tags that aren't semver are skipped. The stable channel also
+/// skips pre-releases, whether flagged on GitHub or in the version itself.
+/// Versions compare by semver, so `1.3.0-beta.2` sorts after `1.3.0-beta.1`
+/// and before `1.3.0`.
+fn select_release(releases: &Value, channel: UpdateChannel) -> Option<Version> {
+    releases
+        .as_array()?
+        .iter()
+        .filter(|release| !release["draft"].as_bool().unwrap_or(false))
+        .filter_map(|release| {
+            let tag = release["tag_name"].as_str()?;
+            let version = Version::parse(tag.trim_start_matches('v')).ok()?;
+            let prerelease =
+                release["prerelease"].as_bool().unwrap_or(false) || !version.pre.is_empty();
+            (channel == UpdateChannel::Beta || !prerelease).then_some(version)
+        })
+        .max()
 }
 
 /// Checks for available updates by querying the GitHub releases API.
@@ -241,16 +312,23 @@ fn fetch_latest_version_with_timeout(timeout: Duration) -> Result<String> {
 /// # Examples
 ///
 /// ```ignore
-/// update::check()?;
+/// update::check(UpdateChannel::Stable)?;
 /// ```
-pub fn check() -> Result<()> {
-    let status = get_update_status()?;
+pub fn check(channel: UpdateChannel) -> Result<()> {
+    let status = get_update_status(channel)?;
 
     match status {
         UpdateStatus::UpdateAvailable { current, latest } => {
             println!("Current version: {}", current);
             println!("Latest version: {}", latest);
-            println!("→ Update available! Run 'botster update' to install");
+            match channel {
+                UpdateChannel::Stable => {
+                    println!("→ Update available! Run 'botster update' to install")
+                }
+                UpdateChannel::Beta => {
+                    println!("→ Update available! Run 'botster update --channel beta' to install")
+                }
+            }
         }
         UpdateStatus::UpToDate { version } => {
             println!("Current version: {}", version);
@@ -276,8 +354,8 @@ pub fn check() -> Result<()> {
 /// - The GitHub API request fails
 /// - The response cannot be parsed
 /// - Version parsing fails
-pub fn get_update_status() -> Result<UpdateStatus> {
-    let latest_version_str = fetch_latest_version()?;
+pub fn get_update_status(channel: UpdateChannel) -> Result<UpdateStatus> {
+    let latest_version_str = fetch_latest_version(channel)?;
 
     let current = Version::parse(VERSION)?;
     let latest = Version::parse(&latest_version_str)?;
@@ -302,7 +380,8 @@ pub fn get_update_status() -> Result<UpdateStatus> {
 /// Used by the Lua `update.check()` primitive to avoid blocking the hub
 /// event loop indefinitely on slow or unreachable GitHub API responses.
 pub fn get_update_status_with_timeout() -> Result<UpdateStatus> {
-    let latest_version_str = fetch_latest_version_with_timeout(LUA_CHECK_TIMEOUT)?;
+    let latest_version_str =
+        fetch_latest_version_with_timeout(LUA_CHECK_TIMEOUT, UpdateChannel::Stable)?;
 
     let current = Version::parse(VERSION)?;
     let latest = Version::parse(&latest_version_str)?;
@@ -322,36 +401,20 @@ pub fn get_update_status_with_timeout() -> Result<UpdateStatus> {
     }
 }
 
-/// Fetches the latest version string from GitHub.
-fn fetch_latest_version() -> Result<String> {
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .get(GITHUB_RELEASES_API)
-        .header("User-Agent", USER_AGENT)
-        .send()?;
-
-    if !response.status().is_success() {
-        anyhow::bail!("Failed to check for updates: {}", response.status());
-    }
-
-    let release: Value = response.json()?;
-    let version = release["tag_name"]
-        .as_str()
-        .ok_or_else(|| anyhow::anyhow!("Invalid release data: missing tag_name"))?
-        .trim_start_matches('v')
-        .to_string();
-
-    Ok(version)
+/// Fetches the latest version on `channel` from GitHub.
+fn fetch_latest_version(channel: UpdateChannel) -> Result<String> {
+    fetch_latest_version_with(&reqwest::blocking::Client::new(), channel)
 }
 
-/// Downloads and installs the latest version.
+/// Downloads and installs the latest version on `channel`.
 ///
 /// Performs the following steps:
 /// 1. Checks if an update is available
 /// 2. Determines the correct binary for the current platform
 /// 3. Downloads the new binary
-/// 4. Verifies the checksum (if available)
-/// 5. Replaces the current binary with the new one
+/// 4. Verifies the minisign signature and the checksum (if available)
+/// 5. Replaces the current binary with the new one, rolling back if the new
+///    binary fails its `--version` self-check
 ///
 /// # Platform Support
 ///
@@ -366,15 +429,17 @@ fn fetch_latest_version() -> Result<String> {
 /// - Already running the latest version
 /// - Platform is not supported
 /// - Download fails
+/// - The signature is missing or invalid and `allow_unsigned` is not set
 /// - Checksum verification fails
+/// - The new binary fails its self-check (the old one is restored)
 /// - File operations fail
 ///
 /// # Examples
 ///
 /// ```ignore
-/// update::install()?;
+/// update::install(false, UpdateChannel::Stable)?;
 /// ```
-pub fn install() -> Result<()> {
+pub fn install(allow_unsigned: bool, channel: UpdateChannel) -> Result<()> {
     use sha2::{Digest, Sha256};
     use std::env;
     use std::fs;
@@ -382,7 +447,7 @@ pub fn install() -> Result<()> {
     println!("Current version: {}", VERSION);
     println!("Checking for updates...");
 
-    let latest_version_str = fetch_latest_version()?;
+    let latest_version_str = fetch_latest_version(channel)?;
     println!("Latest version: {}", latest_version_str);
 
     let current = Version::parse(VERSION)?;
@@ -401,6 +466,7 @@ pub fn install() -> Result<()> {
         GITHUB_RELEASES_DOWNLOAD, latest_version_str, binary_name
     );
     let checksum_url = format!("{}.sha256", download_url);
+    let signature_url = format!("{}.minisig", download_url);
 
     println!("Downloading version {}...", latest_version_str);
 
@@ -418,6 +484,31 @@ pub fn install() -> Result<()> {
 
     let binary_data = binary_response.bytes()?;
 
+    // Download and verify signature. A failed request counts as a missing
+    // signature, which `--allow-unsigned` may override.
+    let signature = match client
+        .get(&signature_url)
+        .header("User-Agent", USER_AGENT)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.text())
+    {
+        Ok(text) => Some(text),
+        Err(e) => {
+            log::warn!("Could not download update signature: {e}");
+            None
+        }
+    };
+
+    match verify_signature(&binary_data, signature.as_deref(), UPDATE_PUBLIC_KEY) {
+        Ok(()) => println!("✓ Signature verified"),
+        Err(e) if allow_unsigned => {
+            log::warn!("Installing unverified update: {e}");
+            println!("⚠ {e}; installing anyway (--allow-unsigned)");
+        }
+        Err(e) => anyhow::bail!("{e}. Run `botster update --allow-unsigned` to install anyway."),
+    }
+
     // Download and verify checksum
     let checksum_response = client
         .get(&checksum_url)
@@ -461,8 +552,8 @@ pub fn install() -> Result<()> {
         fs::set_permissions(&temp_path, perms)?;
     }
 
-    // Replace current binary — try direct first, escalate to sudo if needed
-    let replaced = replace_binary(&temp_path, &current_exe);
+    // Replace current binary, restoring it if the new one doesn't run
+    let replaced = install_with_rollback(&temp_path, &current_exe);
 
     // Clean up temp file if it still exists (sudo mv would have moved it)
     let _ = fs::remove_file(&temp_path);
@@ -474,11 +565,111 @@ pub fn install() -> Result<()> {
     Ok(())
 }
 
+/// Checks a downloaded binary against its `.minisig` and the release key.
+///
+/// Fails if this build has no release key, the release has no signature,
+/// or the signature doesn't verify.
+fn verify_signature(data: &[u8], signature: Option<&str>, public_key: Option<&str>) -> Result<()> {
+    let public_key = public_key
+        .context("This build has no update signing key, so the download can't be verified")?;
+    let signature = signature.context("The release has no signature (.minisig)")?;
+    crate::minisign::PublicKey::from_base64(public_key)
+        .context("Invalid update signing key")?
+        .verify(data, signature)
+        .context("Update signature verification failed")
+}
+
+/// Installs `new_binary` over `current_exe`, rolling back if it doesn't run.
+///
+/// The current binary is copied to `<exe>.bak` first (or to the temp dir if
+/// the install directory isn't writable). Once swapped in, the new binary
+/// must exit successfully on `--version`; otherwise the backup is moved
+/// back. The backup is only removed after a successful self-check.
+fn install_with_rollback(new_binary: &Path, current_exe: &Path) -> Result<()> {
+    use std::fs;
+
+    let backup = back_up_binary(current_exe)?;
+
+    if let Err(e) = replace_binary(new_binary, current_exe) {
+        let _ = fs::remove_file(&backup);
+        return Err(e);
+    }
+
+    match self_check(current_exe) {
+        Ok(()) => {
+            let _ = fs::remove_file(&backup);
+            Ok(())
+        }
+        Err(e) => {
+            log::warn!("Updated binary failed its self-check: {e}");
+            replace_binary(&backup, current_exe).with_context(|| {
+                format!(
+                    "Updated binary failed to run ({e}) and restoring the previous version \
+                     failed; it is saved at {}",
+                    backup.display()
+                )
+            })?;
+            anyhow::bail!("Update rolled back, the new binary failed to run: {e}");
+        }
+    }
+}
+
+/// Copies `exe` to `<exe>.bak`, or into the temp dir if that isn't writable.
+fn back_up_binary(exe: &Path) -> Result<PathBuf> {
+    use std::fs;
+
+    let mut name = exe
+        .file_name()
+        .context("Binary path has no file name")?
+        .to_os_string();
+    name.push(".bak");
+    let sibling = exe.with_file_name(name);
+    match fs::copy(exe, &sibling) {
+        Ok(_) => return Ok(sibling),
+        Err(e) => log::debug!("Cannot back up to {}: {e}", sibling.display()),
+    }
+
+    let fallback = std::env::temp_dir().join(format!("botster-backup-{}", std::process::id()));
+    fs::copy(exe, &fallback).context("Failed to back up the current binary")?;
+    Ok(fallback)
+}
+
+/// Runs `exe --version`, failing if it won't start, exits nonzero, or hangs.
+fn self_check(exe: &Path) -> Result<()> {
+    let mut child = std::process::Command::new(exe)
+        .arg("--version")
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .with_context(|| format!("Failed to run {}", exe.display()))?;
+
+    let deadline = Instant::now() + SELF_CHECK_TIMEOUT;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            if status.success() {
+                return Ok(());
+            }
+            anyhow::bail!("`{} --version` exited with {}", exe.display(), status);
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            anyhow::bail!(
+                "`{} --version` did not finish within {:?}",
+                exe.display(),
+                SELF_CHECK_TIMEOUT
+            );
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
 /// Replaces the current binary with the new one, escalating to `sudo` if needed.
 ///
 /// Tries a direct `fs::rename` first. If that fails with a permission error,
 /// falls back to `sudo mv` so the user gets a password prompt on their terminal.
-fn replace_binary(src: &std::path::Path, dest: &std::path::Path) -> Result<()> {
+fn replace_binary(src: &Path, dest: &Path) -> Result<()> {
     use std::fs;
 
     // Try direct rename first (works when user owns the install dir)
@@ -577,6 +768,118 @@ mod tests {
         assert_ne!(available, ahead);
     }
 
+    fn mock_releases() -> Value {
+        serde_json::json!([
+            { "tag_name": "v2.0.0", "draft": true, "prerelease": false },
+            { "tag_name": "v1.3.0-beta.2", "draft": false, "prerelease": true },
+            { "tag_name": "v1.3.0-beta.1", "draft": false, "prerelease": true },
+            { "tag_name": "v1.2.0", "draft": false, "prerelease": false },
+            { "tag_name": "nightly", "draft": false, "prerelease": true },
+            { "tag_name": "v1.1.0", "draft": false, "prerelease": false },
+        ])
+    }
+
+    #[test]
+    fn test_select_release_stable_skips_prereleases_and_drafts() {
+        let version = select_release(&mock_releases(), UpdateChannel::Stable).unwrap();
+        assert_eq!(version.to_string(), "1.2.0");
+    }
+
+    #[test]
+    fn test_select_release_beta_picks_newest_prerelease() {
+        let version = select_release(&mock_releases(), UpdateChannel::Beta).unwrap();
+        assert_eq!(version.to_string(), "1.3.0-beta.2");
+    }
+
+    #[test]
+    fn test_select_release_beta_prefers_final_release_over_its_prereleases() {
+        let mut releases = mock_releases();
+        releases.as_array_mut().unwrap().push(serde_json::json!({
+            "tag_name": "v1.3.0", "draft": false, "prerelease": false
+        }));
+        for channel in [UpdateChannel::Stable, UpdateChannel::Beta] {
+            let version = select_release(&releases, channel).unwrap();
+            assert_eq!(version.to_string(), "1.3.0");
+        }
+    }
+
+    #[test]
+    fn test_select_release_stable_ignores_unflagged_prerelease_tag() {
+        let releases = serde_json::json!([
+            { "tag_name": "v1.4.0-rc.1", "draft": false, "prerelease": false },
+            { "tag_name": "v1.2.0", "draft": false, "prerelease": false },
+        ]);
+        let version = select_release(&releases, UpdateChannel::Stable).unwrap();
+        assert_eq!(version.to_string(), "1.2.0");
+        assert_eq!(
+            select_release(&serde_json::json!([]), UpdateChannel::Beta),
+            None
+        );
+    }
+
+    #[test]
+    fn test_update_channel_names() {
+        use clap::ValueEnum;
+
+        for channel in UpdateChannel::value_variants() {
+            assert_eq!(
+                UpdateChannel::from_str(channel.as_str(), false),
+                Ok(*channel)
+            );
+        }
+        assert!(UpdateChannel::from_str("nightly", false).is_err());
+    }
+
+    #[test]
+    fn test_verify_signature_refuses_missing_signature_or_key() {
+        let err = verify_signature(b"binary", None, Some("key")).unwrap_err();
+        assert!(err.to_string().contains("no signature"), "{err}");
+
+        let err = verify_signature(b"binary", Some("sig"), None).unwrap_err();
+        assert!(err.to_string().contains("no update signing key"), "{err}");
+    }
+
+    /// Writes an executable shell script to `dir/name`.
+    #[cfg(unix)]
+    fn write_script(dir: &Path, name: &str, body: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+        let path = dir.join(name);
+        std::fs::write(&path, format!("#!/bin/sh\n{body}\n")).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_install_with_rollback_restores_binary_that_fails_self_check() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let current = write_script(dir.path(), "botster", "echo botster 1.0.0");
+        let original = std::fs::read(&current).unwrap();
+        let broken = write_script(dir.path(), "botster-update", "exit 1");
+
+        let err = install_with_rollback(&broken, &current).unwrap_err();
+
+        assert!(err.to_string().contains("rolled back"), "{err}");
+        assert_eq!(std::fs::read(&current).unwrap(), original);
+        assert!(self_check(&current).is_ok());
+        assert!(!dir.path().join("botster.bak").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_install_with_rollback_removes_backup_after_self_check() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let current = write_script(dir.path(), "botster", "echo botster 1.0.0");
+        let update = write_script(dir.path(), "botster-update", "echo botster 2.0.0");
+        let updated = std::fs::read(&update).unwrap();
+
+        install_with_rollback(&update, &current).unwrap();
+
+        assert_eq!(std::fs::read(&current).unwrap(), updated);
+        assert!(!update.exists());
+        assert!(!dir.path().join("botster.bak").exists());
+    }
+
     #[test]
     fn test_get_platform_returns_valid_value() {
         // This test should pass on any supported platform
@@ -9,12 +9,24 @@
 //! # List all worktrees for the current repository
 //! botster list-worktrees
 //!
+//! # ...as JSON, for scripts
+//! botster list-worktrees --json
+//!
 //! # Delete a worktree by issue number
 //! botster delete-worktree 42
+//!
+//! # Show what would be deleted without deleting it
+//! botster delete-worktree 42 --dry-run
+//!
+//! # Forget worktrees whose directories were deleted
+//! botster prune-worktrees
 //! ```
 
+use crate::git::{self, WorktreeDeletion};
 use crate::{Config, WorktreeManager};
 use anyhow::Result;
+use serde::Serialize;
+use std::path::Path;
 use std::process::Command;
 
 /// Deletes a git worktree by issue number.
@@ -22,67 +34,187 @@ use std::process::Command;
 /// Removes the worktree directory and cleans up the git worktree reference.
 /// Also runs any teardown scripts defined in the worktree.
 ///
+/// With `dry_run`, prints what would be deleted (path, branch, and any
+/// uncommitted changes or unpushed commits) and changes nothing. A worktree
+/// with uncommitted changes or unpushed commits is only deleted with `force`.
+///
 /// # Errors
 ///
 /// Returns an error if:
 /// - Configuration cannot be loaded
-/// - The worktree doesn't exist
+/// - Not in a git repository
+/// - The worktree has uncommitted changes or unpushed commits and `force`
+///   is not set
 /// - Git operations fail
 ///
 /// # Examples
 ///
 /// ```ignore
-/// // Delete the worktree for issue #42
-/// worktree::delete(42)?;
+/// // Preview, then delete the worktree for issue #42
+/// worktree::delete(42, true, false)?;
+/// worktree::delete(42, false, false)?;
 /// ```
-pub fn delete(issue_number: u32) -> Result<()> {
+pub fn delete(issue_number: u32, dry_run: bool, force: bool) -> Result<()> {
+    let git_manager = configured_manager()?;
+    let (repo_path, repo_name) = WorktreeManager::detect_current_repo()?;
+    let Some(plan) =
+        git_manager.plan_issue_worktree_deletion(&repo_path, &repo_name, issue_number)?
+    else {
+        println!("No worktree found for issue #{}", issue_number);
+        return Ok(());
+    };
+
+    if dry_run {
+        print!("{}", describe_deletion(issue_number, &plan));
+        return Ok(());
+    }
+
+    if let Some(reason) = &plan.unpushed {
+        if !force {
+            anyhow::bail!(
+                "Refusing to delete worktree {}: {}; pass --force to delete it anyway",
+                plan.path.display(),
+                reason
+            );
+        }
+    }
+    git_manager.delete_planned_worktree(&repo_path, &plan, force)?;
+
+    println!("Successfully deleted worktree for issue #{}", issue_number);
+    Ok(())
+}
+
+/// Builds a worktree manager from the loaded configuration.
+fn configured_manager() -> Result<WorktreeManager> {
     let config = Config::load()?;
     let git_manager = WorktreeManager::new(config.worktree_base);
+    Ok(match &config.branch_template {
+        Some(template) => git_manager.with_branch_template(template),
+        None => git_manager,
+    })
+}
 
-    git_manager.delete_worktree_by_issue_number(issue_number)?;
+/// Formats the `--dry-run` report for deleting an issue's worktree.
+fn describe_deletion(issue_number: u32, plan: &WorktreeDeletion) -> String {
+    let status = match &plan.unpushed {
+        Some(reason) => format!("{reason} (requires --force)"),
+        None => "clean".to_string(),
+    };
+    format!(
+        "Would delete worktree for issue #{}\n  Path:   {}\n  Branch: {}\n  Status: {}\n",
+        issue_number,
+        plan.path.display(),
+        plan.branch,
+        status
+    )
+}
 
-    println!("Successfully deleted worktree for issue #{}", issue_number);
+/// Unregisters worktrees of the current repository whose directories no
+/// longer exist, printing each pruned path.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - Configuration cannot be loaded
+/// - Not in a git repository
+/// - Git commands fail
+pub fn prune() -> Result<()> {
+    let config = Config::load()?;
+    let git_manager = WorktreeManager::new(config.worktree_base);
+
+    let pruned = git_manager.prune_stale_worktrees()?;
+    if pruned.is_empty() {
+        println!("No stale worktrees found");
+    } else {
+        for path in &pruned {
+            println!("Pruned {}", path);
+        }
+    }
     Ok(())
 }
 
 /// Lists all git worktrees for the current repository.
 ///
-/// Displays a formatted table of worktree paths and their associated branches.
-/// Worktrees following the botster naming convention (issue-N) are highlighted.
+/// Displays a formatted table of worktree paths, their branches, and the
+/// issue number parsed from each branch with the configured
+/// `branch_template` (`botster-issue-N` by default).
+///
+/// With `json`, prints an array of objects instead, one per worktree:
+///
+/// ```text
+/// [
+///   {
+///     "path": "/path/to/repo",
+///     "branch": "main",
+///     "issue_number": null,
+///     "is_main": true,
+///     "locked": false,
+///     "prunable": false,
+///     "dirty": false
+///   }
+/// ]
+/// ```
+///
+/// `branch` is `null` for a detached HEAD, and `issue_number` is parsed from
+/// the branch with the configured `branch_template`. `prunable` marks a
+/// worktree whose directory is gone (see `botster prune-worktrees`), and
+/// `dirty` is `null` when the worktree's status can't be read.
 ///
 /// # Output Format
 ///
 /// ```text
 /// Worktrees for repository: owner/repo
 ///
-/// Path                                     Branch
-/// ----------------------------------------------------------------------
-/// /path/to/worktree-1                      issue-42
-/// /path/to/worktree-2                      feature-branch
+/// Path                                     Branch                         Issue  Status
+/// ------------------------------------------------------------------------------------------
+/// /path/to/repo                            main                           -      main
+/// /path/to/repo-botster-issue-42           botster-issue-42               #42
+/// /path/to/repo-feature                    (detached)                     -      detached, locked
 /// ```
 ///
 /// # Errors
 ///
 /// Returns an error if:
+/// - Configuration cannot be loaded
 /// - Not in a git repository
 /// - Git commands fail
 ///
 /// # Examples
 ///
 /// ```ignore
-/// worktree::list()?;
+/// worktree::list(false)?;
 /// ```
-pub fn list() -> Result<()> {
+pub fn list(json: bool) -> Result<()> {
     // Detect current repository
     let (repo_path, repo_name) = WorktreeManager::detect_current_repo()?;
+    let manager = configured_manager()?;
+
+    if json {
+        let entries = worktree_entries(&repo_path, &manager)?;
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
 
     println!("Worktrees for repository: {}", repo_name);
     println!();
 
-    // Run `git worktree list --porcelain` for machine-readable output
+    let worktrees = list_repo_worktrees(&repo_path)?;
+
+    // Display worktrees in a formatted way
+    if worktrees.is_empty() {
+        println!("No worktrees found");
+    } else {
+        print_worktree_table(&worktrees, &manager);
+    }
+
+    Ok(())
+}
+
+/// Runs `git worktree list --porcelain` in `repo_path` and parses it.
+fn list_repo_worktrees(repo_path: &Path) -> Result<Vec<WorktreeInfo>> {
     let output = Command::new("git")
         .args(["worktree", "list", "--porcelain"])
-        .current_dir(&repo_path)
+        .current_dir(repo_path)
         .output()?;
 
     if !output.status.success() {
@@ -92,24 +224,61 @@ pub fn list() -> Result<()> {
         );
     }
 
-    let worktree_output = String::from_utf8_lossy(&output.stdout);
-    let worktrees = parse_porcelain_output(&worktree_output);
+    Ok(parse_porcelain_output(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
 
-    // Display worktrees in a formatted way
-    if worktrees.is_empty() {
-        println!("No worktrees found");
-    } else {
-        print_worktree_table(&worktrees);
-    }
+/// One worktree as printed by `list-worktrees --json`.
+#[derive(Debug, Serialize)]
+struct WorktreeEntry {
+    path: String,
+    branch: Option<String>,
+    issue_number: Option<u32>,
+    is_main: bool,
+    locked: bool,
+    prunable: bool,
+    /// `None` when `git status` fails, e.g. for a prunable worktree.
+    dirty: Option<bool>,
+}
 
-    Ok(())
+/// Collects the `--json` entries for every worktree of `repo_path`.
+///
+/// A worktree whose status can't be read, e.g. because its directory was
+/// deleted, has no `dirty` value rather than being reported as clean.
+fn worktree_entries(repo_path: &Path, manager: &WorktreeManager) -> Result<Vec<WorktreeEntry>> {
+    Ok(list_repo_worktrees(repo_path)?
+        .into_iter()
+        .map(|wt| {
+            let branch = (!wt.branch.is_empty()).then_some(wt.branch);
+            WorktreeEntry {
+                issue_number: branch
+                    .as_deref()
+                    .and_then(|b| manager.issue_number_for_branch(b)),
+                dirty: git::has_uncommitted_changes(Path::new(&wt.path)).ok(),
+                path: wt.path,
+                branch,
+                is_main: wt.is_main,
+                locked: wt.locked,
+                prunable: wt.prunable,
+            }
+        })
+        .collect())
 }
 
 /// Parsed worktree information.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 struct WorktreeInfo {
     path: String,
     branch: String,
+    /// Git lists the main worktree first.
+    is_main: bool,
+    /// HEAD is not on a branch; `branch` is empty.
+    detached: bool,
+    locked: bool,
+    /// The worktree's directory is missing; `git worktree prune` would
+    /// remove it.
+    prunable: bool,
 }
 
 /// Parses git worktree list --porcelain output.
@@ -118,36 +287,37 @@ struct WorktreeInfo {
 /// ```text
 /// worktree <path>
 /// HEAD <sha>
-/// branch <ref>
+/// branch <ref> | detached
+/// locked [<reason>]
+/// prunable [<reason>]
 /// <blank line>
 /// ```
 fn parse_porcelain_output(output: &str) -> Vec<WorktreeInfo> {
     let mut worktrees = Vec::new();
-    let mut current_path = String::new();
-    let mut current_branch = String::new();
+    let mut current = WorktreeInfo::default();
 
     for line in output.lines() {
         if let Some(path) = line.strip_prefix("worktree ") {
-            current_path = path.to_string();
+            current.path = path.to_string();
         } else if let Some(branch) = line.strip_prefix("branch refs/heads/") {
-            current_branch = branch.to_string();
-        } else if line.is_empty() && !current_path.is_empty() {
+            current.branch = branch.to_string();
+        } else if line == "detached" {
+            current.detached = true;
+        } else if line == "locked" || line.starts_with("locked ") {
+            current.locked = true;
+        } else if line == "prunable" || line.starts_with("prunable ") {
+            current.prunable = true;
+        } else if line.is_empty() && !current.path.is_empty() {
             // End of worktree entry
-            worktrees.push(WorktreeInfo {
-                path: current_path.clone(),
-                branch: current_branch.clone(),
-            });
-            current_path.clear();
-            current_branch.clear();
+            current.is_main = worktrees.is_empty();
+            worktrees.push(std::mem::take(&mut current));
         }
     }
 
     // Handle last entry if file doesn't end with blank line
-    if !current_path.is_empty() {
-        worktrees.push(WorktreeInfo {
-            path: current_path,
-            branch: current_branch,
-        });
+    if !current.path.is_empty() {
+        current.is_main = worktrees.is_empty();
+        worktrees.push(current);
     }
 
     worktrees
@@ -156,18 +326,57 @@ fn parse_porcelain_output(output: &str) -> Vec<WorktreeInfo> {
 /// Column width for path display.
 const PATH_COLUMN_WIDTH: usize = 40;
 
+/// Column width for branch display.
+const BRANCH_COLUMN_WIDTH: usize = 30;
+
+/// Column width for issue number display.
+const ISSUE_COLUMN_WIDTH: usize = 6;
+
 /// Total table width including separator.
-const TABLE_WIDTH: usize = 70;
+const TABLE_WIDTH: usize = 90;
 
 /// Prints worktree information as a formatted table.
-fn print_worktree_table(worktrees: &[WorktreeInfo]) {
-    println!("{:<PATH_COLUMN_WIDTH$} Branch", "Path");
+fn print_worktree_table(worktrees: &[WorktreeInfo], manager: &WorktreeManager) {
+    println!(
+        "{:<PATH_COLUMN_WIDTH$} {:<BRANCH_COLUMN_WIDTH$} {:<ISSUE_COLUMN_WIDTH$} Status",
+        "Path", "Branch", "Issue"
+    );
     println!("{}", "-".repeat(TABLE_WIDTH));
 
     for wt in worktrees {
-        let display_branch = format_branch_name(&wt.branch);
-        println!("{:<PATH_COLUMN_WIDTH$} {}", wt.path, display_branch);
+        println!("{}", format_worktree_row(wt, manager));
+    }
+}
+
+/// Formats one table row: path, branch, issue number, and status flags.
+fn format_worktree_row(wt: &WorktreeInfo, manager: &WorktreeManager) -> String {
+    let issue = manager
+        .issue_number_for_branch(&wt.branch)
+        .map_or_else(|| "-".to_string(), |n| format!("#{n}"));
+
+    let mut status = Vec::new();
+    if wt.is_main {
+        status.push("main");
     }
+    if wt.detached {
+        status.push("detached");
+    }
+    if wt.locked {
+        status.push("locked");
+    }
+    if wt.prunable {
+        status.push("prunable");
+    }
+
+    format!(
+        "{:<PATH_COLUMN_WIDTH$} {:<BRANCH_COLUMN_WIDTH$} {:<ISSUE_COLUMN_WIDTH$} {}",
+        wt.path,
+        format_branch_name(&wt.branch),
+        issue,
+        status.join(", ")
+    )
+    .trim_end()
+    .to_string()
 }
 
 /// Formats branch name for display.
@@ -223,6 +432,64 @@ branch refs/heads/feature-branch
         assert_eq!(worktrees[0].branch, "");
     }
 
+    #[test]
+    fn test_format_worktree_row_shows_issue_number() {
+        let output = "\
+worktree /path/to/main
+HEAD abc123
+branch refs/heads/main
+
+worktree /path/to/main-botster-issue-42
+HEAD def456
+branch refs/heads/botster-issue-42
+
+worktree /path/to/main-issue-7
+HEAD def456
+branch refs/heads/issue-7
+";
+        let worktrees = parse_porcelain_output(output);
+        let manager = WorktreeManager::new("/worktrees".into());
+        let rows: Vec<String> = worktrees
+            .iter()
+            .map(|wt| format_worktree_row(wt, &manager))
+            .collect();
+
+        assert_eq!(
+            rows[1],
+            format!(
+                "{:<PATH_COLUMN_WIDTH$} {:<BRANCH_COLUMN_WIDTH$} #42",
+                "/path/to/main-botster-issue-42", "botster-issue-42"
+            )
+        );
+        assert!(rows[0].ends_with(" -      main"));
+        assert!(rows[2].ends_with(" -"), "issue-7 is not a botster branch");
+
+        let custom =
+            WorktreeManager::new("/worktrees".into()).with_branch_template("issue-{issue}");
+        assert!(format_worktree_row(&worktrees[2], &custom).ends_with(" #7"));
+    }
+
+    #[test]
+    fn test_format_worktree_row_flags_detached_and_locked() {
+        let output = "\
+worktree /path/to/main
+HEAD abc123
+branch refs/heads/main
+
+worktree /path/to/detached
+HEAD def456
+detached
+locked
+";
+        let worktrees = parse_porcelain_output(output);
+        let manager = WorktreeManager::new("/worktrees".into());
+        let row = format_worktree_row(&worktrees[1], &manager);
+
+        assert!(worktrees[1].detached);
+        assert!(row.contains("(detached)"));
+        assert!(row.ends_with(" -      detached, locked"));
+    }
+
     #[test]
     fn test_parse_porcelain_output_no_trailing_newline() {
         let output = "worktree /path/to/main\nHEAD abc123\nbranch refs/heads/main";
@@ -232,6 +499,204 @@ branch refs/heads/feature-branch
         assert_eq!(worktrees[0].path, "/path/to/main");
     }
 
+    #[test]
+    fn test_parse_porcelain_output_flags_main_and_locked() {
+        let output = "\
+worktree /path/to/main
+HEAD abc123
+branch refs/heads/main
+
+worktree /path/to/locked
+HEAD def456
+branch refs/heads/botster-issue-7
+locked moving disks
+
+worktree /path/to/locked-no-reason
+HEAD def456
+detached
+locked
+";
+        let worktrees = parse_porcelain_output(output);
+
+        assert_eq!(worktrees.len(), 3);
+        assert!(worktrees[0].is_main && !worktrees[0].locked);
+        assert!(!worktrees[1].is_main && worktrees[1].locked);
+        assert!(!worktrees[2].is_main && worktrees[2].locked);
+    }
+
+    #[test]
+    fn test_parse_porcelain_output_flags_prunable() {
+        let output = "\
+worktree /path/to/main
+HEAD abc123
+branch refs/heads/main
+
+worktree /path/to/gone
+HEAD def456
+branch refs/heads/botster-issue-7
+prunable gitdir file points to non-existent location
+
+worktree /path/to/gone-no-reason
+HEAD def456
+detached
+prunable
+";
+        let worktrees = parse_porcelain_output(output);
+
+        assert_eq!(worktrees.len(), 3);
+        assert!(!worktrees[0].prunable);
+        assert!(worktrees[1].prunable && !worktrees[1].locked);
+        assert!(worktrees[2].prunable);
+
+        let manager = WorktreeManager::new("/worktrees".into());
+        assert!(format_worktree_row(&worktrees[1], &manager).ends_with(" #7     prunable"));
+    }
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    #[test]
+    fn test_worktree_entries_json_shape() {
+        let temp = tempfile::tempdir().unwrap();
+        let repo = temp.path().join("repo");
+        std::fs::create_dir(&repo).unwrap();
+        git(&repo, &["init", "-q", "-b", "main"]);
+        git(&repo, &["config", "user.email", "test@example.com"]);
+        git(&repo, &["config", "user.name", "Test"]);
+        git(&repo, &["commit", "-q", "--allow-empty", "-m", "init"]);
+
+        let worktree = temp.path().join("repo-botster-issue-42");
+        git(
+            &repo,
+            &[
+                "worktree",
+                "add",
+                "-q",
+                "-b",
+                "botster-issue-42",
+                worktree.to_str().unwrap(),
+            ],
+        );
+        std::fs::write(worktree.join("scratch.txt"), "wip").unwrap();
+
+        let manager = WorktreeManager::new(temp.path().to_path_buf());
+        let entries = worktree_entries(&repo, &manager).unwrap();
+        let json = serde_json::to_value(&entries).unwrap();
+
+        let main = &json[0];
+        assert_eq!(main["branch"], "main");
+        assert_eq!(main["issue_number"], serde_json::Value::Null);
+        assert_eq!(main["is_main"], true);
+        assert_eq!(main["locked"], false);
+        assert_eq!(main["prunable"], false);
+        assert_eq!(main["dirty"], false);
+
+        let issue = &json[1];
+        let mut keys: Vec<_> = issue.as_object().unwrap().keys().collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            [
+                "branch",
+                "dirty",
+                "is_main",
+                "issue_number",
+                "locked",
+                "path",
+                "prunable"
+            ]
+        );
+        assert!(issue["path"]
+            .as_str()
+            .unwrap()
+            .ends_with("repo-botster-issue-42"));
+        assert_eq!(issue["branch"], "botster-issue-42");
+        assert_eq!(issue["issue_number"], 42);
+        assert_eq!(issue["is_main"], false);
+        assert_eq!(issue["dirty"], true);
+    }
+
+    #[test]
+    fn test_worktree_entries_reports_unreadable_status_as_null() {
+        let temp = tempfile::tempdir().unwrap();
+        let repo = temp.path().join("repo");
+        std::fs::create_dir(&repo).unwrap();
+        git(&repo, &["init", "-q", "-b", "main"]);
+        git(&repo, &["config", "user.email", "test@example.com"]);
+        git(&repo, &["config", "user.name", "Test"]);
+        git(&repo, &["commit", "-q", "--allow-empty", "-m", "init"]);
+
+        let worktree = temp.path().join("repo-botster-issue-7");
+        git(
+            &repo,
+            &[
+                "worktree",
+                "add",
+                "-q",
+                "-b",
+                "botster-issue-7",
+                worktree.to_str().unwrap(),
+            ],
+        );
+        std::fs::remove_dir_all(&worktree).unwrap();
+
+        let manager = WorktreeManager::new(temp.path().to_path_buf());
+        let entries = worktree_entries(&repo, &manager).unwrap();
+        let json = serde_json::to_value(&entries).unwrap();
+
+        let gone = &json[1];
+        assert_eq!(gone["prunable"], true);
+        assert_eq!(
+            gone["dirty"],
+            serde_json::Value::Null,
+            "a missing worktree is not reported as clean"
+        );
+    }
+
+    #[test]
+    fn test_describe_deletion_clean_worktree() {
+        let plan = WorktreeDeletion {
+            path: "/worktrees/repo-botster-issue-42".into(),
+            branch: "botster-issue-42".to_string(),
+            unpushed: None,
+        };
+        assert_eq!(
+            describe_deletion(42, &plan),
+            "Would delete worktree for issue #42\n  Path:   /worktrees/repo-botster-issue-42\n  Branch: botster-issue-42\n  Status: clean\n"
+        );
+    }
+
+    #[test]
+    fn test_describe_deletion_flags_dirty_worktree() {
+        let plan = WorktreeDeletion {
+            path: "/worktrees/repo-botster-issue-42".into(),
+            branch: "botster-issue-42".to_string(),
+            unpushed: Some("worktree has uncommitted changes".to_string()),
+        };
+        assert!(describe_deletion(42, &plan)
+            .contains("Status: worktree has uncommitted changes (requires --force)"));
+    }
+
+    #[test]
+    fn test_describe_deletion_flags_unpushed_commits() {
+        let plan = WorktreeDeletion {
+            path: "/worktrees/repo-botster-issue-42".into(),
+            branch: "botster-issue-42".to_string(),
+            unpushed: Some("branch botster-issue-42 has 2 unpushed commit(s)".to_string()),
+        };
+        assert!(describe_deletion(42, &plan).contains(
+            "Status: branch botster-issue-42 has 2 unpushed commit(s) (requires --force)"
+        ));
+    }
+
     #[test]
     fn test_format_branch_name_normal() {
         assert_eq!(format_branch_name("main"), "main");
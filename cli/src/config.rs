@@ -7,8 +7,13 @@ use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
-use std::{fs, path::PathBuf};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
 
+use crate::git::CleanupPolicy;
 use crate::keyring::Credentials;
 
 /// Configuration for the botster CLI.
@@ -25,14 +30,170 @@ pub struct Config {
     pub agent_timeout: u64,
     /// Maximum number of concurrent agent sessions.
     pub max_sessions: usize,
+    /// Per-profile limits keyed by agent name (e.g. `"codex"`).
+    /// `max_sessions` still caps the total across all profiles.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, ProfileLimits>,
+    /// Per-repo overrides keyed by repo name (`owner/name`, as detected
+    /// from the origin remote). See [`Config::for_repo`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub repos: BTreeMap<String, RepoOverride>,
     /// Base directory for creating worktrees.
     pub worktree_base: PathBuf,
+    /// Repos (`owner/name`) this hub will spawn agents for.
+    /// Empty means every repo is accepted.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_repos: Vec<String>,
+    /// Answer an agent's "do you trust this folder?" prompt automatically.
+    /// When false the agent is flagged as needing attention instead.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub auto_trust: bool,
+    /// Case-insensitive substrings that identify a trust prompt.
+    /// Empty means the built-in patterns in `lua/lib/trust_prompt.lua`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trust_prompt_patterns: Vec<String>,
+    /// Bytes written to the PTY when auto-trusting (defaults to Enter).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trust_prompt_response: Option<String>,
+    /// Quiet window in seconds for coalescing repeated mentions to one agent.
+    /// Unset uses the default in `lua/lib/mention_batcher.lua`; 0 disables it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mention_debounce_secs: Option<f64>,
+    /// What happens to an agent's worktree when its issue is closed.
+    /// Unknown values fall back to `keep` with a warning.
+    #[serde(
+        default,
+        deserialize_with = "default_on_invalid",
+        skip_serializing_if = "is_default_cleanup_policy"
+    )]
+    pub cleanup_policy: CleanupPolicy,
+    /// Branch name for issue agents: `{issue}` is the issue number and
+    /// `{user}` is `$USER`. Unset uses [`crate::git::DEFAULT_BRANCH_TEMPLATE`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch_template: Option<String>,
+    /// Seconds an agent may sit idle before it is closed automatically.
+    /// Unset or 0 disables idle auto-close; pinned agents are always exempt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_close_secs: Option<u64>,
+    /// Event types (`github_mention`, `create_agent`) or GitHub source types
+    /// (`issue_comment`, `pr_comment`, ...) this hub acts on.
+    /// Empty means every mention is accepted; see `lua/lib/command_filter.lua`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub event_type_allowlist: Vec<String>,
+    /// Issue/PR labels this hub acts on. Empty means any; when set, mentions
+    /// that arrive without label data are skipped.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub label_allowlist: Vec<String>,
+    /// Longest prompt or mention text, in characters, handed to an agent.
+    /// Unset or 0 means no limit; see `lua/lib/prompt_limit.lua`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_prompt_chars: Option<usize>,
+    /// Which part of an over-long prompt to keep.
+    /// Unknown values fall back to `head_tail` with a warning.
+    #[serde(
+        default,
+        deserialize_with = "default_on_invalid",
+        skip_serializing_if = "is_default_prompt_truncation"
+    )]
+    pub prompt_truncation: PromptTruncation,
+    /// Address for the headless health endpoint (e.g. `127.0.0.1:9090`).
+    /// Unset means no listener; see [`crate::hub::health`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health_bind: Option<String>,
+    /// Extra STUN/TURN servers for browser WebRTC connections, tried in
+    /// addition to the ones the server hands out. Add a TURN relay here when
+    /// browsers can't reach this machine directly (e.g. symmetric NAT).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ice_servers: Vec<IceServerConfig>,
+    /// Inclusive `[first, last]` range of localhost ports handed to
+    /// port-forwarded sessions as `$PORT`. Unset uses the default range in
+    /// `lua/lib/session.lua`; narrow it to what a firewall allows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tunnel_port_range: Option<(u16, u16)>,
     /// Deprecated: hub names now live exclusively in Rails.
     /// Kept for backwards-compatible deserialization of old config files.
     #[serde(default, skip)]
     _hub_name: Option<String>,
 }
 
+/// Resource limits and prompt settings for a single agent profile.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProfileLimits {
+    /// Maximum number of agents of this profile running at once.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent: Option<usize>,
+    /// Text prepended to this profile's task prompts.
+    /// Unset uses the default in `lua/lib/agent_preamble.lua`; `""` disables it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_preamble: Option<String>,
+}
+
+/// A STUN or TURN server used to establish WebRTC connections.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct IceServerConfig {
+    /// Server URL, e.g. `turn:turn.example.com:3478`.
+    pub urls: String,
+    /// TURN username.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// TURN password.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credential: Option<String>,
+}
+
+/// Keys that `botster config <key> <value>` may change.
+pub const SETTABLE_KEYS: &[&str] = &[
+    "server_url",
+    "api_key",
+    "poll_interval",
+    "worktree_base",
+    "max_sessions",
+];
+
+/// Settings that replace the top-level ones for a single repo.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RepoOverride {
+    /// Base directory for this repo's worktrees.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worktree_base: Option<PathBuf>,
+}
+
+/// Which part of an over-long prompt survives truncation.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptTruncation {
+    /// Keep the beginning.
+    Head,
+    /// Keep the end.
+    Tail,
+    /// Keep the beginning and the end, eliding the middle.
+    #[default]
+    HeadTail,
+}
+
+fn is_default_prompt_truncation(truncation: &PromptTruncation) -> bool {
+    *truncation == PromptTruncation::default()
+}
+
+fn is_default_cleanup_policy(policy: &CleanupPolicy) -> bool {
+    *policy == CleanupPolicy::default()
+}
+
+/// Deserializes a setting, falling back to its default (with a warning)
+/// when the value is not one the enum knows. One typo in config.json must
+/// not stop the whole file from loading.
+fn default_on_invalid<'de, D, T>(deserializer: D) -> std::result::Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::de::DeserializeOwned + Default,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(serde_json::from_value(value.clone()).unwrap_or_else(|e| {
+        log::warn!("Ignoring invalid config value {value}: {e}; using the default");
+        T::default()
+    }))
+}
+
 impl Default for Config {
     fn default() -> Self {
         // Worktree base: in test mode use project tmp/, otherwise use home directory
@@ -58,7 +219,24 @@ impl Default for Config {
             poll_interval: 5,
             agent_timeout: 3600,
             max_sessions: 20,
+            profiles: BTreeMap::new(),
+            repos: BTreeMap::new(),
             worktree_base,
+            allowed_repos: Vec::new(),
+            auto_trust: false,
+            trust_prompt_patterns: Vec::new(),
+            trust_prompt_response: None,
+            mention_debounce_secs: None,
+            cleanup_policy: CleanupPolicy::default(),
+            branch_template: None,
+            idle_close_secs: None,
+            event_type_allowlist: Vec::new(),
+            label_allowlist: Vec::new(),
+            max_prompt_chars: None,
+            prompt_truncation: PromptTruncation::default(),
+            health_bind: None,
+            ice_servers: Vec::new(),
+            tunnel_port_range: None,
             _hub_name: None,
         }
     }
@@ -124,13 +302,45 @@ impl Config {
         Ok(config)
     }
 
-    fn load_from_file() -> Result<Self> {
-        let config_path = Self::config_dir()?.join("config.json");
+    /// Loads only what is in `config.json`, without environment overrides
+    /// or the keyring token. Use this before [`Config::save`] so overrides
+    /// are not written back to disk. A missing file yields the defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed.
+    pub fn load_from_file() -> Result<Self> {
+        Self::load_from_path(&Self::config_dir()?.join("config.json"))
+    }
+
+    /// Settings from an already parsed `config.json`, with environment
+    /// overrides applied but no keyring token.
+    ///
+    /// Keys missing from `value` keep their defaults, so a partial file
+    /// resolves the same way everywhere. A value that doesn't parse yields
+    /// the defaults, as [`Config::load`] does.
+    #[must_use]
+    pub fn from_file_value(value: &serde_json::Value) -> Self {
+        let mut merged = serde_json::to_value(Self::default()).unwrap_or_default();
+        if let (Some(merged), Some(file)) = (merged.as_object_mut(), value.as_object()) {
+            for (key, setting) in file {
+                merged.insert(key.clone(), setting.clone());
+            }
+        }
+        let mut config = serde_json::from_value(merged).unwrap_or_else(|e| {
+            log::warn!("Ignoring unreadable config.json: {e}");
+            Self::default()
+        });
+        config.apply_env_overrides();
+        config
+    }
+
+    fn load_from_path(config_path: &Path) -> Result<Self> {
         if config_path.exists() {
-            let content = fs::read_to_string(&config_path)?;
+            let content = fs::read_to_string(config_path)?;
             Ok(serde_json::from_str(&content)?)
         } else {
-            anyhow::bail!("Config file not found")
+            Ok(Self::default())
         }
     }
 
@@ -170,16 +380,144 @@ impl Config {
     /// Persists the current configuration to disk.
     /// Note: Token is NOT saved here (use save_token for that).
     pub fn save(&self) -> Result<()> {
-        let config_path = Self::config_dir()?.join("config.json");
-        fs::write(&config_path, serde_json::to_string_pretty(self)?)?;
+        self.save_to_path(&Self::config_dir()?.join("config.json"))
+    }
+
+    fn save_to_path(&self, config_path: &Path) -> Result<()> {
+        fs::write(config_path, serde_json::to_string_pretty(self)?)?;
+
+        // Set restrictive permissions (owner read/write only)
+        #[cfg(unix)]
+        fs::set_permissions(config_path, fs::Permissions::from_mode(0o600))?;
+
+        Ok(())
+    }
+
+    /// Writes this config's value for the top-level `key` into `config.json`,
+    /// leaving every other key in the file as it is.
+    ///
+    /// The file is edited as plain JSON, so keys `Config` doesn't know about
+    /// (set by Lua `config.set` or plugins) survive.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, parsed or written.
+    pub fn save_key(&self, key: &str) -> Result<()> {
+        self.save_key_to_path(&Self::config_dir()?.join("config.json"), key)
+    }
+
+    fn save_key_to_path(&self, config_path: &Path, key: &str) -> Result<()> {
+        let mut root = if config_path.exists() {
+            serde_json::from_str(&fs::read_to_string(config_path)?)
+                .with_context(|| format!("Failed to parse {}", config_path.display()))?
+        } else {
+            serde_json::json!({})
+        };
+        let value = serde_json::to_value(self)?
+            .get(key)
+            .cloned()
+            .unwrap_or(serde_json::Value::Null);
+        root.as_object_mut()
+            .with_context(|| format!("{} is not a JSON object", config_path.display()))?
+            .insert(key.to_string(), value);
+
+        fs::write(config_path, serde_json::to_string_pretty(&root)?)?;
 
         // Set restrictive permissions (owner read/write only)
         #[cfg(unix)]
-        fs::set_permissions(&config_path, fs::Permissions::from_mode(0o600))?;
+        fs::set_permissions(config_path, fs::Permissions::from_mode(0o600))?;
+
+        Ok(())
+    }
 
+    /// Updates the setting `key` from its command-line string form.
+    ///
+    /// Only [`SETTABLE_KEYS`] are accepted. `api_key` only updates the
+    /// in-memory token; persist it with [`Config::save_token`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `key` is not settable or `value` does not parse.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "server_url" => self.server_url = value.to_string(),
+            "api_key" => {
+                anyhow::ensure!(
+                    value.starts_with("btstr_"),
+                    "api_key must start with 'btstr_'"
+                );
+                self.token = value.to_string();
+            }
+            "poll_interval" => {
+                self.poll_interval = value.parse().with_context(|| {
+                    format!("poll_interval must be a number of seconds, got '{value}'")
+                })?;
+            }
+            "worktree_base" => self.worktree_base = PathBuf::from(value),
+            "max_sessions" => {
+                self.max_sessions = value.parse().with_context(|| {
+                    format!("max_sessions must be a whole number, got '{value}'")
+                })?;
+            }
+            _ => anyhow::bail!(
+                "Unknown config key '{key}' (valid keys: {})",
+                SETTABLE_KEYS.join(", ")
+            ),
+        }
         Ok(())
     }
 
+    /// Check whether messages for `repo` should be processed.
+    ///
+    /// See [`repo_in_allowlist`].
+    pub fn is_repo_allowed(&self, repo: &str) -> bool {
+        repo_in_allowlist(&self.allowed_repos, repo)
+    }
+
+    /// Effective configuration for `repo`, with its [`RepoOverride`] applied.
+    ///
+    /// Repo names match ignoring ASCII case, as in [`repo_in_allowlist`].
+    /// Repos without an entry get the top-level settings unchanged.
+    #[must_use]
+    pub fn for_repo(&self, repo: &str) -> Config {
+        let mut config = self.clone();
+        let Some(overrides) = self
+            .repos
+            .iter()
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case(repo.trim()))
+            .map(|(_, overrides)| overrides)
+        else {
+            return config;
+        };
+
+        if let Some(worktree_base) = &overrides.worktree_base {
+            config.worktree_base = worktree_base.clone();
+        }
+        config
+    }
+
+    /// Check whether another `profile` agent may be spawned.
+    ///
+    /// See [`spawn_limit_error`].
+    pub fn spawn_limit_error(
+        &self,
+        profile: &str,
+        profile_active: usize,
+        total_active: usize,
+    ) -> Option<String> {
+        let max_concurrent = self
+            .profiles
+            .get(profile)
+            .and_then(|limits| limits.max_concurrent);
+        spawn_limit_error(
+            Some(self.max_sessions),
+            max_concurrent,
+            profile,
+            profile_active,
+            total_active,
+        )
+    }
+
     /// Get the API token for authentication.
     pub fn get_api_key(&self) -> &str {
         &self.token
@@ -216,6 +554,41 @@ impl Config {
     }
 }
 
+/// Check `repo` against an `allowed_repos` list.
+///
+/// An empty list allows everything. Matching ignores ASCII case and
+/// surrounding whitespace, since GitHub repo names are case-insensitive.
+pub fn repo_in_allowlist(allowed: &[String], repo: &str) -> bool {
+    allowed.is_empty()
+        || allowed
+            .iter()
+            .any(|entry| entry.trim().eq_ignore_ascii_case(repo.trim()))
+}
+
+/// Describe why spawning another `profile` agent would exceed a limit.
+///
+/// `max_concurrent` is the profile's own cap and `max_sessions` the global
+/// cap across all profiles. Returns `None` when the spawn is allowed.
+pub fn spawn_limit_error(
+    max_sessions: Option<usize>,
+    max_concurrent: Option<usize>,
+    profile: &str,
+    profile_active: usize,
+    total_active: usize,
+) -> Option<String> {
+    if let Some(limit) = max_concurrent {
+        if profile_active >= limit {
+            return Some(format!("max {limit} {profile} agents reached"));
+        }
+    }
+    if let Some(limit) = max_sessions {
+        if total_active >= limit {
+            return Some(format!("max {limit} agents reached (max_sessions)"));
+        }
+    }
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -261,4 +634,262 @@ mod tests {
         config.token.clear();
         assert!(!config.has_token());
     }
+
+    #[test]
+    fn test_empty_allowlist_allows_any_repo() {
+        let config = Config::default();
+        assert!(config.allowed_repos.is_empty());
+        assert!(config.is_repo_allowed("owner/repo"));
+    }
+
+    #[test]
+    fn test_allowlist_skips_unlisted_repo() {
+        let mut config = Config::default();
+        config.allowed_repos = vec!["acme/widgets".to_string()];
+
+        assert!(config.is_repo_allowed("acme/widgets"));
+        assert!(config.is_repo_allowed("Acme/Widgets"));
+        assert!(!config.is_repo_allowed("acme/other"));
+        assert!(!config.is_repo_allowed(""));
+    }
+
+    #[test]
+    fn test_allowed_repos_deserializes_with_default() {
+        let json = r#"{
+            "server_url": "https://example.com",
+            "poll_interval": 5,
+            "agent_timeout": 3600,
+            "max_sessions": 20,
+            "worktree_base": "/tmp/wt"
+        }"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert!(config.allowed_repos.is_empty());
+
+        let serialized = serde_json::to_string(&config).unwrap();
+        assert!(!serialized.contains("allowed_repos"));
+    }
+
+    #[test]
+    fn test_profile_limit_blocks_third_spawn_but_not_other_profiles() {
+        let mut config = Config::default();
+        config.profiles.insert(
+            "codex".to_string(),
+            ProfileLimits {
+                max_concurrent: Some(2),
+                ..ProfileLimits::default()
+            },
+        );
+
+        assert!(config.spawn_limit_error("codex", 1, 1).is_none());
+        assert_eq!(
+            config.spawn_limit_error("codex", 2, 2).as_deref(),
+            Some("max 2 codex agents reached")
+        );
+        assert!(config.spawn_limit_error("claude", 0, 2).is_none());
+    }
+
+    #[test]
+    fn test_max_sessions_caps_all_profiles() {
+        let mut config = Config::default();
+        config.max_sessions = 3;
+
+        assert!(config.spawn_limit_error("claude", 2, 2).is_none());
+        assert_eq!(
+            config.spawn_limit_error("claude", 0, 3).as_deref(),
+            Some("max 3 agents reached (max_sessions)")
+        );
+    }
+
+    #[test]
+    fn test_for_repo_applies_overrides() {
+        let json = r#"{
+            "server_url": "https://trybotster.com",
+            "poll_interval": 5,
+            "agent_timeout": 3600,
+            "max_sessions": 20,
+            "worktree_base": "/tmp/sessions",
+            "repos": {
+                "acme/api": { "worktree_base": "/srv/acme" }
+            }
+        }"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+
+        let effective = config.for_repo("Acme/API");
+        assert_eq!(effective.worktree_base, PathBuf::from("/srv/acme"));
+        assert_eq!(effective.poll_interval, 5);
+        assert_eq!(effective.max_sessions, 20);
+    }
+
+    #[test]
+    fn test_for_repo_falls_back_to_defaults() {
+        let mut config = Config {
+            worktree_base: PathBuf::from("/tmp/sessions"),
+            ..Config::default()
+        };
+        config
+            .repos
+            .insert("acme/api".to_string(), RepoOverride::default());
+
+        let other = config.for_repo("acme/web");
+        assert_eq!(other.worktree_base, PathBuf::from("/tmp/sessions"));
+
+        let empty = config.for_repo("acme/api");
+        assert_eq!(empty.worktree_base, PathBuf::from("/tmp/sessions"));
+    }
+
+    #[test]
+    fn test_set_persists_across_reload() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.json");
+        let mut config = Config::default();
+
+        config.set("poll_interval", "12").unwrap();
+        config.set("worktree_base", "/srv/sessions").unwrap();
+        config.set("api_key", "btstr_new").unwrap();
+        config.save_to_path(&path).unwrap();
+
+        let reloaded = Config::load_from_path(&path).unwrap();
+        assert_eq!(reloaded.poll_interval, 12);
+        assert_eq!(reloaded.worktree_base, PathBuf::from("/srv/sessions"));
+        // The token lives in the keyring, never in config.json.
+        assert!(reloaded.token.is_empty());
+    }
+
+    #[test]
+    fn test_save_key_keeps_unknown_keys() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.json");
+        fs::write(
+            &path,
+            r#"{"poll_interval": 5, "plugin_setting": {"enabled": true}, "cleanup_policy": "purge"}"#,
+        )
+        .unwrap();
+
+        let mut config = Config::default();
+        config.set("max_sessions", "4").unwrap();
+        config.save_key_to_path(&path, "max_sessions").unwrap();
+
+        let saved: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["max_sessions"], 4);
+        assert_eq!(saved["poll_interval"], 5);
+        assert_eq!(saved["plugin_setting"]["enabled"], true);
+        assert_eq!(saved["cleanup_policy"], "purge", "left for the user to fix");
+    }
+
+    #[test]
+    fn test_set_rejects_unknown_key_and_bad_value() {
+        let mut config = Config::default();
+
+        let err = config.set("colour", "blue").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown config key 'colour' (valid keys: server_url, api_key, \
+             poll_interval, worktree_base, max_sessions)"
+        );
+        assert!(config.set("max_sessions", "many").is_err());
+        assert!(config.set("api_key", "not-a-token").is_err());
+        assert_eq!(config.max_sessions, 20);
+    }
+
+    #[test]
+    fn test_trust_prompt_settings_round_trip() {
+        let mut config = Config::default();
+        let serialized = serde_json::to_string(&config).unwrap();
+        assert!(!serialized.contains("auto_trust"));
+        assert!(!serialized.contains("trust_prompt_patterns"));
+
+        config.auto_trust = true;
+        config.trust_prompt_patterns = vec!["trust this workspace".to_string()];
+        let serialized = serde_json::to_string(&config).unwrap();
+        let restored: Config = serde_json::from_str(&serialized).unwrap();
+        assert!(restored.auto_trust);
+        assert_eq!(restored.trust_prompt_patterns, config.trust_prompt_patterns);
+        assert!(restored.trust_prompt_response.is_none());
+    }
+
+    #[test]
+    fn test_cleanup_policy_round_trip() {
+        let mut config = Config::default();
+        assert_eq!(config.cleanup_policy, CleanupPolicy::Keep);
+        let serialized = serde_json::to_string(&config).unwrap();
+        assert!(!serialized.contains("cleanup_policy"));
+
+        config.cleanup_policy = CleanupPolicy::Archive;
+        let serialized = serde_json::to_string(&config).unwrap();
+        assert!(serialized.contains(r#""cleanup_policy":"archive""#));
+        let restored: Config = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(restored.cleanup_policy, CleanupPolicy::Archive);
+
+        let mut value = serde_json::to_value(Config::default()).unwrap();
+        value["cleanup_policy"] = "purge".into();
+        let restored: Config = serde_json::from_value(value).unwrap();
+        assert_eq!(restored.cleanup_policy, CleanupPolicy::Keep);
+    }
+
+    #[test]
+    fn test_ice_servers_parse_turn_entry() {
+        let json = r#"{
+            "server_url": "https://example.com",
+            "poll_interval": 5,
+            "agent_timeout": 3600,
+            "max_sessions": 20,
+            "worktree_base": "/tmp/wt",
+            "ice_servers": [
+                {"urls": "turn:turn.example.com:3478", "username": "u", "credential": "p"}
+            ]
+        }"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            config.ice_servers,
+            vec![IceServerConfig {
+                urls: "turn:turn.example.com:3478".to_string(),
+                username: Some("u".to_string()),
+                credential: Some("p".to_string()),
+            }]
+        );
+        assert!(!serde_json::to_string(&Config::default())
+            .unwrap()
+            .contains("ice_servers"));
+    }
+
+    #[test]
+    fn test_branch_template_round_trip() {
+        let mut config = Config::default();
+        let serialized = serde_json::to_string(&config).unwrap();
+        assert!(!serialized.contains("branch_template"));
+
+        config.branch_template = Some("bot/issue-{issue}".to_string());
+        let serialized = serde_json::to_string(&config).unwrap();
+        let restored: Config = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(
+            restored.branch_template.as_deref(),
+            Some("bot/issue-{issue}")
+        );
+    }
+
+    #[test]
+    fn test_prompt_limit_round_trip() {
+        let mut config = Config::default();
+        let serialized = serde_json::to_string(&config).unwrap();
+        assert!(!serialized.contains("max_prompt_chars"));
+        assert!(!serialized.contains("prompt_truncation"));
+
+        config.max_prompt_chars = Some(4000);
+        config.prompt_truncation = PromptTruncation::Tail;
+        let serialized = serde_json::to_string(&config).unwrap();
+        assert!(serialized.contains(r#""prompt_truncation":"tail""#));
+        let restored: Config = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(restored.max_prompt_chars, Some(4000));
+        assert_eq!(restored.prompt_truncation, PromptTruncation::Tail);
+
+        let restored: Config =
+            serde_json::from_str(&serialized.replace(r#""tail""#, r#""head_tail""#)).unwrap();
+        assert_eq!(restored.prompt_truncation, PromptTruncation::HeadTail);
+
+        let restored: Config =
+            serde_json::from_str(&serialized.replace(r#""tail""#, r#""middle""#)).unwrap();
+        assert_eq!(restored.max_prompt_chars, Some(4000));
+        assert_eq!(restored.prompt_truncation, PromptTruncation::HeadTail);
+    }
 }
@@ -6,23 +6,133 @@
 
 use anyhow::{Context, Result};
 use globset::{Glob, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     fs,
+    io::{BufRead, BufReader},
+    os::unix::process::CommandExt,
     path::{Path, PathBuf},
+    process::Stdio,
+    time::{Duration, Instant},
 };
 
+/// Branch name template used when `branch_template` is not configured.
+pub const DEFAULT_BRANCH_TEMPLATE: &str = "botster-issue-{issue}";
+
+/// Script sourced in a worktree before it is deleted, e.g. to stop dev
+/// servers and free ports. Looked up in the worktree, then the main repo.
+pub const TEARDOWN_SCRIPT: &str = ".botster_teardown";
+
+/// How long a [`TEARDOWN_SCRIPT`] may run before its process group is
+/// killed and the deletion goes ahead.
+pub const TEARDOWN_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How long an overrunning teardown script gets after SIGTERM before its
+/// process group is sent SIGKILL.
+const TEARDOWN_KILL_GRACE: Duration = Duration::from_secs(2);
+
 /// Manages git worktrees for agent sessions.
 #[derive(Debug)]
 pub struct WorktreeManager {
     /// Base directory for worktree storage.
     base_dir: PathBuf,
+    /// Issue branch name with `{user}` already expanded and `{issue}` left
+    /// for [`Self::branch_name_for_issue`].
+    branch_template: String,
+    /// Environment for the [`TEARDOWN_SCRIPT`].
+    teardown_env: HashMap<String, String>,
+    /// Deadline for the [`TEARDOWN_SCRIPT`].
+    teardown_timeout: Duration,
 }
 
 impl WorktreeManager {
     /// Creates a new worktree manager with the specified base directory.
     pub fn new(base_dir: PathBuf) -> Self {
-        Self { base_dir }
+        Self {
+            base_dir,
+            branch_template: DEFAULT_BRANCH_TEMPLATE.to_string(),
+            teardown_env: HashMap::new(),
+            teardown_timeout: TEARDOWN_TIMEOUT,
+        }
+    }
+
+    /// Uses `template` for issue branch names instead of
+    /// [`DEFAULT_BRANCH_TEMPLATE`].
+    ///
+    /// `{issue}` is replaced by the issue number and `{user}` by `$USER`.
+    /// A template without exactly one `{issue}` is ignored with a warning,
+    /// since issue numbers could not be parsed back out of its branches.
+    #[must_use]
+    pub fn with_branch_template(mut self, template: &str) -> Self {
+        if template.matches("{issue}").count() == 1 {
+            let user = std::env::var("USER").unwrap_or_default();
+            self.branch_template = template.replace("{user}", &user);
+        } else {
+            log::warn!("Ignoring branch_template {template:?}: it must contain {{issue}} once");
+        }
+        self
+    }
+
+    /// Runs the [`TEARDOWN_SCRIPT`] with `env` added to its environment,
+    /// normally the environment the worktree's session was spawned with.
+    #[must_use]
+    pub fn with_teardown_env(mut self, env: HashMap<String, String>) -> Self {
+        self.teardown_env = env;
+        self
+    }
+
+    /// Kills the [`TEARDOWN_SCRIPT`] after `timeout` instead of
+    /// [`TEARDOWN_TIMEOUT`].
+    #[must_use]
+    pub fn with_teardown_timeout(mut self, timeout: Duration) -> Self {
+        self.teardown_timeout = timeout;
+        self
+    }
+
+    /// Branch name for an issue agent, from the configured template.
+    #[must_use]
+    pub fn branch_name_for_issue(&self, issue_number: u32) -> String {
+        self.branch_template
+            .replace("{issue}", &issue_number.to_string())
+    }
+
+    /// Directory under the base dir for `repo`'s clone (empty `suffix`) or
+    /// one of its worktrees, named `{repo}-{suffix}` with the repo part from
+    /// [`normalize_repo_name`].
+    ///
+    /// Directories created before repo names were normalized only had `/`
+    /// replaced. If such a directory exists and the normalized one doesn't,
+    /// it is returned instead so existing worktrees are reused rather than
+    /// duplicated.
+    fn repo_dir(&self, repo: &str, suffix: &str) -> PathBuf {
+        let name = |repo_safe: String| {
+            if suffix.is_empty() {
+                repo_safe
+            } else {
+                format!("{}-{}", repo_safe, suffix)
+            }
+        };
+        let path = self.base_dir.join(name(normalize_repo_name(repo)));
+        let legacy = self.base_dir.join(name(legacy_repo_name(repo)));
+        if !path.exists() && legacy.exists() {
+            log::info!("Using pre-normalization directory {}", legacy.display());
+            legacy
+        } else {
+            path
+        }
+    }
+
+    /// Issue number encoded in `branch_name`, if it was built from the
+    /// configured template.
+    #[must_use]
+    pub fn issue_number_for_branch(&self, branch_name: &str) -> Option<u32> {
+        let (prefix, suffix) = self.branch_template.split_once("{issue}")?;
+        let digits = branch_name.strip_prefix(prefix)?.strip_suffix(suffix)?;
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        digits.parse().ok()
     }
 
     /// Copy files from `source_repo` to `dest` matching glob patterns in `patterns_file`.
@@ -154,9 +264,38 @@ impl WorktreeManager {
     }
 
     /// Creates a worktree from the current repository
+    ///
+    /// The worktree is tagged with `issue_number` so
+    /// [`Self::find_existing_worktree_for_issue`] finds it even if its branch
+    /// is later renamed.
     pub fn create_worktree_from_current(&self, issue_number: u32) -> Result<PathBuf> {
-        let branch_name = format!("botster-issue-{}", issue_number);
-        self.create_worktree_with_branch(&branch_name)
+        let branch_name = self.branch_name_for_issue(issue_number);
+        let worktree_path = self.create_worktree_with_branch(&branch_name)?;
+        record_worktree_issue(&worktree_path, issue_number);
+        Ok(worktree_path)
+    }
+
+    /// Tags the worktree at `path` with `issue_number`, so issue lookups find
+    /// it whatever its branch. Failure is logged, not returned.
+    pub fn tag_worktree_issue(path: &Path, issue_number: u32) {
+        record_worktree_issue(path, issue_number);
+    }
+
+    /// Returns the issue number the worktree at `path` was tagged with.
+    #[must_use]
+    pub fn worktree_issue(path: &Path) -> Option<u32> {
+        read_worktree_issue(path)
+    }
+
+    /// Finds a worktree of the repo at `repo_path` tagged with `issue_number`.
+    pub fn find_worktree_for_issue(
+        &self,
+        repo_path: &Path,
+        issue_number: u32,
+    ) -> Result<Option<PathBuf>> {
+        Ok(git_worktree_paths(repo_path)?
+            .into_iter()
+            .find(|path| path.exists() && read_worktree_issue(path) == Some(issue_number)))
     }
 
     /// Creates a worktree for an explicit repository root.
@@ -166,16 +305,26 @@ impl WorktreeManager {
         branch_name: &str,
     ) -> Result<PathBuf> {
         let repo_name = repo_name_for_root(repo_path)?;
-        let repo_safe = repo_name.replace('/', "-");
-        let sanitized_branch = branch_name.replace('/', "-");
-        let worktree_path = self
-            .base_dir
-            .join(format!("{}-{}", repo_safe, sanitized_branch));
+        let worktree_path = self.repo_dir(&repo_name, &branch_name.replace('/', "-"));
 
         self.cleanup_worktree(&repo_path.to_path_buf(), &worktree_path)?;
 
         let branch_exists = git_branch_exists(repo_path, branch_name);
         let output = if branch_exists {
+            // A crash can leave the branch registered to a worktree whose
+            // directory is gone, which makes `worktree add` refuse it.
+            let _ = std::process::Command::new("git")
+                .args(["worktree", "prune"])
+                .current_dir(repo_path)
+                .output();
+            if let Some(path) = self.find_worktree_for_branch(repo_path, branch_name)? {
+                return Err(WorktreeError::BranchInUse {
+                    branch: branch_name.to_string(),
+                    path,
+                }
+                .into());
+            }
+
             log::info!("Using existing branch: {}", branch_name);
             std::process::Command::new("git")
                 .args([
@@ -205,6 +354,7 @@ impl WorktreeManager {
             anyhow::bail!("Failed to create worktree: {}", stderr);
         }
 
+        exclude_hub_files(&worktree_path);
         Ok(worktree_path)
     }
 
@@ -250,10 +400,9 @@ impl WorktreeManager {
 
     /// Creates or reuses a git worktree for the given repo and issue (clone from GitHub)
     pub fn create_worktree(&self, repo: &str, issue_number: u32) -> Result<PathBuf> {
-        let repo_safe = repo.replace('/', "-");
         fs::create_dir_all(&self.base_dir)?;
 
-        let clone_dir = self.base_dir.join(&repo_safe);
+        let clone_dir = self.repo_dir(repo, "");
 
         // Clone if needed
         if !clone_dir.exists() {
@@ -273,10 +422,13 @@ impl WorktreeManager {
             }
         }
 
-        let branch_name = format!("botster-{}-{}", repo_safe, issue_number);
-        let worktree_path = self
-            .base_dir
-            .join(format!("{}-{}", repo_safe, issue_number));
+        // Keep using a branch named before repo names were normalized.
+        let branch_name = [normalize_repo_name(repo), legacy_repo_name(repo)]
+            .into_iter()
+            .map(|repo_safe| format!("botster-{}-{}", repo_safe, issue_number))
+            .find(|branch| git_branch_exists(&clone_dir, branch))
+            .unwrap_or_else(|| format!("botster-{}-{}", normalize_repo_name(repo), issue_number));
+        let worktree_path = self.repo_dir(repo, &issue_number.to_string());
 
         // Remove existing worktree if present
         self.cleanup_worktree(&clone_dir, &worktree_path)?;
@@ -316,6 +468,8 @@ impl WorktreeManager {
             anyhow::bail!("Failed to create worktree: {}", stderr);
         }
 
+        record_worktree_issue(&worktree_path, issue_number);
+
         // Mark as trusted for Claude
         let claude_dir = worktree_path.join(".claude");
         fs::create_dir_all(&claude_dir)?;
@@ -330,6 +484,7 @@ impl WorktreeManager {
             serde_json::to_string_pretty(&settings)?,
         )?;
 
+        exclude_hub_files(&worktree_path);
         Ok(worktree_path)
     }
 
@@ -370,8 +525,7 @@ impl WorktreeManager {
 
     /// Lists all existing worktrees for a repo
     pub fn list_worktrees(&self, repo: &str) -> Result<Vec<String>> {
-        let repo_safe = repo.replace('/', "-");
-        let clone_dir = self.base_dir.join(&repo_safe);
+        let clone_dir = self.repo_dir(repo, "");
 
         if !clone_dir.exists() {
             return Ok(Vec::new());
@@ -393,9 +547,33 @@ impl WorktreeManager {
         issue_number: u32,
     ) -> Result<Option<(PathBuf, String)>> {
         let (repo_path, repo_name) = Self::detect_current_repo()?;
-        let repo_safe = repo_name.replace('/', "-");
-        let branch_name = format!("botster-issue-{}", issue_number);
-        let worktree_path = self.base_dir.join(format!("{}-{}", repo_safe, branch_name));
+        self.find_existing_worktree_for_issue_in_repo(&repo_path, &repo_name, issue_number)
+    }
+
+    /// Finds an existing worktree for an issue under an explicit repository
+    /// root.
+    ///
+    /// Worktrees tagged with the issue at creation are matched first, whatever
+    /// their branch. Otherwise the configured branch naming is used.
+    pub fn find_existing_worktree_for_issue_in_repo(
+        &self,
+        repo_path: &Path,
+        repo_name: &str,
+        issue_number: u32,
+    ) -> Result<Option<(PathBuf, String)>> {
+        if let Some(path) = self.find_worktree_for_issue(repo_path, issue_number)? {
+            let branch = git_current_branch(&path)?;
+            log::info!(
+                "Found worktree tagged with issue #{} at {} (branch {})",
+                issue_number,
+                path.display(),
+                branch
+            );
+            return Ok(Some((path, branch)));
+        }
+
+        let branch_name = self.branch_name_for_issue(issue_number);
+        let worktree_path = self.repo_dir(repo_name, &branch_name);
 
         // Check if the worktree directory exists
         if !worktree_path.exists() {
@@ -416,7 +594,7 @@ impl WorktreeManager {
         // Verify the worktree is valid by checking if git recognizes it
         let output = std::process::Command::new("git")
             .args(["worktree", "list", "--porcelain"])
-            .current_dir(&repo_path)
+            .current_dir(repo_path)
             .output()?;
 
         if !output.status.success() {
@@ -446,18 +624,47 @@ impl WorktreeManager {
         Ok(None)
     }
 
-    /// Prunes all stale worktrees for a repo
-    pub fn prune_stale_worktrees(&self, repo: &str) -> Result<()> {
-        let repo_safe = repo.replace('/', "-");
-        let clone_dir = self.base_dir.join(&repo_safe);
+    /// Prunes worktrees of the current repository whose directories are gone.
+    ///
+    /// Returns the paths that were unregistered.
+    pub fn prune_stale_worktrees(&self) -> Result<Vec<String>> {
+        let (repo_path, _) = Self::detect_current_repo()?;
+        self.prune_stale_worktrees_for_repo_root(&repo_path)
+    }
 
-        if clone_dir.exists() {
-            std::process::Command::new("git")
-                .args(["worktree", "prune"])
-                .current_dir(&clone_dir)
-                .output()?;
+    /// Prunes worktrees under an explicit repository root whose directories
+    /// are gone, returning the paths that were unregistered.
+    ///
+    /// Locked worktrees are left registered, as `git worktree prune` does.
+    pub fn prune_stale_worktrees_for_repo_root(&self, repo_path: &Path) -> Result<Vec<String>> {
+        let missing: Vec<PathBuf> = git_worktree_paths(repo_path)?
+            .into_iter()
+            .filter(|path| !path.exists())
+            .collect();
+        if missing.is_empty() {
+            return Ok(Vec::new());
         }
-        Ok(())
+
+        let output = std::process::Command::new("git")
+            .args(["worktree", "prune"])
+            .current_dir(repo_path)
+            .output()
+            .context("Failed to run git worktree prune")?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("Failed to prune worktrees: {}", stderr.trim());
+        }
+
+        let remaining = git_worktree_paths(repo_path)?;
+        let pruned = missing
+            .into_iter()
+            .filter(|path| !remaining.contains(path))
+            .map(|path| path.display().to_string())
+            .collect::<Vec<_>>();
+        for path in &pruned {
+            log::info!("Pruned stale worktree {}", path);
+        }
+        Ok(pruned)
     }
 
     /// Deletes a worktree by path, running teardown scripts first.
@@ -546,6 +753,8 @@ impl WorktreeManager {
             return Ok(());
         }
 
+        self.run_teardown_script(&repo_path, worktree_path);
+
         log::info!("Deleting worktree at {}", worktree_path.display());
 
         // Remove the worktree using git
@@ -604,38 +813,78 @@ impl WorktreeManager {
         Ok(())
     }
 
+    /// Describes the worktree deleting issue `issue_number` would remove,
+    /// without touching anything. Returns `None` if the issue has no worktree.
+    pub fn plan_issue_worktree_deletion(
+        &self,
+        repo_path: &Path,
+        repo_name: &str,
+        issue_number: u32,
+    ) -> Result<Option<WorktreeDeletion>> {
+        let Some((path, branch)) =
+            self.find_existing_worktree_for_issue_in_repo(repo_path, repo_name, issue_number)?
+        else {
+            return Ok(None);
+        };
+        let unpushed = unpushed_work(&path, &branch, true)?;
+        Ok(Some(WorktreeDeletion {
+            path,
+            branch,
+            unpushed,
+        }))
+    }
+
     /// Deletes a worktree by issue number.
-    pub fn delete_worktree_by_issue_number(&self, issue_number: u32) -> Result<()> {
+    ///
+    /// A worktree with uncommitted changes or unpushed commits is only
+    /// deleted with `force`.
+    pub fn delete_worktree_by_issue_number(&self, issue_number: u32, force: bool) -> Result<()> {
         // Detect the current repo
         let (repo_path, repo_name) = Self::detect_current_repo()?;
 
-        let repo_safe = repo_name.replace('/', "-");
-        let branch_name = format!("botster-issue-{}", issue_number);
-        let worktree_path = self
-            .base_dir
-            .join(format!("{}-{}", repo_safe, issue_number));
+        match self.plan_issue_worktree_deletion(&repo_path, &repo_name, issue_number)? {
+            Some(plan) => self.delete_planned_worktree(&repo_path, &plan, force),
+            None => {
+                log::warn!(
+                    "Worktree for issue #{} does not exist, skipping deletion",
+                    issue_number
+                );
+                Ok(())
+            }
+        }
+    }
 
-        if !worktree_path.exists() {
-            log::warn!(
-                "Worktree for issue #{} does not exist at {}, skipping deletion",
-                issue_number,
-                worktree_path.display()
-            );
-            return Ok(());
+    /// Removes the worktree and branch described by `plan`.
+    ///
+    /// Refuses a worktree with unpushed work unless `force` is set.
+    pub fn delete_planned_worktree(
+        &self,
+        repo_path: &Path,
+        plan: &WorktreeDeletion,
+        force: bool,
+    ) -> Result<()> {
+        if let Some(reason) = &plan.unpushed {
+            if !force {
+                anyhow::bail!(
+                    "Refusing to delete worktree {}: {}",
+                    plan.path.display(),
+                    reason
+                );
+            }
         }
 
-        log::info!("Deleting worktree for issue #{}", issue_number);
+        self.run_teardown_script(repo_path, &plan.path);
 
         // Remove the worktree using git
-        log::info!("Removing worktree at {}", worktree_path.display());
+        log::info!("Removing worktree at {}", plan.path.display());
         let output = std::process::Command::new("git")
             .args([
                 "worktree",
                 "remove",
-                worktree_path.to_str().expect("path is valid UTF-8"),
+                plan.path.to_str().expect("path is valid UTF-8"),
                 "--force",
             ])
-            .current_dir(&repo_path)
+            .current_dir(repo_path)
             .output()?;
 
         if !output.status.success() {
@@ -644,20 +893,360 @@ impl WorktreeManager {
         }
 
         // Delete the branch
-        log::info!("Deleting branch {}", branch_name);
+        log::info!("Deleting branch {}", plan.branch);
         let output = std::process::Command::new("git")
-            .args(["branch", "-D", &branch_name])
-            .current_dir(&repo_path)
+            .args(["branch", "-D", &plan.branch])
+            .current_dir(repo_path)
             .output()?;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
-            log::warn!("Failed to delete branch {}: {}", branch_name, stderr);
+            log::warn!("Failed to delete branch {}: {}", plan.branch, stderr);
         }
 
-        log::info!("Successfully deleted worktree for issue #{}", issue_number);
+        log::info!("Successfully deleted worktree at {}", plan.path.display());
         Ok(())
     }
+
+    /// Sources the [`TEARDOWN_SCRIPT`] in `worktree_path`, if there is one.
+    ///
+    /// Output is logged. A failing script is reported but never stops the
+    /// deletion. The script runs in its own process group; if it is still
+    /// running after the teardown timeout, the whole group (including
+    /// anything it started in the background) is terminated.
+    fn run_teardown_script(&self, repo_path: &Path, worktree_path: &Path) {
+        let Some(script) = [worktree_path, repo_path]
+            .iter()
+            .map(|dir| dir.join(TEARDOWN_SCRIPT))
+            .find(|script| script.is_file())
+        else {
+            return;
+        };
+
+        log::info!("Running teardown script {}", script.display());
+        let spawned = std::process::Command::new("sh")
+            .args(["-c", ". \"$1\"", "botster-teardown"])
+            .arg(&script)
+            .current_dir(worktree_path)
+            .env("BOTSTER_WORKTREE_PATH", worktree_path)
+            .envs(&self.teardown_env)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .process_group(0)
+            .spawn();
+        let mut child = match spawned {
+            Ok(child) => child,
+            Err(e) => {
+                log::warn!("Failed to run teardown script {}: {}", script.display(), e);
+                return;
+            }
+        };
+
+        // Log output as it arrives. The readers are not joined: a process
+        // that left the group could hold the pipes open indefinitely.
+        if let Some(stdout) = child.stdout.take() {
+            std::thread::spawn(move || {
+                for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                    log::info!("[teardown] {}", line);
+                }
+            });
+        }
+        if let Some(stderr) = child.stderr.take() {
+            std::thread::spawn(move || {
+                for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                    log::warn!("[teardown] {}", line);
+                }
+            });
+        }
+
+        let deadline = Instant::now() + self.teardown_timeout;
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break Some(status),
+                Ok(None) if Instant::now() < deadline => {
+                    std::thread::sleep(Duration::from_millis(20));
+                }
+                Ok(None) => break None,
+                Err(e) => {
+                    log::warn!(
+                        "Failed to wait for teardown script {}: {}",
+                        script.display(),
+                        e
+                    );
+                    break None;
+                }
+            }
+        };
+
+        let Some(status) = status else {
+            log::warn!(
+                "Teardown script {} still running after {:?}, killing it and deleting worktree anyway",
+                script.display(),
+                self.teardown_timeout
+            );
+            // The leader is our unreaped child, so its pid is still the
+            // group id and cannot have been reused.
+            if let Ok(pgid) = libc::pid_t::try_from(child.id()) {
+                // SAFETY: killpg only sends a signal to the script's group.
+                unsafe {
+                    libc::killpg(pgid, libc::SIGTERM);
+                }
+                crate::process::wait_process_group(pgid, TEARDOWN_KILL_GRACE, || {
+                    let _ = child.try_wait();
+                });
+            }
+            let _ = child.wait();
+            return;
+        };
+
+        if !status.success() {
+            log::warn!(
+                "Teardown script {} failed ({}), deleting worktree anyway",
+                script.display(),
+                status
+            );
+        }
+    }
+
+    /// Applies a [`CleanupPolicy`] to a worktree whose issue was closed.
+    ///
+    /// `Keep` is a no-op. `Delete` and `Archive` first check for work that
+    /// would be lost and bail without touching anything if they find some.
+    /// `Archive` tags the branch head as `archive/<branch>` before removing
+    /// the worktree and branch, so unpushed commits stay reachable.
+    pub fn cleanup_worktree_with_policy(
+        &self,
+        worktree_path: &Path,
+        branch_name: &str,
+        policy: CleanupPolicy,
+    ) -> Result<()> {
+        if policy == CleanupPolicy::Keep || !worktree_path.exists() {
+            return Ok(());
+        }
+
+        // Archive preserves commits via the tag, so only uncommitted changes
+        // would be lost. Delete loses both.
+        if let Some(reason) =
+            unpushed_work(worktree_path, branch_name, policy == CleanupPolicy::Delete)?
+        {
+            anyhow::bail!(
+                "Refusing to {} worktree {}: {}",
+                policy.as_str(),
+                worktree_path.display(),
+                reason
+            );
+        }
+
+        if policy == CleanupPolicy::Archive {
+            let tag = archive_tag(worktree_path, branch_name)?;
+            log::info!("Archived branch {} as tag {}", branch_name, tag);
+        }
+
+        self.delete_worktree_by_path(worktree_path, branch_name)
+    }
+}
+
+/// A worktree and branch that a delete would remove.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorktreeDeletion {
+    /// Worktree directory.
+    pub path: PathBuf,
+    /// Branch deleted along with the worktree.
+    pub branch: String,
+    /// Work the delete would lose (uncommitted changes or unpushed
+    /// commits), or `None` when there is none.
+    pub unpushed: Option<String>,
+}
+
+/// Worktree creation failures a caller may want to handle.
+///
+/// Returned inside [`anyhow::Error`]; use `downcast_ref` to match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorktreeError {
+    /// The branch is already checked out in another live worktree.
+    BranchInUse {
+        /// Branch that was requested.
+        branch: String,
+        /// Worktree that has it checked out.
+        path: PathBuf,
+    },
+}
+
+impl std::fmt::Display for WorktreeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BranchInUse { branch, path } => write!(
+                f,
+                "Branch {branch} is already checked out at {}",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for WorktreeError {}
+
+/// What to do with an agent's worktree when its issue is closed.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CleanupPolicy {
+    /// Remove the worktree directory and delete the branch.
+    Delete,
+    /// Leave the worktree and branch in place.
+    #[default]
+    Keep,
+    /// Remove the worktree directory, keeping the branch as an `archive/` tag.
+    Archive,
+}
+
+impl CleanupPolicy {
+    /// Returns the config spelling of the policy.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Delete => "delete",
+            Self::Keep => "keep",
+            Self::Archive => "archive",
+        }
+    }
+
+    /// Parses a config value, returning `None` for unknown policies.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "delete" => Some(Self::Delete),
+            "keep" => Some(Self::Keep),
+            "archive" => Some(Self::Archive),
+            _ => None,
+        }
+    }
+}
+
+/// Whether `worktree_path` has uncommitted changes, untracked files included.
+///
+/// # Errors
+///
+/// Returns an error if `git status` cannot run in `worktree_path`.
+pub fn has_uncommitted_changes(worktree_path: &Path) -> Result<bool> {
+    Ok(unpushed_work(worktree_path, "HEAD", false)?.is_some())
+}
+
+/// Describes work in `worktree_path` that removing it would lose, if any.
+///
+/// Uncommitted changes always count. With `check_commits`, commits on
+/// `branch_name` that no remote-tracking ref contains count too.
+fn unpushed_work(
+    worktree_path: &Path,
+    branch_name: &str,
+    check_commits: bool,
+) -> Result<Option<String>> {
+    let output = std::process::Command::new("git")
+        .args(["status", "--porcelain"])
+        .current_dir(worktree_path)
+        .output()
+        .context("Failed to run git status")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git status failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    if !output.stdout.is_empty() {
+        return Ok(Some("worktree has uncommitted changes".to_string()));
+    }
+
+    if !check_commits {
+        return Ok(None);
+    }
+
+    // Commits another branch, tag or remote-tracking ref still reaches are
+    // not lost when this branch goes, so repos without a remote work too.
+    // (`--exclude` patterns for `--branches` are relative to refs/heads.)
+    let exclude = format!("--exclude={branch_name}");
+    let count = git_stdout(
+        worktree_path,
+        &[
+            "rev-list",
+            "--count",
+            branch_name,
+            "--not",
+            &exclude,
+            "--branches",
+            "--tags",
+            "--remotes",
+        ],
+    )?;
+    let count: u64 = count
+        .parse()
+        .with_context(|| format!("Unexpected git rev-list output: {count:?}"))?;
+    if count > 0 {
+        return Ok(Some(format!(
+            "branch {branch_name} has {count} unpushed commit(s)"
+        )));
+    }
+
+    Ok(None)
+}
+
+/// Tags the head of `branch_name` as `archive/<branch>` and returns the tag.
+///
+/// An existing tag already pointing at the same commit is reused. One that
+/// points elsewhere (an earlier branch of the same name) is left alone and
+/// a numbered tag (`archive/<branch>-2`, ...) is created instead.
+fn archive_tag(worktree_path: &Path, branch_name: &str) -> Result<String> {
+    let head = git_stdout(
+        worktree_path,
+        &[
+            "rev-parse",
+            "--verify",
+            &format!("refs/heads/{branch_name}^{{commit}}"),
+        ],
+    )?;
+
+    for n in 1u32.. {
+        let tag = if n == 1 {
+            format!("archive/{branch_name}")
+        } else {
+            format!("archive/{branch_name}-{n}")
+        };
+        let existing = std::process::Command::new("git")
+            .args([
+                "rev-parse",
+                "-q",
+                "--verify",
+                &format!("refs/tags/{tag}^{{commit}}"),
+            ])
+            .current_dir(worktree_path)
+            .output()
+            .context("Failed to run git rev-parse")?;
+        if existing.status.success() {
+            if String::from_utf8_lossy(&existing.stdout).trim() == head {
+                return Ok(tag);
+            }
+            continue;
+        }
+
+        git_stdout(worktree_path, &["tag", &tag, &head])
+            .with_context(|| format!("Failed to tag {branch_name} as {tag}"))?;
+        return Ok(tag);
+    }
+    unreachable!("ran out of archive tag names")
+}
+
+/// Runs git in `dir` and returns its trimmed stdout, failing on a non-zero exit.
+fn git_stdout(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .with_context(|| format!("Failed to run git {}", args[0]))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
 }
 
 /// Returns the set of absolute paths for directories that git considers entirely
@@ -819,6 +1408,37 @@ fn git_remote_url(path: &Path) -> Result<String> {
     Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
 }
 
+/// Normalizes a repo name ("owner/name") for use in session keys and
+/// worktree directory names.
+///
+/// GitHub treats repo names case-insensitively, so `Owner/Repo` and
+/// `owner/repo` must map to the same key. The result is lowercase and every
+/// character outside `[a-z0-9._-]` (including the `/` separator) becomes `-`.
+pub fn normalize_repo_name(repo: &str) -> String {
+    repo.chars()
+        .map(|c| {
+            let c = c.to_ascii_lowercase();
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Repo name as directory names used it before [`normalize_repo_name`].
+fn legacy_repo_name(repo: &str) -> String {
+    repo.replace('/', "-")
+}
+
+/// Agent and worktree directory name for `branch` in `repo`:
+/// `{normalized-repo}-{branch}` with slashes in the branch replaced.
+#[must_use]
+pub fn worktree_dir_name(repo: &str, branch: &str) -> String {
+    format!("{}-{}", normalize_repo_name(repo), branch.replace('/', "-"))
+}
+
 /// Detects the repo name ("owner/name") for a given directory path.
 ///
 /// Finds the git root via `git rev-parse --show-toplevel`, then extracts the
@@ -862,6 +1482,148 @@ fn repo_name_for_root(repo_path: &Path) -> Result<String> {
         .to_string())
 }
 
+/// File in a worktree's private git dir recording the issue it was created
+/// for. Living under `.git/worktrees/<name>/` keeps it out of `git status`
+/// and removes it along with the worktree.
+const WORKTREE_ISSUE_FILE: &str = "botster_meta";
+
+/// Contents of [`WORKTREE_ISSUE_FILE`].
+#[derive(Serialize, Deserialize)]
+struct WorktreeMeta {
+    issue_number: u32,
+}
+
+/// Files the hub reads from a worktree's root that must never be committed
+/// (see `lua/lib/completion_sentinel.lua`).
+const WORKTREE_EXCLUDES: &[&str] = &[
+    ".botster_done",
+    ".botster_done.tmp",
+    ".botster_done.consumed",
+];
+
+/// Adds [`WORKTREE_EXCLUDES`] to the `info/exclude` file of the repo that
+/// owns the worktree at `path` (shared by all its worktrees), so an agent's
+/// `git add -A` skips them. Failure is logged, not returned.
+fn exclude_hub_files(path: &Path) {
+    let result = (|| -> Result<()> {
+        let output = std::process::Command::new("git")
+            .args(["rev-parse", "--git-path", "info/exclude"])
+            .current_dir(path)
+            .output()
+            .context("Failed to run git rev-parse --git-path")?;
+        if !output.status.success() {
+            anyhow::bail!("Not in a git repository");
+        }
+        let exclude = path.join(String::from_utf8_lossy(&output.stdout).trim());
+        let existing = fs::read_to_string(&exclude).unwrap_or_default();
+        let missing: Vec<&str> = WORKTREE_EXCLUDES
+            .iter()
+            .copied()
+            .filter(|pattern| !existing.lines().any(|line| line.trim() == *pattern))
+            .collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let mut contents = existing;
+        if !contents.is_empty() && !contents.ends_with('\n') {
+            contents.push('\n');
+        }
+        for pattern in missing {
+            contents.push_str(pattern);
+            contents.push('\n');
+        }
+        if let Some(parent) = exclude.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&exclude, contents)?;
+        Ok(())
+    })();
+    if let Err(e) = result {
+        log::warn!(
+            "Failed to exclude hub files in worktree {}: {}",
+            path.display(),
+            e
+        );
+    }
+}
+
+/// Returns the private git dir of the worktree at `path`.
+fn git_worktree_dir(path: &Path) -> Result<PathBuf> {
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", "--absolute-git-dir"])
+        .current_dir(path)
+        .output()
+        .context("Failed to run git rev-parse --absolute-git-dir")?;
+    if !output.status.success() {
+        anyhow::bail!("Not in a git repository");
+    }
+    Ok(PathBuf::from(
+        String::from_utf8_lossy(&output.stdout).trim().to_string(),
+    ))
+}
+
+/// Tags the worktree at `path` with `issue_number`. Failure is logged, not
+/// returned: the worktree is still usable, only lookup by issue degrades.
+fn record_worktree_issue(path: &Path, issue_number: u32) {
+    let result = git_worktree_dir(path).and_then(|dir| {
+        let meta = serde_json::to_string(&WorktreeMeta { issue_number })?;
+        fs::write(dir.join(WORKTREE_ISSUE_FILE), meta)?;
+        Ok(())
+    });
+    if let Err(e) = result {
+        log::warn!(
+            "Failed to tag worktree {} with issue #{}: {}",
+            path.display(),
+            issue_number,
+            e
+        );
+    }
+}
+
+/// Reads the issue number the worktree at `path` was tagged with, if any.
+fn read_worktree_issue(path: &Path) -> Option<u32> {
+    let dir = git_worktree_dir(path).ok()?;
+    let meta = fs::read_to_string(dir.join(WORKTREE_ISSUE_FILE)).ok()?;
+    serde_json::from_str::<WorktreeMeta>(&meta)
+        .ok()
+        .map(|meta| meta.issue_number)
+}
+
+/// Returns the branch checked out at `path` (`HEAD` when detached).
+fn git_current_branch(path: &Path) -> Result<String> {
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", "--abbrev-ref", "HEAD"])
+        .current_dir(path)
+        .output()
+        .context("Failed to run git rev-parse --abbrev-ref HEAD")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to read current branch: {}", stderr.trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Lists the paths of every worktree registered in the repo at `path`,
+/// including the main working tree.
+fn git_worktree_paths(path: &Path) -> Result<Vec<PathBuf>> {
+    let output = std::process::Command::new("git")
+        .args(["worktree", "list", "--porcelain"])
+        .current_dir(path)
+        .output()
+        .context("Failed to run git worktree list")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to list worktrees: {}", stderr.trim());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.strip_prefix("worktree "))
+        .map(PathBuf::from)
+        .collect())
+}
+
 /// Checks whether a local branch exists in the repo at `path`.
 fn git_branch_exists(path: &Path, branch_name: &str) -> bool {
     std::process::Command::new("git")
@@ -922,6 +1684,67 @@ mod tests {
         assert!(manager.base_dir.to_str().is_some());
     }
 
+    #[test]
+    fn test_normalize_repo_name_ignores_case() {
+        assert_eq!(normalize_repo_name("owner/repo"), "owner-repo");
+        assert_eq!(normalize_repo_name("Owner/Repo"), "owner-repo");
+        assert_eq!(normalize_repo_name("OWNER/REPO"), "owner-repo");
+    }
+
+    #[test]
+    fn test_normalize_repo_name_replaces_special_characters() {
+        assert_eq!(
+            normalize_repo_name("my-org/my_repo.rs"),
+            "my-org-my_repo.rs"
+        );
+        assert_eq!(normalize_repo_name("owner/re po:v2"), "owner-re-po-v2");
+        assert_eq!(normalize_repo_name("owner/répo"), "owner-r-po");
+
+        let key = normalize_repo_name("Owner/Some Repo!");
+        assert_eq!(normalize_repo_name(&key), key, "normalizing is idempotent");
+        assert!(key
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "._-".contains(c)));
+    }
+
+    #[test]
+    fn test_repo_dir_falls_back_to_pre_normalization_name() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = WorktreeManager::new(temp_dir.path().to_path_buf());
+        let normalized = temp_dir.path().join("owner-repo-feature-x");
+        let legacy = temp_dir.path().join("Owner-Repo-feature-x");
+
+        assert_eq!(manager.repo_dir("Owner/Repo", "feature-x"), normalized);
+
+        fs::create_dir_all(&legacy).unwrap();
+        assert_eq!(
+            manager.repo_dir("Owner/Repo", "feature-x"),
+            legacy,
+            "an existing worktree under the old name is reused"
+        );
+        assert_eq!(
+            manager.repo_dir("Owner/Repo", ""),
+            temp_dir.path().join("owner-repo"),
+            "clone dir falls back independently"
+        );
+
+        fs::create_dir_all(&normalized).unwrap();
+        assert_eq!(manager.repo_dir("Owner/Repo", "feature-x"), normalized);
+        assert_eq!(
+            worktree_dir_name("Owner/Repo", "feature/x"),
+            "owner-repo-feature-x"
+        );
+    }
+
+    #[test]
+    fn test_normalize_repo_name_keeps_distinct_repos_distinct() {
+        let keys: HashSet<String> = ["owner/repo", "owner/repo2", "other/repo", "owner/repo.js"]
+            .into_iter()
+            .map(normalize_repo_name)
+            .collect();
+        assert_eq!(keys.len(), 4);
+    }
+
     #[test]
     fn test_cleanup_nonexistent_worktree() {
         let temp_dir = TempDir::new().unwrap();
@@ -944,4 +1767,526 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap().len(), 0);
     }
+
+    fn git(dir: &Path, args: &[&str]) {
+        let output = std::process::Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "git {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    fn commit_file(dir: &Path, name: &str) {
+        fs::write(dir.join(name), name).unwrap();
+        git(dir, &["add", name]);
+        git(dir, &["commit", "-m", name]);
+    }
+
+    fn tag_exists(repo: &Path, tag: &str) -> bool {
+        std::process::Command::new("git")
+            .args([
+                "show-ref",
+                "--verify",
+                "--quiet",
+                &format!("refs/tags/{tag}"),
+            ])
+            .current_dir(repo)
+            .output()
+            .is_ok_and(|o| o.status.success())
+    }
+
+    /// Builds a repo with a pushed `main`, plus a pushed `botster-issue-1`
+    /// worktree under `<tmp>/worktrees`. Returns (manager, repo, worktree).
+    fn setup_issue_worktree(temp_dir: &TempDir) -> (WorktreeManager, PathBuf, PathBuf) {
+        let root = temp_dir.path().canonicalize().unwrap();
+        let origin = root.join("origin.git");
+        let repo = root.join("repo");
+        let base = root.join("worktrees");
+        fs::create_dir_all(&repo).unwrap();
+        fs::create_dir_all(&base).unwrap();
+
+        git(&root, &["init", "--bare", "-b", "main", "origin.git"]);
+        git(&repo, &["init", "-b", "main"]);
+        commit_file(&repo, "README.md");
+        git(
+            &repo,
+            &["remote", "add", "origin", origin.to_str().unwrap()],
+        );
+        git(&repo, &["push", "origin", "main"]);
+
+        let worktree = base.join("repo-1");
+        git(
+            &repo,
+            &[
+                "worktree",
+                "add",
+                "-b",
+                "botster-issue-1",
+                worktree.to_str().unwrap(),
+            ],
+        );
+        commit_file(&worktree, "fix.txt");
+        git(&worktree, &["push", "origin", "botster-issue-1"]);
+
+        (WorktreeManager::new(base), repo, worktree)
+    }
+
+    #[test]
+    fn test_cleanup_policy_keep_leaves_worktree_and_branch() {
+        let temp_dir = TempDir::new().unwrap();
+        let (manager, repo, worktree) = setup_issue_worktree(&temp_dir);
+        commit_file(&worktree, "unpushed.txt");
+
+        manager
+            .cleanup_worktree_with_policy(&worktree, "botster-issue-1", CleanupPolicy::Keep)
+            .unwrap();
+
+        assert!(worktree.exists());
+        assert!(git_branch_exists(&repo, "botster-issue-1"));
+        assert!(!tag_exists(&repo, "archive/botster-issue-1"));
+    }
+
+    #[test]
+    fn test_cleanup_policy_delete_removes_worktree_and_branch() {
+        let temp_dir = TempDir::new().unwrap();
+        let (manager, repo, worktree) = setup_issue_worktree(&temp_dir);
+
+        manager
+            .cleanup_worktree_with_policy(&worktree, "botster-issue-1", CleanupPolicy::Delete)
+            .unwrap();
+
+        assert!(!worktree.exists());
+        assert!(!git_branch_exists(&repo, "botster-issue-1"));
+        assert!(!tag_exists(&repo, "archive/botster-issue-1"));
+    }
+
+    #[test]
+    fn test_cleanup_policy_delete_refuses_unpushed_commits() {
+        let temp_dir = TempDir::new().unwrap();
+        let (manager, repo, worktree) = setup_issue_worktree(&temp_dir);
+        commit_file(&worktree, "unpushed.txt");
+
+        let err = manager
+            .cleanup_worktree_with_policy(&worktree, "botster-issue-1", CleanupPolicy::Delete)
+            .unwrap_err();
+
+        assert!(err.to_string().contains("unpushed commit"), "{err}");
+        assert!(worktree.exists());
+        assert!(git_branch_exists(&repo, "botster-issue-1"));
+    }
+
+    #[test]
+    fn test_cleanup_policy_archive_tags_branch_and_removes_worktree() {
+        let temp_dir = TempDir::new().unwrap();
+        let (manager, repo, worktree) = setup_issue_worktree(&temp_dir);
+        // Unpushed commits are fine: the tag keeps them reachable.
+        commit_file(&worktree, "unpushed.txt");
+
+        manager
+            .cleanup_worktree_with_policy(&worktree, "botster-issue-1", CleanupPolicy::Archive)
+            .unwrap();
+
+        assert!(!worktree.exists());
+        assert!(!git_branch_exists(&repo, "botster-issue-1"));
+        assert!(tag_exists(&repo, "archive/botster-issue-1"));
+        git(
+            &repo,
+            &["cat-file", "-e", "archive/botster-issue-1:unpushed.txt"],
+        );
+    }
+
+    #[test]
+    fn test_cleanup_policy_delete_without_remote_checks_local_branches() {
+        let temp_dir = TempDir::new().unwrap();
+        let (manager, repo, worktree) = setup_issue_worktree(&temp_dir);
+        git(&repo, &["remote", "remove", "origin"]);
+
+        // Everything on the branch is already on main: nothing to lose.
+        git(&repo, &["merge", "--ff-only", "botster-issue-1"]);
+        manager
+            .cleanup_worktree_with_policy(&worktree, "botster-issue-1", CleanupPolicy::Delete)
+            .unwrap();
+        assert!(!git_branch_exists(&repo, "botster-issue-1"));
+    }
+
+    #[test]
+    fn test_cleanup_policy_archive_keeps_existing_tag() {
+        let temp_dir = TempDir::new().unwrap();
+        let (manager, repo, worktree) = setup_issue_worktree(&temp_dir);
+        // An earlier branch of the same name was archived at another commit.
+        git(&repo, &["tag", "archive/botster-issue-1", "main"]);
+        commit_file(&worktree, "unpushed.txt");
+
+        manager
+            .cleanup_worktree_with_policy(&worktree, "botster-issue-1", CleanupPolicy::Archive)
+            .unwrap();
+
+        assert!(!worktree.exists());
+        git(
+            &repo,
+            &["cat-file", "-e", "archive/botster-issue-1-2:unpushed.txt"],
+        );
+        let old = std::process::Command::new("git")
+            .args(["rev-parse", "archive/botster-issue-1", "main"])
+            .current_dir(&repo)
+            .output()
+            .unwrap();
+        let old = String::from_utf8_lossy(&old.stdout);
+        let mut lines = old.lines();
+        assert_eq!(lines.next(), lines.next(), "old tag must not move");
+    }
+
+    #[test]
+    fn test_cleanup_policy_archive_refuses_uncommitted_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let (manager, repo, worktree) = setup_issue_worktree(&temp_dir);
+        fs::write(worktree.join("scratch.txt"), "wip").unwrap();
+
+        let err = manager
+            .cleanup_worktree_with_policy(&worktree, "botster-issue-1", CleanupPolicy::Archive)
+            .unwrap_err();
+
+        assert!(err.to_string().contains("uncommitted changes"), "{err}");
+        assert!(worktree.join("scratch.txt").exists());
+        assert!(git_branch_exists(&repo, "botster-issue-1"));
+        assert!(!tag_exists(&repo, "archive/botster-issue-1"));
+    }
+
+    #[test]
+    fn test_create_worktree_reuses_branch_without_worktree() {
+        let temp_dir = TempDir::new().unwrap();
+        let (manager, repo, _) = setup_issue_worktree(&temp_dir);
+        git(&repo, &["branch", "botster-issue-7"]);
+
+        let path = manager
+            .create_worktree_for_repo_root(&repo, "botster-issue-7")
+            .unwrap();
+
+        assert!(path.join("README.md").exists());
+        assert_eq!(
+            manager
+                .find_worktree_for_branch(&repo, "botster-issue-7")
+                .unwrap(),
+            Some(path)
+        );
+    }
+
+    #[test]
+    fn test_created_worktree_ignores_completion_sentinel() {
+        let temp_dir = TempDir::new().unwrap();
+        let (manager, repo, _) = setup_issue_worktree(&temp_dir);
+
+        let path = manager
+            .create_worktree_for_repo_root(&repo, "botster-issue-7")
+            .unwrap();
+        // A second worktree must not append the patterns again.
+        manager
+            .create_worktree_for_repo_root(&repo, "botster-issue-8")
+            .unwrap();
+        fs::write(path.join(".botster_done"), "{}").unwrap();
+
+        let status = std::process::Command::new("git")
+            .args(["status", "--porcelain"])
+            .current_dir(&path)
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&status.stdout), "");
+        let exclude = fs::read_to_string(repo.join(".git/info/exclude")).unwrap();
+        assert_eq!(exclude.lines().filter(|l| *l == ".botster_done").count(), 1);
+    }
+
+    #[test]
+    fn test_create_worktree_reclaims_branch_from_deleted_worktree() {
+        let temp_dir = TempDir::new().unwrap();
+        let (manager, repo, worktree) = setup_issue_worktree(&temp_dir);
+        // Simulate a crash that lost the directory but not git's record of it.
+        fs::remove_dir_all(&worktree).unwrap();
+
+        let path = manager
+            .create_worktree_for_repo_root(&repo, "botster-issue-1")
+            .unwrap();
+
+        assert!(path.join("fix.txt").exists());
+    }
+
+    #[test]
+    fn test_create_worktree_reports_branch_in_use() {
+        let temp_dir = TempDir::new().unwrap();
+        let (manager, repo, worktree) = setup_issue_worktree(&temp_dir);
+        let elsewhere = temp_dir.path().canonicalize().unwrap().join("elsewhere");
+        git(
+            &repo,
+            &[
+                "worktree",
+                "move",
+                worktree.to_str().unwrap(),
+                elsewhere.to_str().unwrap(),
+            ],
+        );
+
+        let err = manager
+            .create_worktree_for_repo_root(&repo, "botster-issue-1")
+            .unwrap_err();
+
+        assert_eq!(
+            err.downcast_ref::<WorktreeError>(),
+            Some(&WorktreeError::BranchInUse {
+                branch: "botster-issue-1".to_string(),
+                path: elsewhere,
+            })
+        );
+    }
+
+    #[test]
+    fn test_prune_stale_worktrees_reports_deleted_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        let (manager, repo, worktree) = setup_issue_worktree(&temp_dir);
+        assert!(manager
+            .prune_stale_worktrees_for_repo_root(&repo)
+            .unwrap()
+            .is_empty());
+
+        fs::remove_dir_all(&worktree).unwrap();
+        let pruned = manager.prune_stale_worktrees_for_repo_root(&repo).unwrap();
+
+        assert_eq!(pruned, vec![worktree.display().to_string()]);
+        assert!(!git_worktree_paths(&repo).unwrap().contains(&worktree));
+        assert!(git_branch_exists(&repo, "botster-issue-1"));
+    }
+
+    #[test]
+    fn test_branch_template_round_trips_through_create() {
+        let temp_dir = TempDir::new().unwrap();
+        let (manager, repo, _) = setup_issue_worktree(&temp_dir);
+        let manager = manager.with_branch_template("bot/issue-{issue}");
+
+        let branch = manager.branch_name_for_issue(42);
+        assert_eq!(branch, "bot/issue-42");
+        let path = manager
+            .create_worktree_for_repo_root(&repo, &branch)
+            .unwrap();
+
+        let output = std::process::Command::new("git")
+            .args(["rev-parse", "--abbrev-ref", "HEAD"])
+            .current_dir(&path)
+            .output()
+            .unwrap();
+        let head = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        assert_eq!(manager.issue_number_for_branch(&head), Some(42));
+    }
+
+    #[test]
+    fn test_branch_template_parsing() {
+        let manager = WorktreeManager::new(PathBuf::from("/tmp"));
+        assert_eq!(manager.branch_name_for_issue(7), "botster-issue-7");
+        assert_eq!(manager.issue_number_for_branch("botster-issue-7"), Some(7));
+        assert_eq!(manager.issue_number_for_branch("botster-issue-"), None);
+        assert_eq!(manager.issue_number_for_branch("botster-issue-7x"), None);
+        assert_eq!(manager.issue_number_for_branch("feature-7"), None);
+
+        let manager = manager.with_branch_template("issue-{issue}-wip");
+        assert_eq!(manager.issue_number_for_branch("issue-12-wip"), Some(12));
+        assert_eq!(manager.issue_number_for_branch("issue-12"), None);
+
+        let manager = manager.with_branch_template("no-placeholder");
+        assert_eq!(manager.branch_name_for_issue(3), "issue-3-wip");
+    }
+
+    #[test]
+    fn test_plan_issue_worktree_deletion_reports_dirty_worktree() {
+        let temp_dir = TempDir::new().unwrap();
+        let (manager, repo, worktree) = setup_issue_worktree(&temp_dir);
+        record_worktree_issue(&worktree, 1);
+
+        let plan = manager
+            .plan_issue_worktree_deletion(&repo, "repo", 1)
+            .unwrap()
+            .expect("issue 1 has a worktree");
+        assert_eq!(plan.path, worktree);
+        assert_eq!(plan.branch, "botster-issue-1");
+        assert_eq!(plan.unpushed, None);
+
+        fs::write(worktree.join("wip.txt"), "wip").unwrap();
+        let plan = manager
+            .plan_issue_worktree_deletion(&repo, "repo", 1)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            plan.unpushed.as_deref(),
+            Some("worktree has uncommitted changes")
+        );
+        assert!(worktree.exists(), "planning deletes nothing");
+
+        assert_eq!(
+            manager
+                .plan_issue_worktree_deletion(&repo, "repo", 2)
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_plan_issue_worktree_deletion_reports_unpushed_commits() {
+        let temp_dir = TempDir::new().unwrap();
+        let (manager, repo, worktree) = setup_issue_worktree(&temp_dir);
+        record_worktree_issue(&worktree, 1);
+        commit_file(&worktree, "local.txt");
+
+        let plan = manager
+            .plan_issue_worktree_deletion(&repo, "repo", 1)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            plan.unpushed.as_deref(),
+            Some("branch botster-issue-1 has 1 unpushed commit(s)")
+        );
+
+        let err = manager
+            .delete_planned_worktree(&repo, &plan, false)
+            .unwrap_err();
+        assert!(err.to_string().contains("unpushed commit"));
+        assert!(git_branch_exists(&repo, "botster-issue-1"));
+    }
+
+    #[test]
+    fn test_delete_planned_worktree_refuses_dirty_worktree_without_force() {
+        let temp_dir = TempDir::new().unwrap();
+        let (manager, repo, worktree) = setup_issue_worktree(&temp_dir);
+        record_worktree_issue(&worktree, 1);
+        fs::write(worktree.join("wip.txt"), "wip").unwrap();
+        let plan = manager
+            .plan_issue_worktree_deletion(&repo, "repo", 1)
+            .unwrap()
+            .unwrap();
+
+        let err = manager
+            .delete_planned_worktree(&repo, &plan, false)
+            .unwrap_err();
+        assert!(err.to_string().contains("uncommitted changes"));
+        assert!(worktree.join("wip.txt").exists());
+        assert!(git_branch_exists(&repo, "botster-issue-1"));
+
+        manager.delete_planned_worktree(&repo, &plan, true).unwrap();
+        assert!(!worktree.exists());
+        assert!(!git_branch_exists(&repo, "botster-issue-1"));
+    }
+
+    #[test]
+    fn test_delete_worktree_runs_teardown_script_before_removal() {
+        let temp_dir = TempDir::new().unwrap();
+        let (manager, repo, worktree) = setup_issue_worktree(&temp_dir);
+        let marker = temp_dir.path().join("teardown.marker");
+        fs::write(
+            repo.join(TEARDOWN_SCRIPT),
+            "test -f fix.txt && echo \"$BOTSTER_SESSION_UUID $BOTSTER_WORKTREE_PATH\" > \"$MARKER\"\n",
+        )
+        .unwrap();
+
+        let env = HashMap::from([
+            ("BOTSTER_SESSION_UUID".to_string(), "sess-1".to_string()),
+            ("MARKER".to_string(), marker.display().to_string()),
+        ]);
+        manager
+            .with_teardown_env(env)
+            .delete_worktree_by_path(&worktree, "botster-issue-1")
+            .unwrap();
+
+        assert!(!worktree.exists());
+        assert_eq!(
+            fs::read_to_string(&marker).unwrap().trim(),
+            format!("sess-1 {}", worktree.display()),
+            "teardown ran inside the worktree with the session env"
+        );
+    }
+
+    #[test]
+    fn test_failing_teardown_script_does_not_block_deletion() {
+        let temp_dir = TempDir::new().unwrap();
+        let (manager, repo, worktree) = setup_issue_worktree(&temp_dir);
+        fs::write(repo.join(TEARDOWN_SCRIPT), "echo stopping >&2\nexit 1\n").unwrap();
+
+        manager
+            .delete_worktree_by_path(&worktree, "botster-issue-1")
+            .unwrap();
+
+        assert!(!worktree.exists());
+        assert!(!git_branch_exists(&repo, "botster-issue-1"));
+    }
+
+    #[test]
+    fn test_hung_teardown_script_is_killed_after_timeout() {
+        let temp_dir = TempDir::new().unwrap();
+        let (manager, repo, worktree) = setup_issue_worktree(&temp_dir);
+        let marker = temp_dir.path().join("teardown.marker");
+        fs::write(
+            repo.join(TEARDOWN_SCRIPT),
+            "(sleep 2; touch \"$MARKER\") &\nsleep 30\n",
+        )
+        .unwrap();
+
+        let env = HashMap::from([("MARKER".to_string(), marker.display().to_string())]);
+        let started = Instant::now();
+        manager
+            .with_teardown_env(env)
+            .with_teardown_timeout(Duration::from_millis(200))
+            .delete_worktree_by_path(&worktree, "botster-issue-1")
+            .unwrap();
+
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert!(!worktree.exists());
+
+        std::thread::sleep(Duration::from_secs(3));
+        assert!(
+            !marker.exists(),
+            "background job in the script's process group was killed too"
+        );
+    }
+
+    #[test]
+    fn test_find_existing_worktree_for_issue_uses_issue_tag() {
+        let temp_dir = TempDir::new().unwrap();
+        let (manager, repo, _) = setup_issue_worktree(&temp_dir);
+        let custom = manager
+            .create_worktree_for_repo_root(&repo, "fix-login-crash")
+            .unwrap();
+
+        // Neither branch follows the naming convention for issue 9.
+        assert_eq!(
+            manager
+                .find_existing_worktree_for_issue_in_repo(&repo, "repo", 9)
+                .unwrap(),
+            None
+        );
+
+        record_worktree_issue(&custom, 9);
+        // The tag stays out of the worktree itself.
+        assert!(unpushed_work(&custom, "fix-login-crash", false)
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            manager
+                .find_existing_worktree_for_issue_in_repo(&repo, "repo", 9)
+                .unwrap(),
+            Some((custom, "fix-login-crash".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_cleanup_policy_parse() {
+        assert_eq!(
+            CleanupPolicy::parse("archive"),
+            Some(CleanupPolicy::Archive)
+        );
+        assert_eq!(CleanupPolicy::parse("delete"), Some(CleanupPolicy::Delete));
+        assert_eq!(CleanupPolicy::parse("keep"), Some(CleanupPolicy::Keep));
+        assert_eq!(CleanupPolicy::parse("purge"), None);
+        assert_eq!(CleanupPolicy::default(), CleanupPolicy::Keep);
+    }
 }
@@ -34,7 +34,8 @@
 
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 use serde::Deserialize;
 use tokio::sync::mpsc;
@@ -52,6 +53,50 @@ const CONFIRM_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_se
 /// of silence means the connection silently died (no close frame, no error).
 const RECEIVE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
 
+/// Initial reconnection delay after the first failure.
+const INITIAL_BACKOFF: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Upper bound on the reconnection delay (before jitter).
+const MAX_BACKOFF: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Application-level heartbeat action (see `lua/handlers/hub_commands.lua`).
+const HEARTBEAT_ACTION: &str = "heartbeat";
+
+/// Exponential reconnect backoff with jitter.
+///
+/// The base delay doubles on each consecutive failure up to [`MAX_BACKOFF`],
+/// then up to 50% random jitter is added on top. Without jitter, every hub
+/// that lost its connection during a brief server outage would retry on the
+/// exact same schedule and stampede Rails the moment it came back.
+#[derive(Debug, Default)]
+struct ReconnectBackoff {
+    /// Failures since the last successful connect.
+    consecutive_failures: u32,
+}
+
+impl ReconnectBackoff {
+    /// Base delay (without jitter) for the current failure count.
+    fn base_delay(&self) -> std::time::Duration {
+        let exponent = self.consecutive_failures.saturating_sub(1).min(16);
+        INITIAL_BACKOFF
+            .saturating_mul(1 << exponent)
+            .min(MAX_BACKOFF)
+    }
+
+    /// Record a failure and return how long to wait before retrying.
+    fn next_delay(&mut self) -> std::time::Duration {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        let base = self.base_delay();
+        let jitter_ms = rand::random::<u64>() % (base.as_millis() as u64 / 2 + 1);
+        base + std::time::Duration::from_millis(jitter_ms)
+    }
+
+    /// Record a successful connect.
+    fn reset(&mut self) {
+        self.consecutive_failures = 0;
+    }
+}
+
 /// Message received from the hub command channel.
 #[derive(Debug, Clone, Deserialize)]
 pub struct CommandMessage {
@@ -89,6 +134,11 @@ pub struct ActionCableConnection {
     subscribe_tx: mpsc::UnboundedSender<SubscribeRequest>,
     perform_tx: mpsc::UnboundedSender<ChannelPerform>,
     shutdown: Arc<AtomicBool>,
+    /// When the server last sent anything (including protocol pings).
+    last_message_at: Arc<Mutex<Option<Instant>>>,
+    /// Set once the server welcomes us, cleared when the connection drops
+    /// or a heartbeat is missed.
+    connected: Arc<AtomicBool>,
 }
 
 /// Handle for a single channel subscription.
@@ -138,11 +188,16 @@ impl ActionCableConnection {
         let (subscribe_tx, subscribe_rx) = mpsc::unbounded_channel();
         let (perform_tx, perform_rx) = mpsc::unbounded_channel();
         let shutdown = Arc::new(AtomicBool::new(false));
+        let last_message_at = Arc::new(Mutex::new(None));
+        let connected = Arc::new(AtomicBool::new(false));
 
         let config = ConnectionConfig {
             server_url: server_url.to_string(),
             api_key: api_key.to_string(),
             shutdown: Arc::clone(&shutdown),
+            last_message_at: Arc::clone(&last_message_at),
+            connected: Arc::clone(&connected),
+            missed_heartbeat: AtomicBool::new(false),
         };
 
         tokio::spawn(run_connection_loop(config, subscribe_rx, perform_rx));
@@ -151,9 +206,31 @@ impl ActionCableConnection {
             subscribe_tx,
             perform_tx,
             shutdown,
+            last_message_at,
+            connected,
         }
     }
 
+    /// When the server last sent a message on this connection.
+    ///
+    /// ActionCable pings every few seconds, so a recent value means the
+    /// server is reachable. `None` until the first message arrives.
+    #[must_use]
+    pub fn last_message_at(&self) -> Option<Instant> {
+        self.last_message_at.lock().ok().and_then(|last| *last)
+    }
+
+    /// Whether the server is currently reachable on this connection.
+    ///
+    /// `false` until the server welcomes us, and again as soon as a
+    /// heartbeat is missed (no message for [`RECEIVE_TIMEOUT`]) or the socket
+    /// drops. Becomes `true` again once a reconnect is welcomed.
+    #[must_use]
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+            && !heartbeat_missed(self.last_message_at(), Instant::now())
+    }
+
     /// Subscribe to an ActionCable channel.
     ///
     /// Sends a subscribe command to the WebSocket and returns a
@@ -233,6 +310,21 @@ struct ConnectionConfig {
     server_url: String,
     api_key: String,
     shutdown: Arc<AtomicBool>,
+    last_message_at: Arc<Mutex<Option<Instant>>>,
+    connected: Arc<AtomicBool>,
+    /// Latched when a welcomed connection drops, so the first heartbeat after
+    /// the next welcome tells the server this hub was unreachable.
+    missed_heartbeat: AtomicBool,
+}
+
+/// Whether the server has gone quiet for longer than [`RECEIVE_TIMEOUT`]
+/// as of `now`. A connection that never heard from the server counts as
+/// having missed its heartbeat.
+fn heartbeat_missed(last_message_at: Option<Instant>, now: Instant) -> bool {
+    match last_message_at {
+        Some(at) => now.saturating_duration_since(at) >= RECEIVE_TIMEOUT,
+        None => true,
+    }
 }
 
 /// Build the WebSocket URL from the server URL.
@@ -252,12 +344,7 @@ async fn run_connection_loop(
     mut subscribe_rx: mpsc::UnboundedReceiver<SubscribeRequest>,
     mut perform_rx: mpsc::UnboundedReceiver<ChannelPerform>,
 ) {
-    /// Initial reconnection delay in seconds.
-    const INITIAL_BACKOFF_SECS: u64 = 1;
-    /// Maximum reconnection delay in seconds.
-    const MAX_BACKOFF_SECS: u64 = 60;
-
-    let mut backoff_secs: u64 = INITIAL_BACKOFF_SECS;
+    let mut backoff = ReconnectBackoff::default();
 
     // Active subscriptions: identifier -> message sender
     // Persisted across reconnections for automatic re-subscribe
@@ -283,28 +370,34 @@ async fn run_connection_loop(
             match crate::ws::connect(&ws_url, &[("Authorization", &bearer)]).await {
                 Ok(pair) => {
                     log::info!("[ActionCable] WebSocket connected");
-                    backoff_secs = INITIAL_BACKOFF_SECS;
                     pair
                 }
                 Err(e) => {
+                    let delay = backoff.next_delay();
                     log::warn!(
-                        "[ActionCable] Connection failed: {} (retry in {}s)",
+                        "[ActionCable] Connection failed: {} (attempt {}, retry in {}ms)",
                         e,
-                        backoff_secs
+                        backoff.consecutive_failures,
+                        delay.as_millis()
                     );
-                    tokio::time::sleep(std::time::Duration::from_secs(backoff_secs)).await;
-                    backoff_secs = (backoff_secs * 2).min(MAX_BACKOFF_SECS);
+                    tokio::time::sleep(delay).await;
                     continue;
                 }
             };
 
-        // Wait for ActionCable welcome message
+        // Wait for ActionCable welcome message. A server that accepts the
+        // socket but never welcomes us counts as a failure for backoff.
         if !wait_for_welcome(&mut writer, &mut reader).await {
-            log::warn!("[ActionCable] Did not receive welcome, reconnecting...");
-            tokio::time::sleep(std::time::Duration::from_secs(backoff_secs)).await;
-            backoff_secs = (backoff_secs * 2).min(MAX_BACKOFF_SECS);
+            let delay = backoff.next_delay();
+            log::warn!(
+                "[ActionCable] Did not receive welcome, reconnecting in {}ms...",
+                delay.as_millis()
+            );
+            tokio::time::sleep(delay).await;
             continue;
         }
+        backoff.reset();
+        config.connected.store(true, Ordering::SeqCst);
 
         // Re-subscribe all active channels after (re)connect
         for identifier in subscriptions.keys() {
@@ -333,18 +426,20 @@ async fn run_connection_loop(
             &mut perform_rx,
         )
         .await;
+        config.connected.store(false, Ordering::SeqCst);
 
         if let ConnectionLoopExit::Shutdown = loop_result {
             return;
         }
+        config.missed_heartbeat.store(true, Ordering::SeqCst);
 
         // Disconnected -- will reconnect after backoff
+        let delay = backoff.next_delay();
         log::info!(
-            "[ActionCable] Disconnected, reconnecting in {}s",
-            backoff_secs
+            "[ActionCable] Disconnected, reconnecting in {}ms",
+            delay.as_millis()
         );
-        tokio::time::sleep(std::time::Duration::from_secs(backoff_secs)).await;
-        backoff_secs = (backoff_secs * 2).min(MAX_BACKOFF_SECS);
+        tokio::time::sleep(delay).await;
     }
 }
 
@@ -450,6 +545,9 @@ async fn run_message_loop(
             msg = reader.recv() => {
                 // Any received message proves the connection is alive — reset deadline
                 receive_deadline.as_mut().reset(tokio::time::Instant::now() + RECEIVE_TIMEOUT);
+                if let Ok(mut last) = config.last_message_at.lock() {
+                    *last = Some(Instant::now());
+                }
 
                 match msg {
                     Some(Ok(crate::ws::WsMessage::Text(text))) => {
@@ -465,7 +563,7 @@ async fn run_message_loop(
                                 .partition(|p| confirmed.contains(&p.identifier));
                             pending_performs = still_pending;
                             for request in ready {
-                                if let Err(e) = send_perform(writer, &request).await {
+                                if let Err(e) = send_perform(writer, &request, &config.missed_heartbeat).await {
                                     log::warn!("[ActionCable] Failed to send queued perform '{}': {}", request.action, e);
                                     return ConnectionLoopExit::Disconnected;
                                 }
@@ -512,7 +610,7 @@ async fn run_message_loop(
             // Process outgoing perform requests
             Some(request) = perform_rx.recv() => {
                 if confirmed.contains(&request.identifier) {
-                    if let Err(e) = send_perform(writer, &request).await {
+                    if let Err(e) = send_perform(writer, &request, &config.missed_heartbeat).await {
                         log::warn!("[ActionCable] Failed to send perform '{}': {}", request.action, e);
                         return ConnectionLoopExit::Disconnected;
                     }
@@ -671,13 +769,9 @@ async fn handle_text_message(
 async fn send_perform(
     writer: &mut crate::ws::WsWriter,
     request: &ChannelPerform,
+    missed_heartbeat: &AtomicBool,
 ) -> anyhow::Result<()> {
-    let mut data_obj = serde_json::json!({ "action": request.action });
-    if let serde_json::Value::Object(ref map) = request.data {
-        for (k, v) in map {
-            data_obj[k] = v.clone();
-        }
-    }
+    let data_obj = perform_data(request, missed_heartbeat);
 
     let perform_cmd = serde_json::json!({
         "command": "message",
@@ -690,6 +784,23 @@ async fn send_perform(
     Ok(())
 }
 
+/// Build the `data` object for a perform: the action plus its payload.
+///
+/// The first heartbeat after [`ConnectionConfig::missed_heartbeat`] was
+/// latched carries `missed_heartbeat: true` and clears the latch.
+fn perform_data(request: &ChannelPerform, missed_heartbeat: &AtomicBool) -> serde_json::Value {
+    let mut data_obj = serde_json::json!({ "action": request.action });
+    if let serde_json::Value::Object(ref map) = request.data {
+        for (k, v) in map {
+            data_obj[k] = v.clone();
+        }
+    }
+    if request.action == HEARTBEAT_ACTION && missed_heartbeat.swap(false, Ordering::SeqCst) {
+        data_obj["missed_heartbeat"] = serde_json::Value::Bool(true);
+    }
+    data_obj
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -791,4 +902,83 @@ mod tests {
         assert_eq!(data_parsed["action"], "ack");
         assert_eq!(data_parsed["sequence"], 42);
     }
+
+    #[test]
+    fn test_reconnect_backoff_widens_on_consecutive_failures() {
+        let mut backoff = ReconnectBackoff::default();
+
+        let mut previous_base = std::time::Duration::ZERO;
+        for _ in 0..4 {
+            let delay = backoff.next_delay();
+            let base = backoff.base_delay();
+            assert!(base > previous_base, "base delay should grow each failure");
+            assert!(delay >= base, "jitter is only ever added");
+            assert!(delay <= base + base / 2, "jitter is bounded to 50%");
+            previous_base = base;
+        }
+        assert_eq!(backoff.base_delay(), std::time::Duration::from_secs(8));
+    }
+
+    #[test]
+    fn test_reconnect_backoff_is_capped() {
+        let mut backoff = ReconnectBackoff::default();
+        for _ in 0..50 {
+            backoff.next_delay();
+        }
+        assert_eq!(backoff.base_delay(), MAX_BACKOFF);
+        assert!(backoff.next_delay() <= MAX_BACKOFF + MAX_BACKOFF / 2);
+    }
+
+    #[test]
+    fn test_reconnect_backoff_resets_on_success() {
+        let mut backoff = ReconnectBackoff::default();
+        for _ in 0..5 {
+            backoff.next_delay();
+        }
+        backoff.reset();
+
+        let delay = backoff.next_delay();
+        assert_eq!(backoff.base_delay(), INITIAL_BACKOFF);
+        assert!(delay < INITIAL_BACKOFF * 2);
+    }
+
+    #[test]
+    fn test_heartbeat_missed_after_receive_timeout() {
+        let last = Instant::now();
+
+        assert!(!heartbeat_missed(Some(last), last));
+        assert!(!heartbeat_missed(
+            Some(last),
+            last + RECEIVE_TIMEOUT - std::time::Duration::from_millis(1)
+        ));
+        assert!(heartbeat_missed(Some(last), last + RECEIVE_TIMEOUT));
+        assert!(heartbeat_missed(None, last), "never heard from the server");
+    }
+
+    #[test]
+    fn test_first_heartbeat_after_drop_reports_missed_heartbeat() {
+        let perform = |action: &str| ChannelPerform {
+            identifier: "{}".to_string(),
+            action: action.to_string(),
+            data: serde_json::json!({}),
+        };
+        let missed = AtomicBool::new(false);
+
+        let data = perform_data(&perform("heartbeat"), &missed);
+        assert_eq!(data, serde_json::json!({ "action": "heartbeat" }));
+
+        missed.store(true, Ordering::SeqCst);
+        let data = perform_data(&perform("ack"), &missed);
+        assert_eq!(
+            data.get("missed_heartbeat"),
+            None,
+            "only heartbeats carry it"
+        );
+
+        let data = perform_data(&perform("heartbeat"), &missed);
+        assert_eq!(data["missed_heartbeat"], true);
+
+        let data = perform_data(&perform("heartbeat"), &missed);
+        assert_eq!(data.get("missed_heartbeat"), None, "reported once");
+    }
 }
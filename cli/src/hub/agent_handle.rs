@@ -324,7 +324,7 @@ impl PtyHandle {
     /// Returns a receiver that will receive all PTY events:
     /// - `Output(Vec<u8>)` - Terminal output data
     /// - `Resized { rows, cols }` - PTY was resized
-    /// - `ProcessExited { exit_code }` - PTY process exited
+    /// - `ProcessExited { exit_code, signal }` - PTY process exited
     ///
     /// # Lagging
     ///
@@ -404,6 +404,11 @@ impl PtyHandle {
         let _ = self.event_tx.send(PtyEvent::process_exited(exit_code));
     }
 
+    /// Broadcast a `ProcessExited` event for a process killed by `signal`.
+    pub fn notify_process_signalled(&self, signal: i32) {
+        let _ = self.event_tx.send(PtyEvent::process_signalled(signal));
+    }
+
     /// Get the HTTP forwarding port for this PTY.
     ///
     /// Returns the port allocated for HTTP preview proxying, or `None` if
@@ -732,7 +737,7 @@ mod tests {
         pty.notify_process_exited(Some(42));
 
         match rx.try_recv() {
-            Ok(PtyEvent::ProcessExited { exit_code }) => {
+            Ok(PtyEvent::ProcessExited { exit_code, .. }) => {
                 assert_eq!(exit_code, Some(42));
             }
             other => panic!("expected ProcessExited, got {other:?}"),
@@ -747,7 +752,7 @@ mod tests {
         pty.notify_process_exited(None);
 
         match rx.try_recv() {
-            Ok(PtyEvent::ProcessExited { exit_code }) => {
+            Ok(PtyEvent::ProcessExited { exit_code, .. }) => {
                 assert_eq!(exit_code, None);
             }
             other => panic!("expected ProcessExited with None, got {other:?}"),
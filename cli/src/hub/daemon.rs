@@ -8,6 +8,7 @@
 //! ```text
 //! {config_dir}/hubs/{hub_id}/
 //!   hub.pid              # PID of the running hub process
+//!   status.json          # Sessions snapshot for `botster status`
 //!
 //! /tmp/botster-{uid}/
 //!   {hub_id}.sock        # Unix domain socket for IPC
@@ -18,7 +19,7 @@
 
 use std::fs::{self, File};
 use std::os::unix::io::AsRawFd;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
@@ -127,6 +128,11 @@ pub fn manifest_path(hub_id: &str) -> Result<PathBuf> {
     Ok(hub_dir(hub_id)?.join("manifest.json"))
 }
 
+/// Get the status snapshot path for a hub.
+pub fn status_path(hub_id: &str) -> Result<PathBuf> {
+    Ok(hub_dir(hub_id)?.join("status.json"))
+}
+
 /// Get the Unix socket path for a hub.
 ///
 /// Uses `/tmp/botster-{uid}/` instead of the config dir because macOS
@@ -182,6 +188,109 @@ pub fn write_manifest(hub_id: &str, server_id: Option<&str>) -> Result<()> {
     Ok(())
 }
 
+/// Snapshot of a running hub, rewritten on every cleanup tick and read by
+/// `botster status`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HubStatus {
+    /// Stable local hub hash ID.
+    pub hub_id: String,
+    /// Server-assigned hub ID, once registered.
+    pub botster_id: Option<String>,
+    /// PID of the hub process that wrote this snapshot.
+    pub pid: u32,
+    /// Last write timestamp (unix seconds).
+    pub updated_at: u64,
+    /// Every session the hub is tracking, agents and accessories.
+    #[serde(default)]
+    pub sessions: Vec<SessionStatus>,
+}
+
+/// One session in a [`HubStatus`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SessionStatus {
+    /// Session UUID.
+    pub session_uuid: String,
+    /// Human-readable label.
+    pub label: String,
+    /// `agent` or `accessory`.
+    pub session_type: String,
+    /// `running`, or `disconnected` when the session process is gone.
+    pub status: String,
+}
+
+impl HubStatus {
+    /// Number of agent sessions (accessories excluded).
+    #[must_use]
+    pub fn agent_count(&self) -> usize {
+        self.sessions
+            .iter()
+            .filter(|session| session.session_type == "agent")
+            .count()
+    }
+
+    /// Whether the hub that wrote this snapshot is still running.
+    ///
+    /// The writing process must be alive and the snapshot fresh: a snapshot
+    /// not rewritten for [`STATUS_STALE_AFTER_SECS`] belongs to a dead hub
+    /// whose PID was reused.
+    #[must_use]
+    pub fn is_live(&self) -> bool {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        now.saturating_sub(self.updated_at) <= STATUS_STALE_AFTER_SECS && pid_is_live(self.pid)
+    }
+}
+
+/// Age in seconds after which a [`HubStatus`] counts as stale. The hub
+/// rewrites it every 5 second cleanup tick, so this allows several missed
+/// ticks.
+pub const STATUS_STALE_AFTER_SECS: u64 = 30;
+
+/// Write a hub's status snapshot.
+///
+/// Goes through a temp file and rename so `botster status` never reads a
+/// half-written snapshot.
+pub fn write_status(status: &HubStatus) -> Result<()> {
+    let path = status_path(&status.hub_id)?;
+    let content = serde_json::to_string_pretty(status).context("Failed to serialize hub status")?;
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, content)
+        .and_then(|()| fs::rename(&tmp_path, &path))
+        .with_context(|| format!("Failed to write hub status: {}", path.display()))?;
+    Ok(())
+}
+
+/// Read a status snapshot from `path`.
+///
+/// Returns `None` if the file is missing or invalid JSON.
+pub fn read_status_at(path: &Path) -> Option<HubStatus> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Read the status snapshot of every hub that has written one.
+///
+/// Snapshots are returned as-is; use [`HubStatus::is_live`] to tell a
+/// running hub from one that crashed without cleaning up.
+pub fn read_all_statuses() -> Vec<HubStatus> {
+    let hubs_dir = match crate::config::Config::config_dir() {
+        Ok(dir) => dir.join("hubs"),
+        Err(_) => return Vec::new(),
+    };
+
+    let entries = match fs::read_dir(&hubs_dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| read_status_at(&entry.path().join("status.json")))
+        .collect()
+}
+
 /// Read a hub runtime manifest.
 ///
 /// Returns `None` if the manifest is missing or invalid JSON.
@@ -370,6 +479,9 @@ pub fn cleanup_on_shutdown(hub_id: &str) {
     if let Ok(path) = manifest_path(hub_id) {
         let _ = fs::remove_file(&path);
     }
+    if let Ok(path) = status_path(hub_id) {
+        let _ = fs::remove_file(&path);
+    }
     log::info!(
         "Cleaned up daemon files for hub {}",
         &hub_id[..hub_id.len().min(8)]
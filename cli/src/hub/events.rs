@@ -66,6 +66,8 @@ pub(crate) enum HubEvent {
         session_name: String,
         /// Exit code if available (None if killed by signal or unknown).
         exit_code: Option<i32>,
+        /// Signal that killed the process, if any.
+        signal: Option<i32>,
     },
 
     /// PTY output observed directly from the session PTY stream.
@@ -89,6 +91,18 @@ pub(crate) enum HubEvent {
         browser_identity: String,
     },
 
+    /// A browser's WebRTC peer connection changed state.
+    ///
+    /// Sent from the channel's event loop on peer connection state changes,
+    /// so the Hub can tear down a dropped peer immediately instead of waiting
+    /// for the next cleanup tick to notice.
+    WebRtcConnectionChanged {
+        /// Browser identity for the peer connection.
+        browser_identity: String,
+        /// New connection state.
+        state: WebRtcPeerState,
+    },
+
     /// A bounded WebRTC ingress queue filled up for a browser peer.
     ///
     /// Indicates the Hub is no longer keeping up with inbound frames from that
@@ -301,6 +315,8 @@ pub(crate) enum HubEvent {
         session_uuid: String,
         /// Exit code, or `None` if killed by signal or socket EOF.
         exit_code: Option<i32>,
+        /// Signal that killed the process, if any.
+        signal: Option<i32>,
     },
 
     /// A background reconnect task completed successfully.
@@ -322,16 +338,20 @@ pub(crate) enum HubEvent {
     /// A session was removed from `HandleCache` by `hub.unregister_session()`.
     ///
     /// The Hub removes any per-session routing state whose `session_uuid`
-    /// matches so in-memory indexes do not grow without bound when sessions cycle.
+    /// matches so in-memory indexes do not grow without bound when sessions cycle,
+    /// and closes browser preview streams into the session's forwarded port.
     SessionUnregistered {
         /// The session UUID that was removed.
         session_uuid: String,
+        /// Forwarded port the session was serving, if any.
+        port: Option<u16>,
     },
 
     /// Async worktree deletion completed.
     ///
-    /// Sent by the `spawn_blocking` task in the `WorktreeRequest::Delete`
-    /// handler after `delete_worktree_by_path` finishes (success or failure).
+    /// Sent by the `spawn_blocking` task in the `WorktreeRequest::Delete` and
+    /// `WorktreeRequest::Cleanup` handlers once the removal finishes (success
+    /// or failure).
     /// The main loop removes the worktree from `HandleCache` on success so
     /// `worktree.list()` / `worktree.find()` reflect the deletion immediately.
     WorktreeDeleteCompleted {
@@ -357,6 +377,9 @@ pub(crate) enum HubEvent {
         channel: crate::channel::WebRtcChannel,
         /// Encrypted answer envelope, ready for Lua relay. `None` on failure.
         encrypted_answer: Option<serde_json::Value>,
+        /// Why negotiation failed, reported to the browser. `None` on success
+        /// or when only answer encryption failed.
+        error: Option<crate::channel::WebRtcError>,
     },
 }
 
@@ -377,6 +400,7 @@ impl HubEvent {
             Self::PtyProcessExited { .. } => "pty_process_exited",
             Self::PtyOutputObserved { .. } => "pty_output_observed",
             Self::DcOpened { .. } => "dc_opened",
+            Self::WebRtcConnectionChanged { .. } => "webrtc_connection_changed",
             Self::WebRtcIngressBackpressure { .. } => "webrtc_ingress_backpressure",
             Self::TimerFired { .. } => "timer_fired",
             Self::AcChannelMessage { .. } => "ac_channel_message",
@@ -446,6 +470,17 @@ impl HubEvent {
     }
 }
 
+/// Peer connection state carried by [`HubEvent::WebRtcConnectionChanged`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum WebRtcPeerState {
+    /// ICE and DTLS completed; the peer is reachable.
+    Connected,
+    /// The peer became unreachable.
+    Disconnected,
+    /// ICE or DTLS failed; the connection will not recover.
+    Failed,
+}
+
 #[derive(Clone, Debug, Default)]
 pub(crate) struct HubEventTypeSnapshot {
     pub enqueue_ok: u64,
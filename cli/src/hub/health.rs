@@ -0,0 +1,324 @@
+//! Health and readiness endpoint for headless hubs.
+//!
+//! Process supervisors (systemd, Kubernetes, container healthchecks) probe a
+//! headless hub over plain HTTP instead of scraping logs:
+//!
+//! - `GET /healthz` — the process is alive and answering. Always `200`.
+//! - `GET /readyz` — the hub is registered with the server, its event loop
+//!   is ticking and the server has been heard from recently. `200` when all
+//!   hold, `503` otherwise.
+//!
+//! Both return the same JSON [`HealthReport`] so a failing probe shows why.
+//! The listener is off unless `health_bind` is set in `config.json`, and it
+//! is a single std thread speaking just enough HTTP/1.1 for probes.
+
+// Rust guideline compliant 2026-02
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// Event loop silence after which workers count as stalled. The loop gets a
+/// `CleanupTick` every 5 seconds, so this allows several missed ticks.
+const WORKER_STALL_AFTER: Duration = Duration::from_secs(30);
+
+/// Server silence after which it counts as unreachable. ActionCable pings
+/// every few seconds and the connection reconnects after 15 seconds of quiet.
+const SERVER_SILENCE_AFTER: Duration = Duration::from_secs(30);
+
+/// How long a probe may take, in total, to send its request line and
+/// headers. The listener is single-threaded, so this bounds how long one
+/// slow client can hold up every other probe.
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Upper bound on request header lines read before answering.
+const MAX_HEADER_LINES: usize = 64;
+
+/// Readiness inputs, updated by the hub and read by the listener thread.
+#[derive(Debug)]
+pub struct HealthState {
+    started_at: Instant,
+    /// Offline hubs have no server, so registration and reachability are
+    /// not required for readiness.
+    offline: bool,
+    inner: Mutex<HealthInner>,
+}
+
+#[derive(Debug, Default)]
+struct HealthInner {
+    registered: bool,
+    agents: usize,
+    last_tick: Option<Instant>,
+    last_server_message: Option<Instant>,
+}
+
+/// JSON body returned by both endpoints.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HealthReport {
+    /// Whether every readiness check passes.
+    pub ready: bool,
+    /// The hub has a server-assigned ID.
+    pub registered: bool,
+    /// The event loop ticked recently.
+    pub workers_running: bool,
+    /// The server sent something recently.
+    pub server_reachable: bool,
+    /// Running agent sessions (accessories excluded).
+    pub agents: usize,
+    /// Seconds since the server was last heard from, if ever.
+    pub last_poll_age_secs: Option<u64>,
+    /// Seconds since the health state was created.
+    pub uptime_secs: u64,
+}
+
+impl HealthState {
+    /// Create state for a hub that is not yet registered.
+    #[must_use]
+    pub fn new(offline: bool) -> Self {
+        Self {
+            started_at: Instant::now(),
+            offline,
+            inner: Mutex::new(HealthInner::default()),
+        }
+    }
+
+    /// Record whether the hub holds a server-assigned ID.
+    pub fn set_registered(&self, registered: bool) {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.registered = registered;
+        }
+    }
+
+    /// Record one event loop tick with the current agent count and the
+    /// most recent message from the server.
+    pub fn record_tick(&self, agents: usize, last_server_message: Option<Instant>) {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.agents = agents;
+            inner.last_tick = Some(Instant::now());
+            inner.last_server_message = last_server_message;
+        }
+    }
+
+    /// Evaluate the readiness checks.
+    #[must_use]
+    pub fn report(&self) -> HealthReport {
+        let inner = match self.inner.lock() {
+            Ok(inner) => inner,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let last_poll_age = inner.last_server_message.map(|at| at.elapsed());
+
+        let registered = self.offline || inner.registered;
+        let workers_running = inner
+            .last_tick
+            .is_some_and(|at| at.elapsed() < WORKER_STALL_AFTER);
+        let server_reachable =
+            self.offline || last_poll_age.is_some_and(|age| age < SERVER_SILENCE_AFTER);
+
+        HealthReport {
+            ready: registered && workers_running && server_reachable,
+            registered,
+            workers_running,
+            server_reachable,
+            agents: inner.agents,
+            last_poll_age_secs: last_poll_age.map(|age| age.as_secs()),
+            uptime_secs: self.started_at.elapsed().as_secs(),
+        }
+    }
+}
+
+/// Bind `bind` and answer probes on a background thread.
+///
+/// Returns the bound address (useful when `bind` asks for port 0).
+///
+/// # Errors
+///
+/// Returns an error if the address cannot be bound.
+pub fn spawn_listener(bind: &str, state: Arc<HealthState>) -> std::io::Result<SocketAddr> {
+    let listener = TcpListener::bind(bind)?;
+    let addr = listener.local_addr()?;
+
+    std::thread::Builder::new()
+        .name("health-http".to_string())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        if let Err(e) = handle_connection(stream, &state) {
+                            log::debug!("[health] probe failed: {e}");
+                        }
+                    }
+                    Err(e) => log::warn!("[health] accept failed: {e}"),
+                }
+            }
+        })?;
+
+    Ok(addr)
+}
+
+/// Reads from a probe's stream until a fixed deadline, so a client that
+/// trickles bytes can't keep each individual read under the timeout.
+struct DeadlineReader<'a> {
+    stream: &'a TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                "request not received in time",
+            ));
+        }
+        self.stream.set_read_timeout(Some(remaining))?;
+        let mut stream = self.stream;
+        stream.read(buf)
+    }
+}
+
+/// Answer a single request and close the connection.
+fn handle_connection(mut stream: TcpStream, state: &HealthState) -> std::io::Result<()> {
+    let mut reader = BufReader::new(DeadlineReader {
+        stream: &stream,
+        deadline: Instant::now() + REQUEST_READ_TIMEOUT,
+    });
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    // Drain headers so closing the socket doesn't reset the connection
+    // before the client reads the response.
+    let mut header = String::new();
+    for _ in 0..MAX_HEADER_LINES {
+        header.clear();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+
+    let (status, body) = route(&request_line, state);
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes())?;
+    stream.flush()
+}
+
+/// Map a request line to a status line and JSON body.
+fn route(request_line: &str, state: &HealthState) -> (&'static str, String) {
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let path = parts.next().unwrap_or("").split('?').next().unwrap_or("");
+
+    if method != "GET" {
+        return (
+            "405 Method Not Allowed",
+            r#"{"error":"method not allowed"}"#.to_string(),
+        );
+    }
+
+    let report = state.report();
+    let body = serde_json::to_string(&report).unwrap_or_else(|_| "{}".to_string());
+    match path {
+        "/healthz" => ("200 OK", body),
+        "/readyz" if report.ready => ("200 OK", body),
+        "/readyz" => ("503 Service Unavailable", body),
+        _ => ("404 Not Found", r#"{"error":"not found"}"#.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn get(addr: SocketAddr, path: &str) -> (String, serde_json::Value) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.lines().next().unwrap().to_string();
+        (status, serde_json::from_str(body).unwrap())
+    }
+
+    #[test]
+    fn readyz_reports_not_ready_until_registered() {
+        let state = Arc::new(HealthState::new(false));
+        let addr = spawn_listener("127.0.0.1:0", Arc::clone(&state)).unwrap();
+
+        state.record_tick(2, Some(Instant::now()));
+        let (status, body) = get(addr, "/readyz");
+        assert_eq!(status, "HTTP/1.1 503 Service Unavailable");
+        assert_eq!(body["ready"], false);
+        assert_eq!(body["registered"], false);
+        assert_eq!(body["workers_running"], true);
+
+        state.set_registered(true);
+        let (status, body) = get(addr, "/readyz");
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert_eq!(body["ready"], true);
+        assert_eq!(body["agents"], 2);
+        assert_eq!(body["last_poll_age_secs"], 0);
+    }
+
+    #[test]
+    fn slow_probe_cannot_hold_the_listener_past_the_deadline() {
+        let state = Arc::new(HealthState::new(false));
+        let addr = spawn_listener("127.0.0.1:0", Arc::clone(&state)).unwrap();
+
+        // One header line every 500ms stays under a per-read timeout, so
+        // without an overall deadline this would hold the listener for 6s.
+        let slow = std::thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            let _ = write!(stream, "GET /healthz HTTP/1.1\r\n");
+            for _ in 0..12 {
+                std::thread::sleep(Duration::from_millis(500));
+                let _ = write!(stream, "X-Slow: 1\r\n");
+            }
+        });
+        std::thread::sleep(Duration::from_millis(100));
+
+        let started = Instant::now();
+        let (status, _) = get(addr, "/healthz");
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert!(
+            started.elapsed() < REQUEST_READ_TIMEOUT + Duration::from_secs(1),
+            "probe waited {:?} behind a slow client",
+            started.elapsed()
+        );
+        slow.join().unwrap();
+    }
+
+    #[test]
+    fn healthz_answers_before_readiness() {
+        let state = Arc::new(HealthState::new(false));
+        let addr = spawn_listener("127.0.0.1:0", Arc::clone(&state)).unwrap();
+
+        let (status, body) = get(addr, "/healthz");
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert_eq!(body["ready"], false);
+        assert!(body["last_poll_age_secs"].is_null());
+
+        let (status, _) = get(addr, "/metrics");
+        assert_eq!(status, "HTTP/1.1 404 Not Found");
+    }
+
+    #[test]
+    fn offline_hub_needs_only_a_running_loop() {
+        let state = HealthState::new(true);
+        assert!(!state.report().ready, "no tick yet");
+
+        state.record_tick(0, None);
+        let report = state.report();
+        assert!(report.ready);
+        assert!(report.server_reachable);
+    }
+}
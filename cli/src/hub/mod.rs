@@ -45,6 +45,7 @@ pub mod agent_handle;
 pub mod daemon;
 pub(crate) mod events;
 pub mod handle_cache;
+pub mod health;
 pub mod registration;
 pub mod run;
 mod server_comms;
@@ -342,6 +343,8 @@ pub struct Hub {
     pub botster_id: Option<String>,
     /// Shared copy of `botster_id` for Lua primitives (updated on registration).
     pub shared_server_id: SharedServerId,
+    /// Readiness inputs served by the optional health endpoint.
+    pub health: Arc<health::HealthState>,
     /// Async runtime for relay and preview channel operations.
     ///
     /// Wrapped in `Arc` so tests can share a single runtime across all
@@ -722,6 +725,7 @@ impl Hub {
             hub_identifier,
             botster_id: None,
             shared_server_id: Arc::new(Mutex::new(None)),
+            health: Arc::new(health::HealthState::new(crate::env::is_offline())),
             tokio_runtime,
             quit: false,
             exec_restart: false,
@@ -1148,6 +1152,74 @@ impl Hub {
         }
     }
 
+    /// Start the health endpoint if `health_bind` is configured.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configured address cannot be bound.
+    pub fn start_health_listener(&self) -> anyhow::Result<()> {
+        let Some(bind) = self.config.health_bind.as_deref() else {
+            return Ok(());
+        };
+        let addr = health::spawn_listener(bind, Arc::clone(&self.health))
+            .map_err(|e| anyhow::anyhow!("Failed to bind health endpoint on {bind}: {e}"))?;
+        log::info!("Health endpoint listening on http://{addr}");
+        Ok(())
+    }
+
+    /// Feed the health endpoint from a `CleanupTick`.
+    ///
+    /// Connections that missed a heartbeat are left out, so the hub reports
+    /// the server as unreachable until one of them reconnects.
+    fn record_health_tick(&self) {
+        let agents = self
+            .handle_cache
+            .get_all_sessions()
+            .iter()
+            .filter(|session| session.session_type() == SessionType::Agent)
+            .count();
+        let last_server_message = self
+            .lua_ac_connections
+            .values()
+            .filter(|conn| conn.connection.is_connected())
+            .filter_map(|conn| conn.connection.last_message_at())
+            .max();
+        self.health.record_tick(agents, last_server_message);
+    }
+
+    /// Rewrite the status snapshot read by `botster status`.
+    fn write_status_snapshot(&self) {
+        let sessions = self
+            .handle_cache
+            .get_all_sessions()
+            .iter()
+            .map(|session| daemon::SessionStatus {
+                session_uuid: session.session_uuid().to_string(),
+                label: session.label().to_string(),
+                session_type: session.session_type().to_string(),
+                status: if session.pty().session_connection_alive() {
+                    "running"
+                } else {
+                    "disconnected"
+                }
+                .to_string(),
+            })
+            .collect();
+        let status = daemon::HubStatus {
+            hub_id: self.hub_identifier.clone(),
+            botster_id: self.botster_id.clone(),
+            pid: std::process::id(),
+            updated_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            sessions,
+        };
+        if let Err(e) = daemon::write_status(&status) {
+            log::debug!("Failed to write hub status: {e}");
+        }
+    }
+
     /// Run the Hub event loop without TUI.
     ///
     /// For TUI mode, use `crate::tui::run_with_hub()` instead - the TUI
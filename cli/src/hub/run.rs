@@ -139,13 +139,17 @@ pub(crate) fn run_event_loop(
                         None => std::future::pending().await,
                     }
                 } => {
-                    hub.handle_pty_input(input);
-                    // Drain remaining in batch
+                    // Drain remaining in batch, merging runs of typed text so a
+                    // burst of buffered keystrokes becomes one PTY write.
+                    let mut batch = vec![input];
                     if let Some(ref mut rx) = pty_input_rx {
                         while let Ok(more) = rx.try_recv() {
-                            hub.handle_pty_input(more);
+                            batch.push(more);
                         }
                     }
+                    for input in crate::channel::webrtc::PtyInputIncoming::coalesce(batch) {
+                        hub.handle_pty_input(input);
+                    }
                 }
 
                 // File transfer from browser (image paste/drop)
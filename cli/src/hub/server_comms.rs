@@ -415,6 +415,13 @@ impl Hub {
             .insert(peer_id.to_string());
     }
 
+    /// Whether `peer_id` has a terminal forwarder attached to `session_uuid`.
+    fn terminal_peer_attached(&self, session_uuid: &str, peer_id: &str) -> bool {
+        self.terminal_session_peers
+            .get(session_uuid)
+            .is_some_and(|peers| peers.contains(peer_id))
+    }
+
     fn unregister_terminal_forwarder_peer(&mut self, forwarder_id: &str, promote_next: bool) {
         let Some((session_uuid, peer_id)) = self.terminal_forwarder_peers.remove(forwarder_id)
         else {
@@ -606,17 +613,20 @@ impl Hub {
                 session_uuid,
                 session_name,
                 exit_code,
+                signal,
             } => {
                 log::info!(
-                    "[Hub] PTY process exited for {}:{} (code={:?})",
+                    "[Hub] PTY process exited for {}:{} (code={:?}, signal={:?})",
                     session_uuid,
                     session_name,
-                    exit_code
+                    exit_code,
+                    signal
                 );
                 let data = serde_json::json!({
                     "session_uuid": session_uuid,
                     "session_name": session_name,
                     "exit_code": exit_code,
+                    "signal": signal,
                 });
                 if let Err(e) = self.lua.fire_json_event("process_exited", &data) {
                     log::error!("Failed to fire process_exited event: {e}");
@@ -745,6 +755,8 @@ impl Hub {
             }
             // LuaFileChange removed — hot-reload now handled by Lua's module_watcher
             HubEvent::CleanupTick => {
+                self.record_health_tick();
+                self.write_status_snapshot();
                 self.cleanup_disconnected_webrtc_channels();
                 self.poll_stream_frames_outgoing();
                 self.send_backpressure_recovery_snapshots();
@@ -897,6 +909,35 @@ impl Hub {
                     );
                 }
             }
+            HubEvent::WebRtcConnectionChanged {
+                browser_identity,
+                state,
+            } => {
+                use crate::channel::ConnectionState;
+                use crate::hub::events::WebRtcPeerState;
+
+                match state {
+                    WebRtcPeerState::Connected => {
+                        self.webrtc_connection_started.remove(&browser_identity);
+                    }
+                    WebRtcPeerState::Disconnected | WebRtcPeerState::Failed => {
+                        // A replacement channel for the same browser may already
+                        // be negotiating; only tear down the one that dropped.
+                        let dropped = self
+                            .webrtc_channels
+                            .get(&browser_identity)
+                            .is_some_and(|ch| ch.state() == ConnectionState::Disconnected);
+                        if dropped {
+                            let reason = if state == WebRtcPeerState::Failed {
+                                "failed"
+                            } else {
+                                "disconnected"
+                            };
+                            self.cleanup_webrtc_channel(&browser_identity, reason);
+                        }
+                    }
+                }
+            }
             HubEvent::WebRtcIngressBackpressure {
                 browser_identity,
                 source,
@@ -1326,11 +1367,19 @@ impl Hub {
                         let result_tx = self.worktree_result_tx.clone();
                         let branch_clone = branch.clone();
                         let label_clone = label.clone();
+                        let issue_number = metadata
+                            .get("issue_number")
+                            .and_then(serde_json::Value::as_u64)
+                            .and_then(|n| u32::try_from(n).ok());
 
                         self.tokio_runtime.spawn(async move {
                             let result = tokio::task::spawn_blocking(move || {
                                 let manager = WorktreeManager::new(worktree_base);
-                                manager.create_worktree_with_branch(&branch_clone)
+                                let path = manager.create_worktree_with_branch(&branch_clone)?;
+                                if let Some(issue_number) = issue_number {
+                                    WorktreeManager::tag_worktree_issue(&path, issue_number);
+                                }
+                                Ok::<_, anyhow::Error>(path)
                             })
                             .await;
 
@@ -1359,7 +1408,7 @@ impl Hub {
                             }
                         });
                     }
-                    WorktreeRequest::Delete { path, branch } => {
+                    WorktreeRequest::Delete { path, branch, env } => {
                         log::info!(
                             "[Lua] Dispatching async worktree.delete({}, {})",
                             path,
@@ -1372,7 +1421,8 @@ impl Hub {
 
                         self.tokio_runtime.spawn(async move {
                             let result = tokio::task::spawn_blocking(move || {
-                                let manager = WorktreeManager::new(worktree_base);
+                                let manager =
+                                    WorktreeManager::new(worktree_base).with_teardown_env(env);
                                 manager.delete_worktree_by_path(
                                     std::path::Path::new(&path_clone),
                                     &branch_clone,
@@ -1386,6 +1436,49 @@ impl Hub {
                                 Err(e) => Err(format!("spawn_blocking panicked: {e}")),
                             };
 
+                            let _ =
+                                event_tx.send(super::events::HubEvent::WorktreeDeleteCompleted {
+                                    path,
+                                    branch,
+                                    result: outcome,
+                                });
+                        });
+                    }
+                    WorktreeRequest::Cleanup {
+                        path,
+                        branch,
+                        policy,
+                        env,
+                    } => {
+                        log::info!(
+                            "[Lua] Dispatching async worktree.cleanup({}, {}, {})",
+                            path,
+                            branch,
+                            policy.as_str()
+                        );
+                        let worktree_base = self.config.worktree_base.clone();
+                        let event_tx = self.hub_event_tx.clone();
+                        let path_clone = path.clone();
+                        let branch_clone = branch.clone();
+
+                        self.tokio_runtime.spawn(async move {
+                            let result = tokio::task::spawn_blocking(move || {
+                                let manager =
+                                    WorktreeManager::new(worktree_base).with_teardown_env(env);
+                                manager.cleanup_worktree_with_policy(
+                                    std::path::Path::new(&path_clone),
+                                    &branch_clone,
+                                    policy,
+                                )
+                            })
+                            .await;
+
+                            let outcome = match result {
+                                Ok(Ok(())) => Ok(()),
+                                Ok(Err(e)) => Err(e.to_string()),
+                                Err(e) => Err(format!("spawn_blocking panicked: {e}")),
+                            };
+
                             let _ =
                                 event_tx.send(super::events::HubEvent::WorktreeDeleteCompleted {
                                     path,
@@ -1438,16 +1531,18 @@ impl Hub {
             HubEvent::SessionProcessExited {
                 session_uuid,
                 exit_code,
+                signal,
             } => {
                 log::info!(
-                    "[Session] ProcessExited uuid='{}' exit={:?}",
+                    "[Session] ProcessExited uuid='{}' exit={:?} signal={:?}",
                     session_uuid,
-                    exit_code
+                    exit_code,
+                    signal
                 );
 
                 // Reader death on a session-backed handle: attempt reconnect
                 // instead of immediately declaring the session dead.
-                if exit_code.is_none() {
+                if exit_code.is_none() && signal.is_none() {
                     if let Some(session_handle) = self.handle_cache.get_session(&session_uuid) {
                         let pty = session_handle.pty();
                         if pty.is_session_backed() {
@@ -1482,11 +1577,15 @@ impl Hub {
 
                 // Real process exit or non-session-backed: normal handling.
                 if let Some(session_handle) = self.handle_cache.get_session(&session_uuid) {
-                    session_handle.pty().notify_process_exited(exit_code);
+                    match signal {
+                        Some(signal) => session_handle.pty().notify_process_signalled(signal),
+                        None => session_handle.pty().notify_process_exited(exit_code),
+                    }
                 }
                 let data = serde_json::json!({
                     "session_uuid": session_uuid,
                     "exit_code": exit_code,
+                    "signal": signal,
                 });
                 if let Err(e) = self.lua.fire_json_event("session_process_exited", &data) {
                     log::error!("[Session] Failed to fire session_process_exited event: {e}");
@@ -1584,7 +1683,22 @@ impl Hub {
                 }
             }
 
-            HubEvent::SessionUnregistered { session_uuid } => {
+            HubEvent::SessionUnregistered { session_uuid, port } => {
+                if let Some(port) = port {
+                    let closed: usize = self
+                        .stream_muxes
+                        .values_mut()
+                        .map(|mux| mux.close_port(port))
+                        .sum();
+                    if closed > 0 {
+                        log::debug!(
+                            "[Session] Closed {} preview stream(s) to port {} for '{}'",
+                            closed,
+                            port,
+                            session_uuid
+                        );
+                    }
+                }
                 self.terminal_profiles.clear_session(&session_uuid);
                 self.terminal_session_peers.remove(&session_uuid);
                 self.terminal_forwarder_peers
@@ -1600,6 +1714,7 @@ impl Hub {
                 offer_generation,
                 mut channel,
                 encrypted_answer,
+                error,
             } => {
                 let current_generation = self
                     .webrtc_offer_generation
@@ -1624,6 +1739,9 @@ impl Hub {
                         "[WebRTC] Offer handling failed for {} — discarding channel so the next retry can start cleanly",
                         &browser_identity[..browser_identity.len().min(8)]
                     );
+                    if let Some(error) = error {
+                        self.send_webrtc_error(&browser_identity, &error);
+                    }
                     self.webrtc_connection_started.remove(&browser_identity);
                     self.webrtc_offer_generation.remove(&browser_identity);
                     self.webrtc_pending_ice_candidates.remove(&browser_identity);
@@ -1767,6 +1885,18 @@ impl Hub {
 
     /// Handle a single binary PTY input from a browser (WebRTC).
     pub fn handle_pty_input(&mut self, input: crate::channel::webrtc::PtyInputIncoming) {
+        // Only the browser's own attached terminals accept its keystrokes or
+        // focus changes, so a stale or forged subscription can't type into
+        // another agent or take over its color profile.
+        if !self.terminal_peer_attached(&input.session_uuid, &input.browser_identity) {
+            log::warn!(
+                "[PTY-INPUT] Rejected input from {} for session {} it is not attached to",
+                &input.browser_identity[..input.browser_identity.len().min(8)],
+                input.session_uuid
+            );
+            return;
+        }
+
         if input.data == b"\x1b[I" {
             self.set_active_terminal_peer(&input.session_uuid, &input.browser_identity, true);
             self.lua
@@ -1909,6 +2039,42 @@ impl Hub {
         true
     }
 
+    /// Tell a browser why its WebRTC negotiation failed.
+    ///
+    /// Sent as an encrypted `{"type": "error"}` signal alongside answers and
+    /// ICE candidates, carrying [`crate::channel::WebRtcError::code`] and its
+    /// user-facing message. Best effort: without an Olm session the browser
+    /// simply never hears back, as before.
+    fn send_webrtc_error(&self, browser_identity: &str, error: &crate::channel::WebRtcError) {
+        let Some(ref cs) = self.browser.crypto_service else {
+            return;
+        };
+        let payload = serde_json::json!({
+            "type": "error",
+            "code": error.code(),
+            "message": error.user_message(),
+        });
+        let plaintext = serde_json::to_vec(&payload).unwrap_or_default();
+        let olm_key = crate::relay::extract_olm_key(browser_identity);
+        let envelope = match cs.lock() {
+            Ok(mut guard) => guard
+                .encrypt(&plaintext, olm_key)
+                .map_err(|e| e.to_string())
+                .and_then(|envelope| serde_json::to_value(&envelope).map_err(|e| e.to_string())),
+            Err(e) => Err(format!("crypto mutex poisoned: {e}")),
+        };
+        match envelope {
+            Ok(envelope) => {
+                self.emit_outgoing_signal(browser_identity, envelope, "error");
+            }
+            Err(e) => log::warn!(
+                "[WebRTC] Could not send {} error to {}: {e}",
+                error.code(),
+                &browser_identity[..browser_identity.len().min(8)]
+            ),
+        }
+    }
+
     fn handle_signaling_message(&mut self, message: serde_json::Value) {
         let msg_type = message.get("type").and_then(|v| v.as_str()).unwrap_or("");
         let browser_identity = message
@@ -1957,18 +2123,35 @@ impl Hub {
                             );
                             return;
                         };
+                        let codecs: Option<Vec<String>> = signal_data
+                            .get("codecs")
+                            .and_then(|v| v.as_array())
+                            .map(|list| {
+                                list.iter()
+                                    .filter_map(|c| c.as_str().map(str::to_string))
+                                    .collect()
+                            });
                         log::info!(
                             "[Lua] Processing WebRTC offer from {}",
                             &browser_identity[..browser_identity.len().min(8)]
                         );
-                        self.handle_webrtc_offer(sdp, browser_identity);
+                        self.handle_webrtc_offer(sdp, browser_identity, codecs.as_deref());
                     }
                     "ice" => {
                         let candidate = signal_data
                             .get("candidate")
                             .cloned()
                             .unwrap_or(serde_json::Value::Null);
-                        self.handle_browser_ice_candidate(browser_identity, candidate);
+                        // Channel failures are logged with candidate detail
+                        // inside; only the missing-session case is left.
+                        if let Err(crate::channel::WebRtcError::SessionNotFound) =
+                            self.handle_browser_ice_candidate(browser_identity, candidate)
+                        {
+                            log::warn!(
+                                "[Lua] ICE candidate for unknown browser {}",
+                                &browser_identity[..browser_identity.len().min(8)]
+                            );
+                        }
                     }
                     other => {
                         log::warn!(
@@ -1992,11 +2175,15 @@ impl Hub {
         }
     }
 
+    /// Apply (or queue, while an offer is in flight) a browser ICE candidate.
+    ///
+    /// Returns [`WebRtcError::SessionNotFound`](crate::channel::WebRtcError::SessionNotFound)
+    /// when the browser has no channel and no pending offer.
     fn handle_browser_ice_candidate(
         &mut self,
         browser_identity: &str,
         candidate: serde_json::Value,
-    ) {
+    ) -> Result<(), crate::channel::WebRtcError> {
         const MAX_QUEUED_ICE_PER_BROWSER: usize = 128;
 
         let candidate_str = candidate
@@ -2008,7 +2195,7 @@ impl Hub {
                 "[Lua] Ignoring empty ICE candidate for {}",
                 &browser_identity[..browser_identity.len().min(8)]
             );
-            return;
+            return Ok(());
         }
 
         let sdp_mid = candidate.get("sdpMid").and_then(|m| m.as_str());
@@ -2033,6 +2220,7 @@ impl Hub {
                     sdp_mline_index,
                     Self::ice_candidate_preview(candidate_str),
                 );
+                return Err(error);
             }
         } else if self.webrtc_offer_generation.contains_key(browser_identity) {
             let current_generation = self
@@ -2055,11 +2243,9 @@ impl Hub {
                 queue.len()
             );
         } else {
-            log::warn!(
-                "[Lua] ICE candidate for unknown browser {}",
-                &browser_identity[..browser_identity.len().min(8)]
-            );
+            return Err(crate::channel::WebRtcError::SessionNotFound);
         }
+        Ok(())
     }
 
     /// Handle a single incoming stream frame from WebRTC.
@@ -2232,16 +2418,18 @@ impl Hub {
                             break;
                         }
                     }
-                    Ok(PtyEvent::ProcessExited { exit_code }) => {
+                    Ok(PtyEvent::ProcessExited { exit_code, signal }) => {
                         log::info!(
-                            "[NotifWatcher] Process exited (code={:?}) for {}",
+                            "[NotifWatcher] Process exited (code={:?}, signal={:?}) for {}",
                             exit_code,
+                            signal,
                             key
                         );
                         let event = super::events::HubEvent::PtyProcessExited {
                             session_uuid: session_uuid.clone(),
                             session_name: session_name.clone(),
                             exit_code,
+                            signal,
                         };
                         let _ = hub_tx.send(event);
                         break;
@@ -3548,7 +3736,7 @@ impl Hub {
                             }
                         }
                     }
-                    Ok(PtyEvent::ProcessExited { exit_code }) => {
+                    Ok(PtyEvent::ProcessExited { exit_code, .. }) => {
                         log::info!(
                             "[Lua] PTY process exited (code={:?}) for session {}",
                             exit_code,
@@ -4271,7 +4459,7 @@ impl Hub {
                         let mut should_break = false;
                         for event in stashed {
                             match event {
-                                PtyEvent::ProcessExited { exit_code } => {
+                                PtyEvent::ProcessExited { exit_code, .. } => {
                                     log::info!(
                                         "[Lua-TUI] PTY process exited (code={:?}) for session {} (stashed)",
                                         exit_code, session_uuid
@@ -4313,7 +4501,7 @@ impl Hub {
                             break; // exit forwarder on process exit
                         }
                     }
-                    Ok(PtyEvent::ProcessExited { exit_code }) => {
+                    Ok(PtyEvent::ProcessExited { exit_code, .. }) => {
                         log::info!(
                             "[Lua-TUI] PTY process exited (code={:?}) for session {}",
                             exit_code,
@@ -4647,7 +4835,7 @@ impl Hub {
                             }
                         }
                     }
-                    Ok(PtyEvent::ProcessExited { exit_code }) => {
+                    Ok(PtyEvent::ProcessExited { exit_code, .. }) => {
                         log::info!(
                             "[Lua-Socket] PTY process exited (code={:?}) for {} session {}",
                             exit_code,
@@ -4737,7 +4925,7 @@ impl Hub {
             return;
         };
         let inputs: Vec<_> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
-        for input in inputs {
+        for input in crate::channel::webrtc::PtyInputIncoming::coalesce(inputs) {
             if let Some(session_handle) = self.handle_cache.get_session(&input.session_uuid) {
                 if let Err(e) = session_handle.pty().write_input_direct(&input.data) {
                     log::error!("[PTY-INPUT] Write failed: {e}");
@@ -5271,8 +5459,13 @@ impl Hub {
     /// take 10+ seconds), answer encryption — runs in a spawned async task that
     /// posts `HubEvent::WebRtcOfferCompleted` when done. This prevents the event
     /// loop from freezing during ICE config HTTP requests.
-    fn handle_webrtc_offer(&mut self, sdp: &str, browser_identity: &str) {
-        use crate::channel::{ChannelConfig, WebRtcChannel};
+    fn handle_webrtc_offer(
+        &mut self,
+        sdp: &str,
+        browser_identity: &str,
+        codecs: Option<&[String]>,
+    ) {
+        use crate::channel::{negotiate_codec, ChannelConfig, WebRtcChannel};
 
         if crate::env::is_offline() {
             log::warn!("[WebRTC] Rejecting offer — hub is in offline mode");
@@ -5292,6 +5485,17 @@ impl Hub {
         let is_new_connection = !self.webrtc_channels.contains_key(browser_identity);
 
         if is_new_connection {
+            // Terminal output only ever leaves the hub end-to-end encrypted.
+            // Without a crypto service, refuse the offer instead of panicking.
+            let Some(crypto_service) = self.browser.crypto_service.clone() else {
+                log::error!(
+                    "[WebRTC] Refusing offer from {}: crypto service unavailable, \
+                     terminal relay requires end-to-end encryption",
+                    &browser_identity[..browser_identity.len().min(8)]
+                );
+                return;
+            };
+
             // Clean up stale channels from the same device (same Olm key, different tab UUID).
             let olm_key = crate::relay::extract_olm_key(browser_identity);
             let stale: Vec<String> = self
@@ -5340,14 +5544,10 @@ impl Hub {
                 .signal_tx(self.webrtc_outgoing_signal_tx.clone())
                 .stream_frame_tx(self.stream_frame_tx.clone())
                 .hub_event_tx(self.hub_event_tx.clone())
-                .crypto_service(
-                    self.browser
-                        .crypto_service
-                        .clone()
-                        .expect("crypto service required"),
-                )
+                .crypto_service(crypto_service)
                 .pty_input_tx(self.pty_input_tx.clone())
-                .file_input_tx(self.file_input_tx.clone());
+                .file_input_tx(self.file_input_tx.clone())
+                .ice_servers(self.config.ice_servers.clone());
 
             let mut channel = builder.build();
 
@@ -5386,6 +5586,16 @@ impl Hub {
             return;
         };
 
+        // Pick the compression codec from what the browser advertised in the
+        // offer. ICE restarts re-advertise, so this is refreshed every offer.
+        let codec = negotiate_codec(codecs);
+        log::debug!(
+            "[WebRTC] Negotiated {} compression for {}",
+            codec.name(),
+            &browser_identity[..browser_identity.len().min(8)]
+        );
+        channel.set_compression_codec(codec);
+
         let crypto = self.browser.crypto_service.clone();
         let event_tx = self.hub_event_tx.clone();
         let sdp = sdp.to_string();
@@ -5403,6 +5613,7 @@ impl Hub {
         // Spawn async task for SDP negotiation + answer encryption.
         self.tokio_runtime.spawn(async move {
             let started_at = Instant::now();
+            let mut error = None;
             let answer_value = match channel.handle_sdp_offer(&sdp, &browser_id).await {
                 Ok(answer_sdp) => {
                     log::info!(
@@ -5454,6 +5665,7 @@ impl Hub {
                         "[WebRTC] Failed to handle offer after {}ms: {e}",
                         started_at.elapsed().as_millis()
                     );
+                    error = Some(e);
                     None
                 }
             };
@@ -5464,6 +5676,7 @@ impl Hub {
                 offer_generation,
                 channel,
                 encrypted_answer: answer_value,
+                error,
             });
         });
     }
@@ -6116,6 +6329,10 @@ impl Hub {
             self.config.get_api_key(),
             &self.device.fingerprint,
         );
+        // Registration falls back to the local identifier when it fails, so
+        // only a distinct ID counts as registered for readiness.
+        self.health
+            .set_registered(botster_id != self.hub_identifier);
         // Store server-assigned ID (used for all server communication)
         self.botster_id = Some(botster_id.clone());
         // Sync to shared copy for Lua primitives
@@ -6579,6 +6796,7 @@ mod tests {
 
         hub.handle_hub_event(crate::hub::events::HubEvent::SessionUnregistered {
             session_uuid: session_uuid.to_string(),
+            port: None,
         });
 
         hub.learn_terminal_probe_replies(
@@ -6767,6 +6985,26 @@ mod tests {
     fn test_browser_focus_input_updates_active_terminal_peer() {
         let (mut hub, _request_tx, _output_rx) = e2e_hub();
         let session_uuid = "sess-browser-focus";
+        hub.handle_cache
+            .add_session(test_session_handle(session_uuid));
+        assert!(hub.try_attach_terminal_forwarder(&test_forwarder_request(
+            "browser-a",
+            session_uuid,
+            "terminal_browser_focus",
+        )));
+
+        // A browser not attached to the terminal cannot take focus.
+        hub.handle_pty_input(crate::channel::webrtc::PtyInputIncoming {
+            session_uuid: session_uuid.to_string(),
+            browser_identity: "browser-b".to_string(),
+            data: b"\x1b[I".to_vec(),
+        });
+        assert!(hub
+            .active_terminal_peers
+            .lock()
+            .expect("active peers mutex")
+            .get(session_uuid)
+            .is_none());
 
         hub.handle_pty_input(crate::channel::webrtc::PtyInputIncoming {
             session_uuid: session_uuid.to_string(),
@@ -6797,6 +7035,73 @@ mod tests {
             .is_none());
     }
 
+    #[test]
+    fn test_browser_input_only_reaches_attached_session() {
+        let (mut hub, _request_tx, _output_rx) = e2e_hub();
+        for (browser, session_uuid) in
+            [("browser-a", "sess-input-a"), ("browser-b", "sess-input-b")]
+        {
+            hub.handle_cache
+                .add_session(test_session_handle(session_uuid));
+            assert!(hub.try_attach_terminal_forwarder(&test_forwarder_request(
+                browser,
+                session_uuid,
+                &format!("terminal_{session_uuid}"),
+            )));
+        }
+        let typed_into = |hub: &Hub, session_uuid: &str| {
+            hub.handle_cache
+                .get_session(session_uuid)
+                .expect("session registered")
+                .pty()
+                .last_human_input_ms()
+                > 0
+        };
+        let send = |hub: &mut Hub, browser: &str, session_uuid: &str| {
+            hub.handle_pty_input(crate::channel::webrtc::PtyInputIncoming {
+                session_uuid: session_uuid.to_string(),
+                browser_identity: browser.to_string(),
+                data: b"ls\r".to_vec(),
+            });
+        };
+
+        // Browser A is not attached to B's terminal, so its input is dropped.
+        send(&mut hub, "browser-a", "sess-input-b");
+        assert!(!typed_into(&hub, "sess-input-b"));
+
+        send(&mut hub, "browser-a", "sess-input-a");
+        send(&mut hub, "browser-b", "sess-input-b");
+        assert!(typed_into(&hub, "sess-input-a"));
+        assert!(typed_into(&hub, "sess-input-b"));
+    }
+
+    #[test]
+    fn test_ice_candidate_for_unknown_browser_is_session_not_found() {
+        let (mut hub, _request_tx, _output_rx) = e2e_hub();
+        let candidate = serde_json::json!({
+            "candidate": "candidate:1 1 udp 2122260223 192.168.1.2 54321 typ host",
+            "sdpMid": "0",
+            "sdpMLineIndex": 0,
+        });
+
+        assert_eq!(
+            hub.handle_browser_ice_candidate("browser-unknown", candidate.clone()),
+            Err(crate::channel::WebRtcError::SessionNotFound)
+        );
+
+        // While an offer is in flight the candidate is queued, not rejected.
+        hub.webrtc_offer_generation
+            .insert("browser-pending".to_string(), 1);
+        assert_eq!(
+            hub.handle_browser_ice_candidate("browser-pending", candidate),
+            Ok(())
+        );
+        assert_eq!(
+            hub.webrtc_pending_ice_candidates["browser-pending"].len(),
+            1
+        );
+    }
+
     #[test]
     fn test_tui_focus_request_updates_active_terminal_peer() {
         let (mut hub, _request_tx, _output_rx) = e2e_hub();
@@ -48,6 +48,8 @@ pub mod ghostty_vt;
 pub mod git;
 pub mod hosted_preview;
 pub mod keyring;
+pub mod logging;
+pub mod minisign;
 pub mod notifications;
 pub mod process;
 pub mod server;
@@ -0,0 +1,229 @@
+//! Process-wide logger with a filter that can be changed at runtime.
+//!
+//! `env_logger` fixes its filter when it is built, so a running hub could
+//! only be made more verbose by restarting it with a new `RUST_LOG`. This
+//! module wraps an `env_logger::Logger` that accepts every record and applies
+//! a separate, swappable filter in front of it.
+//!
+//! The initial filter comes from `--log-level`, then `RUST_LOG`, then the
+//! caller's default. [`set_level`] replaces it later (Lua: `log.set_level`).
+
+use std::str::FromStr;
+use std::sync::{OnceLock, RwLock};
+
+use anyhow::{bail, Result};
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// The installed logger, kept so [`set_level`] can reach its filter.
+static LOGGER: OnceLock<&'static ReloadableLogger> = OnceLock::new();
+
+/// A logger whose filter can be replaced after installation.
+#[derive(Debug)]
+pub struct ReloadableLogger {
+    /// Formats and writes records. Built to accept everything.
+    inner: env_logger::Logger,
+    /// Active filter and the spec it was parsed from.
+    ///
+    /// Stored as an `env_logger::Logger` purely for its `RUST_LOG`-style
+    /// directive matching; it never writes anything.
+    filter: RwLock<(String, env_logger::Logger)>,
+}
+
+impl ReloadableLogger {
+    /// Wrap `builder` (target, format, ...) with an initial filter `spec`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `spec` is not a valid filter (see [`validate_spec`]).
+    pub fn new(builder: env_logger::Builder, spec: &str) -> Result<Self> {
+        Ok(Self::with_filter(builder, spec, build_filter(spec)?))
+    }
+
+    fn with_filter(
+        mut builder: env_logger::Builder,
+        spec: &str,
+        filter: env_logger::Logger,
+    ) -> Self {
+        builder.filter_level(LevelFilter::Trace);
+        Self {
+            inner: builder.build(),
+            filter: RwLock::new((spec.to_string(), filter)),
+        }
+    }
+
+    /// Replace the filter. Returns the most verbose level it allows.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `spec` is not a valid filter; the old one stays.
+    pub fn set_spec(&self, spec: &str) -> Result<LevelFilter> {
+        let filter = build_filter(spec)?;
+        let max = filter.filter();
+        *self.filter.write().expect("log filter lock poisoned") = (spec.to_string(), filter);
+        Ok(max)
+    }
+
+    /// The spec the current filter was parsed from.
+    pub fn spec(&self) -> String {
+        self.filter
+            .read()
+            .expect("log filter lock poisoned")
+            .0
+            .clone()
+    }
+
+    /// The most verbose level the current filter allows.
+    pub fn max_level(&self) -> LevelFilter {
+        self.filter
+            .read()
+            .expect("log filter lock poisoned")
+            .1
+            .filter()
+    }
+}
+
+impl Log for ReloadableLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.filter
+            .read()
+            .is_ok_and(|filter| filter.1.enabled(metadata))
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        let passes = self
+            .filter
+            .read()
+            .is_ok_and(|filter| filter.1.matches(record));
+        if passes {
+            self.inner.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Check a filter spec such as `debug` or `info,botster::hub=trace`.
+///
+/// This is the `RUST_LOG` syntax restricted to `level` and `module=level`
+/// directives. `env_logger` silently skips bad directives (and prints to
+/// stderr, which would corrupt the TUI), so specs are validated up front.
+///
+/// # Errors
+///
+/// Returns an error naming the first invalid directive.
+pub fn validate_spec(spec: &str) -> Result<()> {
+    if spec.trim().is_empty() {
+        bail!("log level must not be empty");
+    }
+    for directive in spec.split(',').map(str::trim) {
+        let (module, level) = directive.rsplit_once('=').unwrap_or(("", directive));
+        if module.contains(char::is_whitespace) || (directive.contains('=') && module.is_empty()) {
+            bail!("invalid log directive: {directive:?}");
+        }
+        if LevelFilter::from_str(level).is_err() {
+            bail!("unknown log level {level:?} in {directive:?}");
+        }
+    }
+    Ok(())
+}
+
+fn parse_filter(spec: &str) -> env_logger::Logger {
+    env_logger::Builder::new().parse_filters(spec).build()
+}
+
+fn build_filter(spec: &str) -> Result<env_logger::Logger> {
+    validate_spec(spec)?;
+    Ok(parse_filter(spec))
+}
+
+/// Install `builder` as the global logger.
+///
+/// The filter is `flag` if given, else `RUST_LOG`, else `default`. Only the
+/// flag is validated; `RUST_LOG` keeps `env_logger`'s lenient parsing.
+///
+/// # Errors
+///
+/// Returns an error if `flag` is invalid or a logger is already installed.
+pub fn init(builder: env_logger::Builder, flag: Option<&str>, default: &str) -> Result<()> {
+    let logger = if let Some(spec) = flag {
+        ReloadableLogger::new(builder, spec)?
+    } else {
+        let spec = std::env::var("RUST_LOG").unwrap_or_else(|_| default.to_string());
+        ReloadableLogger::with_filter(builder, &spec, parse_filter(&spec))
+    };
+
+    let logger: &'static ReloadableLogger = Box::leak(Box::new(logger));
+    log::set_logger(logger).map_err(|e| anyhow::anyhow!("logger already installed: {e}"))?;
+    log::set_max_level(logger.max_level());
+    let _ = LOGGER.set(logger);
+    Ok(())
+}
+
+/// Change the global log filter at runtime.
+///
+/// # Errors
+///
+/// Returns an error if `spec` is invalid or [`init`] has not run.
+pub fn set_level(spec: &str) -> Result<()> {
+    let Some(logger) = LOGGER.get() else {
+        bail!("runtime log level changes need the botster logger");
+    };
+    let max = logger.set_spec(spec)?;
+    log::set_max_level(max);
+    log::info!("Log level set to {spec}");
+    Ok(())
+}
+
+/// The current global log filter spec, if [`init`] has run.
+pub fn current_level() -> Option<String> {
+    LOGGER.get().map(|logger| logger.spec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::Level;
+
+    fn passes(logger: &ReloadableLogger, level: Level, target: &str) -> bool {
+        logger.enabled(&Metadata::builder().level(level).target(target).build())
+    }
+
+    #[test]
+    fn set_spec_changes_which_records_pass() {
+        let logger = ReloadableLogger::new(env_logger::Builder::new(), "info").unwrap();
+        assert!(passes(&logger, Level::Info, "botster::hub"));
+        assert!(!passes(&logger, Level::Debug, "botster::hub"));
+
+        assert_eq!(logger.set_spec("debug").unwrap(), LevelFilter::Debug);
+        assert!(passes(&logger, Level::Debug, "botster::hub"));
+        assert!(!passes(&logger, Level::Trace, "botster::hub"));
+
+        logger.set_spec("warn").unwrap();
+        assert!(!passes(&logger, Level::Info, "botster::hub"));
+        assert!(passes(&logger, Level::Warn, "botster::hub"));
+        assert_eq!(logger.spec(), "warn");
+    }
+
+    #[test]
+    fn module_directives_only_raise_that_module() {
+        let logger = ReloadableLogger::new(env_logger::Builder::new(), "info").unwrap();
+        logger.set_spec("info,botster::hub=trace").unwrap();
+
+        assert!(passes(&logger, Level::Trace, "botster::hub::run"));
+        assert!(!passes(&logger, Level::Debug, "botster::tui"));
+        assert_eq!(logger.max_level(), LevelFilter::Trace);
+    }
+
+    #[test]
+    fn invalid_spec_is_rejected_and_keeps_old_filter() {
+        let logger = ReloadableLogger::new(env_logger::Builder::new(), "info").unwrap();
+
+        assert!(logger.set_spec("loud").is_err());
+        assert!(logger.set_spec("botster=verbose").is_err());
+        assert!(logger.set_spec("  ").is_err());
+        assert_eq!(logger.spec(), "info");
+        assert!(!passes(&logger, Level::Debug, "botster"));
+    }
+}
@@ -38,6 +38,7 @@
 //! - Success: `value, nil`
 //! - Failure: `nil, error_message`
 
+use std::collections::HashMap;
 use std::net::TcpListener;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
@@ -52,6 +53,37 @@ use super::json::json_to_lua;
 /// Invalidated (set to None) on `config.set()`, lazily repopulated on next read.
 type ConfigCache = Arc<Mutex<Option<serde_json::Value>>>;
 
+/// Listeners holding ports handed out by `config.reserve_port()`.
+///
+/// Each port stays bound here until `config.release_port()` is called just
+/// before the session that asked for it spawns, so nothing else on the
+/// machine (including another session's probe) can take it in between.
+type HeldPorts = Arc<Mutex<HashMap<u16, TcpListener>>>;
+
+/// Error returned when no port can be reserved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PortReserveError {
+    /// Every port in the inclusive range is excluded, held, or bound elsewhere.
+    RangeExhausted {
+        /// First port in the range.
+        start: u16,
+        /// Last port in the range.
+        finish: u16,
+    },
+}
+
+impl std::fmt::Display for PortReserveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RangeExhausted { start, finish } => {
+                write!(f, "No available localhost port in range {start}-{finish}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PortReserveError {}
+
 /// Config directory under the user's config path (scoped by build profile).
 fn config_dir_name() -> &'static str {
     crate::env::APP_NAME
@@ -127,6 +159,41 @@ fn read_config_cached(cache: &ConfigCache) -> std::io::Result<serde_json::Value>
     Ok(config)
 }
 
+/// Check `repo` against the `allowed_repos` array in a config file value.
+///
+/// A missing or malformed list allows every repo.
+fn config_allows_repo(config: &serde_json::Value, repo: &str) -> bool {
+    let allowed: Vec<String> = config
+        .get("allowed_repos")
+        .and_then(|v| v.as_array())
+        .map(|list| {
+            list.iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    crate::config::repo_in_allowlist(&allowed, repo)
+}
+
+/// Check per-profile `max_concurrent` and global `max_sessions` limits in a
+/// config file value.
+///
+/// Limits resolve through [`crate::config::Config`], so an unset
+/// `max_sessions` gets the same default and `BOTSTER_MAX_SESSIONS` override
+/// as the rest of the hub.
+fn config_spawn_limit_error(
+    config: &serde_json::Value,
+    profile: &str,
+    profile_active: usize,
+    total_active: usize,
+) -> Option<String> {
+    crate::config::Config::from_file_value(config).spawn_limit_error(
+        profile,
+        profile_active,
+        total_active,
+    )
+}
+
 /// Returns true when `127.0.0.1:port` is currently bindable.
 fn port_is_available(port: u16) -> bool {
     TcpListener::bind(("127.0.0.1", port)).is_ok()
@@ -142,6 +209,39 @@ fn find_available_port_in_range(start: u16, finish: u16, excluded: &[u16]) -> Op
     (start..=finish).find(|port| !excluded.contains(port) && port_is_available(*port))
 }
 
+/// Bind the first free port in an inclusive range and hold its listener.
+///
+/// Ports already held or listed in `excluded` are skipped, so back-to-back
+/// calls never return the same port until it is released.
+fn reserve_port_in_range(
+    held: &HeldPorts,
+    start: u16,
+    finish: u16,
+    excluded: &[u16],
+) -> Result<u16, PortReserveError> {
+    let mut held = held.lock().expect("held ports lock poisoned");
+    if start <= finish {
+        for port in start..=finish {
+            if excluded.contains(&port) || held.contains_key(&port) {
+                continue;
+            }
+            if let Ok(listener) = TcpListener::bind(("127.0.0.1", port)) {
+                held.insert(port, listener);
+                return Ok(port);
+            }
+        }
+    }
+    Err(PortReserveError::RangeExhausted { start, finish })
+}
+
+/// Drop the listener holding `port`, if any. Returns whether one was held.
+fn release_held_port(held: &HeldPorts, port: u16) -> bool {
+    held.lock()
+        .expect("held ports lock poisoned")
+        .remove(&port)
+        .is_some()
+}
+
 /// Register the `config` table with configuration functions.
 ///
 /// Creates a global `config` table with methods:
@@ -152,6 +252,10 @@ fn find_available_port_in_range(start: u16, finish: u16, excluded: &[u16]) -> Op
 /// - `config.data_dir()` - Get the `~/.botster` path
 /// - `config.env(key)` - Read an environment variable
 /// - `config.find_available_port(start, finish, excluded?)` - Probe localhost ports
+/// - `config.reserve_port(start, finish, excluded?)` - Bind and hold a localhost port
+/// - `config.release_port(port)` - Free a port held by `reserve_port`
+/// - `config.repo_allowed(repo)` - Check `repo` against `allowed_repos`
+/// - `config.spawn_limit_error(profile, profile_active, total_active)` - Check agent limits
 ///
 /// # Errors
 ///
@@ -366,6 +470,84 @@ pub fn register(lua: &Lua) -> Result<()> {
         .set("find_available_port", find_available_port_fn)
         .map_err(|e| anyhow!("Failed to set config.find_available_port: {e}"))?;
 
+    // config.reserve_port(start, finish, excluded?) -> (port, nil) or (nil, error_string)
+    //
+    // Like find_available_port, but keeps the port bound until
+    // config.release_port(port) so it can't be taken before the session
+    // that asked for it spawns.
+    let held_ports: HeldPorts = Arc::new(Mutex::new(HashMap::new()));
+    let held_reserve = Arc::clone(&held_ports);
+    let reserve_port_fn = lua
+        .create_function(
+            move |_, (start, finish, excluded): (u16, u16, Option<Vec<u16>>)| {
+                let excluded = excluded.unwrap_or_default();
+                match reserve_port_in_range(&held_reserve, start, finish, &excluded) {
+                    Ok(port) => Ok((Some(port), None::<String>)),
+                    Err(e) => Ok((None::<u16>, Some(e.to_string()))),
+                }
+            },
+        )
+        .map_err(|e| anyhow!("Failed to create config.reserve_port function: {e}"))?;
+
+    config_table
+        .set("reserve_port", reserve_port_fn)
+        .map_err(|e| anyhow!("Failed to set config.reserve_port: {e}"))?;
+
+    // config.release_port(port) -> boolean
+    //
+    // Drops the listener holding a reserved port. Returns false if the port
+    // wasn't held (already released, or never reserved here).
+    let held_release = Arc::clone(&held_ports);
+    let release_port_fn = lua
+        .create_function(move |_, port: u16| Ok(release_held_port(&held_release, port)))
+        .map_err(|e| anyhow!("Failed to create config.release_port function: {e}"))?;
+
+    config_table
+        .set("release_port", release_port_fn)
+        .map_err(|e| anyhow!("Failed to set config.release_port: {e}"))?;
+
+    // config.repo_allowed(repo) -> boolean
+    //
+    // Returns false when `allowed_repos` is non-empty and does not list
+    // `repo`. Unreadable config allows everything (today's behavior).
+    let cache_allowed = Arc::clone(&cache);
+    let repo_allowed_fn = lua
+        .create_function(move |_, repo: Option<String>| {
+            let repo = repo.unwrap_or_default();
+            Ok(read_config_cached(&cache_allowed)
+                .map_or(true, |config| config_allows_repo(&config, &repo)))
+        })
+        .map_err(|e| anyhow!("Failed to create config.repo_allowed function: {e}"))?;
+
+    config_table
+        .set("repo_allowed", repo_allowed_fn)
+        .map_err(|e| anyhow!("Failed to set config.repo_allowed: {e}"))?;
+
+    // config.spawn_limit_error(profile, profile_active, total_active) -> string|nil
+    //
+    // Returns an error message when spawning another `profile` agent would
+    // exceed `profiles.<profile>.max_concurrent` or `max_sessions`.
+    // Unreadable config falls back to the default limits.
+    let cache_limits = Arc::clone(&cache);
+    let spawn_limit_fn = lua
+        .create_function(
+            move |_, (profile, profile_active, total_active): (String, usize, usize)| {
+                let config = read_config_cached(&cache_limits)
+                    .unwrap_or_else(|_| serde_json::Value::Object(serde_json::Map::new()));
+                Ok(config_spawn_limit_error(
+                    &config,
+                    &profile,
+                    profile_active,
+                    total_active,
+                ))
+            },
+        )
+        .map_err(|e| anyhow!("Failed to create config.spawn_limit_error function: {e}"))?;
+
+    config_table
+        .set("spawn_limit_error", spawn_limit_fn)
+        .map_err(|e| anyhow!("Failed to set config.spawn_limit_error: {e}"))?;
+
     lua.globals()
         .set("config", config_table)
         .map_err(|e| anyhow!("Failed to register config table globally: {e}"))?;
@@ -399,6 +581,45 @@ mod tests {
         let _: Function = config_table
             .get("find_available_port")
             .expect("config.find_available_port should exist");
+        let _: Function = config_table
+            .get("repo_allowed")
+            .expect("config.repo_allowed should exist");
+        let _: Function = config_table
+            .get("spawn_limit_error")
+            .expect("config.spawn_limit_error should exist");
+    }
+
+    #[test]
+    fn test_config_allows_repo_respects_allowlist() {
+        let open = serde_json::json!({});
+        assert!(config_allows_repo(&open, "owner/repo"));
+
+        let restricted = serde_json::json!({ "allowed_repos": ["acme/widgets"] });
+        assert!(config_allows_repo(&restricted, "acme/widgets"));
+        assert!(!config_allows_repo(&restricted, "owner/repo"));
+    }
+
+    #[test]
+    fn test_config_spawn_limit_error_reads_profiles() {
+        let config = serde_json::json!({
+            "max_sessions": 5,
+            "profiles": { "codex": { "max_concurrent": 2 } }
+        });
+        assert!(config_spawn_limit_error(&config, "codex", 1, 1).is_none());
+        assert_eq!(
+            config_spawn_limit_error(&config, "codex", 2, 2).as_deref(),
+            Some("max 2 codex agents reached")
+        );
+        assert!(config_spawn_limit_error(&config, "claude", 0, 2).is_none());
+        assert!(config_spawn_limit_error(&config, "claude", 0, 5).is_some());
+    }
+
+    #[test]
+    fn test_config_spawn_limit_error_defaults_max_sessions() {
+        let config = serde_json::json!({});
+        let max = crate::config::Config::default().max_sessions;
+        assert!(config_spawn_limit_error(&config, "codex", 9, max - 1).is_none());
+        assert!(config_spawn_limit_error(&config, "codex", 9, max).is_some());
     }
 
     #[test]
@@ -558,6 +779,50 @@ mod tests {
         assert_ne!(found, occupied);
     }
 
+    #[test]
+    fn test_reserve_port_exhausts_small_range_without_duplicates() {
+        let probe = TcpListener::bind(("127.0.0.1", 0)).expect("bind probe listener");
+        let start = probe.local_addr().expect("probe addr").port();
+        drop(probe);
+        let finish = start.saturating_add(3);
+
+        let held: HeldPorts = Arc::new(Mutex::new(HashMap::new()));
+        let mut reserved = Vec::new();
+        let err = loop {
+            match reserve_port_in_range(&held, start, finish, &[]) {
+                Ok(port) => reserved.push(port),
+                Err(e) => break e,
+            }
+            assert!(
+                reserved.len() <= 4,
+                "reserved more ports than the range holds"
+            );
+        };
+
+        assert_eq!(err, PortReserveError::RangeExhausted { start, finish });
+        assert!(!reserved.is_empty(), "expected at least one free port");
+        let unique: std::collections::HashSet<u16> = reserved.iter().copied().collect();
+        assert_eq!(
+            unique.len(),
+            reserved.len(),
+            "duplicate ports: {reserved:?}"
+        );
+        for port in &reserved {
+            assert!(
+                TcpListener::bind(("127.0.0.1", *port)).is_err(),
+                "reserved port {port} should still be held"
+            );
+        }
+
+        // Releasing one makes exactly that port available again
+        assert!(release_held_port(&held, reserved[0]));
+        assert!(!release_held_port(&held, reserved[0]));
+        assert_eq!(
+            reserve_port_in_range(&held, start, finish, &[]),
+            Ok(reserved[0])
+        );
+    }
+
     #[test]
     fn test_find_available_port_lua_returns_value() {
         let lua = Lua::new();
@@ -296,7 +296,8 @@ pub fn register(lua: &Lua) -> Result<()> {
 
     // fs.stat(path) -> (table, nil) or (nil, error_string)
     //
-    // Returns { type = "file"|"dir", size = N, exists = true } or { exists = false }.
+    // Returns { type = "file"|"dir", size = N, modified = unix_secs, exists = true }
+    // or { exists = false }. `modified` is nil where the platform has no mtime.
     let stat_fn = lua
         .create_function(|lua, path: String| {
             let p = Path::new(&path);
@@ -311,6 +312,12 @@ pub fn register(lua: &Lua) -> Result<()> {
                     table.set("exists", true)?;
                     table.set("type", if meta.is_dir() { "dir" } else { "file" })?;
                     table.set("size", meta.len())?;
+                    let modified = meta
+                        .modified()
+                        .ok()
+                        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                        .map(|d| d.as_secs());
+                    table.set("modified", modified)?;
                     Ok((Some(table), None::<String>))
                 }
                 Err(e) => Ok((None::<mlua::Table>, Some(format!("Failed to stat: {e}")))),
@@ -854,6 +861,15 @@ mod tests {
         assert_eq!(result.get::<bool>("exists").unwrap(), true);
         assert_eq!(result.get::<String>("type").unwrap(), "file");
         assert_eq!(result.get::<u64>("size").unwrap(), 5);
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let modified = result.get::<u64>("modified").unwrap();
+        assert!(
+            modified <= now && now - modified < 60,
+            "{modified} vs {now}"
+        );
     }
 
     #[test]
@@ -340,6 +340,9 @@ pub(crate) fn register(
     let tx_unreg = hub_event_tx.clone();
     let unregister_session_fn = lua
         .create_function(move |_, session_uuid: String| {
+            let port = cache3
+                .get_session(&session_uuid)
+                .and_then(|handle| handle.pty().port());
             let removed = cache3.remove_session(&session_uuid);
             if removed {
                 log::info!("[Lua] Unregistered session '{}'", session_uuid);
@@ -347,6 +350,7 @@ pub(crate) fn register(
                 if let Some(ref sender) = *guard {
                     let _ = sender.send(HubEvent::SessionUnregistered {
                         session_uuid: session_uuid.clone(),
+                        port,
                     });
                 }
             }
@@ -637,12 +641,35 @@ pub(crate) fn register(
                 // Read optional label for process title identification
                 let label: Option<String> = opts.get("label").ok();
 
+                // The session process spawns the command after it detaches,
+                // where a failure never reaches the caller. Catch the common
+                // ones here so create_agent can report why.
+                let env_map: std::collections::HashMap<String, String> =
+                    env_pairs.iter().cloned().collect();
+                crate::agent::spawn::check_spawn(
+                    crate::agent::spawn::program_name(&command, &command_args),
+                    std::path::Path::new(&worktree_path),
+                    &env_map,
+                    &init_commands,
+                )
+                .map_err(|e| LuaError::runtime(format!("spawn_session: {e}")))?;
+
                 // Determine socket path
                 let socket_path =
                     crate::session::session_socket_path(&session_uuid).map_err(|e| {
                         LuaError::runtime(format!("spawn_session: socket path error: {e}"))
                     })?;
 
+                // A restarted agent keeps its session UUID. Its previous
+                // process must have finished its socket/pid cleanup before
+                // we fork, or that cleanup would delete the new files.
+                // `Session:restart` waits for this on a timer.
+                if crate::session::session_process_is_live(&session_uuid) {
+                    return Err(LuaError::runtime(
+                        "spawn_session: previous session process is still running",
+                    ));
+                }
+
                 // Fork session process
                 let exe = std::env::current_exe()
                     .map_err(|e| LuaError::runtime(format!("spawn_session: current_exe: {e}")))?;
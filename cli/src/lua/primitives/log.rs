@@ -10,6 +10,10 @@
 //! log.warn("Configuration not found, using defaults")
 //! log.error("Failed to connect to server")
 //! log.debug("Processing item: " .. item_id)
+//!
+//! -- Change the process-wide filter at runtime (RUST_LOG syntax)
+//! local ok, err = log.set_level("debug")
+//! log.level() -- "debug"
 //! ```
 //!
 //! Messages are routed through Rust's `log` crate, so they appear in
@@ -26,6 +30,8 @@ use mlua::Lua;
 /// - `log.warn(msg)` - Warning level message
 /// - `log.error(msg)` - Error level message
 /// - `log.debug(msg)` - Debug level message
+/// - `log.set_level(spec)` - Change the filter, returns `true` or `false, err`
+/// - `log.level()` - Current filter spec, or nil if not adjustable
 ///
 /// # Errors
 ///
@@ -79,6 +85,27 @@ pub fn register(lua: &Lua) -> Result<()> {
         .set("debug", debug_fn)
         .map_err(|e| anyhow!("Failed to set log.debug: {e}"))?;
 
+    // log.set_level(spec) -> true | false, err
+    let set_level_fn = lua
+        .create_function(|_, spec: String| {
+            Ok(match crate::logging::set_level(&spec) {
+                Ok(()) => (true, None),
+                Err(e) => (false, Some(e.to_string())),
+            })
+        })
+        .map_err(|e| anyhow!("Failed to create log.set_level function: {e}"))?;
+    log_table
+        .set("set_level", set_level_fn)
+        .map_err(|e| anyhow!("Failed to set log.set_level: {e}"))?;
+
+    // log.level() -> spec | nil
+    let level_fn = lua
+        .create_function(|_, ()| Ok(crate::logging::current_level()))
+        .map_err(|e| anyhow!("Failed to create log.level function: {e}"))?;
+    log_table
+        .set("level", level_fn)
+        .map_err(|e| anyhow!("Failed to set log.level: {e}"))?;
+
     // Register the table globally
     lua.globals()
         .set("log", log_table)
@@ -105,6 +132,10 @@ mod tests {
         let _: Function = log_table.get("warn").expect("log.warn should exist");
         let _: Function = log_table.get("error").expect("log.error should exist");
         let _: Function = log_table.get("debug").expect("log.debug should exist");
+        let _: Function = log_table
+            .get("set_level")
+            .expect("log.set_level should exist");
+        let _: Function = log_table.get("level").expect("log.level should exist");
     }
 
     #[test]
@@ -126,4 +157,18 @@ mod tests {
             .exec()
             .expect("log.debug should be callable");
     }
+
+    #[test]
+    fn test_set_level_reports_errors_without_raising() {
+        let lua = Lua::new();
+        register(&lua).expect("Should register log primitives");
+
+        // Tests never install the botster logger, so this always fails.
+        let (ok, err): (bool, Option<String>) = lua
+            .load(r#"return log.set_level("debug")"#)
+            .eval()
+            .expect("log.set_level should not raise");
+        assert!(!ok);
+        assert!(err.is_some());
+    }
 }
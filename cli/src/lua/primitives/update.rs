@@ -84,7 +84,7 @@ pub(crate) fn register(lua: &Lua, hub_event_tx: HubEventSender) -> Result<()> {
             }
 
             // install() downloads and replaces the binary synchronously
-            let install_result = update::install();
+            let install_result = update::install(false, update::UpdateChannel::Stable);
 
             if let Err(e) = install_result {
                 INSTALL_IN_PROGRESS.store(false, Ordering::SeqCst);
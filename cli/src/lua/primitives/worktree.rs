@@ -13,6 +13,8 @@
 //!   `worktree_create_failed` Lua events when done.
 //! - **Delete** (`delete`) sends `HubEvent::LuaWorktreeRequest` for Hub to
 //!   process asynchronously
+//! - **Cleanup** (`cleanup`) is `delete` with a `CleanupPolicy` applied,
+//!   refusing to discard unpushed work
 //!
 //! # Usage in Lua
 //!
@@ -40,11 +42,16 @@
 //! -- Create worktree asynchronously (returns immediately, fires event on completion)
 //! worktree.create_async({ label = "key", branch = "feature-branch", prompt = "..." })
 //!
-//! -- Delete worktree (sends event for async processing)
-//! worktree.delete("/path/to/worktree", "feature-branch")
+//! -- Delete worktree (sends event for async processing). The repo's
+//! -- `.botster_teardown` script is sourced in it first, with `env` if given.
+//! worktree.delete("/path/to/worktree", "feature-branch", env)
+//!
+//! -- Apply a cleanup policy ("delete", "keep" or "archive")
+//! worktree.cleanup("/path/to/worktree", "botster-issue-42", "archive")
 //! ```
 
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::Arc;
 
@@ -52,7 +59,8 @@ use anyhow::{anyhow, Result};
 use mlua::prelude::*;
 
 use super::HubEventSender;
-use crate::git::WorktreeManager;
+use crate::config::Config;
+use crate::git::{CleanupPolicy, WorktreeManager};
 use crate::hub::events::HubEvent;
 use crate::hub::handle_cache::HandleCache;
 
@@ -90,6 +98,21 @@ pub enum WorktreeRequest {
         path: String,
         /// Branch name associated with the worktree.
         branch: String,
+        /// Environment for the worktree's teardown script.
+        env: HashMap<String, String>,
+    },
+    /// Remove a worktree according to a cleanup policy.
+    ///
+    /// Unlike `Delete`, this refuses to discard uncommitted or unpushed work.
+    Cleanup {
+        /// Filesystem path of the worktree.
+        path: String,
+        /// Branch name associated with the worktree.
+        branch: String,
+        /// What to do with the worktree and branch.
+        policy: CleanupPolicy,
+        /// Environment for the worktree's teardown script.
+        env: HashMap<String, String>,
     },
 }
 
@@ -170,18 +193,46 @@ fn filter_detachable_worktrees(worktrees: Vec<serde_json::Value>) -> Vec<serde_j
         .collect()
 }
 
+/// Worktree manager for the repo at `repo_root`, placing worktrees under
+/// that repo's `repos.<name>.worktree_base` from `config.json` when set.
+fn manager_for_root(default_base: &Path, repo_root: &Path) -> WorktreeManager {
+    let config = Config::load_from_file().unwrap_or_default();
+    let base = if config.repos.is_empty() {
+        default_base.to_path_buf()
+    } else {
+        match crate::git::repo_name_for_path(repo_root) {
+            Ok(repo) => repo_worktree_base(config, default_base, &repo),
+            Err(_) => default_base.to_path_buf(),
+        }
+    };
+    WorktreeManager::new(base)
+}
+
+/// `default_base`, unless `config` overrides the worktree base for `repo`.
+fn repo_worktree_base(mut config: Config, default_base: &Path, repo: &str) -> PathBuf {
+    // The hub's base may come from BOTSTER_WORKTREE_BASE rather than the
+    // file, so only an explicit per-repo override should replace it.
+    config.worktree_base = default_base.to_path_buf();
+    config.for_repo(repo).worktree_base
+}
+
 /// Register worktree primitives with the Lua state.
 ///
 /// Adds the following functions to the `worktree` table:
 /// - `worktree.list()` - Get all worktrees as a table of {branch, path}
 /// - `worktree.exists(branch)` - Check if worktree exists for branch
-/// - `worktree.find(branch)` - Find worktree path for branch (nil if not found)
+/// - `worktree.find(branch, issue_number?)` - Find worktree path for branch (nil if not found)
 /// - `worktree.create(branch)` - Synchronously create worktree, returns path
 /// - `worktree.delete(path, branch)` - Request worktree deletion (async)
 /// - `worktree.repo_root()` - Get the main repository root path (nil if not in repo)
 /// - `worktree.list_for_root(path)` - List git worktrees for an explicit repo root
-/// - `worktree.find_for_root(path, branch)` - Find a worktree path for an explicit repo root
-/// - `worktree.create_for_root(path, branch)` - Create a worktree for an explicit repo root
+/// - `worktree.find_for_root(path, branch, issue_number?)` - Find a worktree path for an explicit repo root
+/// - `worktree.create_for_root(path, branch, issue_number?)` - Create a worktree for an explicit repo root
+///
+/// With an `issue_number`, `find`/`find_for_root` first look for a worktree
+/// tagged with that issue, and `create_for_root` tags the new worktree.
+/// The `*_for_root` functions honor a per-repo `worktree_base` override
+/// (see [`Config::for_repo`]).
 ///
 /// # Arguments
 ///
@@ -266,18 +317,28 @@ pub(crate) fn register(
 
     let find_for_root_base = worktree_base.clone();
     let find_for_root_fn = lua
-        .create_function(move |_, (repo_root, branch): (String, String)| {
-            let manager = WorktreeManager::new(find_for_root_base.clone());
-            let found = manager
-                .find_worktree_for_branch(std::path::Path::new(&repo_root), &branch)
-                .map_err(|e| {
-                    mlua::Error::runtime(format!(
-                        "Failed to find worktree for {} in {}: {}",
-                        branch, repo_root, e
-                    ))
-                })?;
-            Ok(found.map(|path| path.to_string_lossy().to_string()))
-        })
+        .create_function(
+            move |_, (repo_root, branch, issue_number): (String, String, Option<u32>)| {
+                let root = Path::new(&repo_root);
+                let manager = manager_for_root(&find_for_root_base, root);
+                let tagged = match issue_number {
+                    Some(issue_number) => manager.find_worktree_for_issue(root, issue_number),
+                    None => Ok(None),
+                };
+                let found = tagged
+                    .and_then(|tagged| match tagged {
+                        Some(path) => Ok(Some(path)),
+                        None => manager.find_worktree_for_branch(root, &branch),
+                    })
+                    .map_err(|e| {
+                        mlua::Error::runtime(format!(
+                            "Failed to find worktree for {} in {}: {}",
+                            branch, repo_root, e
+                        ))
+                    })?;
+                Ok(found.map(|path| path.to_string_lossy().to_string()))
+            },
+        )
         .map_err(|e| anyhow!("Failed to create worktree.find_for_root function: {e}"))?;
 
     worktree
@@ -300,17 +361,22 @@ pub(crate) fn register(
         .set("exists", exists_fn)
         .map_err(|e| anyhow!("Failed to set worktree.exists: {e}"))?;
 
-    // worktree.find(branch) -> path string or nil
+    // worktree.find(branch, issue_number?) -> path string or nil
     //
-    // Finds the filesystem path for a worktree by branch name.
+    // Finds the filesystem path for a worktree by branch name, preferring
+    // one tagged with `issue_number` when given.
     // Returns nil if no worktree exists for the branch.
     let cache3 = Arc::clone(&handle_cache);
     let find_fn = lua
-        .create_function(move |_, branch: String| {
+        .create_function(move |_, (branch, issue_number): (String, Option<u32>)| {
             let worktrees = cache3.get_worktrees();
-            let path = worktrees
-                .iter()
-                .find(|(_, b)| b == &branch)
+            let tagged = issue_number.and_then(|issue_number| {
+                worktrees.iter().find(|(p, _)| {
+                    WorktreeManager::worktree_issue(std::path::Path::new(p)) == Some(issue_number)
+                })
+            });
+            let path = tagged
+                .or_else(|| worktrees.iter().find(|(_, b)| b == &branch))
                 .map(|(p, _)| p.clone());
             Ok(path)
         })
@@ -356,18 +422,23 @@ pub(crate) fn register(
 
     let create_for_root_base = worktree_base.clone();
     let create_for_root_fn = lua
-        .create_function(move |_, (repo_root, branch): (String, String)| {
-            let manager = WorktreeManager::new(create_for_root_base.clone());
-            let path = manager
-                .create_worktree_for_repo_root(std::path::Path::new(&repo_root), &branch)
-                .map_err(|e| {
-                    mlua::Error::runtime(format!(
-                        "Failed to create worktree for {} in {}: {}",
-                        branch, repo_root, e
-                    ))
-                })?;
-            Ok(path.to_string_lossy().to_string())
-        })
+        .create_function(
+            move |_, (repo_root, branch, issue_number): (String, String, Option<u32>)| {
+                let root = Path::new(&repo_root);
+                let path = manager_for_root(&create_for_root_base, root)
+                    .create_worktree_for_repo_root(root, &branch)
+                    .map_err(|e| {
+                        mlua::Error::runtime(format!(
+                            "Failed to create worktree for {} in {}: {}",
+                            branch, repo_root, e
+                        ))
+                    })?;
+                if let Some(issue_number) = issue_number {
+                    WorktreeManager::tag_worktree_issue(&path, issue_number);
+                }
+                Ok(path.to_string_lossy().to_string())
+            },
+        )
         .map_err(|e| anyhow!("Failed to create worktree.create_for_root function: {e}"))?;
 
     worktree
@@ -487,28 +558,66 @@ pub(crate) fn register(
         .set("copy_from_patterns", copy_fn)
         .map_err(|e| anyhow!("Failed to set worktree.copy_from_patterns: {e}"))?;
 
-    // worktree.delete(path, branch) - Queue worktree deletion
+    // worktree.delete(path, branch, env?) - Queue worktree deletion
     //
-    // Queues a request to delete a worktree. Hub processes it asynchronously.
-    let tx = hub_event_tx;
+    // Queues a request to delete a worktree. Hub processes it asynchronously,
+    // sourcing the repo's teardown script with `env` first.
+    let tx = hub_event_tx.clone();
     let delete_fn = lua
-        .create_function(move |_, (path, branch): (String, String)| {
+        .create_function(
+            move |_, (path, branch, env): (String, String, Option<HashMap<String, String>>)| {
+                let guard = tx.lock().expect("HubEventSender mutex poisoned");
+                if let Some(ref sender) = *guard {
+                    let _ = sender.send(HubEvent::LuaWorktreeRequest(WorktreeRequest::Delete {
+                        path,
+                        branch,
+                        env: env.unwrap_or_default(),
+                    }));
+                } else {
+                    ::log::warn!(
+                        "[Worktree] delete() called before hub_event_tx set — event dropped"
+                    );
+                }
+                Ok(())
+            },
+        )
+        .map_err(|e| anyhow!("Failed to create worktree.delete function: {e}"))?;
+
+    worktree
+        .set("delete", delete_fn)
+        .map_err(|e| anyhow!("Failed to set worktree.delete: {e}"))?;
+
+    // worktree.cleanup(path, branch, policy, env?) - Queue policy-driven cleanup
+    //
+    // `policy` is "delete", "keep" or "archive"; "keep" queues nothing. Hub
+    // checks for unpushed work before removing anything.
+    let tx = hub_event_tx;
+    type CleanupArgs = (String, String, String, Option<HashMap<String, String>>);
+    let cleanup_fn = lua
+        .create_function(move |_, (path, branch, policy, env): CleanupArgs| {
+            let policy = CleanupPolicy::parse(&policy)
+                .ok_or_else(|| LuaError::runtime(format!("unknown cleanup policy: {policy}")))?;
+            if policy == CleanupPolicy::Keep {
+                return Ok(());
+            }
             let guard = tx.lock().expect("HubEventSender mutex poisoned");
             if let Some(ref sender) = *guard {
-                let _ = sender.send(HubEvent::LuaWorktreeRequest(WorktreeRequest::Delete {
+                let _ = sender.send(HubEvent::LuaWorktreeRequest(WorktreeRequest::Cleanup {
                     path,
                     branch,
+                    policy,
+                    env: env.unwrap_or_default(),
                 }));
             } else {
-                ::log::warn!("[Worktree] delete() called before hub_event_tx set — event dropped");
+                ::log::warn!("[Worktree] cleanup() called before hub_event_tx set — event dropped");
             }
             Ok(())
         })
-        .map_err(|e| anyhow!("Failed to create worktree.delete function: {e}"))?;
+        .map_err(|e| anyhow!("Failed to create worktree.cleanup function: {e}"))?;
 
     worktree
-        .set("delete", delete_fn)
-        .map_err(|e| anyhow!("Failed to set worktree.delete: {e}"))?;
+        .set("cleanup", cleanup_fn)
+        .map_err(|e| anyhow!("Failed to set worktree.cleanup: {e}"))?;
 
     // Register globally
     lua.globals()
@@ -716,9 +825,38 @@ mod tests {
 
         let event = rx.try_recv().expect("Should have received event");
         match event {
-            HubEvent::LuaWorktreeRequest(WorktreeRequest::Delete { path, branch }) => {
+            HubEvent::LuaWorktreeRequest(WorktreeRequest::Delete { path, branch, env }) => {
                 assert_eq!(path, "/path/to/wt");
                 assert_eq!(branch, "feature-branch");
+                assert!(env.is_empty());
+            }
+            _ => panic!("Expected LuaWorktreeRequest(Delete) event"),
+        }
+    }
+
+    #[test]
+    fn test_delete_carries_teardown_env() {
+        let lua = Lua::new();
+        let tx = new_hub_event_sender();
+        let (sender, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        *tx.lock().unwrap() = Some(sender.into());
+        let cache = Arc::new(HandleCache::new());
+        let base = PathBuf::from("/tmp/test-worktrees");
+
+        register(&lua, tx, cache, base).expect("Should register");
+
+        lua.load(
+            r#"worktree.delete("/path/to/wt", "feature-branch", { BOTSTER_SESSION_UUID = "sess-1" })"#,
+        )
+        .exec()
+        .expect("Should delete worktree");
+
+        match rx.try_recv().expect("Should have received event") {
+            HubEvent::LuaWorktreeRequest(WorktreeRequest::Delete { env, .. }) => {
+                assert_eq!(
+                    env.get("BOTSTER_SESSION_UUID").map(String::as_str),
+                    Some("sess-1")
+                );
             }
             _ => panic!("Expected LuaWorktreeRequest(Delete) event"),
         }
@@ -754,6 +892,65 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cleanup_sends_event_with_policy() {
+        let lua = Lua::new();
+        let tx = new_hub_event_sender();
+        let (sender, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        *tx.lock().unwrap() = Some(sender.into());
+        let cache = Arc::new(HandleCache::new());
+        let base = PathBuf::from("/tmp/test-worktrees");
+
+        register(&lua, tx, cache, base).expect("Should register");
+
+        lua.load(r#"worktree.cleanup("/path/to/wt", "botster-issue-7", "archive")"#)
+            .exec()
+            .expect("Should queue cleanup");
+
+        match rx.try_recv().expect("Should have received event") {
+            HubEvent::LuaWorktreeRequest(WorktreeRequest::Cleanup {
+                path,
+                branch,
+                policy,
+                ..
+            }) => {
+                assert_eq!(path, "/path/to/wt");
+                assert_eq!(branch, "botster-issue-7");
+                assert_eq!(policy, CleanupPolicy::Archive);
+            }
+            _ => panic!("Expected LuaWorktreeRequest(Cleanup) event"),
+        }
+    }
+
+    #[test]
+    fn test_cleanup_keep_sends_nothing() {
+        let lua = Lua::new();
+        let tx = new_hub_event_sender();
+        let (sender, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        *tx.lock().unwrap() = Some(sender.into());
+        let cache = Arc::new(HandleCache::new());
+        let base = PathBuf::from("/tmp/test-worktrees");
+
+        register(&lua, tx, cache, base).expect("Should register");
+
+        lua.load(r#"worktree.cleanup("/path/to/wt", "botster-issue-7", "keep")"#)
+            .exec()
+            .expect("Keep should be accepted");
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_cleanup_rejects_unknown_policy() {
+        let lua = Lua::new();
+        let (tx, cache, base) = create_test_queue_and_cache();
+        register(&lua, tx, cache, base).expect("Should register");
+
+        let result = lua
+            .load(r#"worktree.cleanup("/path/to/wt", "botster-issue-7", "purge")"#)
+            .exec();
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_repo_root_returns_value_or_nil() {
         let lua = Lua::new();
@@ -836,6 +1033,70 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_create_for_root_tags_issue_for_find() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let repo = root.join("repo");
+        std::fs::create_dir_all(&repo).unwrap();
+        for args in [
+            &["init", "-b", "main"][..],
+            &["commit", "--allow-empty", "-m", "init"][..],
+        ] {
+            let status = Command::new("git")
+                .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(&repo)
+                .status()
+                .unwrap();
+            assert!(status.success(), "git {args:?} failed");
+        }
+
+        let lua = Lua::new();
+        let (tx, cache, _) = create_test_queue_and_cache();
+        register(&lua, tx, cache, root.join("worktrees")).expect("Should register");
+        lua.globals().set("repo", repo.to_str().unwrap()).unwrap();
+
+        let (created, by_issue, by_other_issue): (String, Option<String>, Option<String>) = lua
+            .load(
+                r#"
+                local created = worktree.create_for_root(repo, "fix-login-crash", 9)
+                return created,
+                    worktree.find_for_root(repo, "botster-issue-9", 9),
+                    worktree.find_for_root(repo, "botster-issue-10", 10)
+            "#,
+            )
+            .eval()
+            .expect("Should create and find worktree");
+
+        assert_eq!(by_issue.as_deref(), Some(created.as_str()));
+        assert_eq!(by_other_issue, None);
+    }
+
+    #[test]
+    fn test_repo_worktree_base_uses_only_the_repo_override() {
+        let mut config = Config {
+            worktree_base: PathBuf::from("/from/config-file"),
+            ..Config::default()
+        };
+        config.repos.insert(
+            "acme/api".to_string(),
+            crate::config::RepoOverride {
+                worktree_base: Some(PathBuf::from("/srv/acme")),
+            },
+        );
+        let hub_base = Path::new("/from/env");
+
+        assert_eq!(
+            repo_worktree_base(config.clone(), hub_base, "Acme/API"),
+            PathBuf::from("/srv/acme")
+        );
+        assert_eq!(
+            repo_worktree_base(config, hub_base, "acme/web"),
+            PathBuf::from("/from/env")
+        );
+    }
+
     #[test]
     fn test_create_returns_error_for_invalid_branch() {
         let lua = Lua::new();
@@ -219,6 +219,10 @@ fn run_headless() -> Result<()> {
 
     let mut hub = Hub::new(config)?;
 
+    // Probes can reach /healthz during setup; /readyz stays 503 until the
+    // hub is registered and its event loop is running.
+    hub.start_health_listener()?;
+
     if botster::env::is_offline() {
         println!("Setting up in offline mode (no network)...");
     } else {
@@ -374,6 +378,10 @@ fn run_with_tui() -> Result<()> {
 #[command(version = VERSION)]
 #[command(about = "Interactive PTY-based daemon for GitHub automation")]
 struct Cli {
+    /// Initial log filter, e.g. `debug` or `info,botster::hub=trace`.
+    /// Overrides `RUST_LOG`; change it later with the `set_log_level` command.
+    #[arg(long, global = true)]
+    log_level: Option<String>,
     #[command(subcommand)]
     command: Commands,
 }
@@ -388,7 +396,10 @@ enum Commands {
         #[arg(long)]
         offline: bool,
     },
+    /// Show running hubs and their agent sessions
     Status,
+    /// Show the config, one value by dot-notation key (e.g. "poll_interval"),
+    /// or set a value when one is given
     Config {
         key: Option<String>,
         value: Option<String>,
@@ -408,6 +419,9 @@ enum Commands {
         key: String,
         /// Value to set (will be parsed as JSON)
         value: String,
+        /// Start from an empty object if the file does not exist
+        #[arg(long)]
+        create: bool,
     },
     /// Delete a key from a JSON file using dot notation
     JsonDelete {
@@ -420,14 +434,32 @@ enum Commands {
     DeleteWorktree {
         /// Issue number of the worktree to delete
         issue_number: u32,
+        /// Show what would be deleted without deleting anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Delete even if the worktree has uncommitted changes or unpushed commits
+        #[arg(long)]
+        force: bool,
     },
     /// List all git worktrees for the current repository
-    ListWorktrees,
+    ListWorktrees {
+        /// Print worktrees as a JSON array
+        #[arg(long)]
+        json: bool,
+    },
+    /// Forget worktrees whose directories were deleted
+    PruneWorktrees,
     /// Update botster to the latest version
     Update {
         /// Show version without updating
         #[arg(long)]
         check: bool,
+        /// Install even if the release signature is missing or invalid
+        #[arg(long)]
+        allow_unsigned: bool,
+        /// Release channel: "stable", or "beta" to include pre-releases
+        #[arg(long, value_enum, default_value_t = commands::update::UpdateChannel::Stable)]
+        channel: commands::update::UpdateChannel,
     },
     /// Get the connection URL for a running hub (for testing/automation)
     GetConnectionUrl {
@@ -934,10 +966,11 @@ fn main() -> Result<()> {
     let is_mcp_serve = matches!(cli.command, Commands::McpServe | Commands::Context { .. });
 
     if is_mcp_serve {
-        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn"))
+        let mut builder = env_logger::Builder::new();
+        builder
             .target(env_logger::Target::Stderr)
-            .format_timestamp_secs()
-            .init();
+            .format_timestamp_secs();
+        botster::logging::init(builder, cli.log_level.as_deref(), "warn")?;
     } else {
         // Each non-MCP process (hub, tui, attach) gets its own timestamped log
         // file so concurrent processes and sequential runs never overwrite each
@@ -977,10 +1010,11 @@ fn main() -> Result<()> {
         // 10 MB cap — large enough for a full session, small enough to avoid
         // runaway disk use on long-lived hub processes.
         let capped_writer = CappedFileWriter::new(log_file, 10 * 1024 * 1024);
-        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
+        let mut builder = env_logger::Builder::new();
+        builder
             .target(env_logger::Target::Pipe(Box::new(capped_writer)))
-            .format_timestamp_secs()
-            .init();
+            .format_timestamp_secs();
+        botster::logging::init(builder, cli.log_level.as_deref(), "info")?;
     }
 
     // Route ghostty's Zig logs through Rust's log crate (instead of stderr).
@@ -1077,37 +1111,49 @@ fn main() -> Result<()> {
             }
         }
         Commands::Status => {
-            println!("Status command not yet implemented");
-        }
-        Commands::Config { key, value } => {
-            let config = Config::load()?;
-            match (key, value) {
-                (None, None) => println!("{}", serde_json::to_string_pretty(&config)?),
-                (Some(k), None) => println!("Config key '{}' query not implemented", k),
-                (Some(k), Some(v)) => println!("Would set {} = {}", k, v),
-                _ => {}
-            }
+            commands::status::run()?;
         }
+        Commands::Config { key, value } => match (key, value) {
+            (None, _) => commands::config::show(&Config::load()?)?,
+            (Some(k), None) => commands::config::get(&Config::load()?, &k)?,
+            (Some(k), Some(v)) => commands::config::set(&k, &v)?,
+        },
         Commands::JsonGet { file, key } => {
             commands::json::get(&file, &key)?;
         }
-        Commands::JsonSet { file, key, value } => {
-            commands::json::set(&file, &key, &value)?;
+        Commands::JsonSet {
+            file,
+            key,
+            value,
+            create,
+        } => {
+            commands::json::set(&file, &key, &value, create)?;
         }
         Commands::JsonDelete { file, key } => {
             commands::json::delete(&file, &key)?;
         }
-        Commands::DeleteWorktree { issue_number } => {
-            commands::worktree::delete(issue_number)?;
+        Commands::DeleteWorktree {
+            issue_number,
+            dry_run,
+            force,
+        } => {
+            commands::worktree::delete(issue_number, dry_run, force)?;
+        }
+        Commands::ListWorktrees { json } => {
+            commands::worktree::list(json)?;
         }
-        Commands::ListWorktrees => {
-            commands::worktree::list()?;
+        Commands::PruneWorktrees => {
+            commands::worktree::prune()?;
         }
-        Commands::Update { check } => {
+        Commands::Update {
+            check,
+            allow_unsigned,
+            channel,
+        } => {
             if check {
-                commands::update::check()?;
+                commands::update::check(channel)?;
             } else {
-                commands::update::install()?;
+                commands::update::install(allow_unsigned, channel)?;
             }
         }
         Commands::GetConnectionUrl { hub } => {
@@ -0,0 +1,230 @@
+//! Minisign signature verification.
+//!
+//! Verifies detached `.minisig` signatures made by `minisign -S` (which signs
+//! the BLAKE2b-512 hash of the file) or `minisign -S -l` (which signs the raw
+//! file). `botster update` uses this to check downloaded binaries against the
+//! release public key compiled into the binary, since a checksum served from
+//! the same host as the binary only guards against corruption.
+//!
+//! # Format
+//!
+//! A public key is base64 of `"Ed" || key_id[8] || ed25519_key[32]`. A
+//! signature file is four lines:
+//!
+//! ```text
+//! untrusted comment: <text>
+//! base64("Ed" or "ED" || key_id[8] || signature[64])
+//! trusted comment: <text>
+//! base64(global_signature[64])
+//! ```
+//!
+//! The global signature covers the signature and the trusted comment, so the
+//! comment can't be swapped either.
+
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use ed25519_dalek::{Signature, VerifyingKey};
+
+/// Signature algorithm over the raw file.
+const ALG_LEGACY: &[u8; 2] = b"Ed";
+
+/// Signature algorithm over the BLAKE2b-512 hash of the file.
+const ALG_PREHASHED: &[u8; 2] = b"ED";
+
+/// A minisign public key.
+#[derive(Debug, Clone)]
+pub struct PublicKey {
+    key_id: [u8; 8],
+    key: VerifyingKey,
+}
+
+impl PublicKey {
+    /// Parses a public key as written by `minisign -G`.
+    ///
+    /// Accepts the base64 line alone or the whole `minisign.pub` file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the text is not a minisign Ed25519 public key.
+    pub fn from_base64(text: &str) -> Result<Self> {
+        let line = text
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with("untrusted comment:"))
+            .context("Empty minisign public key")?;
+        let bytes = BASE64
+            .decode(line)
+            .context("Minisign public key is not valid base64")?;
+        if bytes.len() != 42 || &bytes[..2] != ALG_LEGACY {
+            anyhow::bail!("Not a minisign Ed25519 public key");
+        }
+
+        let key_id = bytes[2..10].try_into().expect("slice is 8 bytes");
+        let key = VerifyingKey::from_bytes(bytes[10..42].try_into().expect("slice is 32 bytes"))
+            .context("Invalid Ed25519 public key")?;
+        Ok(Self { key_id, key })
+    }
+
+    /// Verifies `minisig`, the contents of a `.minisig` file, over `data`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the signature file is malformed, was made with a
+    /// different key, or doesn't match `data` or its trusted comment.
+    pub fn verify(&self, data: &[u8], minisig: &str) -> Result<()> {
+        let mut lines = minisig.lines();
+        lines
+            .next()
+            .filter(|line| line.starts_with("untrusted comment:"))
+            .context("Signature is missing its untrusted comment")?;
+        let signature_line = lines.next().context("Signature is missing")?;
+        let trusted_comment = lines
+            .next()
+            .and_then(|line| line.strip_prefix("trusted comment: "))
+            .context("Signature is missing its trusted comment")?;
+        let global_line = lines
+            .next()
+            .context("Signature is missing its global signature")?;
+
+        let signature_bytes = BASE64
+            .decode(signature_line.trim())
+            .context("Signature is not valid base64")?;
+        if signature_bytes.len() != 74 {
+            anyhow::bail!("Signature has the wrong length");
+        }
+        if signature_bytes[2..10] != self.key_id {
+            anyhow::bail!("Signature was made with a different key");
+        }
+        let raw_signature: [u8; 64] = signature_bytes[10..74]
+            .try_into()
+            .expect("slice is 64 bytes");
+        let signature = Signature::from_bytes(&raw_signature);
+
+        let verified = match &signature_bytes[..2] {
+            alg if alg == ALG_PREHASHED => {
+                let hash = blake2b_simd::blake2b(data);
+                self.key.verify_strict(hash.as_bytes(), &signature)
+            }
+            alg if alg == ALG_LEGACY => self.key.verify_strict(data, &signature),
+            _ => anyhow::bail!("Unsupported signature algorithm"),
+        };
+        verified.map_err(|_| anyhow::anyhow!("Signature does not match"))?;
+
+        let global_bytes: [u8; 64] = BASE64
+            .decode(global_line.trim())
+            .context("Global signature is not valid base64")?
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("Global signature has the wrong length"))?;
+        let mut signed = raw_signature.to_vec();
+        signed.extend_from_slice(trusted_comment.as_bytes());
+        self.key
+            .verify_strict(&signed, &Signature::from_bytes(&global_bytes))
+            .map_err(|_| anyhow::anyhow!("Trusted comment signature does not match"))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    const KEY_ID: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+    fn signing_key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32])
+    }
+
+    fn public_key_text(key: &SigningKey) -> String {
+        let mut bytes = ALG_LEGACY.to_vec();
+        bytes.extend_from_slice(&KEY_ID);
+        bytes.extend_from_slice(key.verifying_key().as_bytes());
+        format!(
+            "untrusted comment: minisign public key\n{}\n",
+            BASE64.encode(bytes)
+        )
+    }
+
+    /// Produces a `.minisig` the way `minisign -S` does.
+    fn sign(key: &SigningKey, data: &[u8], prehashed: bool) -> String {
+        let (alg, signature) = if prehashed {
+            (
+                ALG_PREHASHED,
+                key.sign(blake2b_simd::blake2b(data).as_bytes()),
+            )
+        } else {
+            (ALG_LEGACY, key.sign(data))
+        };
+        let mut signature_bytes = alg.to_vec();
+        signature_bytes.extend_from_slice(&KEY_ID);
+        signature_bytes.extend_from_slice(&signature.to_bytes());
+
+        let trusted_comment = "timestamp:1700000000\tfile:botster-linux-x86_64";
+        let mut global_signed = signature.to_bytes().to_vec();
+        global_signed.extend_from_slice(trusted_comment.as_bytes());
+        let global = key.sign(&global_signed);
+
+        format!(
+            "untrusted comment: signature from minisign secret key\n{}\ntrusted comment: {}\n{}\n",
+            BASE64.encode(signature_bytes),
+            trusted_comment,
+            BASE64.encode(global.to_bytes())
+        )
+    }
+
+    #[test]
+    fn test_valid_signatures_verify() {
+        let key = signing_key(7);
+        let public = PublicKey::from_base64(&public_key_text(&key)).unwrap();
+        let data = b"botster binary";
+
+        public.verify(data, &sign(&key, data, true)).unwrap();
+        public.verify(data, &sign(&key, data, false)).unwrap();
+    }
+
+    #[test]
+    fn test_tampered_binary_is_rejected() {
+        let key = signing_key(7);
+        let public = PublicKey::from_base64(&public_key_text(&key)).unwrap();
+        let minisig = sign(&key, b"botster binary", true);
+
+        let err = public.verify(b"malicious binary", &minisig).unwrap_err();
+        assert_eq!(err.to_string(), "Signature does not match");
+    }
+
+    #[test]
+    fn test_tampered_signature_is_rejected() {
+        let key = signing_key(7);
+        let public = PublicKey::from_base64(&public_key_text(&key)).unwrap();
+        let data = b"botster binary";
+
+        let mut lines: Vec<String> = sign(&key, data, true).lines().map(String::from).collect();
+        let mut signature_bytes = BASE64.decode(&lines[1]).unwrap();
+        signature_bytes[20] ^= 0xff;
+        lines[1] = BASE64.encode(signature_bytes);
+        assert!(public.verify(data, &lines.join("\n")).is_err());
+
+        let mut lines: Vec<String> = sign(&key, data, true).lines().map(String::from).collect();
+        lines[2] = "trusted comment: timestamp:0\tfile:other".to_string();
+        let err = public.verify(data, &lines.join("\n")).unwrap_err();
+        assert_eq!(err.to_string(), "Trusted comment signature does not match");
+    }
+
+    #[test]
+    fn test_signature_from_another_key_is_rejected() {
+        let public = PublicKey::from_base64(&public_key_text(&signing_key(7))).unwrap();
+        let data = b"botster binary";
+
+        assert!(public
+            .verify(data, &sign(&signing_key(9), data, true))
+            .is_err());
+    }
+
+    #[test]
+    fn test_malformed_public_key_is_rejected() {
+        assert!(PublicKey::from_base64("").is_err());
+        assert!(PublicKey::from_base64("not base64!").is_err());
+        assert!(PublicKey::from_base64(&BASE64.encode([0u8; 10])).is_err());
+    }
+}
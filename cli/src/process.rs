@@ -25,7 +25,7 @@
 
 use std::path::Path;
 use std::process::Command;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 /// Kills orphaned processes that have their working directory inside the given worktree.
 ///
@@ -283,6 +283,53 @@ pub fn get_parent_pid(pid: u32) -> Option<u32> {
     }
 }
 
+/// How a process group ended in [`wait_process_group`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupExit {
+    /// Every member exited within the grace period.
+    Graceful,
+    /// Members outlived the grace period and the group was sent SIGKILL.
+    Killed,
+}
+
+/// Whether any process is left in process group `pgid`.
+fn process_group_alive(pgid: libc::pid_t) -> bool {
+    // SAFETY: signal 0 only checks that the group exists.
+    if unsafe { libc::killpg(pgid, 0) } == 0 {
+        return true;
+    }
+    // EPERM means members exist but belong to someone else.
+    std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Waits for a process group that was asked to exit, killing it only if it doesn't.
+///
+/// The caller sends the polite signal (SIGTERM, a PTY hangup). This polls
+/// every 10ms for up to `grace` until the group is empty and sends SIGKILL
+/// only if members remain, so an agent that handles SIGTERM gets to flush
+/// its state. `reap` runs before each check so the caller can collect its
+/// own child: an unreaped zombie leader still counts as a group member.
+pub fn wait_process_group(pgid: libc::pid_t, grace: Duration, mut reap: impl FnMut()) -> GroupExit {
+    let deadline = Instant::now() + grace;
+    loop {
+        reap();
+        if !process_group_alive(pgid) {
+            return GroupExit::Graceful;
+        }
+        if Instant::now() >= deadline {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    log::info!("Process group {pgid} outlived {grace:?}, sending SIGKILL");
+    // SAFETY: killpg only sends a signal; ESRCH just means the group is gone.
+    unsafe {
+        libc::killpg(pgid, libc::SIGKILL);
+    }
+    GroupExit::Killed
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -333,4 +380,56 @@ mod tests {
             assert!(!pids.contains(&ppid));
         }
     }
+
+    /// Spawn `sh -c script` as the leader of its own process group.
+    fn spawn_group(script: &str) -> (std::process::Child, libc::pid_t) {
+        use std::os::unix::process::CommandExt;
+        let child = Command::new("sh")
+            .arg("-c")
+            .arg(script)
+            .process_group(0)
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .expect("spawn sh");
+        let pgid = libc::pid_t::try_from(child.id()).unwrap();
+        // Give the shell time to install its trap.
+        std::thread::sleep(Duration::from_millis(100));
+        (child, pgid)
+    }
+
+    #[test]
+    fn test_wait_process_group_lets_sigterm_handler_finish() {
+        use std::os::unix::process::ExitStatusExt;
+        let (mut child, pgid) = spawn_group("trap 'exit 3' TERM; while :; do sleep 0.05; done");
+        unsafe {
+            libc::killpg(pgid, libc::SIGTERM);
+        }
+
+        let mut status = None;
+        let exit = wait_process_group(pgid, Duration::from_secs(5), || {
+            if status.is_none() {
+                status = child.try_wait().ok().flatten();
+            }
+        });
+
+        assert_eq!(exit, GroupExit::Graceful);
+        let status = status.expect("child reaped during the wait");
+        assert_eq!(status.code(), Some(3), "the TERM trap ran");
+        assert_eq!(status.signal(), None, "no SIGKILL was sent");
+    }
+
+    #[test]
+    fn test_wait_process_group_kills_group_ignoring_sigterm() {
+        use std::os::unix::process::ExitStatusExt;
+        let (mut child, pgid) = spawn_group("trap '' TERM; while :; do sleep 0.05; done");
+        unsafe {
+            libc::killpg(pgid, libc::SIGTERM);
+        }
+
+        let exit = wait_process_group(pgid, Duration::from_millis(200), || {});
+
+        assert_eq!(exit, GroupExit::Killed);
+        let status = child.wait().unwrap();
+        assert_eq!(status.signal(), Some(libc::SIGKILL));
+    }
 }
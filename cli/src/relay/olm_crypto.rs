@@ -12,7 +12,7 @@
 //!
 //! | Path | Wire format | Used for |
 //! |------|-------------|----------|
-//! | `encrypt()` / `decrypt()` | `OlmEnvelope` JSON (`{t, b, k?}`) | ActionCable signaling (SDP, ICE) |
+//! | `encrypt()` / `decrypt()` | `OlmEnvelope` JSON (`{v, t, b, k?}`) | ActionCable signaling (SDP, ICE) |
 //! | `encrypt_binary()` / `decrypt_binary()` | `[type:1][key?:32][ciphertext]` | DataChannel messages |
 //!
 //! # Binary Inner Content Format (DataChannel)
@@ -74,10 +74,27 @@ fn decode_b64(input: &str) -> Result<Vec<u8>> {
         .context("Invalid base64")
 }
 
+/// Decode a base64 bundle field and check its length, naming the field on
+/// failure.
+fn decode_field(field: &str, input: &str, size: usize) -> Result<Vec<u8>> {
+    let bytes = decode_b64(input).with_context(|| format!("{field}: invalid base64"))?;
+    anyhow::ensure!(
+        bytes.len() == size,
+        "{field}: expected {size} bytes, got {}",
+        bytes.len()
+    );
+    Ok(bytes)
+}
+
 /// Protocol version for vodozemac crypto messages.
 /// Version 6 indicates direct vodozemac (no matrix-sdk-crypto wrapper).
 pub const PROTOCOL_VERSION: u8 = 6;
 
+/// `OlmEnvelope` version of envelopes written before the `v` field existed.
+///
+/// Still accepted on decrypt so browsers that omit `v` keep working.
+pub const LEGACY_ENVELOPE_VERSION: u8 = 0;
+
 /// Olm PreKey message type (session establishment).
 pub const MSG_TYPE_PREKEY: u8 = 0;
 
@@ -115,11 +132,17 @@ pub const CONTENT_FILE_CHUNK: u8 = 0x04;
 /// Encrypted message envelope (minimal wire format).
 ///
 /// Uses short keys to minimize wire size:
+/// - `v`: wire format version ([`PROTOCOL_VERSION`]; absent means legacy)
 /// - `t`: message type (0=PreKey, 1=Normal)
 /// - `b`: ciphertext (base64 unpadded)
 /// - `k`: sender's Curve25519 identity key (base64, only on PreKey)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OlmEnvelope {
+    /// Wire format version. Parses as [`LEGACY_ENVELOPE_VERSION`] when the
+    /// field is missing; any other version than that or
+    /// [`PROTOCOL_VERSION`] is rejected on decrypt.
+    #[serde(rename = "v", default)]
+    pub version: u8,
     /// Message type: 0=PreKey, 1=Normal.
     #[serde(rename = "t")]
     pub message_type: u8,
@@ -225,6 +248,43 @@ impl DeviceKeyBundle {
         Ok(buf)
     }
 
+    /// Check the bundle before trusting it.
+    ///
+    /// Verifies that `version` is [`PROTOCOL_VERSION`], that every key
+    /// decodes to its expected size, and that `signature` is `ed25519_key`'s
+    /// signature over `[version][identity][signing][otk]`. The error names
+    /// the field that failed.
+    pub fn validate(&self) -> Result<()> {
+        use binary_format::*;
+        use vodozemac::{Ed25519PublicKey, Ed25519Signature};
+
+        anyhow::ensure!(
+            self.version == PROTOCOL_VERSION,
+            "version: expected {PROTOCOL_VERSION}, got {}",
+            self.version
+        );
+
+        let curve25519 = decode_field("curve25519_key", &self.curve25519_key, CURVE25519_KEY_SIZE)?;
+        let ed25519 = decode_field("ed25519_key", &self.ed25519_key, ED25519_KEY_SIZE)?;
+        let one_time = decode_field("one_time_key", &self.one_time_key, ONE_TIME_KEY_SIZE)?;
+        let signature = decode_field("signature", &self.signature, SIGNATURE_SIZE)?;
+
+        let signing_key = Ed25519PublicKey::from_slice(ed25519.as_slice().try_into()?)
+            .map_err(|e| anyhow::anyhow!("ed25519_key: {e}"))?;
+        let signature = Ed25519Signature::from_slice(&signature)
+            .map_err(|e| anyhow::anyhow!("signature: {e}"))?;
+
+        let mut signed = Vec::with_capacity(SIGNATURE_OFFSET);
+        signed.push(self.version);
+        signed.extend_from_slice(&curve25519);
+        signed.extend_from_slice(&ed25519);
+        signed.extend_from_slice(&one_time);
+
+        signing_key
+            .verify(&signed, &signature)
+            .map_err(|_| anyhow::anyhow!("signature: does not verify against ed25519_key"))
+    }
+
     /// Deserialize from compact binary format.
     ///
     /// `hub_id` is set to empty string (comes from URL path).
@@ -401,6 +461,7 @@ impl VodozemacCrypto {
         };
 
         Ok(OlmEnvelope {
+            version: PROTOCOL_VERSION,
             message_type,
             ciphertext,
             sender_key: if message_type == MSG_TYPE_PREKEY {
@@ -413,11 +474,19 @@ impl VodozemacCrypto {
 
     /// Decrypt an `OlmEnvelope`, returning plaintext bytes.
     ///
+    /// Envelopes with an unknown `version` are rejected before decoding.
+    ///
     /// For PreKey messages: looks up existing session by sender_key, or creates
     /// a new inbound session. Supports multiple concurrent browser sessions.
     /// For Normal messages: uses `peer_key` for direct lookup when available,
     /// otherwise falls back to trying all sessions.
     pub fn decrypt(&mut self, envelope: &OlmEnvelope, peer_key: Option<&str>) -> Result<Vec<u8>> {
+        anyhow::ensure!(
+            matches!(envelope.version, LEGACY_ENVELOPE_VERSION | PROTOCOL_VERSION),
+            "Unsupported envelope version: {}",
+            envelope.version
+        );
+
         let ciphertext_bytes = STANDARD_NO_PAD
             .decode(&envelope.ciphertext)
             .context("Invalid base64 ciphertext")?;
@@ -738,6 +807,76 @@ mod tests {
         assert_eq!(bundle.signature, restored.signature);
     }
 
+    #[test]
+    fn test_bundle_validates() {
+        let mut crypto = VodozemacCrypto::new("test-hub-validate");
+        let bundle = crypto.build_device_key_bundle().unwrap();
+        bundle.validate().unwrap();
+
+        let restored = DeviceKeyBundle::from_binary(&bundle.to_binary().unwrap()).unwrap();
+        restored.validate().unwrap();
+    }
+
+    #[test]
+    fn test_bundle_wrong_version_fails_validation() {
+        let mut crypto = VodozemacCrypto::new("test-hub-validate-version");
+        let bundle = DeviceKeyBundle {
+            version: PROTOCOL_VERSION + 1,
+            ..crypto.build_device_key_bundle().unwrap()
+        };
+
+        let err = bundle.validate().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "version: expected {PROTOCOL_VERSION}, got {}",
+                PROTOCOL_VERSION + 1
+            )
+        );
+    }
+
+    #[test]
+    fn test_bundle_tampered_signature_fails_validation() {
+        let mut crypto = VodozemacCrypto::new("test-hub-validate-sig");
+        let bundle = crypto.build_device_key_bundle().unwrap();
+        let mut signature = decode_b64(&bundle.signature).unwrap();
+        signature[0] ^= 0x01;
+        let bundle = DeviceKeyBundle {
+            signature: STANDARD_NO_PAD.encode(signature),
+            ..bundle
+        };
+
+        let err = bundle.validate().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "signature: does not verify against ed25519_key"
+        );
+    }
+
+    #[test]
+    fn test_bundle_validation_names_bad_field() {
+        let mut crypto = VodozemacCrypto::new("test-hub-validate-field");
+        let bundle = crypto.build_device_key_bundle().unwrap();
+
+        let bad_base64 = DeviceKeyBundle {
+            one_time_key: "not base64!".to_string(),
+            ..bundle.clone()
+        };
+        assert_eq!(
+            bad_base64.validate().unwrap_err().to_string(),
+            "one_time_key: invalid base64"
+        );
+
+        let short_key = DeviceKeyBundle {
+            curve25519_key: STANDARD_NO_PAD.encode([1u8; 16]),
+            ..bundle
+        };
+        assert_eq!(
+            short_key.validate().unwrap_err().to_string(),
+            "curve25519_key: expected 32 bytes, got 16"
+        );
+    }
+
     /// Verify the browser's signature verification approach works:
     /// extract raw bytes from the binary bundle and verify with Ed25519.
     #[test]
@@ -825,12 +964,14 @@ mod tests {
     #[test]
     fn test_envelope_serialization() {
         let envelope = OlmEnvelope {
+            version: PROTOCOL_VERSION,
             message_type: MSG_TYPE_NORMAL,
             ciphertext: "dGVzdA".to_string(),
             sender_key: None,
         };
 
         let json = serde_json::to_string(&envelope).unwrap();
+        assert!(json.contains(&format!(r#""v":{PROTOCOL_VERSION}"#)));
         assert!(json.contains(r#""t":1"#));
         assert!(
             !json.contains(r#""k""#),
@@ -838,14 +979,58 @@ mod tests {
         );
 
         let restored: OlmEnvelope = serde_json::from_str(&json).unwrap();
+        assert_eq!(envelope.version, restored.version);
         assert_eq!(envelope.message_type, restored.message_type);
         assert_eq!(envelope.ciphertext, restored.ciphertext);
         assert!(restored.sender_key.is_none());
     }
 
+    #[test]
+    fn test_envelope_version_mismatch_is_rejected() {
+        let mut cli = VodozemacCrypto::new("test-envelope-version-cli");
+        let mut browser = VodozemacCrypto::new("test-envelope-version-browser");
+        let cli_key = cli.identity_key().to_string();
+        let bundle = cli.build_device_key_bundle().unwrap();
+        browser
+            .create_outbound_session(&bundle.curve25519_key, &bundle.one_time_key)
+            .unwrap();
+
+        let mut envelope = browser.encrypt(b"hello", &cli_key).unwrap();
+        assert_eq!(envelope.version, PROTOCOL_VERSION);
+        envelope.version = PROTOCOL_VERSION + 1;
+
+        let err = cli.decrypt(&envelope, None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Unsupported envelope version: {}", PROTOCOL_VERSION + 1)
+        );
+        assert!(!cli.has_session(), "rejected before touching sessions");
+    }
+
+    #[test]
+    fn test_legacy_envelope_without_version_decrypts() {
+        let mut cli = VodozemacCrypto::new("test-envelope-legacy-cli");
+        let mut browser = VodozemacCrypto::new("test-envelope-legacy-browser");
+        let cli_key = cli.identity_key().to_string();
+        let bundle = cli.build_device_key_bundle().unwrap();
+        browser
+            .create_outbound_session(&bundle.curve25519_key, &bundle.one_time_key)
+            .unwrap();
+
+        // What browsers send today: no "v".
+        let envelope = browser.encrypt(b"hello", &cli_key).unwrap();
+        let mut json = serde_json::to_value(&envelope).unwrap();
+        json.as_object_mut().unwrap().remove("v");
+
+        let legacy: OlmEnvelope = serde_json::from_value(json).unwrap();
+        assert_eq!(legacy.version, LEGACY_ENVELOPE_VERSION);
+        assert_eq!(cli.decrypt(&legacy, None).unwrap(), b"hello");
+    }
+
     #[test]
     fn test_prekey_envelope_includes_sender_key() {
         let envelope = OlmEnvelope {
+            version: PROTOCOL_VERSION,
             message_type: MSG_TYPE_PREKEY,
             ciphertext: "dGVzdA".to_string(),
             sender_key: Some("sender_key_here".to_string()),
@@ -164,6 +164,10 @@ const VAPID_VERSION: u8 = 1;
 const PUSH_SUB_VERSION: u8 = 1;
 
 /// CLI long-term vodozemac account persistence format version.
+///
+/// Bump when the stored layout changes. Files with any other version
+/// (including pre-versioned `0`) are not migrated: loading fails, the hub
+/// starts with a fresh identity, and browsers re-pair from the new QR code.
 const ACCOUNT_VERSION: u8 = 1;
 
 /// Device-level config directory (same as `device.json` location).
@@ -326,9 +330,7 @@ pub fn load_vodozemac_account(hub_id: &str) -> Result<Option<AccountPickle>> {
         fs::read_to_string(&account_path).context("Failed to read vodozemac account file")?;
     let encrypted: EncryptedData =
         serde_json::from_str(&content).context("Failed to parse vodozemac account file")?;
-    let plaintext = crate::crypto::decrypt(&key, &encrypted)?;
-    let account: AccountPickle =
-        serde_json::from_slice(&plaintext).context("Failed to deserialize vodozemac account")?;
+    let account = decode_vodozemac_account(&key, &encrypted)?;
 
     log::info!(
         "Loaded persisted vodozemac account for hub {}",
@@ -337,6 +339,17 @@ pub fn load_vodozemac_account(hub_id: &str) -> Result<Option<AccountPickle>> {
     Ok(Some(account))
 }
 
+/// Decrypt a stored account, rejecting versions other than [`ACCOUNT_VERSION`].
+fn decode_vodozemac_account(key: &[u8; 32], encrypted: &EncryptedData) -> Result<AccountPickle> {
+    anyhow::ensure!(
+        encrypted.version == ACCOUNT_VERSION,
+        "Unsupported vodozemac account version {} (expected {ACCOUNT_VERSION})",
+        encrypted.version
+    );
+    let plaintext = crate::crypto::decrypt(key, encrypted)?;
+    serde_json::from_slice(&plaintext).context("Failed to deserialize vodozemac account")
+}
+
 /// Save the CLI's long-term vodozemac account for a hub.
 ///
 /// This preserves the hub identity/signing keys across reboot while keeping
@@ -439,4 +452,35 @@ mod tests {
         assert_eq!(decrypted, plaintext);
         assert_eq!(encrypted.version, TEST_CRYPTO_VERSION);
     }
+
+    #[test]
+    fn test_vodozemac_account_round_trips_at_current_version() {
+        let key = [7u8; 32];
+        let account = vodozemac::olm::Account::new();
+        let plaintext = serde_json::to_vec(&account.pickle()).unwrap();
+        let encrypted = crate::crypto::encrypt(&key, &plaintext, super::ACCOUNT_VERSION).unwrap();
+
+        let pickle = super::decode_vodozemac_account(&key, &encrypted).unwrap();
+        let restored = vodozemac::olm::Account::from_pickle(pickle);
+        assert_eq!(restored.curve25519_key(), account.curve25519_key());
+    }
+
+    #[test]
+    fn test_vodozemac_account_rejects_unknown_version() {
+        let key = [7u8; 32];
+        let plaintext = serde_json::to_vec(&vodozemac::olm::Account::new().pickle()).unwrap();
+        let version = super::ACCOUNT_VERSION + 1;
+        let encrypted = crate::crypto::encrypt(&key, &plaintext, version).unwrap();
+
+        let Err(err) = super::decode_vodozemac_account(&key, &encrypted) else {
+            panic!("version {version} should be rejected");
+        };
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Unsupported vodozemac account version {version} (expected {})",
+                super::ACCOUNT_VERSION
+            )
+        );
+    }
 }
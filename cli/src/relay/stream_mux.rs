@@ -58,6 +58,8 @@ pub struct StreamFrame {
 
 /// Per-stream state holding the write channel and connection task handle.
 struct StreamHandle {
+    /// Local port the stream connects to.
+    port: u16,
     /// Sender for writing data to the TCP stream's write half.
     write_tx: mpsc::Sender<Vec<u8>>,
     /// Connection task handle (owns TCP connect, reader loop, and writer subtask).
@@ -132,6 +134,21 @@ impl StreamMultiplexer {
         }
     }
 
+    /// Close every stream connected to `port` (cleanup when the session
+    /// serving that port closes). Returns how many streams were closed.
+    pub fn close_port(&mut self, port: u16) -> usize {
+        let stream_ids: Vec<u16> = self
+            .streams
+            .iter()
+            .filter(|(_, handle)| handle.port == port)
+            .map(|(stream_id, _)| *stream_id)
+            .collect();
+        for stream_id in &stream_ids {
+            self.handle_close(*stream_id);
+        }
+        stream_ids.len()
+    }
+
     /// Handle FRAME_OPEN: connect to localhost:port and set up bidirectional forwarding.
     fn handle_open(&mut self, stream_id: u16, payload: Vec<u8>) {
         if payload.len() < 2 {
@@ -239,6 +256,7 @@ impl StreamMultiplexer {
         self.streams.insert(
             stream_id,
             StreamHandle {
+                port,
                 write_tx,
                 _task: reader_task,
             },
@@ -292,3 +310,30 @@ impl StreamMultiplexer {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn close_port_only_closes_streams_to_that_port() {
+        let closed = tokio::net::TcpListener::bind(("127.0.0.1", 0))
+            .await
+            .unwrap();
+        let kept = tokio::net::TcpListener::bind(("127.0.0.1", 0))
+            .await
+            .unwrap();
+        let closed_port = closed.local_addr().unwrap().port();
+        let kept_port = kept.local_addr().unwrap().port();
+
+        let mut mux = StreamMultiplexer::new();
+        mux.handle_frame(FRAME_OPEN, 1, closed_port.to_be_bytes().to_vec());
+        mux.handle_frame(FRAME_OPEN, 2, closed_port.to_be_bytes().to_vec());
+        mux.handle_frame(FRAME_OPEN, 3, kept_port.to_be_bytes().to_vec());
+
+        assert_eq!(mux.close_port(closed_port), 2);
+        assert_eq!(mux.streams.len(), 1);
+        assert!(mux.streams.contains_key(&3));
+        assert_eq!(mux.close_port(closed_port), 0);
+    }
+}
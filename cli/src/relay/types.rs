@@ -232,6 +232,12 @@ pub enum BrowserCommand {
         /// Whether to delete the worktree as well.
         delete_worktree: Option<bool>,
     },
+    /// Kill and respawn an agent's shell in place, keeping its worktree.
+    #[serde(rename = "restart_agent")]
+    RestartAgent {
+        /// Agent session key to restart.
+        id: String,
+    },
     /// Toggle PTY view (CLI/Server).
     #[serde(rename = "toggle_pty_view")]
     TogglePtyView,
@@ -419,6 +425,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_browser_command_restart_agent_parsing() {
+        let json = r#"{"type":"restart_agent","id":"agent-abc-123"}"#;
+        let cmd: BrowserCommand = serde_json::from_str(json).unwrap();
+        match cmd {
+            BrowserCommand::RestartAgent { id } => assert_eq!(id, "agent-abc-123"),
+            _ => panic!("Wrong variant"),
+        }
+    }
+
     #[test]
     fn test_browser_command_create_agent_parsing() {
         let json = r#"{"type":"create_agent","issue_or_branch":"42","prompt":"Fix the bug"}"#;
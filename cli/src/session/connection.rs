@@ -448,17 +448,26 @@ fn session_reader(
 
                 // ── Existing control frames ─────────────────────────────────
                 FRAME_PROCESS_EXITED => {
-                    let exit_code = frame
-                        .json::<serde_json::Value>()
-                        .ok()
-                        .and_then(|v| v["exit_code"].as_i64())
-                        .map(|c| c as i32);
+                    let payload = frame.json::<serde_json::Value>().ok();
+                    let field = |key: &str| {
+                        payload
+                            .as_ref()
+                            .and_then(|v| v[key].as_i64())
+                            .map(|c| c as i32)
+                    };
+                    let exit_code = field("exit_code");
+                    let signal = field("signal");
                     saw_process_exit = true;
                     let _ = hub_event_tx.send(crate::hub::events::HubEvent::SessionProcessExited {
                         session_uuid: session_uuid.clone(),
                         exit_code,
+                        signal,
                     });
-                    log::info!("[session-reader] process exited (code={:?})", exit_code);
+                    log::info!(
+                        "[session-reader] process exited (code={:?}, signal={:?})",
+                        exit_code,
+                        signal
+                    );
                 }
 
                 _ => {
@@ -477,6 +486,7 @@ fn session_reader(
         let _ = hub_event_tx.send(crate::hub::events::HubEvent::SessionProcessExited {
             session_uuid,
             exit_code: None,
+            signal: None,
         });
     }
 }
@@ -531,4 +541,49 @@ mod tests {
 
         assert_eq!(exits, vec![Some(0)]);
     }
+
+    #[test]
+    fn session_reader_reports_killing_signal() {
+        let (mut writer, reader) = UnixStream::pair().expect("unix pair");
+        let (event_tx, _event_rx) = broadcast::channel(8);
+        let (response_tx, _response_rx) = std::sync::mpsc::channel::<Frame>();
+        let (hub_tx, mut hub_rx) = mpsc::unbounded_channel();
+        let hub_event_tx = crate::hub::events::HubEventTx::from(hub_tx);
+
+        let handle = std::thread::spawn(move || {
+            session_reader(
+                reader,
+                "sess-test-signal".to_string(),
+                event_tx,
+                Arc::new(AtomicBool::new(false)),
+                Arc::new(AtomicBool::new(true)),
+                Arc::new(AtomicBool::new(false)),
+                Arc::new(AtomicU64::new(0)),
+                response_tx,
+                hub_event_tx,
+            );
+        });
+
+        let frame = encode_json(
+            FRAME_PROCESS_EXITED,
+            &serde_json::json!({ "exit_code": null, "signal": 9 }),
+        )
+        .expect("encode exit frame");
+        writer.write_all(&frame).expect("write exit frame");
+        writer.shutdown(Shutdown::Both).expect("shutdown writer");
+
+        handle.join().expect("reader thread joins");
+
+        let mut exits = Vec::new();
+        while let Ok(event) = hub_rx.try_recv() {
+            if let crate::hub::events::HubEvent::SessionProcessExited {
+                exit_code, signal, ..
+            } = event
+            {
+                exits.push((exit_code, signal));
+            }
+        }
+
+        assert_eq!(exits, vec![(None, Some(9))]);
+    }
 }
@@ -124,8 +124,12 @@ enum PtyWriteCommand {
 enum SessionOutput {
     /// Raw PTY output bytes.
     PtyData(Vec<u8>),
-    /// Child process exited with optional exit code.
-    ChildExited(Option<i32>),
+    /// Child process exited: exit code, or the signal that killed it
+    /// (both `None` when the wait itself failed).
+    ChildExited {
+        code: Option<i32>,
+        signal: Option<i32>,
+    },
     /// Pre-encoded event frame (mode changed, title, bell, CWD, notification, prompt mark).
     EventFrame(Vec<u8>),
 }
@@ -544,16 +548,25 @@ fn run_session(
         .name("session-child-waiter".to_string())
         .spawn(move || {
             let mut child = child;
-            let exit_code = match child.wait() {
-                Ok(status) => Some(status.exit_code() as i32),
+            let (code, signal) = match child.wait() {
+                Ok(status) => match crate::agent::AgentStatus::from_exit_status(&status) {
+                    crate::agent::AgentStatus::Failed {
+                        signal: Some(signal),
+                    } => (None, Some(signal)),
+                    _ => (Some(status.exit_code() as i32), None),
+                },
                 Err(e) => {
                     log::warn!("[session] child wait error: {e}");
-                    None
+                    (None, None)
                 }
             };
-            log::info!("[session] child exited (code={:?})", exit_code);
+            log::info!(
+                "[session] child exited (code={:?}, signal={:?})",
+                code,
+                signal
+            );
             shutdown_for_child.store(true, Ordering::Release);
-            let _ = output_tx_child.try_send(SessionOutput::ChildExited(exit_code));
+            let _ = output_tx_child.try_send(SessionOutput::ChildExited { code, signal });
         })
         .context("spawn child waiter thread")?;
 
@@ -616,10 +629,10 @@ fn run_session(
         while let Ok(msg) = output_rx.try_recv() {
             let frame = match msg {
                 SessionOutput::PtyData(data) => encode_frame(FRAME_PTY_OUTPUT, &data),
-                SessionOutput::ChildExited(code) => {
+                SessionOutput::ChildExited { code, signal } => {
                     match encode_json(
                         FRAME_PROCESS_EXITED,
-                        &serde_json::json!({"exit_code": code}),
+                        &serde_json::json!({"exit_code": code, "signal": signal}),
                     ) {
                         Ok(f) => f,
                         Err(_) => encode_frame(FRAME_PROCESS_EXITED, b"{}"),
@@ -710,11 +723,15 @@ fn run_session(
         unsafe {
             libc::killpg(pgid, libc::SIGTERM);
         }
-        thread::sleep(Duration::from_millis(500));
-        unsafe {
-            libc::killpg(pgid, libc::SIGKILL);
+        // The session-child-waiter thread reaps the leader.
+        match crate::process::wait_process_group(pgid, Duration::from_millis(500), || {}) {
+            crate::process::GroupExit::Graceful => {
+                log::info!("[session] process group {pgid} exited after SIGTERM");
+            }
+            crate::process::GroupExit::Killed => {
+                log::info!("[session] sent SIGKILL to process group {pgid}");
+            }
         }
-        log::info!("[session] sent SIGKILL to process group {pgid}");
     }
 
     let socket_existed = socket_path.exists();
@@ -57,7 +57,8 @@ pub const FRAME_GET_SNAPSHOT: u8 = 0x05;
 /// Session → Hub: opaque terminal snapshot response.
 pub const FRAME_SNAPSHOT: u8 = 0x06;
 
-/// Session → Hub: child process exited (JSON payload: `{"exit_code": i32|null}`).
+/// Session → Hub: child process exited
+/// (JSON payload: `{"exit_code": i32|null, "signal": i32|null}`).
 pub const FRAME_PROCESS_EXITED: u8 = 0x07;
 
 /// Hub → Session: keepalive ping.
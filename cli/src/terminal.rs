@@ -490,6 +490,11 @@ impl TerminalParser {
     }
 
     /// Plain-text contents of the visible grid.
+    ///
+    /// SGR styling and other escape sequences are dropped, wide characters
+    /// are emitted once (their spacer cell is skipped), and trailing blanks
+    /// are trimmed. This is what `session:get_screen()` returns to Lua for
+    /// prompt detection and transcript capture.
     pub fn contents(&self) -> String {
         self.terminal
             .format_plain()
@@ -725,6 +730,19 @@ mod tests {
         assert!(contents.contains('H'));
     }
 
+    #[test]
+    fn contents_strips_styling_and_trailing_blanks() {
+        let mut p = TerminalParser::new(24, 80, 100);
+        p.process(b"\x1b[1;31mHello\x1b[0m \x1b[4;38;5;42mworld\x1b[0m   \r\n");
+        p.process("\x1b[7m日本語\x1b[0m ok".as_bytes());
+
+        let contents = p.contents();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.first().copied(), Some("Hello world"));
+        assert_eq!(lines.get(1).copied(), Some("日本語 ok"));
+        assert!(!contents.contains('\x1b'));
+    }
+
     #[test]
     fn resize_updates_dimensions() {
         let mut p = TerminalParser::new(24, 80, 100);
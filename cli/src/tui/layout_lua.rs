@@ -1054,6 +1054,7 @@ mod tests {
             ("new_agent_create_worktree", true),
             ("new_agent_prompt", true),
             ("close_agent_confirm", true),
+            ("restart_agent_confirm", true),
             ("connection_code", true),
             ("error", true),
             ("restarting", true),
@@ -1110,6 +1111,7 @@ mod tests {
             ("new_agent_create_worktree", "Input"),
             ("new_agent_prompt", "Input"),
             ("close_agent_confirm", "Paragraph"),
+            ("restart_agent_confirm", "Paragraph"),
             ("connection_code", "ConnectionCode"),
             ("error", "Paragraph"),
             ("restarting", "Paragraph"),
@@ -32,7 +32,7 @@ use ratatui::{
     Frame, Terminal,
 };
 
-use crate::app::buffer_to_ansi;
+use crate::app::{buffer_to_ansi, render_too_small, terminal_too_small, ColorDepth};
 
 /// A widget's screen area and type, for mouse hit-testing.
 #[derive(Debug, Clone)]
@@ -149,8 +149,14 @@ where
     B: Backend,
     B::Error: std::error::Error + Send + Sync + 'static,
 {
-    // Helper to render UI to a frame
-    let render_ui = |f: &mut Frame| render_frame(f, ctx);
+    // Helper to render UI to a frame; tiny viewports get a size notice
+    let render_ui = |f: &mut Frame| {
+        if terminal_too_small(f.area()) {
+            render_too_small(f);
+        } else {
+            render_frame(f, ctx);
+        }
+    };
 
     // Always render to real terminal for local display
     terminal.draw(render_ui)?;
@@ -162,7 +168,7 @@ where
         let mut virtual_terminal = Terminal::new(backend)?;
 
         // Render to virtual terminal at browser dimensions
-        let completed_frame = virtual_terminal.draw(|f| {
+        virtual_terminal.draw(|f| {
             // Log once when dimensions change
             static LAST_AREA: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
             let area = f.area();
@@ -178,13 +184,19 @@ where
             render_ui(f);
         })?;
 
-        // Convert virtual buffer to ANSI
+        // Convert virtual buffer to ANSI. The backend holds the drawn frame
+        // and the cursor state the UI requested (hidden unless an input
+        // field placed it).
+        let backend = virtual_terminal.backend();
+        let cursor = backend.cursor_visible().then(|| backend.cursor_position());
         let ansi = buffer_to_ansi(
-            completed_frame.buffer,
+            backend.buffer(),
             dims.cols,
             dims.rows,
             None, // No clipping needed, already at correct size
             None,
+            cursor,
+            ColorDepth::TrueColor,
         );
         (ansi, dims.rows, dims.cols)
     } else {
@@ -557,6 +569,56 @@ pub(super) fn render_connection_code_widget(
 mod tests {
     use super::*;
 
+    fn empty_context(
+        panels: &std::collections::HashMap<String, super::super::terminal_panel::TerminalPanel>,
+    ) -> RenderContext<'_> {
+        RenderContext {
+            error_message: None,
+            connection_code: None,
+            bundle_used: false,
+            panels,
+            scroll_offset: 0,
+            is_scrolled: false,
+            focused_session_uuid: None,
+            is_terminal_mode: false,
+            seconds_since_poll: 0,
+            poll_interval: 10,
+            vpn_status: None,
+            terminal_cols: 10,
+            terminal_rows: 5,
+            widget_areas: std::cell::RefCell::new(std::collections::HashMap::new()),
+        }
+    }
+
+    #[test]
+    fn test_render_tiny_terminal_shows_size_notice() {
+        let panels = std::collections::HashMap::new();
+        let ctx = empty_context(&panels);
+        let mut terminal = Terminal::new(TestBackend::new(10, 5)).unwrap();
+
+        let result = render(
+            &mut terminal,
+            &ctx,
+            Some(BrowserDimensions {
+                rows: 5,
+                cols: 10,
+                mode: crate::compat::BrowserMode::default(),
+            }),
+        )
+        .expect("tiny render should not fail");
+
+        let local: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|c| c.symbol())
+            .collect();
+        assert!(local.contains("Terminal"), "got {local:?}");
+        assert!(result.ansi_output.contains("Terminal"));
+        assert!(ctx.widget_areas.borrow().is_empty());
+    }
+
     #[test]
     fn test_render_result_default() {
         let result = RenderResult::default();
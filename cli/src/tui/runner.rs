@@ -1771,6 +1771,16 @@ where
 
         self.panel_pool.refresh_panel_colors();
 
+        // Below the minimum size the Lua layout collapses to zero-width
+        // chunks. Show a size notice and leave subscriptions and PTY sizes
+        // untouched until the terminal grows again.
+        let area = ratatui::layout::Rect::from(self.terminal.size()?);
+        if crate::app::terminal_too_small(area) {
+            self.terminal.draw(crate::app::render_too_small)?;
+            self.last_widget_areas.clear();
+            return Ok(());
+        }
+
         // Read scroll state from the focused panel (no mutex needed)
         let (scroll_offset, is_scrolled) = self
             .panel_pool
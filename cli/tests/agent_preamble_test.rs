@@ -0,0 +1,115 @@
+//! Rust-hosted Lua tests for the agent prompt preamble.
+//!
+//! Agents spawned with a task get `profiles.<name>.agent_preamble` (or the
+//! default in `lib.agent_preamble`) ahead of the task in `BOTSTER_PROMPT`,
+//! unless the repo has a `.botster_prompt_template`.
+
+mod common;
+
+use common::LuaFixture;
+
+/// Fixture whose `spawned_prompt(prompt)` spawns a `codex` agent with
+/// `prompt` and returns its BOTSTER_PROMPT.
+fn fixture() -> LuaFixture {
+    let fixture = LuaFixture::new();
+    fixture.exec(
+        r#"
+        function _G.spawned_prompt(prompt)
+          local Agent = require("lib.agent")
+          Agent.new({
+            repo = "owner/repo",
+            branch_name = "feature-preamble",
+            worktree_path = "$REPO_ROOT",
+            prompt = prompt,
+            issue_number = 42,
+            agent_name = "codex",
+            session = { name = "codex", command = "bash" },
+            target_id = "target-1",
+            target_path = "$REPO_ROOT",
+            target_repo = "owner/repo",
+          })
+          return _G.spawned[#_G.spawned].spawn_config.env.BOTSTER_PROMPT
+        end
+    "#,
+    );
+    fixture
+}
+
+/// Spawn a `codex` agent for "Fix the login bug" with `profiles` as the
+/// config value and return the prompt it received.
+fn prompt_with_profiles(profiles: &str) -> Option<String> {
+    prompt_with(profiles, None)
+}
+
+/// Like [`prompt_with_profiles`], with `template` written to the repo's
+/// `.botster_prompt_template` first.
+fn prompt_with(profiles: &str, template: Option<&str>) -> Option<String> {
+    let fixture = fixture();
+    if let Some(template) = template {
+        std::fs::write(fixture.repo_root.join(".botster_prompt_template"), template).unwrap();
+    }
+
+    fixture.eval(&format!(
+        r#"
+        _G.test_config.profiles = {profiles}
+        return spawned_prompt("Fix the login bug")
+    "#
+    ))
+}
+
+#[test]
+fn default_preamble_precedes_task() {
+    let prompt = prompt_with_profiles("nil").expect("prompt set");
+    assert!(
+        prompt
+            .starts_with("IMPORTANT: You are an autonomous AI agent operating without user input."),
+        "default preamble missing: {prompt:?}"
+    );
+    assert!(prompt.ends_with("\n\nFix the login bug"));
+}
+
+#[test]
+fn configured_preamble_replaces_default() {
+    let prompt =
+        prompt_with_profiles(r#"{ codex = { agent_preamble = "Work alone. Push when done." } }"#)
+            .expect("prompt set");
+    assert_eq!(prompt, "Work alone. Push when done.\n\nFix the login bug");
+}
+
+#[test]
+fn empty_preamble_disables_it() {
+    let prompt = prompt_with_profiles(r#"{ codex = { agent_preamble = "" } }"#);
+    assert_eq!(prompt.as_deref(), Some("Fix the login bug"));
+}
+
+#[test]
+fn other_profiles_settings_do_not_apply() {
+    let prompt =
+        prompt_with_profiles(r#"{ claude = { agent_preamble = "" } }"#).expect("prompt set");
+    assert!(prompt.starts_with("IMPORTANT: You are an autonomous AI agent"));
+}
+
+#[test]
+fn repo_template_substitutes_variables() {
+    let prompt = prompt_with(
+        "nil",
+        Some("You work on {repo} ({branch}), issue #{issue_number}.\nTASK: {task}\n"),
+    )
+    .expect("prompt set");
+    assert_eq!(
+        prompt,
+        "You work on owner/repo (feature-preamble), issue #42.\nTASK: Fix the login bug\n"
+    );
+}
+
+#[test]
+fn repo_template_without_task_placeholder_appends_task() {
+    let prompt = prompt_with("nil", Some("Be brief.\n")).expect("prompt set");
+    assert_eq!(prompt, "Be brief.\n\nFix the login bug");
+}
+
+#[test]
+fn blank_repo_template_falls_back_to_preamble() {
+    let prompt = prompt_with(r#"{ codex = { agent_preamble = "" } }"#, Some("  \n"));
+    assert_eq!(prompt.as_deref(), Some("Fix the login bug"));
+}
@@ -0,0 +1,200 @@
+//! Rust-hosted Lua tests for restoring agents after a hub restart.
+//!
+//! `handlers/agent_restore.lua` snapshots running agents to
+//! `agents_snapshot.json`. A fresh hub (a new Lua VM on the same data dir)
+//! respawns the snapshot's agents in their worktrees once session recovery
+//! reports `sessions_recovered`, without replaying their task prompts.
+
+mod common;
+
+use common::LuaFixture;
+
+fn fixture() -> LuaFixture {
+    let fixture = LuaFixture::new();
+    std::fs::create_dir_all(fixture.path("worktrees")).unwrap();
+    let agent_dir = fixture.repo_root.join(".botster/agents/claude");
+    std::fs::create_dir_all(&agent_dir).unwrap();
+    std::fs::write(agent_dir.join("initialization"), "true\n").unwrap();
+    fixture
+}
+
+/// Start a hub on the fixture's dirs (a fresh VM): load the restore handler
+/// and the agent handler.
+fn start_hub(fixture: &mut LuaFixture) {
+    fixture.restart();
+    fixture.exec(
+        r#"
+        require("handlers.agent_restore")
+        _G.agents = require("handlers.agents")
+        _G.target = {
+          target_id = "target-1",
+          target_path = "$REPO_ROOT",
+          target_repo = "owner/repo",
+        }
+        function _G.finish_recovery()
+          event_handlers.hub_recovery_state({ state = "sessions_recovered" })
+        end
+        "#,
+    );
+}
+
+fn make_worktree(fixture: &LuaFixture, name: &str) -> String {
+    let path = fixture.path("worktrees").join(name);
+    std::fs::create_dir_all(&path).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn agents_reappear_after_restart() {
+    let mut fixture = fixture();
+    let first_wt = make_worktree(&fixture, "issue-1");
+    let second_wt = make_worktree(&fixture, "feature");
+
+    start_hub(&mut fixture);
+    let old_uuids: Vec<String> = fixture
+        .lua
+        .load(format!(
+            r#"
+            finish_recovery()
+            local a = assert(agents.handle_create_agent(
+                "1", "fix it", "{first_wt}", nil, "claude", {{ issue_number = 1 }}, target))
+            local b = assert(agents.handle_create_agent(
+                "feature", nil, "{second_wt}", nil, "claude", nil, target))
+            return {{ a.session_uuid, b.session_uuid }}
+        "#
+        ))
+        .eval()
+        .expect("first run should spawn agents");
+    assert!(fixture.data_dir.join("agents_snapshot.json").exists());
+
+    start_hub(&mut fixture);
+    let (worktrees, branches, issue, prompt, old_closed): (
+        Vec<String>,
+        Vec<String>,
+        i64,
+        Option<String>,
+        bool,
+    ) = fixture
+        .lua
+        .load(format!(
+            r#"
+            finish_recovery()
+            local Agent = require("lib.agent")
+            local ws = require("lib.workspace_store")
+            local worktrees, branches = {{}}, {{}}
+            local issue, prompt
+            for _, agent in ipairs(Agent.list()) do
+                worktrees[#worktrees + 1] = agent.worktree_path
+                branches[#branches + 1] = agent.branch_name
+                if agent.worktree_path == "{first_wt}" then
+                    issue = agent.metadata.issue_number
+                    prompt = agent.prompt
+                end
+            end
+            -- Old manifests are closed unless a respawn reused the UUID.
+            local old_closed = true
+            for _, entry in ipairs(ws.scan_recoverable_sessions("{data_dir}")) do
+                local old = entry.session_uuid == "{old_a}" or entry.session_uuid == "{old_b}"
+                if old and not Agent.get(entry.session_uuid) then
+                    old_closed = false
+                end
+            end
+            table.sort(worktrees)
+            table.sort(branches)
+            return worktrees, branches, issue, prompt, old_closed
+        "#,
+            data_dir = fixture.data_dir.to_str().unwrap(),
+            old_a = old_uuids[0],
+            old_b = old_uuids[1],
+        ))
+        .eval()
+        .expect("restart should restore agents");
+
+    let mut expected = vec![first_wt, second_wt];
+    expected.sort();
+    assert_eq!(worktrees, expected);
+    assert_eq!(branches.len(), 2);
+    assert!(branches.contains(&"feature".to_string()));
+    assert_eq!(issue, 1);
+    assert!(prompt.is_none(), "the task prompt is not replayed");
+    assert!(
+        old_closed,
+        "replaced session manifests must not be recovered again"
+    );
+}
+
+#[test]
+fn agents_without_a_worktree_are_not_restored() {
+    let mut fixture = fixture();
+    let kept_wt = make_worktree(&fixture, "kept");
+    let removed_wt = make_worktree(&fixture, "removed");
+
+    start_hub(&mut fixture);
+    fixture
+        .lua
+        .load(format!(
+            r#"
+            finish_recovery()
+            assert(agents.handle_create_agent("kept", nil, "{kept_wt}", nil, "claude", nil, target))
+            assert(agents.handle_create_agent("removed", nil, "{removed_wt}", nil, "claude", nil, target))
+        "#
+        ))
+        .exec()
+        .expect("first run should spawn agents");
+    std::fs::remove_dir_all(&removed_wt).unwrap();
+
+    start_hub(&mut fixture);
+    let (branches, snapshot_len): (Vec<String>, usize) = fixture
+        .lua
+        .load(
+            r#"
+            finish_recovery()
+            local branches = {}
+            for _, agent in ipairs(require("lib.agent").list()) do
+                branches[#branches + 1] = agent.branch_name
+            end
+            return branches, #require("lib.agent_snapshot").read()
+        "#,
+        )
+        .eval()
+        .expect("restart should evaluate");
+
+    assert_eq!(branches, vec!["kept".to_string()]);
+    assert_eq!(snapshot_len, 1, "the snapshot only lists restored agents");
+}
+
+#[test]
+fn exited_agents_are_not_restored() {
+    let mut fixture = fixture();
+    let running_wt = make_worktree(&fixture, "running");
+    let exited_wt = make_worktree(&fixture, "exited");
+
+    start_hub(&mut fixture);
+    fixture.exec(&format!(
+        r#"
+        finish_recovery()
+        assert(agents.handle_create_agent("running", nil, "{running_wt}", nil, "claude", nil, target))
+        local done = assert(agents.handle_create_agent(
+            "exited", nil, "{exited_wt}", nil, "claude", nil, target))
+        done:update({{ status = "failed", exit_code = 1 }})
+    "#
+    ));
+    assert!(
+        !fixture.data_dir.join("agents_snapshot.json.tmp").exists(),
+        "the snapshot is renamed into place"
+    );
+
+    start_hub(&mut fixture);
+    let branches: Vec<String> = fixture.eval(
+        r#"
+        finish_recovery()
+        local branches = {}
+        for _, agent in ipairs(require("lib.agent").list()) do
+            branches[#branches + 1] = agent.branch_name
+        end
+        return branches
+    "#,
+    );
+
+    assert_eq!(branches, vec!["running".to_string()]);
+}
@@ -0,0 +1,80 @@
+//! Rust-hosted Lua tests for worktree reuse reporting in `handle_create_agent`.
+//!
+//! Spawning for an issue whose worktree already exists should report
+//! `worktree_reused = true`; a fresh issue gets a new worktree and reports
+//! `false`. The flag rides on the session info and the lifecycle events.
+
+mod common;
+
+use common::LuaFixture;
+
+#[test]
+fn existing_issue_worktree_reports_reuse_and_new_issue_reports_creation() {
+    let fixture = LuaFixture::new();
+    let existing = fixture.worktree("repo-botster-issue-7");
+    fixture.exec(
+        r#"
+        _G.lifecycle = {}
+        hooks.on("agent_lifecycle", "test_capture", function(payload)
+          _G.lifecycle[#_G.lifecycle + 1] = payload.status
+        end)
+    "#,
+    );
+
+    let (reused, reused_path, reused_stage, fresh, fresh_created): (
+        Option<bool>,
+        String,
+        bool,
+        Option<bool>,
+        bool,
+    ) = fixture
+        .lua
+        .load(format!(
+            r#"
+            _G.existing_worktrees["botster-issue-7"] = "{existing}"
+            local agents = require("handlers.agents")
+            local target = {{
+              target_id = "target-1",
+              target_path = "{repo_root}",
+              target_repo = "owner/repo",
+            }}
+
+            local function saw(status)
+              for _, s in ipairs(_G.lifecycle) do
+                if s == status then return true end
+              end
+              return false
+            end
+
+            local first = assert(agents.handle_create_agent("7", nil, nil, nil, nil, nil, target))
+            local first_info = first:info()
+            local reused_stage = saw("reusing_worktree") and not saw("creating_worktree")
+
+            _G.lifecycle = {{}}
+            local second = assert(agents.handle_create_agent("8", nil, nil, nil, nil, nil, target))
+            local fresh_created = saw("creating_worktree")
+              and _G.created_worktrees[1] == "botster-issue-8"
+
+            return first_info.worktree_reused, first_info.worktree_path, reused_stage,
+              second:info().worktree_reused, fresh_created
+        "#,
+            existing = existing.to_str().unwrap(),
+            repo_root = fixture.repo_root.to_str().unwrap(),
+        ))
+        .eval()
+        .expect("create agents should evaluate");
+
+    assert_eq!(
+        reused,
+        Some(true),
+        "existing issue worktree should be reused"
+    );
+    assert_eq!(reused_path, existing.to_str().unwrap());
+    assert!(reused_stage, "reuse should broadcast reusing_worktree");
+    assert_eq!(
+        fresh,
+        Some(false),
+        "new issue should report a created worktree"
+    );
+    assert!(fresh_created, "new issue should create its worktree");
+}
@@ -0,0 +1,156 @@
+//! Rust-hosted Lua tests for the worktree cleanup policy.
+//!
+//! When an issue closes, `cleanup_policy` in config.json decides whether the
+//! agent's worktree is kept, deleted, or archived. The server delivers the
+//! close either as a HubCommandChannel command or as a Github::EventsChannel
+//! event to the GitHub plugin; both must apply the policy. The git side (branch and
+//! tag end state, unpushed-work refusal) is covered in `src/git.rs`.
+
+mod common;
+
+use common::LuaFixture;
+
+type Removal = (String, String, Option<String>);
+
+/// Spawns an agent for issue 42, closes the issue through HubCommandChannel
+/// with `policy` configured, and returns (removal requests, agents still
+/// registered).
+fn close_issue_with_policy(policy: Option<&str>) -> (Vec<Removal>, i64) {
+    close_issue_via(policy, false)
+}
+
+/// Like [`close_issue_with_policy`], but the close arrives as the GitHub
+/// plugin's `agent_cleanup` event.
+fn close_issue_via_plugin(policy: Option<&str>) -> (Vec<Removal>, i64) {
+    close_issue_via(policy, true)
+}
+
+fn close_issue_via(policy: Option<&str>, plugin: bool) -> (Vec<Removal>, i64) {
+    let fixture = LuaFixture::new();
+    if plugin {
+        fixture.load_github_plugin();
+    }
+    fixture.worktree("repo-botster-issue-42");
+    // Route emitted events straight to the registered handler so the
+    // webhook's delete_agent reaches handlers.agents.
+    fixture.exec(
+        r#"
+        _G.events.emit = function(name, data)
+          local fn = _G.event_handlers[name]
+          if fn then fn(data) end
+        end
+
+        function _G.close_issue(issue_number)
+          if _G.channel_callbacks["Github::EventsChannel"] then
+            deliver_github(1, "agent_cleanup", {
+              repo = "owner/repo", issue_number = issue_number, is_pr = false,
+              reason = "issue_closed",
+            })
+            return
+          end
+          _G.channel_callbacks["HubCommandChannel"]({
+            type = "message",
+            event_type = "agent_cleanup",
+            sequence = 1,
+            payload = {
+              issue_number = issue_number,
+              target_id = "target-1",
+              target_path = "$REPO_ROOT",
+              target_repo = "owner/repo",
+            },
+          }, "channel")
+        end
+    "#,
+    );
+    if let Some(policy) = policy {
+        fixture.exec(&format!(r#"_G.test_config.cleanup_policy = "{policy}""#));
+    }
+
+    let (removals, remaining): (mlua::Table, i64) = fixture.eval(
+        r#"
+        _G.existing_worktrees["botster-issue-42"] = "$ROOT/repo-botster-issue-42"
+        local agents = require("handlers.agents")
+        require("handlers.hub_commands")
+        local agent = assert(agents.handle_create_agent("42", nil, nil, nil, nil,
+          { issue_number = 42, workspace = "owner/repo#42" }, {
+            target_id = "target-1",
+            target_path = "$REPO_ROOT",
+            target_repo = "owner/repo",
+          }))
+        agent.session = { kill = function() end }
+
+        close_issue(42)
+        return _G.removals, #require("lib.agent").list()
+    "#,
+    );
+
+    let removals = removals
+        .sequence_values::<mlua::Table>()
+        .map(|r| {
+            let r = r.unwrap();
+            let branch: String = r.get("branch").unwrap();
+            assert_eq!(branch, "botster-issue-42");
+            (
+                r.get::<String>("kind").unwrap(),
+                r.get::<String>("path").unwrap(),
+                r.get::<Option<String>>("policy").unwrap(),
+            )
+        })
+        .collect();
+    (removals, remaining)
+}
+
+#[test]
+fn keep_policy_closes_agent_but_leaves_worktree() {
+    let (removals, remaining) = close_issue_with_policy(Some("keep"));
+    assert_eq!(remaining, 0);
+    assert!(
+        removals.is_empty(),
+        "keep must not touch the worktree: {removals:?}"
+    );
+}
+
+#[test]
+fn unset_or_unknown_policy_defaults_to_keep() {
+    let (removals, remaining) = close_issue_with_policy(None);
+    assert_eq!(remaining, 0);
+    assert!(removals.is_empty());
+
+    let (removals, _) = close_issue_with_policy(Some("purge"));
+    assert!(removals.is_empty());
+}
+
+#[test]
+fn delete_policy_requests_guarded_cleanup() {
+    let (removals, remaining) = close_issue_with_policy(Some("delete"));
+    assert_eq!(remaining, 0);
+    assert_eq!(removals.len(), 1);
+    assert_eq!(
+        removals[0].0, "cleanup",
+        "must go through the unpushed-work check"
+    );
+    assert!(removals[0].1.ends_with("repo-botster-issue-42"));
+    assert_eq!(removals[0].2.as_deref(), Some("delete"));
+}
+
+#[test]
+fn archive_policy_requests_archive_cleanup() {
+    let (removals, remaining) = close_issue_with_policy(Some("archive"));
+    assert_eq!(remaining, 0);
+    assert_eq!(removals.len(), 1);
+    assert_eq!(removals[0].0, "cleanup");
+    assert_eq!(removals[0].2.as_deref(), Some("archive"));
+}
+
+#[test]
+fn github_plugin_cleanup_applies_policy() {
+    let (removals, remaining) = close_issue_via_plugin(Some("delete"));
+    assert_eq!(remaining, 0);
+    assert_eq!(removals.len(), 1);
+    assert_eq!(removals[0].0, "cleanup");
+    assert_eq!(removals[0].2.as_deref(), Some("delete"));
+
+    let (removals, remaining) = close_issue_via_plugin(None);
+    assert_eq!(remaining, 0);
+    assert!(removals.is_empty(), "unset policy keeps the worktree");
+}
@@ -0,0 +1,81 @@
+//! Rust-hosted Lua tests for settling server commands.
+//!
+//! `handlers/hub_commands.lua` and the GitHub plugin ack every message they
+//! route and report the ones whose routing raised via `mark_failed` (see
+//! `lib/command_ack.lua`), so the server stops replaying a malformed message.
+
+mod common;
+
+use common::LuaFixture;
+
+fn fixture() -> LuaFixture {
+    let fixture = LuaFixture::new();
+    fixture.exec(
+        r#"
+        require("handlers.hub_commands")
+
+        --- Deliver a create_agent command with `payload`.
+        function _G.deliver(sequence, payload)
+          _G.channel_callbacks["HubCommandChannel"]({
+            type = "message",
+            sequence = sequence,
+            event_type = "create_agent",
+            payload = payload,
+          }, "channel")
+        end
+    "#,
+    );
+    fixture
+}
+
+#[test]
+fn malformed_payload_is_marked_failed_once() {
+    let fixture = fixture();
+    let (acked, failed, sequence, reason, spawned): (usize, usize, i64, String, usize) = fixture
+        .eval(
+            r#"
+            deliver(5, 42)
+            local failure = failures[1]
+            return #acks, #failures, failure.sequence, failure.reason, #emitted
+        "#,
+        );
+
+    assert_eq!(failed, 1, "exactly one mark_failed per malformed command");
+    assert_eq!(acked, 0, "a failed command is not also acked");
+    assert_eq!(sequence, 5);
+    assert!(!reason.is_empty());
+    assert_eq!(spawned, 0);
+}
+
+#[test]
+fn routing_continues_after_a_failed_command() {
+    let fixture = fixture();
+    let (acks, failed, spawned): (Vec<i64>, usize, usize) = fixture.eval(
+        r#"
+        deliver(1, 42)
+        deliver(2, { issue_number = 7, prompt = "please look", target_repo = "owner/repo" })
+        return acks, #failures, #emitted
+    "#,
+    );
+
+    assert_eq!(acks, vec![2]);
+    assert_eq!(failed, 1);
+    assert_eq!(spawned, 1);
+}
+
+#[test]
+fn github_plugin_marks_malformed_events_failed_by_id() {
+    let fixture = LuaFixture::new();
+    fixture.load_github_plugin();
+    let (acks, failed_id, spawned): (Vec<i64>, i64, usize) = fixture.eval(
+        r#"
+        deliver_github(9, "github_mention", 42)
+        deliver_github(10, "github_mention", { issue_number = 7, prompt = "please look" })
+        return acks, failures[1].id, #emitted
+    "#,
+    );
+
+    assert_eq!(acks, vec![10], "the malformed event is not acked");
+    assert_eq!(failed_id, 9);
+    assert_eq!(spawned, 1);
+}
@@ -0,0 +1,135 @@
+//! Rust-hosted Lua tests for the server command filter.
+//!
+//! The GitHub plugin consults `lib/command_filter.lua` before acting on a
+//! `github_mention` from `Github::EventsChannel`. The ActionCable primitive is
+//! stubbed so a test can deliver events in the channel's wire shape and see
+//! what was spawned and acked.
+
+mod common;
+
+use common::LuaFixture;
+
+fn fixture() -> LuaFixture {
+    let fixture = LuaFixture::new();
+    fixture.load_github_plugin();
+    fixture.exec(
+        r#"
+        --- Deliver a mention on issue #7 from a `source_type` GitHub event,
+        -- with the issue's `labels` when given.
+        function _G.mention(id, source_type, labels)
+          deliver_github(id, "github_mention", {
+            issue_number = 7,
+            prompt = "please look",
+            comment_body = "@trybotster please look",
+            comment_author = "octocat",
+            issue_title = "Broken build",
+            issue_url = "https://github.com/owner/repo/issues/7",
+            is_pr = false,
+            labels = labels,
+            structured_context = {
+              source = {
+                type = source_type,
+                repo = "owner/repo",
+                owner = "owner",
+                repo_name = "repo",
+                number = 7,
+                comment_author = "octocat",
+              },
+              respond_to = "owner/repo#7",
+              message = "please look",
+            },
+          })
+        end
+    "#,
+    );
+    fixture
+}
+
+#[test]
+fn non_matching_event_type_is_acked_without_spawning() {
+    let fixture = fixture();
+    let (spawned, acked): (usize, usize) = fixture.eval(
+        r#"
+        _G.test_config.event_type_allowlist = { "issue_comment" }
+        mention(1, "pr_comment")
+        return #emitted, #acks
+    "#,
+    );
+
+    assert_eq!(spawned, 0, "pr_comment is not in the allowlist");
+    assert_eq!(acked, 1, "skipped mentions are still acked");
+}
+
+#[test]
+fn matching_event_type_and_label_spawn() {
+    let fixture = fixture();
+    let (by_type, by_event, by_label, unlabelled): (usize, usize, usize, usize) = fixture.eval(
+        r#"
+        _G.test_config.event_type_allowlist = { "Issue_Comment" }
+        mention(1, "issue_comment")
+        local by_type = #emitted
+
+        _G.test_config.event_type_allowlist = { "github_mention" }
+        mention(2, "pr_review_comment")
+        local by_event = #emitted - by_type
+
+        _G.test_config.event_type_allowlist = nil
+        _G.test_config.label_allowlist = { "bug" }
+        mention(3, "issue_comment", { { name = "bug" }, { name = "ui" } })
+        local by_label = #emitted - by_type - by_event
+
+        mention(4, "issue_comment", { "enhancement" })
+        return by_type, by_event, by_label, #emitted - by_type - by_event - by_label
+    "#,
+    );
+
+    assert_eq!(by_type, 1);
+    assert_eq!(by_event, 1, "the event type itself can be allowlisted");
+    assert_eq!(by_label, 1);
+    assert_eq!(unlabelled, 0, "labels outside the allowlist are skipped");
+}
+
+#[test]
+fn missing_labels_do_not_match_the_label_allowlist() {
+    let fixture = fixture();
+    let (spawned, acked): (usize, usize) = fixture.eval(
+        r#"
+        _G.test_config.label_allowlist = { "bug" }
+        mention(1, "issue_comment")
+        return #emitted, #acks
+    "#,
+    );
+
+    assert_eq!(spawned, 0, "a mention without labels has none allowlisted");
+    assert_eq!(acked, 1);
+}
+
+#[test]
+fn cleanup_events_are_not_filtered() {
+    let fixture = fixture();
+    let acked: usize = fixture.eval(
+        r#"
+        _G.test_config.event_type_allowlist = { "issue_comment" }
+        deliver_github(1, "agent_cleanup", {
+          repo = "owner/repo", issue_number = 7, is_pr = false, reason = "issue_closed",
+        })
+        return #acks
+    "#,
+    );
+
+    assert_eq!(acked, 1);
+}
+
+#[test]
+fn no_allowlists_keeps_default_behavior() {
+    let fixture = fixture();
+    let spawned: usize = fixture.eval(
+        r#"
+        mention(1, "pr_comment")
+        mention(2, nil)
+        return #emitted
+    "#,
+    );
+
+    assert_eq!(spawned, 2);
+}
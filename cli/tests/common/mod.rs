@@ -0,0 +1,323 @@
+//! Shared setup for the Rust-hosted Lua tests.
+//!
+//! [`LuaFixture::new`] builds a Lua VM with the fs/json/log primitives, the
+//! crate's `lua/` directory on `package.path`, and recording stubs for the
+//! hub globals the handlers touch (`hub`, `config`, `events`, `timer`,
+//! `worktree`, `spawn_targets`, `action_cable`, ...). A test overrides only
+//! the stubs it cares about by running Lua through [`LuaFixture::exec`].
+//!
+//! Chunks passed to `exec`/`eval` may reference `$DATA_DIR`, `$REPO_ROOT`
+//! and `$ROOT` (the temp dir); they are substituted before loading.
+
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+
+use mlua::{FromLuaMulti, Lua};
+use tempfile::TempDir;
+
+/// Recording stubs for the hub globals. Loaded by [`LuaFixture::new`].
+const STUBS: &str = r#"
+_G.hooks = require("hub.hooks")
+
+-- Handlers registered with events.on, by event name. `command_message`
+-- emits are recorded; tests that need dispatch override events.emit.
+_G.event_handlers = {}
+_G.emitted = {}
+_G.events = {
+  on = function(name, fn)
+    _G.event_handlers[name] = fn
+    return "sub"
+  end,
+  off = function() end,
+  emit = function(name, data)
+    if name == "command_message" then table.insert(_G.emitted, data) end
+  end,
+}
+
+-- Manual timers: nothing fires until a test calls fire_timers.
+_G.pending_timers = {}
+local timer_seq = 0
+_G.timer = {
+  after = function(_, fn)
+    timer_seq = timer_seq + 1
+    local id = "after:" .. timer_seq
+    _G.pending_timers[id] = fn
+    return id
+  end,
+  every = function() return "heartbeat" end,
+  after_idle = function(id, _, fn)
+    _G.pending_timers[id] = fn
+    return id
+  end,
+  cancel = function(id) _G.pending_timers[id] = nil end,
+}
+
+--- Fire (and clear) pending timers whose id starts with `prefix` (all when nil).
+function _G.fire_timers(prefix)
+  local due = {}
+  for id, fn in pairs(_G.pending_timers) do
+    if not prefix or id:sub(1, #prefix) == prefix then due[id] = fn end
+  end
+  for id, fn in pairs(due) do
+    _G.pending_timers[id] = nil
+    fn()
+  end
+end
+
+-- config.get reads _G.test_config, config.env reads _G.test_env.
+-- config.repo_allowed checks _G.test_config.allowed_repos like the primitive.
+_G.test_config = {}
+_G.test_env = {}
+_G.config = {
+  data_dir = function() return "$DATA_DIR" end,
+  env = function(key) return _G.test_env[key] end,
+  get = function(key) return _G.test_config[key] end,
+  repo_allowed = function(repo)
+    local allowed = _G.test_config.allowed_repos or {}
+    if #allowed == 0 then return true end
+    for _, entry in ipairs(allowed) do
+      if entry:lower() == tostring(repo or ""):lower() then return true end
+    end
+    return false
+  end,
+  find_available_port = function() return 46000 end,
+  reserve_port = function() return 46000 end,
+  release_port = function() return true end,
+}
+
+-- Every spawned PTY handle is recorded; handles record kills. Session
+-- processes listed in _G.live_sessions count as still running.
+_G.spawned = {}
+_G.unregistered = {}
+_G.live_sessions = {}
+_G.hub = {
+  spawn_session = function(spawn_config, session_uuid)
+    if _G.fail_spawn then error("pty spawn failed") end
+    local handle = { session_uuid = session_uuid, spawn_config = spawn_config, killed = false }
+    function handle:kill() self.killed = true end
+    table.insert(_G.spawned, handle)
+    return handle
+  end,
+  register_session = function() return 1 end,
+  unregister_session = function(uuid) table.insert(_G.unregistered, uuid) end,
+  session_socket_exists = function(uuid) return _G.live_sessions[uuid] == true end,
+  update_manifest_workspaces = function() return true end,
+  server_id = function() return "hub-test" end,
+  hub_id = function() return "hub-test" end,
+  exe_dir = function() return "" end,
+  is_offline = function() return false end,
+}
+_G.hub_discovery = {
+  socket_path = function() return "$DATA_DIR/hub.sock" end,
+  manifest_path = function() return "$DATA_DIR/hub-manifest.json" end,
+}
+
+-- Existing worktrees keyed by branch; create_for_root records new branches.
+-- Removal requests (delete/cleanup) are recorded in _G.removals.
+_G.existing_worktrees = {}
+_G.created_worktrees = {}
+_G.removals = {}
+_G.worktree = {
+  list = function() return {} end,
+  list_for_root = function() return {} end,
+  find = function(branch) return _G.existing_worktrees[branch] end,
+  find_for_root = function(_, branch) return _G.existing_worktrees[branch] end,
+  create_for_root = function(_, branch)
+    local path = "$REPO_ROOT-" .. branch
+    fs.mkdir(path)
+    _G.created_worktrees[#_G.created_worktrees + 1] = branch
+    return path
+  end,
+  delete = function(path, branch)
+    _G.removals[#_G.removals + 1] = { kind = "delete", path = path, branch = branch }
+  end,
+  cleanup = function(path, branch, policy)
+    _G.removals[#_G.removals + 1] = {
+      kind = "cleanup", path = path, branch = branch, policy = policy,
+    }
+  end,
+}
+_G.spawn_targets = {
+  get = function(target_id)
+    return { id = target_id, path = "$REPO_ROOT", enabled = true }
+  end,
+  list = function()
+    return { { id = "target-1", path = "$REPO_ROOT", enabled = true } }
+  end,
+  inspect = function()
+    return {
+      repo_name = "owner/repo",
+      repo_root = "$REPO_ROOT",
+      supports_worktrees = true,
+      is_git_repo = true,
+    }
+  end,
+}
+
+-- ActionCable: subscription callbacks are kept by channel name (and the
+-- latest in _G.deliver_callback); performs are recorded, with acks and
+-- failure reports also collected on their own.
+_G.channel_callbacks = {}
+_G.performed = {}
+_G.acks = {}
+_G.failures = {}
+_G.action_cable = {
+  connect = function() return "conn" end,
+  subscribe = function(_, channel, _, callback)
+    _G.channel_callbacks[channel] = callback
+    _G.deliver_callback = callback
+    return channel
+  end,
+  unsubscribe = function() end,
+  perform = function(channel_id, action, data)
+    table.insert(_G.performed, { channel = channel_id, action = action, data = data })
+    if action == "ack" then table.insert(_G.acks, data.sequence or data.id) end
+    if action == "mark_failed" then table.insert(_G.failures, data) end
+  end,
+}
+"#;
+
+/// Extra stubs for the GitHub plugin's load-time calls (secrets, MCP token
+/// fetch, repo detection) plus a helper that delivers a message the way
+/// `Github::EventsChannel#to_wire` shapes it.
+const GITHUB_PLUGIN_STUBS: &str = r#"
+_G.secrets = { get = function() return nil end, set = function() end }
+_G.mcp = { proxy = function() end }
+_G.http = {
+  post = function() return nil, "offline" end,
+  request = function(_, callback) callback(nil, "offline") end,
+}
+hub.detect_repo = function() return "owner/repo" end
+hub.api_token = function() return nil end
+config.server_url = function() return "http://localhost" end
+
+--- Deliver a Github::EventsChannel message to the plugin's subscription.
+function _G.deliver_github(id, event_type, payload)
+  _G.channel_callbacks["Github::EventsChannel"]({
+    type = "message",
+    id = id,
+    event_type = event_type,
+    payload = payload,
+    repo = "owner/repo",
+    created_at = "2026-01-01T00:00:00Z",
+  }, "Github::EventsChannel")
+end
+"#;
+
+/// A Lua VM with the fs/json/log primitives and `lua/` on `package.path`.
+pub fn lua_vm() -> Lua {
+    let lua = Lua::new();
+
+    botster::lua::primitives::fs::register(&lua).expect("fs register");
+    botster::lua::primitives::json::register(&lua).expect("json register");
+    botster::lua::primitives::log::register(&lua).expect("log register");
+
+    let lua_dir = std::env::current_dir()
+        .unwrap()
+        .join("lua")
+        .to_str()
+        .unwrap()
+        .to_string();
+    lua.load(format!(
+        r#"package.path = "{lua_dir}/?.lua;{lua_dir}/?/init.lua;" .. package.path"#
+    ))
+    .exec()
+    .expect("set package.path");
+
+    lua
+}
+
+/// Lua VM with stubbed hub globals, backed by a temp dir holding a data dir
+/// (`$ROOT/data`) and a repo root (`$ROOT/repo`).
+pub struct LuaFixture {
+    pub dir: TempDir,
+    pub data_dir: PathBuf,
+    pub repo_root: PathBuf,
+    pub lua: Lua,
+}
+
+impl Default for LuaFixture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LuaFixture {
+    pub fn new() -> Self {
+        let dir = TempDir::new().unwrap();
+        let data_dir = dir.path().join("data");
+        let repo_root = dir.path().join("repo");
+        std::fs::create_dir_all(&data_dir).unwrap();
+        std::fs::create_dir_all(&repo_root).unwrap();
+
+        let fixture = Self {
+            lua: lua_vm(),
+            dir,
+            data_dir,
+            repo_root,
+        };
+        fixture.exec(STUBS);
+        fixture
+    }
+
+    /// Replace the VM with a freshly stubbed one on the same dirs, as a hub
+    /// restart would.
+    pub fn restart(&mut self) {
+        self.lua = lua_vm();
+        self.exec(STUBS);
+    }
+
+    /// Load the GitHub plugin template against the stubbed globals, as the
+    /// plugin loader would for a hub in `owner/repo`.
+    pub fn load_github_plugin(&self) {
+        self.exec(GITHUB_PLUGIN_STUBS);
+        let plugin =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("../app/templates/plugins/github/init.lua");
+        self.lua
+            .load(plugin.as_path())
+            .exec()
+            .expect("load github plugin");
+    }
+
+    /// Path under the fixture's temp dir.
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.path().join(name)
+    }
+
+    /// Create a directory under the temp dir that looks like a git worktree.
+    pub fn worktree(&self, name: &str) -> PathBuf {
+        let path = self.path(name);
+        std::fs::create_dir_all(&path).unwrap();
+        std::fs::write(path.join(".git"), "gitdir: /tmp/example").unwrap();
+        path
+    }
+
+    /// Substitute `$DATA_DIR`, `$REPO_ROOT` and `$ROOT` in a chunk.
+    pub fn expand(&self, chunk: &str) -> String {
+        chunk
+            .replace("$DATA_DIR", path_str(&self.data_dir))
+            .replace("$REPO_ROOT", path_str(&self.repo_root))
+            .replace("$ROOT", path_str(self.dir.path()))
+    }
+
+    /// Run a Lua chunk, panicking on error.
+    pub fn exec(&self, chunk: &str) {
+        self.lua
+            .load(self.expand(chunk))
+            .exec()
+            .expect("exec lua chunk");
+    }
+
+    /// Evaluate a Lua chunk, panicking on error.
+    pub fn eval<R: FromLuaMulti>(&self, chunk: &str) -> R {
+        self.lua
+            .load(self.expand(chunk))
+            .eval()
+            .expect("eval lua chunk")
+    }
+}
+
+fn path_str(path: &Path) -> &str {
+    path.to_str().expect("utf-8 temp path")
+}
@@ -0,0 +1,224 @@
+//! Rust-hosted Lua tests for the `.botster_done` completion sentinel.
+//!
+//! An agent writes `.botster_done` (JSON) in its worktree when it finishes.
+//! The hub watches for the file and marks the session completed with the
+//! parsed result.
+
+mod common;
+
+use common::LuaFixture;
+use mlua::Lua;
+use tempfile::TempDir;
+
+struct Fixture {
+    _dir: TempDir,
+    lua: Lua,
+    worktree_path: std::path::PathBuf,
+}
+
+fn fixture() -> Fixture {
+    let fixture = LuaFixture::new();
+    let worktree_path = fixture.worktree("botster-issue-7");
+    fixture.exec(
+        r#"
+        -- Fake directory watcher: tests fire the callback after writing files.
+        _G.watch_callbacks = {}
+        _G.watch = {
+          directory = function(path, opts, cb)
+            _G.watch_callbacks[#_G.watch_callbacks + 1] = { path = path, opts = opts, cb = cb }
+            return "watch-" .. #_G.watch_callbacks
+          end,
+          unwatch = function() return true end,
+        }
+        function _G.fire_watch(kind)
+          for _, w in ipairs(_G.watch_callbacks) do
+            w.cb({ path = w.path .. "/.botster_done", kind = kind or "create" })
+          end
+        end
+
+        _G.lifecycle = {}
+        hooks.on("agent_lifecycle", "test_capture", function(payload)
+          _G.lifecycle[#_G.lifecycle + 1] = payload.status
+        end)
+        _G.completed = {}
+        hooks.on("agent_completed", "test_capture", function(payload)
+          _G.completed[#_G.completed + 1] = payload
+        end)
+
+        function _G.spawn_worktree_agent(worktree_path)
+          local Agent = require("lib.agent")
+          local agent = Agent.new({
+            repo = "owner/repo",
+            branch_name = "botster-issue-7",
+            worktree_path = worktree_path,
+            session = { name = "claude", command = "bash" },
+            target_id = "target-1",
+            target_path = "$REPO_ROOT",
+            target_repo = "owner/repo",
+          })
+          agent.session = { write = function() end, kill = function() end }
+          return agent
+        end
+    "#,
+    );
+
+    let LuaFixture { dir, lua, .. } = fixture;
+    Fixture {
+        _dir: dir,
+        lua,
+        worktree_path,
+    }
+}
+
+#[test]
+fn writing_sentinel_marks_agent_completed_with_parsed_result() {
+    let f = fixture();
+
+    let (before, action, number, url, lifecycle, hook_count): (
+        bool,
+        String,
+        i64,
+        String,
+        bool,
+        i64,
+    ) = f
+        .lua
+        .load(format!(
+            r#"
+            local CompletionSentinel = require("lib.completion_sentinel")
+            local agent = spawn_worktree_agent("{worktree_path}")
+            CompletionSentinel.watch(agent)
+            local before = agent.completion == nil
+
+            fs.write("{worktree_path}/.botster_done",
+              '{{"action":"pr_opened","number":123,"url":"https://github.com/owner/repo/pull/123"}}')
+            fire_watch("create")
+            -- A second write after completion is ignored.
+            fire_watch("modify")
+
+            local saw_completed = false
+            for _, status in ipairs(_G.lifecycle) do
+              if status == "completed" then saw_completed = true end
+            end
+            local c = agent.completion or {{}}
+            return before, c.action or "", c.number or 0, c.url or "", saw_completed, #_G.completed
+        "#,
+            worktree_path = f.worktree_path.to_str().unwrap(),
+        ))
+        .eval()
+        .expect("sentinel completion should evaluate");
+
+    assert!(before, "agent is not complete before the sentinel exists");
+    assert_eq!(action, "pr_opened");
+    assert_eq!(number, 123);
+    assert_eq!(url, "https://github.com/owner/repo/pull/123");
+    assert!(lifecycle, "completed lifecycle event should fire");
+    assert_eq!(hook_count, 1, "agent_completed fires exactly once");
+    assert!(
+        !f.worktree_path.join(".botster_done").exists(),
+        "a handled sentinel is moved aside"
+    );
+    assert!(f.worktree_path.join(".botster_done.consumed").exists());
+}
+
+#[test]
+fn existing_sentinel_is_picked_up_when_watch_starts() {
+    let f = fixture();
+
+    let action: String = f
+        .lua
+        .load(format!(
+            r#"
+            local agent = spawn_worktree_agent("{worktree_path}")
+            fs.write("{worktree_path}/.botster_done",
+              '{{"action":"commented","url":"https://github.com/owner/repo/issues/7#issuecomment-1"}}')
+            require("lib.completion_sentinel").watch(agent)
+            return agent.completion and agent.completion.action or ""
+        "#,
+            worktree_path = f.worktree_path.to_str().unwrap(),
+        ))
+        .eval()
+        .expect("startup check should evaluate");
+
+    assert_eq!(action, "commented");
+}
+
+#[test]
+fn leftover_sentinel_does_not_complete_a_new_session() {
+    let f = fixture();
+    let sentinel = f.worktree_path.join(".botster_done");
+    std::fs::write(
+        &sentinel,
+        r#"{"action":"commented","url":"https://github.com/owner/repo/issues/7#issuecomment-1"}"#,
+    )
+    .unwrap();
+    // Written by the previous agent in this worktree, an hour ago.
+    let an_hour_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+    std::fs::File::options()
+        .write(true)
+        .open(&sentinel)
+        .unwrap()
+        .set_modified(an_hour_ago)
+        .unwrap();
+
+    let (on_watch, after_new_write): (bool, bool) = f
+        .lua
+        .load(format!(
+            r#"
+            local agent = spawn_worktree_agent("{worktree_path}")
+            require("lib.completion_sentinel").watch(agent)
+            local on_watch = agent.completion ~= nil
+
+            fs.write("{worktree_path}/.botster_done",
+              '{{"action":"commented","url":"https://github.com/owner/repo/issues/7#issuecomment-2"}}')
+            fire_watch("modify")
+            return on_watch, agent.completion ~= nil
+        "#,
+            worktree_path = f.worktree_path.to_str().unwrap(),
+        ))
+        .eval()
+        .expect("leftover sentinel should evaluate");
+
+    assert!(!on_watch, "a sentinel older than the session is ignored");
+    assert!(
+        after_new_write,
+        "the new agent's own sentinel still completes it"
+    );
+}
+
+#[test]
+fn invalid_sentinel_is_ignored() {
+    let f = fixture();
+
+    let (completed, errors): (bool, Vec<String>) = f
+        .lua
+        .load(format!(
+            r#"
+            local CompletionSentinel = require("lib.completion_sentinel")
+            local agent = spawn_worktree_agent("{worktree_path}")
+            CompletionSentinel.watch(agent)
+            fs.write("{worktree_path}/.botster_done", '{{"action":"pr_opened","url":"x"}}')
+            fire_watch("create")
+
+            local errors = {{}}
+            for _, content in ipairs({{ "", "not json", '{{"action":"merged"}}' }}) do
+              local _, err = CompletionSentinel.parse(content)
+              errors[#errors + 1] = err or ""
+            end
+            return agent.completion ~= nil, errors
+        "#,
+            worktree_path = f.worktree_path.to_str().unwrap(),
+        ))
+        .eval()
+        .expect("invalid sentinel should evaluate");
+
+    assert!(!completed, "pr_opened without a number must not complete");
+    assert_eq!(
+        errors,
+        vec![
+            "empty sentinel file".to_string(),
+            "sentinel file is not a JSON object".to_string(),
+            "unknown action \"merged\"".to_string(),
+        ]
+    );
+}
@@ -0,0 +1,100 @@
+//! Rust-hosted Lua tests for reporting failed agent creation to browsers.
+//!
+//! A failed `create_agent`/`reopen_worktree` hub command answers with an
+//! error frame carrying the failing stage (and, for worktree failures, the
+//! worktrees that could be reopened instead). Success is only ever reported
+//! by `agent_created`.
+
+mod common;
+
+use common::LuaFixture;
+use mlua::Lua;
+use tempfile::TempDir;
+
+fn fixture() -> (TempDir, Lua, String) {
+    let fixture = LuaFixture::new();
+    let agent_dir = fixture.repo_root.join(".botster/agents/claude");
+    std::fs::create_dir_all(&agent_dir).unwrap();
+    std::fs::write(agent_dir.join("initialization"), "true\n").unwrap();
+
+    fixture.exec(
+        r#"
+        _G.worktree.list_for_root = function()
+          return { { path = "$REPO_ROOT-existing", branch = "existing" } }
+        end
+
+        require("handlers.agents")
+        require("handlers.commands")
+        _G.created = {}
+        hooks.on("agent_created", "test_created", function(info)
+            created[#created + 1] = info
+        end)
+        _G.sent = {}
+        _G.client = { send = function(_, msg) sent[#sent + 1] = msg end }
+        "#,
+    );
+
+    let repo_root = fixture.repo_root.to_str().unwrap().to_string();
+    let LuaFixture { dir, lua, .. } = fixture;
+    (dir, lua, repo_root)
+}
+
+#[test]
+fn spawn_failure_sends_structured_error_and_no_success() {
+    let (_dir, lua, repo_root) = fixture();
+    let (frames, frame_type, stage, error, created): (usize, String, String, String, usize) = lua
+        .load(format!(
+            r#"
+            fail_spawn = true
+            require("lib.commands").dispatch(client, "sub-1", {{
+              type = "create_agent",
+              issue_or_branch = "12",
+              agent_name = "claude",
+              target_id = "target-1",
+              target_path = "{repo_root}",
+              target_repo = "owner/repo",
+            }})
+            local frame = sent[1] or {{}}
+            return #sent, frame.type or "", frame.stage or "", frame.error or "", #created
+        "#
+        ))
+        .eval()
+        .expect("failed create should evaluate");
+
+    assert_eq!(frames, 1);
+    assert_eq!(frame_type, "error");
+    assert_eq!(stage, "spawn");
+    assert!(error.contains("pty spawn failed"), "got {error}");
+    assert_eq!(created, 0, "agent_created must not fire for a failed spawn");
+}
+
+#[test]
+fn missing_worktree_lists_available_worktrees() {
+    let (_dir, lua, repo_root) = fixture();
+    let (stage, paths, created): (String, Vec<String>, usize) = lua
+        .load(format!(
+            r#"
+            require("lib.commands").dispatch(client, "sub-1", {{
+              type = "reopen_worktree",
+              path = "{repo_root}-gone",
+              branch = "gone",
+              agent_name = "claude",
+              target_id = "target-1",
+              target_path = "{repo_root}",
+              target_repo = "owner/repo",
+            }})
+            local frame = sent[1] or {{}}
+            local paths = {{}}
+            for _, wt in ipairs(frame.worktrees or {{}}) do
+                paths[#paths + 1] = wt.path
+            end
+            return frame.stage or "", paths, #created
+        "#
+        ))
+        .eval()
+        .expect("reopen of a missing worktree should evaluate");
+
+    assert_eq!(stage, "worktree");
+    assert_eq!(paths, vec![format!("{repo_root}-existing")]);
+    assert_eq!(created, 0);
+}
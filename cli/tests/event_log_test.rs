@@ -0,0 +1,131 @@
+//! Rust-hosted Lua tests for the replayable hub event log.
+//!
+//! `lib/event_log.lua` buffers recent hub events and replays them, marked
+//! historical, on a client's first hub subscribe. These tests record events,
+//! then subscribe a client through `Client:handle_subscribe` with a capturing
+//! transport and read back the frames it was sent.
+
+#![expect(clippy::unwrap_used, clippy::expect_used, reason = "test-code brevity")]
+
+mod common;
+
+use mlua::{Lua, LuaSerdeExt, Value};
+use serde_json::Value as JsonValue;
+
+fn create_lua_vm() -> Lua {
+    let lua = common::lua_vm();
+
+    lua.load(
+        r#"
+        _G.hooks = require("hub.hooks")
+        _G.EventLog = require("lib.event_log")
+        EventLog._reset_for_tests()
+        _G.broadcast = {}
+        EventLog.set_broadcaster(function(frame) table.insert(_G.broadcast, frame) end)
+
+        local Client = require("lib.client")
+
+        --- Subscribe a new client to the hub channel; return the
+        --- hub_event frames it received.
+        function _G.subscribe(peer_id, sub_id, client)
+          client = client or Client.new(peer_id, {
+            send = function(msg) table.insert(client_sent[peer_id], msg) end,
+          })
+          _G.client_sent = _G.client_sent or {}
+          client_sent[peer_id] = client_sent[peer_id] or {}
+          client:handle_subscribe({ subscriptionId = sub_id, channel = "hub" })
+          local events = {}
+          for _, msg in ipairs(client_sent[peer_id]) do
+            if msg.type == "hub_event" then table.insert(events, msg) end
+          end
+          client_sent[peer_id] = {}
+          return events, client
+        end
+    "#,
+    )
+    .exec()
+    .expect("stub globals");
+
+    lua
+}
+
+fn to_json(lua: &Lua, value: Value) -> JsonValue {
+    lua.from_value(value).expect("lua -> json")
+}
+
+#[test]
+fn late_subscriber_receives_recent_events_in_order() {
+    let lua = create_lua_vm();
+    let (live, replayed): (Value, Value) = lua
+        .load(
+            r#"
+            EventLog.record("agent_created", { session_uuid = "sess-1" })
+            EventLog.record("agent_lifecycle", { agent_id = "sess-1", status = "completed" })
+            EventLog.record("agent_deleted", { session_uuid = "sess-2" })
+            local events = subscribe("browser-late", "hub-sub-1")
+            return broadcast, events
+        "#,
+        )
+        .eval()
+        .unwrap();
+    let live = to_json(&lua, live);
+    let replayed = to_json(&lua, replayed);
+
+    let names: Vec<&str> = replayed
+        .as_array()
+        .expect("replayed events")
+        .iter()
+        .map(|frame| frame["event"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["agent_created", "agent_lifecycle", "agent_deleted"]);
+    for (i, frame) in replayed.as_array().unwrap().iter().enumerate() {
+        assert_eq!(frame["historical"], true);
+        assert_eq!(frame["subscriptionId"], "hub-sub-1");
+        assert_eq!(frame["seq"], i + 1);
+    }
+    assert_eq!(replayed[1]["data"]["status"], "completed");
+
+    // Subscribers that were already connected saw them live.
+    assert_eq!(live.as_array().unwrap().len(), 3);
+    assert_eq!(live[0]["historical"], false);
+}
+
+#[test]
+fn replay_happens_once_per_client() {
+    let lua = create_lua_vm();
+    let (first, second): (usize, usize) = lua
+        .load(
+            r#"
+            EventLog.record("agent_created", { session_uuid = "sess-1" })
+            local first, client = subscribe("browser-a", "hub-sub-1")
+            local second = subscribe("browser-a", "hub-sub-2", client)
+            return #first, #second
+        "#,
+        )
+        .eval()
+        .unwrap();
+
+    assert_eq!(first, 1);
+    assert_eq!(second, 0, "a second hub subscription must not replay again");
+}
+
+#[test]
+fn buffer_keeps_only_the_most_recent_events() {
+    let lua = create_lua_vm();
+    let (count, oldest, newest): (usize, u64, u64) = lua
+        .load(
+            r#"
+            for i = 1, EventLog.MAX_EVENTS + 5 do
+              EventLog.record("agent_created", { session_uuid = "sess-" .. i })
+            end
+            local entries = EventLog.entries()
+            return #entries, entries[1].seq, entries[#entries].seq
+        "#,
+        )
+        .eval()
+        .unwrap();
+
+    assert_eq!(count, 100);
+    assert_eq!(oldest, 6);
+    assert_eq!(newest, 105);
+}
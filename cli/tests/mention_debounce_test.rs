@@ -0,0 +1,295 @@
+//! Rust-hosted Lua tests for mention de-bounce.
+//!
+//! Mentions for an agent that already exists are delivered as PTY input.
+//! Mentions arriving within the de-bounce window are coalesced into a single
+//! submit so bursts don't interleave in the agent's input. This holds for
+//! create_agent commands and for mentions the GitHub plugin receives.
+
+mod common;
+
+use common::LuaFixture;
+use mlua::Lua;
+use tempfile::TempDir;
+
+fn fixture() -> (TempDir, Lua) {
+    let fixture = LuaFixture::new();
+    fixture.worktree("repo-botster-issue-42");
+    fixture.exec(
+        r#"
+        _G.existing_worktrees["botster-issue-42"] = "$ROOT/repo-botster-issue-42"
+
+        -- Spawn an agent for issue 42 whose PTY records submitted messages.
+        function _G.spawn_mention_target()
+          local agents = require("handlers.agents")
+          local agent = assert(agents.handle_create_agent("42", nil, nil, nil, nil, {
+            issue_number = 42,
+            workspace = "owner/repo#42",
+          }, {
+            target_id = "target-1",
+            target_path = "$REPO_ROOT",
+            target_repo = "owner/repo",
+          }))
+          agent.submitted = {}
+          agent.session = {
+            send_message = function(_, text) agent.submitted[#agent.submitted + 1] = text end,
+            kill = function() end,
+          }
+          return agent
+        end
+
+        function _G.mention(prompt)
+          _G.event_handlers["command_message"]({
+            type = "create_agent",
+            issue_or_branch = "42",
+            prompt = prompt,
+            target_id = "target-1",
+            target_path = "$REPO_ROOT",
+            target_repo = "owner/repo",
+          })
+        end
+    "#,
+    );
+    let LuaFixture { dir, lua, .. } = fixture;
+    (dir, lua)
+}
+
+#[test]
+fn mentions_within_window_are_submitted_once_in_order() {
+    let (_dir, lua) = fixture();
+
+    let (before_fire, submitted): (i64, Vec<String>) = lua
+        .load(
+            r#"
+            local agent = spawn_mention_target()
+            mention("first")
+            mention("second")
+            mention("third")
+            local before = #agent.submitted
+            fire_timers("mention_debounce:")
+            return before, agent.submitted
+        "#,
+        )
+        .eval()
+        .expect("mention burst should evaluate");
+
+    assert_eq!(
+        before_fire, 0,
+        "nothing is submitted while the window is open"
+    );
+    assert_eq!(
+        submitted.len(),
+        1,
+        "three mentions should produce one submit"
+    );
+    let text = &submitted[0];
+    let first = text.find("first").expect("first mention present");
+    let second = text.find("second").expect("second mention present");
+    let third = text.find("third").expect("third mention present");
+    assert!(
+        first < second && second < third,
+        "order is preserved: {text}"
+    );
+    assert_eq!(text.matches("=== NEW MENTION").count(), 1);
+}
+
+#[test]
+fn duplicate_mention_in_window_is_dropped() {
+    let (_dir, lua) = fixture();
+
+    let submitted: Vec<String> = lua
+        .load(
+            r#"
+            local agent = spawn_mention_target()
+            mention("please rebase")
+            mention("please rebase")
+            fire_timers("mention_debounce:")
+            return agent.submitted
+        "#,
+        )
+        .eval()
+        .expect("duplicate mentions should evaluate");
+
+    assert_eq!(submitted.len(), 1);
+    assert_eq!(submitted[0].matches("please rebase").count(), 1);
+}
+
+#[test]
+fn zero_window_submits_each_mention_immediately() {
+    let (_dir, lua) = fixture();
+
+    let count: i64 = lua
+        .load(
+            r#"
+            _G.test_config.mention_debounce_secs = 0
+            local agent = spawn_mention_target()
+            mention("one")
+            mention("two")
+            return #agent.submitted
+        "#,
+        )
+        .eval()
+        .expect("immediate mentions should evaluate");
+
+    assert_eq!(count, 2);
+}
+
+#[test]
+fn over_limit_mention_is_truncated_before_delivery() {
+    let (_dir, lua) = fixture();
+
+    let submitted: Vec<String> = lua
+        .load(
+            r#"
+            _G.test_config.max_prompt_chars = 20
+            _G.test_config.prompt_truncation = "head"
+            local agent = spawn_mention_target()
+            mention("short one")
+            mention("start of a pasted log " .. string.rep("noise ", 1000))
+            fire_timers("mention_debounce:")
+            return agent.submitted
+        "#,
+        )
+        .eval()
+        .expect("long mention should evaluate");
+
+    assert_eq!(submitted.len(), 1);
+    let text = &submitted[0];
+    assert!(text.contains("short one"), "{text}");
+    assert!(
+        text.contains("start of a pasted lo\n\n[... truncated"),
+        "{text}"
+    );
+    assert!(!text.contains("noise noise"), "{text}");
+}
+
+#[test]
+fn github_plugin_mentions_are_coalesced() {
+    let fixture = LuaFixture::new();
+    fixture.worktree("repo-botster-issue-42");
+    fixture.load_github_plugin();
+    fixture.exec(
+        r#"
+        _G.existing_worktrees["botster-issue-42"] = "$ROOT/repo-botster-issue-42"
+        _G.agent = assert(require("handlers.agents").handle_create_agent("42", nil, nil, nil, nil, {
+          issue_number = 42,
+          workspace = "owner/repo#42",
+        }, {
+          target_id = "target-1",
+          target_path = "$REPO_ROOT",
+          target_repo = "owner/repo",
+        }))
+        agent.submitted = {}
+        agent.session = {
+          send_message = function(_, text) agent.submitted[#agent.submitted + 1] = text end,
+          kill = function() end,
+        }
+    "#,
+    );
+
+    let (before_fire, submitted): (i64, Vec<String>) = fixture.eval(
+        r#"
+        deliver_github(1, "github_mention", { issue_number = 42, prompt = "first" })
+        deliver_github(2, "github_mention", { issue_number = 42, prompt = "second" })
+        local before = #agent.submitted
+        fire_timers("mention_debounce:")
+        return before, agent.submitted
+    "#,
+    );
+
+    assert_eq!(before_fire, 0);
+    assert_eq!(submitted.len(), 1, "two comments produce one submit");
+    assert!(submitted[0].contains("first") && submitted[0].contains("second"));
+}
+
+#[test]
+fn github_plugin_mentions_are_truncated() {
+    let fixture = LuaFixture::new();
+    fixture.worktree("repo-botster-issue-42");
+    fixture.load_github_plugin();
+    let submitted: Vec<String> = fixture.eval(
+        r#"
+        _G.test_config.max_prompt_chars = 20
+        _G.test_config.prompt_truncation = "head"
+        _G.existing_worktrees["botster-issue-42"] = "$ROOT/repo-botster-issue-42"
+        local agent = assert(require("handlers.agents").handle_create_agent("42", nil, nil, nil, nil, {
+          issue_number = 42,
+          workspace = "owner/repo#42",
+        }, {
+          target_id = "target-1",
+          target_path = "$REPO_ROOT",
+          target_repo = "owner/repo",
+        }))
+        agent.submitted = {}
+        agent.session = {
+          send_message = function(_, text) agent.submitted[#agent.submitted + 1] = text end,
+          kill = function() end,
+        }
+        deliver_github(1, "github_mention", {
+          issue_number = 42,
+          prompt = "start of a pasted log " .. string.rep("noise ", 1000),
+        })
+        fire_timers("mention_debounce:")
+        return agent.submitted
+    "#,
+    );
+
+    assert_eq!(submitted.len(), 1);
+    assert!(
+        submitted[0].contains("start of a pasted lo\n\n[... truncated"),
+        "{}",
+        submitted[0]
+    );
+    assert!(!submitted[0].contains("noise noise"));
+}
+
+#[test]
+fn steady_stream_is_delivered_after_max_wait() {
+    let (_dir, lua) = fixture();
+
+    let (before_deadline, submitted): (i64, Vec<String>) = lua
+        .load(
+            r#"
+            local agent = spawn_mention_target()
+            mention("first")
+            mention("second")
+            local before = #agent.submitted
+            -- Mentions keep arriving, so the quiet window never passes; the
+            -- max-wait deadline (5 windows after "first") fires instead.
+            fire_timers("after:")
+            mention("third")
+            fire_timers("mention_debounce:")
+            return before, agent.submitted
+        "#,
+        )
+        .eval()
+        .expect("mention stream should evaluate");
+
+    assert_eq!(before_deadline, 0);
+    assert_eq!(
+        submitted.len(),
+        2,
+        "the deadline flushes, then a new window starts"
+    );
+    assert!(submitted[0].contains("first") && submitted[0].contains("second"));
+    assert!(submitted[1].contains("third"));
+}
+
+#[test]
+fn quiet_window_flush_cancels_the_max_wait() {
+    let (_dir, lua) = fixture();
+
+    let submitted: i64 = lua
+        .load(
+            r#"
+            local agent = spawn_mention_target()
+            mention("first")
+            fire_timers("mention_debounce:")
+            fire_timers("after:")
+            return #agent.submitted
+        "#,
+        )
+        .eval()
+        .expect("mention should evaluate");
+
+    assert_eq!(submitted, 1);
+}
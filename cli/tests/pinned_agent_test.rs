@@ -0,0 +1,141 @@
+//! Rust-hosted Lua tests for pinned agents and idle auto-close.
+//!
+//! With `idle_close_secs` set, `handlers/idle_reaper.lua` closes agents that
+//! stay idle that long. Pinned agents are exempt. Timers are stubbed so a test
+//! can fire the pending idle-close callbacks directly.
+
+mod common;
+
+use common::LuaFixture;
+use mlua::Lua;
+use tempfile::TempDir;
+
+struct Fixture {
+    _dir: TempDir,
+    lua: Lua,
+}
+
+fn fixture() -> Fixture {
+    let fixture = LuaFixture::new();
+    fixture.worktree("repo-botster-issue-1");
+    fixture.worktree("repo-botster-issue-2");
+    fixture.exec(
+        r#"
+        _G.worktree.delete = function() error("idle close must keep the worktree") end
+        _G.worktree.cleanup = function() error("idle close must keep the worktree") end
+
+        local agents = require("handlers.agents")
+        require("handlers.idle_reaper")
+
+        --- Spawn an agent for `issue` in an existing worktree.
+        function _G.spawn(issue, worktree_path, pinned)
+          _G.existing_worktrees["botster-issue-" .. issue] = worktree_path
+          local agent = assert(agents.handle_create_agent(tostring(issue), nil, nil, nil, nil,
+            { issue_number = issue, pinned = pinned }, {
+              target_id = "target-1",
+              target_path = "$REPO_ROOT",
+              target_repo = "owner/repo",
+            }))
+          agent.session = { kill = function() end }
+          return agent
+        end
+
+        --- Drive an agent through output and back to idle, as connections.lua does.
+        function _G.go_idle(agent)
+          agent:update({ is_idle = false })
+          agent:update({ is_idle = true })
+        end
+
+        function _G.is_open(agent)
+          return require("lib.agent").get(agent.session_uuid) ~= nil
+        end
+
+        _G.wt1 = "$ROOT/repo-botster-issue-1"
+        _G.wt2 = "$ROOT/repo-botster-issue-2"
+    "#,
+    );
+    let LuaFixture { dir, lua, .. } = fixture;
+    Fixture { _dir: dir, lua }
+}
+
+#[test]
+fn pinned_idle_agent_survives_while_unpinned_is_closed() {
+    let f = fixture();
+    let (pinned_open, unpinned_open): (bool, bool) = f
+        .lua
+        .load(
+            r#"
+            _G.test_config.idle_close_secs = 600
+            local pinned = spawn(1, wt1, true)
+            local unpinned = spawn(2, wt2)
+            assert(pinned.pinned == true and pinned:info().pinned == true)
+            assert(pinned.metadata.pinned == nil, "pinned is a session field, not metadata")
+
+            go_idle(pinned)
+            go_idle(unpinned)
+            fire_timers("idle_close:")
+            return is_open(pinned), is_open(unpinned)
+        "#,
+        )
+        .eval()
+        .unwrap();
+
+    assert!(pinned_open, "pinned agent must not be auto-closed");
+    assert!(!unpinned_open, "idle unpinned agent should be auto-closed");
+}
+
+#[test]
+fn toggling_pin_on_idle_agent_cancels_and_restarts_countdown() {
+    let f = fixture();
+    let (after_pin, after_unpin): (bool, bool) = f
+        .lua
+        .load(
+            r#"
+            _G.test_config.idle_close_secs = 600
+            local agent = spawn(1, wt1)
+            go_idle(agent)
+            agent:update({ pinned = true })
+            fire_timers("idle_close:")
+            local after_pin = is_open(agent)
+
+            agent:update({ pinned = false })
+            fire_timers("idle_close:")
+            return after_pin, is_open(agent)
+        "#,
+        )
+        .eval()
+        .unwrap();
+
+    assert!(after_pin, "pinning should cancel the pending idle close");
+    assert!(
+        !after_unpin,
+        "unpinning an idle agent should restart the countdown"
+    );
+}
+
+#[test]
+fn idle_close_is_off_by_default_and_ignores_active_agents() {
+    let f = fixture();
+    let (unconfigured_open, active_open): (bool, bool) = f
+        .lua
+        .load(
+            r#"
+            local idle = spawn(1, wt1)
+            go_idle(idle)
+            fire_timers("idle_close:")
+            local unconfigured_open = is_open(idle)
+
+            _G.test_config.idle_close_secs = 600
+            local active = spawn(2, wt2)
+            go_idle(active)
+            active:update({ is_idle = false })
+            fire_timers("idle_close:")
+            return unconfigured_open, is_open(active)
+        "#,
+        )
+        .eval()
+        .unwrap();
+
+    assert!(unconfigured_open, "no idle_close_secs means no auto-close");
+    assert!(active_open, "output should cancel the pending idle close");
+}
@@ -0,0 +1,103 @@
+//! Rust-hosted Lua tests for the `poll_completed` hook.
+//!
+//! Each heartbeat closes a command delivery cycle and reports how many server
+//! commands were fetched and whether they spawned, reused, or failed agents.
+
+mod common;
+
+use common::LuaFixture;
+
+#[test]
+fn poll_with_new_and_existing_agent_reports_spawned_and_reused() {
+    let fixture = LuaFixture::new();
+    let worktree = fixture.worktree("repo-botster-issue-42");
+    fixture.exec(
+        r#"
+        function _G.command(issue)
+          _G.event_handlers["command_message"]({
+            type = "create_agent",
+            issue_or_branch = issue,
+            prompt = "please look",
+            target_id = "target-1",
+            target_path = "$REPO_ROOT",
+            target_repo = "owner/repo",
+          })
+        end
+
+        _G.polls = {}
+        hooks.on("poll_completed", "test_capture", function(counts)
+          _G.polls[#_G.polls + 1] = counts
+        end)
+    "#,
+    );
+
+    let (emitted, counts, quiet): (i64, mlua::Table, bool) = fixture
+        .lua
+        .load(format!(
+            r#"
+            _G.existing_worktrees["botster-issue-42"] = "{worktree}"
+            local CommandStats = require("lib.command_stats")
+            local agents = require("handlers.agents")
+            local existing = assert(agents.handle_create_agent("42", nil, nil, nil, nil,
+              {{ issue_number = 42 }}, {{
+                target_id = "target-1",
+                target_path = "{repo_root}",
+                target_repo = "owner/repo",
+              }}))
+            existing.session = {{ send_message = function() end, kill = function() end }}
+
+            -- Two server messages: one for a new issue, one for issue 42.
+            CommandStats.record_fetched()
+            command("7")
+            CommandStats.record_fetched()
+            command("42")
+
+            CommandStats.flush()
+            local quiet = CommandStats.flush() == nil
+            return #_G.polls, _G.polls[1], quiet
+        "#,
+            worktree = worktree.to_str().unwrap(),
+            repo_root = fixture.repo_root.to_str().unwrap(),
+        ))
+        .eval()
+        .expect("poll cycle should evaluate");
+
+    assert_eq!(emitted, 1, "quiet cycles should not emit");
+    assert_eq!(counts.get::<i64>("fetched").unwrap(), 2);
+    assert_eq!(counts.get::<i64>("spawned").unwrap(), 1);
+    assert_eq!(counts.get::<i64>("reused").unwrap(), 1);
+    assert_eq!(counts.get::<i64>("failed").unwrap(), 0);
+    assert!(quiet, "counters reset after a flush");
+}
+
+#[test]
+fn github_plugin_events_are_counted_and_logged_for_clients() {
+    let fixture = LuaFixture::new();
+    fixture.worktree("repo-botster-issue-7");
+    fixture.load_github_plugin();
+
+    let (fetched, spawned, event, logged): (i64, i64, String, i64) = fixture.eval(
+        r#"
+        _G.existing_worktrees["botster-issue-7"] = "$ROOT/repo-botster-issue-7"
+        require("handlers.agents")
+        events.emit = function(name, data)
+          if name == "command_message" then _G.event_handlers["command_message"](data) end
+        end
+
+        deliver_github(1, "github_mention", { issue_number = 7, prompt = "please look" })
+        local counts = require("lib.command_stats").flush()
+
+        local entries = require("lib.event_log").entries()
+        local last = entries[#entries]
+        return counts.fetched, counts.spawned, last.event, last.data.fetched
+    "#,
+    );
+
+    assert_eq!(fetched, 1, "plugin events count as fetched");
+    assert_eq!(spawned, 1);
+    assert_eq!(
+        event, "poll_completed",
+        "the TUI and browsers see the cycle"
+    );
+    assert_eq!(logged, 1);
+}
@@ -0,0 +1,236 @@
+//! Rust-hosted Lua tests for per-port preview reconnection state.
+//!
+//! Preview probe retry counters are keyed by forwarded port. They bound how
+//! often an unreachable tunnel is re-probed, must be dropped when the port is
+//! freed, and must not leak into a later agent that is handed the same port.
+
+mod common;
+
+use common::LuaFixture;
+use mlua::Lua;
+use tempfile::TempDir;
+
+fn fixture() -> (TempDir, Lua, String) {
+    let fixture = LuaFixture::new();
+    fixture.exec(
+        r#"
+        function _G.spawn_forwarding_agent(repo_root)
+          local Agent = require("lib.agent")
+          local agent = Agent.new({
+            repo = "owner/repo",
+            branch_name = "feature-preview",
+            worktree_path = repo_root,
+            session = { name = "server", command = "bash", forward_port = true },
+            target_id = "target-1",
+            target_path = repo_root,
+            target_repo = "owner/repo",
+          })
+          agent.session = { write = function() end, kill = function() end }
+          return agent
+        end
+    "#,
+    );
+
+    let repo_root = fixture.repo_root.to_str().unwrap().to_string();
+    let LuaFixture { dir, lua, .. } = fixture;
+    (dir, lua, repo_root)
+}
+
+#[test]
+fn closing_agent_clears_preview_state_and_reused_port_starts_fresh() {
+    let (_dir, lua, repo_root) = fixture();
+
+    let (first_port, cleared, second_port, retries): (i64, bool, i64, i64) = lua
+        .load(format!(
+            r#"
+            local PreviewPortState = require("lib.preview_port_state")
+            local first = spawn_forwarding_agent("{repo_root}")
+            local port = first._port
+            PreviewPortState.record_failure(port)
+            PreviewPortState.record_failure(port)
+            assert(PreviewPortState.get(port).retries == 2)
+
+            first:close(false)
+            local cleared = PreviewPortState.get(port) == nil
+
+            local second = spawn_forwarding_agent("{repo_root}")
+            local entry = PreviewPortState.touch(second._port)
+            return port, cleared, second._port, entry.retries
+        "#
+        ))
+        .eval()
+        .expect("preview port lifecycle should evaluate");
+
+    assert_eq!(first_port, 46000);
+    assert!(cleared, "closing the agent should drop its port state");
+    assert_eq!(second_port, first_port, "the freed port should be reused");
+    assert_eq!(retries, 0, "reused port must not inherit retry counters");
+}
+
+#[test]
+fn unreachable_preview_is_reprobed_until_retries_run_out() {
+    let (_dir, lua, repo_root) = fixture();
+
+    let (probes, status, reset): (i64, String, i64) = lua
+        .load(format!(
+            r#"
+            local HostedPreview = require("lib.hosted_preview")
+            local PreviewPortState = require("lib.preview_port_state")
+            local Session = require("lib.session")
+            local url = "https://quiet-tunnel.trycloudflare.com"
+
+            local parent = spawn_forwarding_agent("{repo_root}")
+            parent.hosted_preview = {{ connector_session_uuid = "connector-1" }}
+            local connector = {{
+              session_uuid = "connector-1",
+              metadata = {{
+                system_session = true,
+                system_kind = "hosted_preview_connector",
+                preview_url = url,
+              }},
+              get_meta = function(self, key) return self.metadata[key] end,
+            }}
+            local get = Session.get
+            Session.get = function(id)
+              if id == "connector-1" then return connector end
+              return get(id)
+            end
+
+            local probes = 0
+            hub.probe_preview_dns = function() probes = probes + 1 end
+            local function probe_result(ready)
+              HostedPreview.handle_dns_ready({{
+                connector_session_uuid = "connector-1",
+                parent_session_uuid = parent.session_uuid,
+                url = url,
+                ready = ready,
+                error = not ready and "timed out" or nil,
+              }})
+            end
+
+            -- A success resets the budget for later failures.
+            probe_result(false)
+            probe_result(true)
+            local reset = PreviewPortState.get(parent._port).retries
+
+            for _ = 1, HostedPreview.MAX_PROBE_RETRIES + 1 do
+              probe_result(false)
+            end
+            Session.get = get
+            return probes, parent.hosted_preview.status, reset
+        "#
+        ))
+        .eval()
+        .expect("probe retries should evaluate");
+
+    assert_eq!(reset, 0);
+    assert_eq!(
+        probes,
+        1 + 3,
+        "each failure re-probes until the budget is spent"
+    );
+    assert_eq!(status, "error");
+}
+
+#[test]
+fn reserving_a_port_drops_stale_state() {
+    let (_dir, lua, repo_root) = fixture();
+
+    let retries: Option<i64> = lua
+        .load(format!(
+            r#"
+            local PreviewPortState = require("lib.preview_port_state")
+            -- State left behind for a port that was never released cleanly.
+            PreviewPortState.record_failure(46000, "stale")
+            local agent = spawn_forwarding_agent("{repo_root}")
+            assert(agent._port == 46000)
+            local entry = PreviewPortState.get(46000)
+            return entry and entry.retries or nil
+        "#
+        ))
+        .eval()
+        .expect("reserve should evaluate");
+
+    assert_eq!(retries, None);
+}
+
+#[test]
+fn preview_state_is_bounded_by_lru_capacity() {
+    let (_dir, lua, _repo_root) = fixture();
+
+    let (count, oldest_evicted, recent_kept, touched_kept): (i64, bool, bool, bool) = lua
+        .load(
+            r#"
+            local PreviewPortState = require("lib.preview_port_state")
+            local cap = PreviewPortState.CAPACITY
+            for port = 50000, 50000 + cap - 1 do
+                PreviewPortState.record_failure(port, "boom")
+            end
+            -- Refresh the oldest entry so the next-oldest is evicted instead.
+            PreviewPortState.touch(50000)
+            PreviewPortState.record_failure(60000, "boom")
+            return PreviewPortState.count(),
+                PreviewPortState.get(50001) == nil,
+                PreviewPortState.get(60000) ~= nil,
+                PreviewPortState.get(50000) ~= nil
+        "#,
+        )
+        .eval()
+        .expect("lru should evaluate");
+
+    assert_eq!(count, 64);
+    assert!(oldest_evicted, "least recently used port should be evicted");
+    assert!(recent_kept);
+    assert!(touched_kept, "touching a port refreshes its recency");
+}
+
+#[test]
+fn spawn_close_cycles_release_every_reserved_port() {
+    let (_dir, lua, repo_root) = fixture();
+
+    let (held_after, reserved_after): (i64, i64) = lua
+        .load(format!(
+            r#"
+            -- Hand out the first port not excluded or still held, like the
+            -- real config.reserve_port.
+            local held = {{}}
+            config.reserve_port = function(start, finish, excluded)
+                local skip = {{}}
+                for _, p in ipairs(excluded or {{}}) do skip[p] = true end
+                for port = start, finish do
+                    if not skip[port] and not held[port] then
+                        held[port] = true
+                        return port
+                    end
+                end
+                return nil, "exhausted"
+            end
+            config.release_port = function(port)
+                local was = held[port] == true
+                held[port] = nil
+                return was
+            end
+
+            for _ = 1, 50 do
+                local a = spawn_forwarding_agent("{repo_root}")
+                local b = spawn_forwarding_agent("{repo_root}")
+                assert(a._port ~= b._port, "live agents must not share a port")
+                a:close(false)
+                b:close(false)
+            end
+
+            local function count(t)
+                local n = 0
+                for _ in pairs(t) do n = n + 1 end
+                return n
+            end
+            local port_state = require("hub.state").get("agent_port_state", {{}})
+            return count(held), count(port_state.reserved)
+        "#
+        ))
+        .eval()
+        .expect("spawn/close cycles should evaluate");
+
+    assert_eq!(held_after, 0, "closed agents must not keep ports held");
+    assert_eq!(reserved_after, 0, "closed agents must release their ports");
+}
@@ -0,0 +1,120 @@
+//! Rust-hosted Lua tests for skipping already processed server commands.
+//!
+//! The server replays unsettled commands on resubscribe, so
+//! `handlers/hub_commands.lua` and the GitHub plugin record each command id
+//! (see `lib/processed_commands.lua`) and ack a repeat without routing it
+//! again.
+
+mod common;
+
+use common::LuaFixture;
+
+/// Load the command channel and define `deliver(id, sequence, issue_number)`.
+fn start_hub(fixture: &LuaFixture) {
+    fixture.exec(
+        r#"
+        require("handlers.hub_commands")
+
+        --- Deliver create_agent command `id` for issue `issue_number`.
+        function _G.deliver(id, sequence, issue_number)
+          _G.channel_callbacks["HubCommandChannel"]({
+            type = "message",
+            id = id,
+            sequence = sequence,
+            event_type = "create_agent",
+            payload = {
+              issue_number = issue_number,
+              prompt = "please look",
+              target_repo = "owner/repo",
+            },
+          }, "channel")
+        end
+    "#,
+    );
+}
+
+#[test]
+fn same_command_id_is_processed_once() {
+    let fixture = LuaFixture::new();
+    start_hub(&fixture);
+    let (acks, spawned): (Vec<i64>, usize) = fixture.eval(
+        r#"
+        deliver(101, 1, 7)
+        deliver(101, 1, 7)
+        return acks, #emitted
+    "#,
+    );
+
+    assert_eq!(spawned, 1, "the duplicate must not be routed again");
+    assert_eq!(acks, vec![1, 1], "the duplicate is still acked");
+}
+
+#[test]
+fn processed_ids_survive_a_restart() {
+    let mut fixture = LuaFixture::new();
+    start_hub(&fixture);
+    fixture.exec("deliver(101, 1, 7)");
+
+    // A fresh VM stands in for a restarted hub replaying the unacked command.
+    fixture.restart();
+    start_hub(&fixture);
+    let (acks, spawned): (Vec<i64>, usize) = fixture.eval(
+        r#"
+        deliver(101, 1, 7)
+        deliver(102, 2, 8)
+        return acks, #emitted
+    "#,
+    );
+
+    assert_eq!(spawned, 1, "only the new command is routed");
+    assert_eq!(acks, vec![1, 2]);
+}
+
+#[test]
+fn processed_ids_are_bounded() {
+    let fixture = LuaFixture::new();
+    start_hub(&fixture);
+    let (oldest_forgotten, newest_kept): (bool, bool) = fixture.eval(
+        r#"
+        local ProcessedCommands = require("lib.processed_commands")
+        for id = 1, ProcessedCommands.CAPACITY + 1 do
+            ProcessedCommands.record(id)
+        end
+        return not ProcessedCommands.seen(1),
+            ProcessedCommands.seen(ProcessedCommands.CAPACITY + 1)
+    "#,
+    );
+
+    assert!(oldest_forgotten, "the oldest id is dropped past capacity");
+    assert!(newest_kept);
+}
+
+#[test]
+fn github_events_are_processed_once_across_restarts() {
+    let mut fixture = LuaFixture::new();
+    fixture.load_github_plugin();
+    fixture.exec(r#"deliver_github(101, "github_mention", { issue_number = 7, prompt = "hi" })"#);
+    assert!(fixture.data_dir.join("processed_commands.json").exists());
+    assert!(
+        !fixture
+            .data_dir
+            .join("processed_commands.json.tmp")
+            .exists(),
+        "the list is renamed into place"
+    );
+
+    fixture.restart();
+    fixture.load_github_plugin();
+    start_hub(&fixture);
+    let (acks, spawned): (Vec<i64>, usize) = fixture.eval(
+        r#"
+        deliver_github(101, "github_mention", { issue_number = 7, prompt = "hi" })
+        -- Hub command ids are a separate namespace.
+        deliver(101, 1, 8)
+        return acks, #emitted
+    "#,
+    );
+
+    assert_eq!(acks, vec![101, 1], "the replayed event is acked again");
+    assert_eq!(spawned, 1, "only the hub command is routed");
+}
@@ -0,0 +1,187 @@
+//! Rust-hosted Lua tests for per-profile agent limits in `spawn_agent`.
+//!
+//! `profiles.<name>.max_concurrent` caps agents of one profile while
+//! `max_sessions` caps the total across all profiles. Every create path
+//! (browser command, reopened worktree, server message) is refused with the
+//! `limit_reached` code before a worktree is created. Pinned agents do not
+//! count toward either cap.
+
+mod common;
+
+use common::LuaFixture;
+
+/// Fixture with agent configs for `profiles` and a `config.spawn_limit_error`
+/// that mirrors the Rust primitive for `profiles.codex.max_concurrent = 2`
+/// and `max_sessions = 4`.
+fn fixture(profiles: &[&str]) -> LuaFixture {
+    let fixture = LuaFixture::new();
+    for profile in profiles {
+        let agent_dir = fixture.repo_root.join(".botster/agents").join(profile);
+        std::fs::create_dir_all(&agent_dir).unwrap();
+        std::fs::write(agent_dir.join("initialization"), "true\n").unwrap();
+    }
+    fixture.exec(
+        r#"
+        _G.config.spawn_limit_error = function(profile, profile_active, total_active)
+          if profile == "codex" and profile_active >= 2 then
+            return "max 2 codex agents reached"
+          end
+          if total_active >= 4 then
+            return "max 4 agents reached (max_sessions)"
+          end
+          return nil
+        end
+    "#,
+    );
+    fixture
+}
+
+#[test]
+fn third_codex_spawn_fails_while_claude_still_spawns() {
+    let fixture = fixture(&["codex", "claude"]);
+
+    let (third_err, claude_ok, fifth_err): (String, bool, String) = fixture
+        .lua
+        .load(format!(
+            r#"
+            local agents = require("handlers.agents")
+            local target = {{
+              target_id = "target-1",
+              target_path = "{repo_root}",
+              target_repo = "owner/repo",
+            }}
+
+            assert(agents.handle_create_agent("1", nil, nil, nil, "codex", nil, target))
+            assert(agents.handle_create_agent("2", nil, nil, nil, "codex", nil, target))
+            local third, third_err = agents.handle_create_agent("3", nil, nil, nil, "codex", nil, target)
+            assert(third == nil, "third codex agent should be refused")
+
+            local claude = agents.handle_create_agent("4", nil, nil, nil, "claude", nil, target)
+            assert(agents.handle_create_agent("5", nil, nil, nil, "claude", nil, target))
+            local _, fifth_err = agents.handle_create_agent("6", nil, nil, nil, "claude", nil, target)
+            return third_err or "", claude ~= nil, fifth_err or ""
+        "#,
+            repo_root = fixture.repo_root.to_str().unwrap(),
+        ))
+        .eval()
+        .expect("limit scenario should evaluate");
+
+    assert_eq!(third_err, "max 2 codex agents reached");
+    assert!(claude_ok, "claude is not limited by the codex cap");
+    assert_eq!(fifth_err, "max 4 agents reached (max_sessions)");
+}
+
+#[test]
+fn every_create_path_is_refused_at_max_sessions() {
+    let fixture = fixture(&["claude"]);
+    let reopened = fixture.path("reopened");
+    std::fs::create_dir_all(&reopened).unwrap();
+
+    let (reopen_code, browser_error, browser_code, message_code, created, total): (
+        String,
+        String,
+        String,
+        String,
+        usize,
+        usize,
+    ) = fixture
+        .lua
+        .load(format!(
+            r#"
+            local agents = require("handlers.agents")
+            require("handlers.commands")
+            local commands = require("lib.commands")
+            local target = {{
+              target_id = "target-1",
+              target_path = "{repo_root}",
+              target_repo = "owner/repo",
+            }}
+            for i = 1, 4 do
+                assert(agents.handle_create_agent(tostring(i), nil, nil, nil, "claude", nil, target))
+            end
+            local created_before = #created_worktrees
+
+            local codes = {{}}
+            hooks.on("agent_lifecycle", "test_codes", function(info)
+                if info.status == "failed" then codes[#codes + 1] = info.code end
+            end)
+
+            -- Manual path: reopen an existing worktree.
+            local reopened, _, reopen_failure = agents.handle_create_agent(
+                "reopened", nil, "{reopened}", nil, "claude", nil, target)
+            assert(reopened == nil)
+
+            -- Browser path: the create_agent hub command.
+            local sent = {{}}
+            local client = {{ send = function(_, msg) sent[#sent + 1] = msg end }}
+            commands.dispatch(client, "sub-1", {{
+              type = "create_agent",
+              issue_or_branch = "10",
+              agent_name = "claude",
+              target_id = "target-1",
+              target_path = "{repo_root}",
+              target_repo = "owner/repo",
+            }})
+
+            -- Message path: a server create_agent command.
+            event_handlers.command_message({{
+              type = "create_agent",
+              issue_or_branch = "11",
+              agent_name = "claude",
+              target_id = "target-1",
+              target_path = "{repo_root}",
+              target_repo = "owner/repo",
+            }})
+
+            return reopen_failure and reopen_failure.code or "",
+                sent[1] and sent[1].error or "",
+                sent[1] and sent[1].code or "",
+                codes[#codes] or "",
+                #created_worktrees - created_before,
+                #require("lib.agent").list()
+        "#,
+            repo_root = fixture.repo_root.to_str().unwrap(),
+            reopened = reopened.to_str().unwrap(),
+        ))
+        .eval()
+        .expect("max_sessions scenario should evaluate");
+
+    assert_eq!(reopen_code, "limit_reached");
+    assert_eq!(browser_error, "max 4 agents reached (max_sessions)");
+    assert_eq!(browser_code, "limit_reached");
+    assert_eq!(message_code, "limit_reached");
+    assert_eq!(created, 0, "no worktree is created for a refused spawn");
+    assert_eq!(total, 4);
+}
+
+#[test]
+fn pinned_agents_do_not_count_toward_limits() {
+    let fixture = fixture(&["codex"]);
+
+    let (spawned, err): (bool, String) = fixture
+        .lua
+        .load(format!(
+            r#"
+            local agents = require("handlers.agents")
+            local target = {{
+              target_id = "target-1",
+              target_path = "{repo_root}",
+              target_repo = "owner/repo",
+            }}
+            for i = 1, 2 do
+                assert(agents.handle_create_agent(tostring(i), nil, nil, nil, "codex",
+                    {{ pinned = true }}, target))
+            end
+            local agent, err = agents.handle_create_agent("3", nil, nil, nil, "codex", nil, target)
+            return agent ~= nil, err or ""
+        "#,
+            repo_root = fixture.repo_root.to_str().unwrap(),
+        ))
+        .eval()
+        .expect("pinned scenario should evaluate");
+
+    assert!(
+        spawned,
+        "two pinned codex agents must not block a third: {err}"
+    );
+}
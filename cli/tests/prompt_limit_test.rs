@@ -0,0 +1,105 @@
+//! Rust-hosted Lua tests for the prompt length limit.
+//!
+//! `lib/prompt_limit.lua` cuts over-long prompts and mention bodies down to
+//! `max_prompt_chars`, keeping the head, the tail, or both ends, and notes
+//! the truncation in the text.
+
+mod common;
+
+use mlua::Lua;
+
+fn create_lua_vm() -> Lua {
+    let lua = common::lua_vm();
+
+    lua.load(
+        r#"
+        _G.test_config = {}
+        _G.config = { get = function(key) return _G.test_config[key] end }
+        _G.PromptLimit = require("lib.prompt_limit")
+    "#,
+    )
+    .exec()
+    .expect("stub globals");
+
+    lua
+}
+
+fn truncate(lua: &Lua, strategy: &str, text: &str) -> (String, bool) {
+    lua.load(format!(
+        r#"
+        _G.test_config.max_prompt_chars = 10
+        _G.test_config.prompt_truncation = "{strategy}"
+        return PromptLimit.apply("{text}")
+    "#
+    ))
+    .eval()
+    .expect("apply should evaluate")
+}
+
+#[test]
+fn over_limit_text_is_truncated_per_strategy() {
+    let lua = create_lua_vm();
+    let text = "0123456789abcdefghij";
+
+    let (head, truncated) = truncate(&lua, "head", text);
+    assert!(truncated);
+    assert_eq!(
+        head,
+        "0123456789\n\n[... truncated 10 of 20 characters ...]"
+    );
+
+    let (tail, _) = truncate(&lua, "tail", text);
+    assert_eq!(
+        tail,
+        "[... truncated 10 of 20 characters ...]\n\nabcdefghij"
+    );
+
+    let (both, _) = truncate(&lua, "head_tail", text);
+    assert_eq!(
+        both,
+        "01234\n\n[... truncated 10 of 20 characters ...]\n\nfghij"
+    );
+
+    // Unknown strategies fall back to head_tail.
+    let (fallback, _) = truncate(&lua, "middle", text);
+    assert_eq!(fallback, both);
+}
+
+#[test]
+fn short_text_and_unset_limit_are_untouched() {
+    let lua = create_lua_vm();
+
+    let (short, truncated) = truncate(&lua, "head", "0123456789");
+    assert!(!truncated);
+    assert_eq!(short, "0123456789");
+
+    let (unlimited, truncated): (String, bool) = lua
+        .load(
+            r#"
+            _G.test_config = {}
+            return PromptLimit.apply(string.rep("x", 100000))
+        "#,
+        )
+        .eval()
+        .unwrap();
+    assert!(!truncated);
+    assert_eq!(unlimited.len(), 100_000);
+}
+
+#[test]
+fn limit_counts_characters_not_bytes() {
+    let lua = create_lua_vm();
+
+    // Twelve two-byte characters: the cut must not split one.
+    let (head, truncated) = truncate(&lua, "head", &"é".repeat(12));
+    assert!(truncated);
+    assert!(head.starts_with(&"é".repeat(10)), "{head}");
+    assert!(head.contains("truncated 2 of 12 characters"));
+
+    let (short, truncated) = truncate(&lua, "head", &"é".repeat(10));
+    assert!(
+        !truncated,
+        "10 characters fit even though they are 20 bytes"
+    );
+    assert_eq!(short, "é".repeat(10));
+}
@@ -0,0 +1,118 @@
+//! Rust-hosted Lua tests for `allowed_repos`.
+//!
+//! Webhook commands and GitHub plugin mentions both reach the shared
+//! `create_agent` handler in `handlers/agents.lua`, which skips repos outside
+//! the allowlist before notifying or creating agents.
+
+mod common;
+
+use common::LuaFixture;
+
+fn fixture() -> LuaFixture {
+    let fixture = LuaFixture::new();
+    fixture.worktree("repo-botster-issue-7");
+    fixture.load_github_plugin();
+    fixture.exec(
+        r#"
+        _G.existing_worktrees["botster-issue-7"] = "$ROOT/repo-botster-issue-7"
+        local agents = require("handlers.agents")
+
+        -- Dispatch plugin emits to the agents handler, as the hub would.
+        events.emit = function(name, data)
+          if name == "command_message" then
+            table.insert(_G.emitted, data)
+            _G.event_handlers["command_message"](data)
+          end
+        end
+
+        function _G.mention(id)
+          deliver_github(id, "github_mention", { issue_number = 7, prompt = "please look" })
+        end
+
+        -- An agent for issue 7 whose PTY records submitted messages.
+        function _G.spawn_mention_target()
+          local agent = assert(agents.handle_create_agent("7", nil, nil, nil, nil, {
+            issue_number = 7,
+            workspace = "owner/repo#7",
+          }, {
+            target_id = "target-1",
+            target_path = "$REPO_ROOT",
+            target_repo = "owner/repo",
+          }))
+          agent.submitted = {}
+          agent.session = {
+            send_message = function(_, text) agent.submitted[#agent.submitted + 1] = text end,
+            kill = function() end,
+          }
+          return agent
+        end
+    "#,
+    );
+    fixture
+}
+
+#[test]
+fn plugin_mention_for_unlisted_repo_does_not_spawn() {
+    let fixture = fixture();
+    let (spawned, acked): (usize, usize) = fixture.eval(
+        r#"
+        _G.test_config.allowed_repos = { "other/repo" }
+        mention(1)
+        return #_G.spawned, #acks
+    "#,
+    );
+
+    assert_eq!(spawned, 0, "owner/repo is not in allowed_repos");
+    assert_eq!(acked, 1, "skipped mentions are still acked");
+}
+
+#[test]
+fn plugin_mention_for_unlisted_repo_does_not_reach_existing_agent() {
+    let fixture = fixture();
+    let submitted: usize = fixture.eval(
+        r#"
+        local agent = spawn_mention_target()
+        _G.test_config.allowed_repos = { "other/repo" }
+        mention(1)
+        fire_timers("mention_debounce:")
+        return #agent.submitted
+    "#,
+    );
+
+    assert_eq!(submitted, 0);
+}
+
+#[test]
+fn plugin_mention_for_listed_repo_spawns() {
+    let fixture = fixture();
+    let spawned: usize = fixture.eval(
+        r#"
+        _G.test_config.allowed_repos = { "Owner/Repo" }
+        mention(1)
+        return #_G.spawned
+    "#,
+    );
+
+    assert_eq!(spawned, 1, "matching ignores case");
+}
+
+#[test]
+fn webhook_create_agent_for_unlisted_repo_does_not_spawn() {
+    let fixture = fixture();
+    let spawned: usize = fixture.eval(
+        r#"
+        _G.test_config.allowed_repos = { "other/repo" }
+        _G.event_handlers["command_message"]({
+          type = "create_agent",
+          issue_or_branch = "7",
+          prompt = "please look",
+          target_id = "target-1",
+          target_path = "$REPO_ROOT",
+          target_repo = "owner/repo",
+        })
+        return #_G.spawned
+    "#,
+    );
+
+    assert_eq!(spawned, 0);
+}
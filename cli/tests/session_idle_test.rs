@@ -0,0 +1,83 @@
+//! Rust-hosted Lua tests for per-session idle time.
+//!
+//! `Session:idle_seconds()` counts from the handle's `last_output_at()` (the
+//! session process stamps it on every output chunk), or from creation before
+//! any output, and is reported as `idle_seconds` in `info()`.
+
+mod common;
+
+use common::LuaFixture;
+
+#[test]
+fn idle_time_grows_while_output_is_unchanged() {
+    let fixture = LuaFixture::new();
+    let worktree_path = fixture.path("feature-idle-worktree");
+    std::fs::create_dir_all(&worktree_path).unwrap();
+    fixture.exec(
+        r#"
+        -- Handles report `_G.last_output_ms` like session:last_output_at().
+        _G.last_output_ms = nil
+        local spawn_session = _G.hub.spawn_session
+        _G.hub.spawn_session = function(...)
+          local handle = spawn_session(...)
+          function handle:last_output_at() return _G.last_output_ms end
+          return handle
+        end
+
+        function _G.spawn_agent(worktree_path)
+          local Agent = require("lib.agent")
+          return Agent.new({
+            repo = "owner/repo",
+            branch_name = "feature-idle",
+            worktree_path = worktree_path,
+            session = { name = "claude", command = "bash" },
+            target_id = "target-1",
+            target_path = "$REPO_ROOT",
+            target_repo = "owner/repo",
+          })
+        end
+    "#,
+    );
+
+    let (before_output, after_output, info_after_output, later, last_activity): (
+        i64,
+        i64,
+        i64,
+        i64,
+        i64,
+    ) = fixture
+        .lua
+        .load(format!(
+            r#"
+            local agent = spawn_agent("{worktree}")
+            local now = os.time()
+
+            -- No output yet: idle since creation.
+            agent.created_at = now - 30
+            local before_output = agent:idle_seconds()
+
+            -- Output just arrived.
+            last_output_ms = now * 1000
+            local after_output = agent:idle_seconds()
+            local info_after_output = agent:info().idle_seconds
+
+            -- Nothing new since; the same last output is now 90s old.
+            last_output_ms = (now - 90) * 1000
+            return before_output, after_output, info_after_output,
+                agent:idle_seconds(), agent:last_activity()
+        "#,
+            worktree = worktree_path.to_str().unwrap(),
+        ))
+        .eval()
+        .expect("idle scenario should evaluate");
+
+    assert!(before_output >= 30, "got {before_output}");
+    assert!(
+        after_output <= 1,
+        "output resets idle time, got {after_output}"
+    );
+    assert!(info_after_output <= 1, "info reports idle_seconds");
+    assert!(later >= 90, "idle time grows without output, got {later}");
+    assert!(later > after_output);
+    assert!(last_activity > 0);
+}
@@ -0,0 +1,189 @@
+//! Rust-hosted Lua tests for `Session:reinit`.
+//!
+//! Reinit picks up the latest initialization script by restarting the
+//! session in place: a fresh shell sources the script, and nothing is typed
+//! into the running agent. `hub.spawn_session` is stubbed to record spawn
+//! configs in `_G.spawned`.
+
+mod common;
+
+use common::LuaFixture;
+use mlua::Lua;
+use tempfile::TempDir;
+
+struct Fixture {
+    _dir: TempDir,
+    lua: Lua,
+    worktree_path: std::path::PathBuf,
+    init_script: std::path::PathBuf,
+}
+
+fn fixture() -> Fixture {
+    let fixture = LuaFixture::new();
+    let worktree_path = fixture.worktree("feature-reinit-worktree");
+    let init_script = fixture
+        .repo_root
+        .join(".botster/agents/claude/initialization");
+    std::fs::create_dir_all(init_script.parent().unwrap()).unwrap();
+    std::fs::write(&init_script, "export MCP_CONFIG=v2\n").unwrap();
+    fixture.exec(
+        r#"
+        -- Spawn an agent whose PTY handles record anything written to them.
+        local spawn_session = _G.hub.spawn_session
+        _G.hub.spawn_session = function(...)
+          local handle = spawn_session(...)
+          handle.written = {}
+          function handle:write(data) self.written[#self.written + 1] = data end
+          return handle
+        end
+
+        function _G.spawn_agent(worktree_path)
+          local Agent = require("lib.agent")
+          return Agent.new({
+            repo = "owner/repo",
+            branch_name = "feature-reinit",
+            worktree_path = worktree_path,
+            session = { name = "claude", command = "bash" },
+            target_id = "target-1",
+            target_path = "$REPO_ROOT",
+            target_repo = "owner/repo",
+          })
+        end
+
+        --- Config as the resolver returns it after `initialization` changed.
+        function _G.fresh_config(init_script)
+          return { name = "claude", command = "bash", init_script = init_script }
+        end
+    "#,
+    );
+
+    let LuaFixture { dir, lua, .. } = fixture;
+    Fixture {
+        _dir: dir,
+        lua,
+        worktree_path,
+        init_script,
+    }
+}
+
+#[test]
+fn reinit_restarts_session_sourcing_fresh_init_script() {
+    let f = fixture();
+
+    let (ok, done, old_killed, typed, init_command, config_script): (
+        bool,
+        bool,
+        bool,
+        usize,
+        String,
+        String,
+    ) = f
+        .lua
+        .load(format!(
+            r#"
+            local agent = spawn_agent("{worktree_path}")
+            local old = agent.session
+            local done = false
+            local ok = agent:reinit(fresh_config("{init_script}"), nil, function(ok) done = ok end)
+            local new = _G.spawned[#_G.spawned]
+            return ok, done, old.killed, #old.written,
+              new.spawn_config.init_commands[1] or "", agent._session_config.init_script or ""
+        "#,
+            worktree_path = f.worktree_path.to_str().unwrap(),
+            init_script = f.init_script.to_str().unwrap(),
+        ))
+        .eval()
+        .expect("reinit should evaluate");
+
+    assert!(ok, "reinit of an idle session should start");
+    assert!(done, "the restart should report success");
+    assert!(old_killed, "the old process is replaced");
+    assert_eq!(typed, 0, "nothing is typed into the running agent");
+    assert_eq!(
+        init_command,
+        format!("source '{}'", f.init_script.to_str().unwrap())
+    );
+    assert_eq!(config_script, f.init_script.to_str().unwrap());
+}
+
+#[test]
+fn reinit_quotes_init_script_path() {
+    let f = fixture();
+    let dir = f.worktree_path.join("it's a dir; rm -rf ~");
+    std::fs::create_dir_all(&dir).unwrap();
+    let script = dir.join("initialization");
+    std::fs::write(&script, "export MCP_CONFIG=v3\n").unwrap();
+
+    let init_command: String = f
+        .lua
+        .load(format!(
+            r#"
+            local agent = spawn_agent("{worktree_path}")
+            assert(agent:reinit(fresh_config([[{script}]])))
+            return _G.spawned[#_G.spawned].spawn_config.init_commands[1]
+        "#,
+            worktree_path = f.worktree_path.to_str().unwrap(),
+            script = script.to_str().unwrap(),
+        ))
+        .eval()
+        .expect("reinit should evaluate");
+
+    assert_eq!(
+        init_command,
+        format!(
+            "source '{}/it'\\''s a dir; rm -rf ~/initialization'",
+            f.worktree_path.to_str().unwrap()
+        )
+    );
+}
+
+#[test]
+fn reinit_refuses_busy_session_unless_forced() {
+    let f = fixture();
+
+    let (blocked, err, forced, spawns): (bool, String, bool, usize) = f
+        .lua
+        .load(format!(
+            r#"
+            local agent = spawn_agent("{worktree_path}")
+            agent.is_idle = false
+            local before = #_G.spawned
+            local blocked, err = agent:reinit(fresh_config("{init_script}"))
+            local forced = agent:reinit(fresh_config("{init_script}"), {{ force = true }})
+            return blocked, err or "", forced, #_G.spawned - before
+        "#,
+            worktree_path = f.worktree_path.to_str().unwrap(),
+            init_script = f.init_script.to_str().unwrap(),
+        ))
+        .eval()
+        .expect("busy reinit should evaluate");
+
+    assert!(!blocked, "busy session should not be reinitialized");
+    assert!(err.contains("busy"), "unexpected error: {err}");
+    assert!(forced, "force should bypass the busy guard");
+    assert_eq!(
+        spawns, 1,
+        "only the forced reinit should restart the session"
+    );
+}
+
+#[test]
+fn reinit_fails_when_init_script_is_missing() {
+    let f = fixture();
+
+    let (ok, killed): (bool, bool) = f
+        .lua
+        .load(format!(
+            r#"
+            local agent = spawn_agent("{worktree_path}")
+            local ok = agent:reinit(fresh_config("{worktree_path}/missing-initialization"))
+            return ok, agent.session.killed
+        "#,
+            worktree_path = f.worktree_path.to_str().unwrap(),
+        ))
+        .eval()
+        .expect("missing-script reinit should evaluate");
+
+    assert!(!ok);
+    assert!(!killed, "a failed reinit leaves the session running");
+}
@@ -0,0 +1,236 @@
+//! Rust-hosted Lua tests for `Session:restart`.
+//!
+//! Restart kills a wedged session's shell and spawns a fresh one in place:
+//! same session UUID, same worktree, same prompt, with the init script
+//! sourced again. `hub.spawn_session` is stubbed to record spawn configs.
+
+mod common;
+
+use common::LuaFixture;
+use mlua::{Lua, Table};
+use tempfile::TempDir;
+
+struct Fixture {
+    _dir: TempDir,
+    lua: Lua,
+    worktree_path: std::path::PathBuf,
+    init_script: std::path::PathBuf,
+}
+
+fn fixture() -> Fixture {
+    let fixture = LuaFixture::new();
+    let worktree_path = fixture.worktree("feature-restart-worktree");
+    let init_script = fixture
+        .repo_root
+        .join(".botster/agents/claude/initialization");
+    std::fs::create_dir_all(init_script.parent().unwrap()).unwrap();
+    std::fs::write(
+        worktree_path.join(".botster_prompt"),
+        "fix the flaky test\n",
+    )
+    .unwrap();
+    std::fs::write(&init_script, "export MCP_CONFIG=v2\n").unwrap();
+    fixture.exec(
+        r#"
+        -- Spawned handles (recorded in _G.spawned) report fixed dimensions.
+        local spawn_session = _G.hub.spawn_session
+        _G.hub.spawn_session = function(...)
+          local handle = spawn_session(...)
+          function handle:dimensions() return 40, 120 end
+          return handle
+        end
+
+        function _G.spawn_agent(worktree_path, init_script)
+          local Agent = require("lib.agent")
+          return Agent.new({
+            repo = "owner/repo",
+            branch_name = "feature-restart",
+            worktree_path = worktree_path,
+            prompt = "fix the flaky test",
+            session = { name = "claude", command = "bash", init_script = init_script },
+            target_id = "target-1",
+            target_path = "$REPO_ROOT",
+            target_repo = "owner/repo",
+          })
+        end
+    "#,
+    );
+
+    let LuaFixture { dir, lua, .. } = fixture;
+    Fixture {
+        _dir: dir,
+        lua,
+        worktree_path,
+        init_script,
+    }
+}
+
+#[test]
+fn restart_respawns_in_same_worktree_keeping_identity_and_prompt() {
+    let f = fixture();
+
+    let result: Table = f
+        .lua
+        .load(format!(
+            r#"
+            local agent = spawn_agent("{worktree_path}", "{init_script}")
+            local uuid, created_at = agent.session_uuid, agent.created_at
+            local old = agent.session
+
+            assert(agent:restart())
+            local new = spawned[#spawned]
+            return {{
+                uuid_kept = agent.session_uuid == uuid and new.session_uuid == uuid,
+                created_kept = agent.created_at == created_at,
+                old_killed = old.killed,
+                unregistered = #unregistered,
+                spawns = #spawned,
+                cwd = new.spawn_config.cwd,
+                prompt = new.spawn_config.env.BOTSTER_PROMPT or "",
+                init = new.spawn_config.init_commands[1] or "",
+                rows = new.spawn_config.rows,
+            }}
+        "#,
+            worktree_path = f.worktree_path.to_str().unwrap(),
+            init_script = f.init_script.to_str().unwrap(),
+        ))
+        .eval()
+        .expect("restart should evaluate");
+
+    assert!(
+        result.get::<bool>("uuid_kept").unwrap(),
+        "restart must keep the session UUID"
+    );
+    assert!(
+        result.get::<bool>("created_kept").unwrap(),
+        "creation time orders the agent list"
+    );
+    assert!(
+        result.get::<bool>("old_killed").unwrap(),
+        "the wedged shell must be killed"
+    );
+    assert_eq!(result.get::<usize>("unregistered").unwrap(), 1);
+    assert_eq!(result.get::<usize>("spawns").unwrap(), 2);
+    assert_eq!(
+        result.get::<String>("cwd").unwrap(),
+        f.worktree_path.to_str().unwrap()
+    );
+    let prompt = result.get::<String>("prompt").unwrap();
+    assert!(
+        prompt.contains("fix the flaky test"),
+        "prompt env: {prompt}"
+    );
+    assert_eq!(
+        result.get::<String>("init").unwrap(),
+        format!("source '{}'", f.init_script.to_str().unwrap())
+    );
+    assert_eq!(
+        result.get::<u16>("rows").unwrap(),
+        40,
+        "restart keeps the current terminal size"
+    );
+    assert!(
+        f.worktree_path.join(".botster_prompt").exists(),
+        "restart must leave the worktree's files alone"
+    );
+}
+
+#[test]
+fn failed_respawn_reports_error_and_marks_session_failed() {
+    let f = fixture();
+
+    let (started, ok, err, status): (bool, bool, String, String) = f
+        .lua
+        .load(format!(
+            r#"
+            local agent = spawn_agent("{worktree_path}", "{init_script}")
+            hub.spawn_session = function() error("socket did not appear") end
+            local done_ok, done_err
+            local started = agent:restart(nil, function(ok, err)
+              done_ok, done_err = ok, err
+            end)
+            return started, done_ok, done_err or "", agent.status
+        "#,
+            worktree_path = f.worktree_path.to_str().unwrap(),
+            init_script = f.init_script.to_str().unwrap(),
+        ))
+        .eval()
+        .expect("failed restart should evaluate");
+
+    assert!(started, "the kill half of the restart still happened");
+    assert!(!ok);
+    assert!(
+        err.contains("socket did not appear"),
+        "unexpected error: {err}"
+    );
+    assert_eq!(status, "failed", "a session with no process is not running");
+}
+
+#[test]
+fn respawn_waits_on_a_timer_for_the_old_process_to_exit() {
+    let f = fixture();
+
+    let (spawns_while_live, done_while_live, spawns_after, ok): (usize, bool, usize, bool) = f
+        .lua
+        .load(format!(
+            r#"
+            local agent = spawn_agent("{worktree_path}", "{init_script}")
+            live_sessions[agent.session_uuid] = true
+            local done, done_ok = false, nil
+            assert(agent:restart(nil, function(ok)
+              done, done_ok = true, ok
+            end))
+
+            local spawns_while_live = #spawned
+            fire_timers("after:")
+            local done_while_live = done
+
+            live_sessions[agent.session_uuid] = nil
+            fire_timers("after:")
+            return spawns_while_live, done_while_live, #spawned, done_ok
+        "#,
+            worktree_path = f.worktree_path.to_str().unwrap(),
+            init_script = f.init_script.to_str().unwrap(),
+        ))
+        .eval()
+        .expect("deferred restart should evaluate");
+
+    assert_eq!(
+        spawns_while_live, 1,
+        "restart must not block or respawn while the old process is live"
+    );
+    assert!(!done_while_live);
+    assert_eq!(spawns_after, 2, "respawned once the old process exited");
+    assert!(ok);
+}
+
+#[test]
+fn respawn_gives_up_when_the_old_process_never_exits() {
+    let f = fixture();
+
+    let (ok, err, status): (bool, String, String) = f
+        .lua
+        .load(format!(
+            r#"
+            local agent = spawn_agent("{worktree_path}", "{init_script}")
+            live_sessions[agent.session_uuid] = true
+            local done_ok, done_err
+            assert(agent:restart(nil, function(ok, err)
+              done_ok, done_err = ok, err
+            end))
+            for _ = 1, 1000 do
+              if done_ok ~= nil then break end
+              fire_timers("after:")
+            end
+            return done_ok, done_err or "", agent.status
+        "#,
+            worktree_path = f.worktree_path.to_str().unwrap(),
+            init_script = f.init_script.to_str().unwrap(),
+        ))
+        .eval()
+        .expect("timed out restart should evaluate");
+
+    assert!(!ok);
+    assert!(err.contains("still running"), "unexpected error: {err}");
+    assert_eq!(status, "failed");
+}
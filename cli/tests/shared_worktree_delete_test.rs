@@ -0,0 +1,107 @@
+//! Rust-hosted Lua tests for deleting an agent whose worktree is shared.
+//!
+//! Closing an agent with `delete_worktree` must not remove a worktree another
+//! live session is still running in. The agent still closes; the requesting
+//! client gets an error naming the sessions that kept the worktree.
+
+mod common;
+
+use common::LuaFixture;
+use mlua::Lua;
+use tempfile::TempDir;
+
+fn fixture() -> (TempDir, Lua) {
+    let fixture = LuaFixture::new();
+    fixture.worktree("repo-shared");
+    fixture.exec(
+        r#"
+        local agents = require("handlers.agents")
+        require("handlers.commands")
+        _G.sent = {}
+        _G.client = { send = function(_, msg) sent[#sent + 1] = msg end }
+
+        local target = {
+          target_id = "target-1",
+          target_path = "$REPO_ROOT",
+          target_repo = "owner/repo",
+        }
+        _G.first = assert(agents.handle_create_agent(
+          "shared", nil, "$ROOT/repo-shared", nil, "first", {}, target))
+        _G.second = assert(agents.handle_create_agent(
+          "shared", nil, "$ROOT/repo-shared", nil, "second", {}, target))
+        assert(first.worktree_path == second.worktree_path)
+        "#,
+    );
+
+    let LuaFixture { dir, lua, .. } = fixture;
+    (dir, lua)
+}
+
+#[test]
+fn delete_agent_keeps_worktree_used_by_another_agent() {
+    let (_dir, lua) = fixture();
+    let (removed, first_live, second_live, code, error, blocking): (
+        usize,
+        bool,
+        bool,
+        String,
+        String,
+        Vec<String>,
+    ) = lua
+        .load(
+            r#"
+            require("lib.commands").dispatch(client, "sub-1", {
+              type = "delete_agent",
+              agent_id = first.session_uuid,
+              delete_worktree = true,
+            })
+            local Agent = require("lib.agent")
+            local frame = assert(sent[1], "an error frame is sent")
+            assert(frame.type == "error" and frame.subscriptionId == "sub-1")
+            return #removals, Agent.get(first.session_uuid) ~= nil,
+              Agent.get(second.session_uuid) ~= nil, frame.code, frame.error, frame.sessions
+        "#,
+        )
+        .eval()
+        .unwrap();
+
+    assert_eq!(removed, 0, "the shared worktree must survive");
+    assert!(!first_live, "the agent itself still closes");
+    assert!(second_live);
+    assert_eq!(code, "worktree_in_use");
+    let second: String = lua.load("return second.session_uuid").eval().unwrap();
+    assert_eq!(blocking, vec![second.clone()]);
+    assert!(error.contains(&second), "error names the blocking session");
+
+    // Once the last agent goes, the worktree can be deleted.
+    let (removed, frames): (usize, usize) = lua
+        .load(
+            r#"
+            require("lib.commands").dispatch(client, "sub-1", {
+              type = "delete_agent",
+              agent_id = second.session_uuid,
+              delete_worktree = true,
+            })
+            return #removals, #sent
+        "#,
+        )
+        .eval()
+        .unwrap();
+    assert_eq!(removed, 1);
+    assert_eq!(frames, 1, "no error for an unshared worktree");
+}
+
+#[test]
+fn closing_a_session_directly_keeps_a_shared_worktree() {
+    let (_dir, lua) = fixture();
+    let removed: usize = lua
+        .load(
+            r#"
+            first:close(true)
+            return #removals
+        "#,
+        )
+        .eval()
+        .unwrap();
+    assert_eq!(removed, 0);
+}
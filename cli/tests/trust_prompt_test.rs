@@ -0,0 +1,158 @@
+//! Rust-hosted Lua tests for trust prompt detection.
+//!
+//! A freshly spawned agent may open with a "do you trust this folder?"
+//! prompt. `lib.trust_prompt` scans early PTY output for it and either
+//! answers it (`auto_trust`) or badges the session as needing attention.
+
+mod common;
+
+use common::LuaFixture;
+use mlua::Lua;
+use tempfile::TempDir;
+
+struct Fixture {
+    _dir: TempDir,
+    lua: Lua,
+    worktree_path: std::path::PathBuf,
+}
+
+fn fixture() -> Fixture {
+    let fixture = LuaFixture::new();
+    let worktree_path = fixture.worktree("feature-trust-worktree");
+    fixture.exec(
+        r#"
+        -- Spawn an agent and swap its PTY handle for a fake that records writes.
+        function _G.spawn_recording_agent(worktree_path)
+          local Agent = require("lib.agent")
+          local agent = Agent.new({
+            repo = "owner/repo",
+            branch_name = "feature-trust",
+            worktree_path = worktree_path,
+            session = { name = "claude", command = "bash" },
+            target_id = "target-1",
+            target_path = "$REPO_ROOT",
+            target_repo = "owner/repo",
+          })
+          agent.written = {}
+          agent.session = {
+            write = function(_, data) agent.written[#agent.written + 1] = data end,
+            kill = function() end,
+          }
+          return agent
+        end
+    "#,
+    );
+
+    let LuaFixture { dir, lua, .. } = fixture;
+    Fixture {
+        _dir: dir,
+        lua,
+        worktree_path,
+    }
+}
+
+/// Feed the prompt in two chunks (with ANSI styling) through the detector
+/// and return `(decision, writes, notification)`.
+fn run_prompt(f: &Fixture, config: &str) -> (String, Vec<String>, bool) {
+    f.lua
+        .load(format!(
+            r#"
+            _G.test_config = {config}
+            local TrustPrompt = require("lib.trust_prompt")
+            local agent = spawn_recording_agent("{worktree_path}")
+            local first = TrustPrompt.scan(agent, "\27[1mDo you trust the files\27[0m in ")
+            assert(first == nil, "partial prompt must not match")
+            local decision = TrustPrompt.scan(agent, "this folder?\r\n  1. Yes, proceed\r\n")
+            local again = TrustPrompt.scan(agent, "Do you trust the files in this folder?")
+            assert(again == nil, "prompt is handled once per session")
+            return decision or "", agent.written, agent.notification == true
+        "#,
+            worktree_path = f.worktree_path.to_str().unwrap(),
+        ))
+        .eval::<(String, Vec<String>, bool)>()
+        .expect("trust prompt scan should evaluate")
+}
+
+#[test]
+fn auto_trust_answers_prompt_through_pty() {
+    let f = fixture();
+    let (decision, written, notification) = run_prompt(&f, "{ auto_trust = true }");
+
+    assert_eq!(decision, "auto_trusted");
+    assert_eq!(written, vec!["\r".to_string()]);
+    assert!(!notification, "auto-trusted sessions need no attention");
+}
+
+#[test]
+fn custom_response_is_written_when_configured() {
+    let f = fixture();
+    let (decision, written, _) = run_prompt(
+        &f,
+        r#"{ auto_trust = true, trust_prompt_response = "1\r" }"#,
+    );
+
+    assert_eq!(decision, "auto_trusted");
+    assert_eq!(written, vec!["1\r".to_string()]);
+}
+
+#[test]
+fn without_auto_trust_the_session_is_badged() {
+    let f = fixture();
+    let (decision, written, notification) = run_prompt(&f, "{}");
+
+    assert_eq!(decision, "needs_attention");
+    assert!(written.is_empty(), "nothing is typed without auto_trust");
+    assert!(notification, "session should be flagged for attention");
+}
+
+#[test]
+fn custom_patterns_replace_defaults() {
+    let f = fixture();
+
+    let (default_hit, custom_hit): (bool, bool) = f
+        .lua
+        .load(format!(
+            r#"
+            _G.test_config = {{
+              auto_trust = true,
+              trust_prompt_patterns = {{ "Allow workspace access" }},
+            }}
+            local TrustPrompt = require("lib.trust_prompt")
+            local agent = spawn_recording_agent("{worktree_path}")
+            local default_hit = TrustPrompt.scan(agent, "Do you trust this folder?") ~= nil
+            local custom_hit = TrustPrompt.scan(agent, "allow WORKSPACE access? [y/n]") ~= nil
+            return default_hit, custom_hit
+        "#,
+            worktree_path = f.worktree_path.to_str().unwrap(),
+        ))
+        .eval()
+        .expect("custom pattern scan should evaluate");
+
+    assert!(!default_hit, "configured patterns replace the defaults");
+    assert!(
+        custom_hit,
+        "configured pattern should match case-insensitively"
+    );
+}
+
+#[test]
+fn output_after_early_window_is_ignored() {
+    let f = fixture();
+
+    let decision: Option<String> = f
+        .lua
+        .load(format!(
+            r#"
+            _G.test_config = {{ auto_trust = true }}
+            local TrustPrompt = require("lib.trust_prompt")
+            local agent = spawn_recording_agent("{worktree_path}")
+            agent.created_at = os.time() - TrustPrompt.WINDOW_SECS - 1
+            return TrustPrompt.scan(agent, "Do you trust the files in this folder?")
+        "#,
+            worktree_path = f.worktree_path.to_str().unwrap(),
+        ))
+        .eval()
+        .expect("late scan should evaluate");
+
+    assert!(decision.is_none());
+}
@@ -0,0 +1,7 @@
+# frozen_string_literal: true
+
+class AddFailureReasonToHubCommands < ActiveRecord::Migration[8.1]
+  def change
+    add_column :hub_commands, :failure_reason, :string
+  end
+end
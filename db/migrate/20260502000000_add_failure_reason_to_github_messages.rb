@@ -0,0 +1,7 @@
+# frozen_string_literal: true
+
+class AddFailureReasonToGithubMessages < ActiveRecord::Migration[8.1]
+  def change
+    add_column :github_messages, :failure_reason, :string
+  end
+end
@@ -10,7 +10,7 @@
 #
 # It's strongly recommended that you check this file into your version control system.
 
-ActiveRecord::Schema[8.1].define(version: 2026_04_10_000000) do
+ActiveRecord::Schema[8.1].define(version: 2026_05_02_000000) do
   # These are extensions that must be enabled in order to support this database
   enable_extension "pg_catalog.plpgsql"
 
@@ -99,6 +99,7 @@ ActiveRecord::Schema[8.1].define(version: 2026_04_10_000000) do
     t.datetime "acknowledged_at"
     t.datetime "created_at", null: false
     t.string "event_type", null: false
+    t.string "failure_reason"
     t.integer "issue_number"
     t.jsonb "payload", default: {}, null: false
     t.string "repo", null: false
@@ -129,6 +130,7 @@ ActiveRecord::Schema[8.1].define(version: 2026_04_10_000000) do
     t.datetime "acknowledged_at"
     t.datetime "created_at", null: false
     t.string "event_type", null: false
+    t.string "failure_reason"
     t.bigint "hub_id", null: false
     t.jsonb "payload", default: {}, null: false
     t.bigint "sequence", null: false
@@ -163,6 +163,82 @@ module Github
       assert msg.acknowledged?
     end
 
+    # === Mark Failed Tests ===
+
+    test "mark_failed action records the failure reason" do
+      msg = Integrations::Github::Message.create!(
+        event_type: "github_mention",
+        repo: @test_repo,
+        issue_number: 42,
+        payload: { repo: @test_repo, issue_number: 42 }
+      )
+
+      stub_github_access(success: true) do
+        subscribe repo: @test_repo
+        perform :mark_failed, id: msg.id, reason: "attempt to index a nil value"
+      end
+
+      msg.reload
+      assert msg.failed?
+      assert_equal "attempt to index a nil value", msg.failure_reason
+    end
+
+    test "mark_failed does not override an acknowledged message" do
+      msg = Integrations::Github::Message.create!(
+        event_type: "github_mention",
+        repo: @test_repo,
+        issue_number: 42,
+        payload: { repo: @test_repo, issue_number: 42 }
+      )
+
+      Github::App.stub :create_issue_reaction, { success: true } do
+        msg.acknowledge!
+      end
+
+      stub_github_access(success: true) do
+        subscribe repo: @test_repo
+        perform :mark_failed, id: msg.id, reason: "late failure"
+      end
+
+      assert msg.reload.acknowledged?
+    end
+
+    test "mark_failed ignores messages for other repos" do
+      msg = Integrations::Github::Message.create!(
+        event_type: "github_mention",
+        repo: "other/repo",
+        issue_number: 2,
+        payload: { repo: "other/repo", issue_number: 2 }
+      )
+
+      stub_github_access(success: true) do
+        subscribe repo: @test_repo
+        perform :mark_failed, id: msg.id, reason: "bad payload"
+      end
+
+      assert msg.reload.pending?
+    end
+
+    test "failed messages are not replayed on resubscribe" do
+      msg = Integrations::Github::Message.create!(
+        event_type: "github_mention",
+        repo: @test_repo,
+        issue_number: 42,
+        payload: { repo: @test_repo, issue_number: 42 }
+      )
+
+      stub_github_access(success: true) do
+        subscribe repo: @test_repo
+        assert_equal 1, transmissions.size
+        perform :mark_failed, id: msg.id, reason: "bad payload"
+        unsubscribe
+
+        subscribe repo: @test_repo
+        replayed = transmissions.count { |t| t["id"] == msg.id }
+        assert_equal 1, replayed, "failed message should not be replayed"
+      end
+    end
+
     private
 
     def stub_github_access(success:)
@@ -151,6 +151,42 @@ class HubCommandChannelTest < ActionCable::Channel::TestCase
     assert_nothing_raised { perform :ack, sequence: 999_999 }
   end
 
+  # === Mark Failed Action Tests ===
+
+  test "mark_failed action records the failure reason" do
+    cmd = HubCommand.create_for_hub!(@hub, event_type: "create_agent", payload: { issue_number: 1, prompt: "Test" })
+
+    subscribe hub_id: @hub.id
+    perform :mark_failed, sequence: cmd.sequence, reason: "attempt to index a nil value"
+
+    cmd.reload
+    assert cmd.failed?
+    assert_equal "attempt to index a nil value", cmd.failure_reason
+  end
+
+  test "mark_failed does not override an acknowledged command" do
+    cmd = HubCommand.create_for_hub!(@hub, event_type: "create_agent", payload: { issue_number: 1, prompt: "Test" })
+    cmd.acknowledge!
+
+    subscribe hub_id: @hub.id
+    perform :mark_failed, sequence: cmd.sequence, reason: "late failure"
+
+    assert cmd.reload.acknowledged?
+  end
+
+  test "failed hub commands are not replayed on resubscribe" do
+    cmd = HubCommand.create_for_hub!(@hub, event_type: "create_agent", payload: "malformed")
+
+    subscribe hub_id: @hub.id, start_from: 0
+    assert_equal 1, transmissions.size
+    perform :mark_failed, sequence: cmd.sequence, reason: "bad payload"
+    unsubscribe
+
+    subscribe hub_id: @hub.id, start_from: 0
+    replayed = transmissions.count { |msg| msg["sequence"] == cmd.sequence }
+    assert_equal 1, replayed, "failed command should not be replayed"
+  end
+
   # === Heartbeat Action Tests ===
 
   test "heartbeat updates hub alive and last_seen_at" do
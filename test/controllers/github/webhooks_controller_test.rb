@@ -163,6 +163,42 @@ module Github
       assert_includes prompt, "@trybotster please help"
     end
 
+    test "issue_comment webhook records the issue's labels" do
+      payload = {
+        action: "created",
+        repository: { full_name: "owner/repo" },
+        installation: { id: 12345 },
+        issue: {
+          number: 123,
+          title: "Test issue",
+          body: "Issue body",
+          html_url: "https://github.com/owner/repo/issues/123",
+          labels: [ { name: "bug" }, { name: "ui" } ],
+          pull_request: nil
+        },
+        comment: {
+          id: 456,
+          body: "@trybotster please help",
+          user: { login: "testuser" }
+        }
+      }
+
+      body, signature = sign_webhook_payload(payload)
+
+      with_stubbed_github do
+        post "/github/webhooks",
+          params: body,
+          headers: {
+            "Content-Type" => "application/json",
+            "X-GitHub-Event" => "issue_comment",
+            "X-Hub-Signature-256" => signature
+          }
+      end
+
+      assert_response :success
+      assert_equal [ "bug", "ui" ], Integrations::Github::Message.last.payload["labels"]
+    end
+
     test "PR comment without linked issue creates PR agent" do
       pr_body = "This is just a regular PR description"
       issues = extract_linked_issues(pr_body)
@@ -55,6 +55,19 @@ class HubCommandTest < ActiveSupport::TestCase
     assert_not_nil cmd.acknowledged_at
   end
 
+  test "mark_failed! records status and reason" do
+    hub = hubs(:active_hub)
+    cmd = HubCommand.create_for_hub!(hub,
+      event_type: "create_agent",
+      payload: { issue_number: 1, prompt: "Test" })
+
+    cmd.mark_failed!("boom")
+
+    assert cmd.failed?
+    assert_equal "boom", cmd.failure_reason
+    refute_includes hub.hub_commands.unacked_from(0), cmd
+  end
+
   test "unacked_from scope returns unacked commands after sequence" do
     hub = hubs(:active_hub)
 
@@ -137,6 +137,21 @@ class Integrations::Github::MessageTest < ActiveSupport::TestCase
     assert_equal "pending", message.status
   end
 
+  test "mark_failed! records status and reason" do
+    message = Integrations::Github::Message.create!(
+      event_type: "github_mention",
+      repo: "test/repo",
+      issue_number: 1,
+      payload: { repo: "test/repo", issue_number: 1 }
+    )
+
+    message.mark_failed!("boom")
+
+    assert message.failed?
+    assert_equal "boom", message.failure_reason
+    refute_includes Integrations::Github::Message.pending, message
+  end
+
   # Broadcast tests
   test "broadcasts to repo stream on create" do
     assert_broadcasts("github_events:test/repo", 1) do
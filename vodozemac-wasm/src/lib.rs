@@ -1,5 +1,6 @@
 use wasm_bindgen::prelude::*;
 
+use vodozemac::megolm::{GroupSession, InboundGroupSession, MegolmMessage, SessionKey};
 use vodozemac::olm::{
     Account, InboundCreationResult, OlmMessage, Session, SessionConfig,
 };
@@ -40,6 +41,330 @@ pub fn ed25519_verify(
     }
 }
 
+/// Verify an Ed25519 signature using the base64 forms that
+/// `VodozemacAccount.ed25519Key()` and `sign()` return.
+///
+/// The message stays raw bytes, so binary payloads (key bundles, decoded
+/// base64 blobs) verify without being re-encoded as text first.
+///
+/// # Parameters
+/// - `signing_key` — Ed25519 public key, unpadded base64.
+/// - `message` — the bytes that were signed.
+/// - `signature` — Ed25519 signature, unpadded base64.
+///
+/// # Errors
+/// Returns `JsError` if the key or signature does not decode.
+#[wasm_bindgen(js_name = "ed25519VerifyBase64")]
+pub fn ed25519_verify_base64(
+    signing_key: &str,
+    message: &[u8],
+    signature: &str,
+) -> Result<bool, JsError> {
+    let key = Ed25519PublicKey::from_base64(signing_key)
+        .map_err(|e| JsError::new(&format!("bad signing_key: {e}")))?;
+
+    let sig = Ed25519Signature::from_base64(signature)
+        .map_err(|e| JsError::new(&format!("bad signature: {e}")))?;
+
+    Ok(key.verify(message, &sig).is_ok())
+}
+
+// ---------------------------------------------------------------------------
+// Pickle errors
+// ---------------------------------------------------------------------------
+
+/// Why a stored pickle could not be restored.
+///
+/// Surfaced to JS as an `Error` whose message starts with [`code`](Self::code).
+/// Recovery is the same for every kind: the Olm state cannot be rebuilt, so
+/// discard the stored pickle and re-pair the device. The kind tells the caller
+/// what to report.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UnpickleError {
+    /// Not base64, truncated, garbage, or encrypted with a different key.
+    Malformed(String),
+    /// Decrypted, but the JSON isn't the expected object (e.g. an Account
+    /// pickle handed to `VodozemacSession.fromPickle`).
+    SchemaMismatch(String),
+    /// Written by a newer Olm version than this build understands.
+    UnsupportedVersion(String),
+}
+
+impl UnpickleError {
+    /// Stable prefix for the JS error message.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Malformed(_) => "pickle_malformed",
+            Self::SchemaMismatch(_) => "pickle_schema_mismatch",
+            Self::UnsupportedVersion(_) => "pickle_unsupported_version",
+        }
+    }
+}
+
+impl std::fmt::Display for UnpickleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (Self::Malformed(detail)
+        | Self::SchemaMismatch(detail)
+        | Self::UnsupportedVersion(detail)) = self;
+        write!(
+            f,
+            "{}: {detail}. Discard the stored pickle and re-pair this device.",
+            self.code()
+        )
+    }
+}
+
+impl std::error::Error for UnpickleError {}
+
+impl From<vodozemac::PickleError> for UnpickleError {
+    fn from(err: vodozemac::PickleError) -> Self {
+        use serde_json::error::Category;
+        use vodozemac::PickleError;
+
+        match &err {
+            PickleError::Base64(_) | PickleError::Decryption(_) => Self::Malformed(err.to_string()),
+            PickleError::Serialization(json) => match json.classify() {
+                Category::Io | Category::Syntax | Category::Eof => Self::Malformed(err.to_string()),
+                Category::Data => Self::SchemaMismatch(err.to_string()),
+            },
+        }
+    }
+}
+
+/// A pickle type whose JSON may name the Olm or Megolm version that wrote it.
+trait VersionedPickle: serde::de::DeserializeOwned {
+    /// Whether `json` names a version this build cannot read.
+    fn unsupported_version(_json: &serde_json::Value) -> bool {
+        false
+    }
+}
+
+impl VersionedPickle for vodozemac::olm::AccountPickle {}
+
+impl VersionedPickle for vodozemac::olm::SessionPickle {
+    fn unsupported_version(json: &serde_json::Value) -> bool {
+        config_unreadable::<SessionConfig>(json)
+    }
+}
+
+impl VersionedPickle for vodozemac::megolm::GroupSessionPickle {
+    fn unsupported_version(json: &serde_json::Value) -> bool {
+        config_unreadable::<vodozemac::megolm::SessionConfig>(json)
+    }
+}
+
+impl VersionedPickle for vodozemac::megolm::InboundGroupSessionPickle {
+    fn unsupported_version(json: &serde_json::Value) -> bool {
+        config_unreadable::<vodozemac::megolm::SessionConfig>(json)
+    }
+}
+
+/// Whether `json` has a `config` that does not parse as `C`. The config only
+/// holds the version, so a newer writer is the one way it fails to parse.
+fn config_unreadable<C: serde::de::DeserializeOwned>(json: &serde_json::Value) -> bool {
+    json.get("config")
+        .is_some_and(|config| C::deserialize(config).is_err())
+}
+
+/// Decrypt and deserialize a pickle the way `from_encrypted` does, but parse
+/// the JSON first so a schema error can be told apart from a newer version.
+fn unpickle<T: VersionedPickle>(pickle: &str, pickle_key: &[u8; 32]) -> Result<T, UnpickleError> {
+    use vodozemac::PickleError;
+
+    let decoded = vodozemac::base64_decode(pickle).map_err(PickleError::from)?;
+    let plaintext = vodozemac::hazmat::Cipher::new_pickle(pickle_key)
+        .decrypt_pickle(&decoded)
+        .map_err(PickleError::from)?;
+    let json: serde_json::Value = serde_json::from_slice(&plaintext).map_err(PickleError::from)?;
+
+    T::deserialize(&json).map_err(|err| {
+        let err = PickleError::from(err);
+        if T::unsupported_version(&json) {
+            UnpickleError::UnsupportedVersion(err.to_string())
+        } else {
+            UnpickleError::SchemaMismatch(err.to_string())
+        }
+    })
+}
+
+/// A pickle key that is not exactly 32 bytes.
+///
+/// Surfaced to JS as an `Error` whose message starts with `pickle_invalid_key`.
+/// Unlike [`UnpickleError`] this is a caller bug: nothing was read or written.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidPickleKey {
+    /// Length of the key that was passed.
+    pub len: usize,
+}
+
+impl std::fmt::Display for InvalidPickleKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "pickle_invalid_key: pickle_key must be exactly 32 bytes, got {}",
+            self.len
+        )
+    }
+}
+
+impl std::error::Error for InvalidPickleKey {}
+
+/// Check that `pickle_key` is a 32-byte pickle encryption key.
+fn pickle_key_bytes(pickle_key: &[u8]) -> Result<&[u8; 32], InvalidPickleKey> {
+    pickle_key.try_into().map_err(|_| InvalidPickleKey {
+        len: pickle_key.len(),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Decrypt errors
+// ---------------------------------------------------------------------------
+
+/// Why an Olm or Megolm message could not be decrypted.
+///
+/// Surfaced to JS as an `Error` whose message starts with [`code`](Self::code).
+/// Unlike [`UnpickleError`], the session itself is still usable; what to do
+/// depends on the kind:
+///
+/// - `MessageTooOld` (`MissingMessageKey`, `TooBigMessageGap`, Megolm
+///   `UnknownMessageIndex`): ask the sender to resend; a fresh encryption
+///   uses the current ratchet.
+/// - `WrongSession` (PreKey message for another session ID): route it to
+///   that session, or create an inbound session from it.
+/// - `BadMac` (`InvalidMAC`, Megolm `Signature`): drop the message and do
+///   not retry.
+/// - `Malformed` (`InvalidMACLength`, `InvalidPadding`, unparseable bytes):
+///   drop the message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecryptError {
+    /// The ratchet has moved past this message: it was already decrypted,
+    /// its key was discarded, or it is too far out of order to catch up on.
+    MessageTooOld(String),
+    /// A PreKey message that belongs to a different session.
+    WrongSession(String),
+    /// Authentication failed: the message was tampered with, or it is a
+    /// Normal message encrypted by a different session.
+    BadMac(String),
+    /// Not a well-formed Olm message.
+    Malformed(String),
+}
+
+impl DecryptError {
+    /// Stable prefix for the JS error message.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::MessageTooOld(_) => "decrypt_message_too_old",
+            Self::WrongSession(_) => "decrypt_wrong_session",
+            Self::BadMac(_) => "decrypt_bad_mac",
+            Self::Malformed(_) => "decrypt_malformed",
+        }
+    }
+}
+
+impl std::fmt::Display for DecryptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (Self::MessageTooOld(detail)
+        | Self::WrongSession(detail)
+        | Self::BadMac(detail)
+        | Self::Malformed(detail)) = self;
+        write!(f, "{}: {detail}", self.code())
+    }
+}
+
+impl std::error::Error for DecryptError {}
+
+impl From<vodozemac::olm::DecryptionError> for DecryptError {
+    fn from(err: vodozemac::olm::DecryptionError) -> Self {
+        use vodozemac::olm::DecryptionError;
+
+        match &err {
+            DecryptionError::MissingMessageKey(_) | DecryptionError::TooBigMessageGap(..) => {
+                Self::MessageTooOld(err.to_string())
+            }
+            DecryptionError::InvalidMAC(_) => Self::BadMac(err.to_string()),
+            DecryptionError::InvalidMACLength(..) | DecryptionError::InvalidPadding(_) => {
+                Self::Malformed(err.to_string())
+            }
+        }
+    }
+}
+
+impl From<vodozemac::megolm::DecryptionError> for DecryptError {
+    fn from(err: vodozemac::megolm::DecryptionError) -> Self {
+        use vodozemac::megolm::DecryptionError;
+
+        match &err {
+            DecryptionError::UnknownMessageIndex(..) => Self::MessageTooOld(err.to_string()),
+            DecryptionError::InvalidMAC(_) | DecryptionError::Signature(_) => {
+                Self::BadMac(err.to_string())
+            }
+            DecryptionError::InvalidMACLength(..) | DecryptionError::InvalidPadding(_) => {
+                Self::Malformed(err.to_string())
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Inbound session errors
+// ---------------------------------------------------------------------------
+
+/// Why an inbound session could not be created from a PreKey message.
+///
+/// Surfaced to JS as an `Error` whose message starts with [`code`](Self::code).
+/// The account removes a one-time key only after the PreKey message decrypts,
+/// so neither kind burns the key:
+///
+/// - `Decryption`: the message is corrupt, tampered with or unparseable.
+///   Drop it; the sender can retry with a fresh PreKey message against the
+///   same one-time key.
+/// - `SessionCreation`: the message names a one-time key this account does
+///   not hold (already used, or never published) or a different identity
+///   key. Retrying the same message cannot succeed; the sender needs fresh
+///   keys.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InboundSessionError {
+    /// The PreKey message did not decrypt.
+    Decryption(DecryptError),
+    /// The PreKey message does not match this account's keys.
+    SessionCreation(String),
+}
+
+impl InboundSessionError {
+    /// Stable prefix for the JS error message.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Decryption(_) => "inbound_decryption",
+            Self::SessionCreation(_) => "inbound_session_creation",
+        }
+    }
+}
+
+impl std::fmt::Display for InboundSessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Decryption(err) => write!(f, "{}: {err}", self.code()),
+            Self::SessionCreation(detail) => write!(f, "{}: {detail}", self.code()),
+        }
+    }
+}
+
+impl std::error::Error for InboundSessionError {}
+
+impl From<vodozemac::olm::SessionCreationError> for InboundSessionError {
+    fn from(err: vodozemac::olm::SessionCreationError) -> Self {
+        use vodozemac::olm::SessionCreationError;
+
+        match err {
+            SessionCreationError::Decryption(err) => Self::Decryption(err.into()),
+            SessionCreationError::MissingOneTimeKey(_)
+            | SessionCreationError::MismatchedIdentityKey(..) => {
+                Self::SessionCreation(err.to_string())
+            }
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // VodozemacAccount
 // ---------------------------------------------------------------------------
@@ -60,14 +385,15 @@ impl VodozemacAccount {
 
     /// Restore an Account from an encrypted pickle string.
     /// `pickle_key` must be exactly 32 bytes.
+    ///
+    /// # Errors
+    /// Returns an [`InvalidPickleKey`] message for a bad key length, or an
+    /// [`UnpickleError`] message when the pickle is corrupt.
     #[wasm_bindgen(js_name = "fromPickle")]
     pub fn from_pickle(pickle: &str, pickle_key: &[u8]) -> Result<VodozemacAccount, JsError> {
-        let key: &[u8; 32] = pickle_key
-            .try_into()
-            .map_err(|_| JsError::new("pickle_key must be exactly 32 bytes"))?;
+        let key = pickle_key_bytes(pickle_key)?;
 
-        let account_pickle = vodozemac::olm::AccountPickle::from_encrypted(pickle, key)
-            .map_err(|e| JsError::new(&format!("unpickle failed: {e}")))?;
+        let account_pickle = unpickle::<vodozemac::olm::AccountPickle>(pickle, key)?;
 
         Ok(Self {
             inner: Account::from_pickle(account_pickle),
@@ -77,9 +403,7 @@ impl VodozemacAccount {
     /// Serialize and encrypt the Account into a pickle string.
     /// `pickle_key` must be exactly 32 bytes.
     pub fn pickle(&self, pickle_key: &[u8]) -> Result<String, JsError> {
-        let key: &[u8; 32] = pickle_key
-            .try_into()
-            .map_err(|_| JsError::new("pickle_key must be exactly 32 bytes"))?;
+        let key = pickle_key_bytes(pickle_key)?;
 
         Ok(self.inner.pickle().encrypt(key))
     }
@@ -97,6 +421,9 @@ impl VodozemacAccount {
     }
 
     /// Sign a message with the Ed25519 key. Returns unpadded base64 signature.
+    ///
+    /// `message` is raw bytes and need not be UTF-8; JS strings must be
+    /// encoded (e.g. with `TextEncoder`) before signing.
     pub fn sign(&self, message: &[u8]) -> String {
         self.inner.sign(message).to_base64()
     }
@@ -128,6 +455,10 @@ impl VodozemacAccount {
     /// `prekey_message` — raw bytes of the pre-key message.
     ///
     /// Returns a JS object `{ session: VodozemacSession, plaintext: Uint8Array }`.
+    ///
+    /// # Errors
+    /// Returns an [`InboundSessionError`] message when the PreKey message is
+    /// rejected; the one-time key it names stays available either way.
     #[wasm_bindgen(js_name = "createInboundSession")]
     pub fn create_inbound_session(
         &mut self,
@@ -137,13 +468,8 @@ impl VodozemacAccount {
         let id_key = Curve25519PublicKey::from_base64(identity_key)
             .map_err(|e| JsError::new(&format!("bad identity_key: {e}")))?;
 
-        let prekey_msg = vodozemac::olm::PreKeyMessage::from_bytes(prekey_message)
-            .map_err(|e| JsError::new(&format!("bad prekey_message: {e}")))?;
-
-        let InboundCreationResult { session, plaintext } = self
-            .inner
-            .create_inbound_session(id_key, &prekey_msg)
-            .map_err(|e| JsError::new(&format!("inbound session failed: {e}")))?;
+        let InboundCreationResult { session, plaintext } =
+            self.create_inbound(id_key, prekey_message)?;
 
         // Build the JS return value: { session, plaintext }
         let obj = js_sys::Object::new();
@@ -170,12 +496,9 @@ impl VodozemacAccount {
     /// Return unpublished one-time keys as a JS object `{ keyId: base64Key, ... }`.
     #[wasm_bindgen(js_name = "oneTimeKeys")]
     pub fn one_time_keys(&self) -> Result<JsValue, JsError> {
-        let keys: HashMap<KeyId, Curve25519PublicKey> = self.inner.one_time_keys();
         let obj = js_sys::Object::new();
 
-        for (key_id, curve_key) in keys {
-            let id_str: String = key_id.to_base64();
-            let key_b64 = curve_key.to_base64();
+        for (id_str, key_b64) in self.one_time_key_entries() {
             js_sys::Reflect::set(&obj, &id_str.into(), &key_b64.into())
                 .map_err(|_| JsError::new("Reflect::set one_time_key"))?;
         }
@@ -183,11 +506,118 @@ impl VodozemacAccount {
         Ok(obj.into())
     }
 
-    /// Mark all one-time keys as published.
+    /// Return only the base64 IDs of unpublished one-time keys.
+    ///
+    /// Used to reconcile with the server's view of available keys without
+    /// passing key material around.
+    #[wasm_bindgen(js_name = "oneTimeKeyIds")]
+    pub fn one_time_key_ids(&self) -> Vec<String> {
+        self.one_time_key_entries()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// Generate a new fallback key.
+    ///
+    /// Peers use the fallback key to establish a session once every
+    /// published one-time key is used up. Unlike one-time keys it is not
+    /// consumed by inbound sessions. The account keeps the current and the
+    /// previous fallback key, so generating a new one discards the previous
+    /// key; it is returned (unpadded base64) if there was one.
+    #[wasm_bindgen(js_name = "generateFallbackKey")]
+    pub fn generate_fallback_key(&mut self) -> Option<String> {
+        self.inner
+            .generate_fallback_key()
+            .map(|key| key.to_base64())
+    }
+
+    /// Return the unpublished fallback key as a JS object
+    /// `{ keyId: base64Key }`, or `{}` once it has been published.
+    #[wasm_bindgen(js_name = "fallbackKey")]
+    pub fn fallback_key(&self) -> Result<JsValue, JsError> {
+        let obj = js_sys::Object::new();
+
+        for (id_str, key_b64) in self.fallback_key_entries() {
+            js_sys::Reflect::set(&obj, &id_str.into(), &key_b64.into())
+                .map_err(|_| JsError::new("Reflect::set fallback_key"))?;
+        }
+
+        Ok(obj.into())
+    }
+
+    /// Forget the private part of the previous fallback key.
+    ///
+    /// Call once peers have had time to use the current fallback key.
+    /// Returns `false` if there was no previous key to forget.
+    #[wasm_bindgen(js_name = "forgetFallbackKey")]
+    pub fn forget_fallback_key(&mut self) -> bool {
+        self.inner.forget_fallback_key()
+    }
+
+    /// Mark all one-time and fallback keys as published.
     #[wasm_bindgen(js_name = "markKeysAsPublished")]
     pub fn mark_keys_as_published(&mut self) {
         self.inner.mark_keys_as_published();
     }
+
+    /// Number of one-time keys generated but not yet marked as published.
+    #[wasm_bindgen(js_name = "unpublishedKeyCount")]
+    pub fn unpublished_key_count(&self) -> usize {
+        self.inner.one_time_keys().len()
+    }
+
+    /// Number of published one-time keys not yet used by an inbound session.
+    ///
+    /// Replenish when this drops low; keys consumed by inbound sessions are
+    /// no longer counted.
+    #[wasm_bindgen(js_name = "publishedKeyCount")]
+    pub fn published_key_count(&self) -> usize {
+        self.inner
+            .stored_one_time_key_count()
+            .saturating_sub(self.unpublished_key_count())
+    }
+
+    /// How many one-time keys should be published at once.
+    #[wasm_bindgen(js_name = "maxOneTimeKeys")]
+    pub fn max_one_time_keys(&self) -> usize {
+        self.inner.max_number_of_one_time_keys()
+    }
+}
+
+impl VodozemacAccount {
+    fn create_inbound(
+        &mut self,
+        identity_key: Curve25519PublicKey,
+        prekey_message: &[u8],
+    ) -> Result<InboundCreationResult, InboundSessionError> {
+        let prekey_msg =
+            vodozemac::olm::PreKeyMessage::from_bytes(prekey_message).map_err(|e| {
+                InboundSessionError::Decryption(DecryptError::Malformed(format!(
+                    "bad prekey_message: {e}"
+                )))
+            })?;
+
+        Ok(self
+            .inner
+            .create_inbound_session(identity_key, &prekey_msg)?)
+    }
+
+    /// Unpublished one-time keys as `(base64 key ID, base64 key)` pairs.
+    fn one_time_key_entries(&self) -> Vec<(String, String)> {
+        key_entries(self.inner.one_time_keys())
+    }
+
+    /// The unpublished fallback key, if any, as a `(base64 key ID, base64 key)` pair.
+    fn fallback_key_entries(&self) -> Vec<(String, String)> {
+        key_entries(self.inner.fallback_key())
+    }
+}
+
+fn key_entries(keys: HashMap<KeyId, Curve25519PublicKey>) -> Vec<(String, String)> {
+    keys.into_iter()
+        .map(|(key_id, curve_key)| (key_id.to_base64(), curve_key.to_base64()))
+        .collect()
 }
 
 // ---------------------------------------------------------------------------
@@ -203,14 +633,15 @@ pub struct VodozemacSession {
 impl VodozemacSession {
     /// Restore a Session from an encrypted pickle string.
     /// `pickle_key` must be exactly 32 bytes.
+    ///
+    /// # Errors
+    /// Returns an [`InvalidPickleKey`] message for a bad key length, or an
+    /// [`UnpickleError`] message when the pickle is corrupt.
     #[wasm_bindgen(js_name = "fromPickle")]
     pub fn from_pickle(pickle: &str, pickle_key: &[u8]) -> Result<VodozemacSession, JsError> {
-        let key: &[u8; 32] = pickle_key
-            .try_into()
-            .map_err(|_| JsError::new("pickle_key must be exactly 32 bytes"))?;
+        let key = pickle_key_bytes(pickle_key)?;
 
-        let session_pickle = vodozemac::olm::SessionPickle::from_encrypted(pickle, key)
-            .map_err(|e| JsError::new(&format!("unpickle failed: {e}")))?;
+        let session_pickle = unpickle::<vodozemac::olm::SessionPickle>(pickle, key)?;
 
         Ok(Self {
             inner: Session::from_pickle(session_pickle),
@@ -220,9 +651,7 @@ impl VodozemacSession {
     /// Serialize and encrypt the Session into a pickle string.
     /// `pickle_key` must be exactly 32 bytes.
     pub fn pickle(&self, pickle_key: &[u8]) -> Result<String, JsError> {
-        let key: &[u8; 32] = pickle_key
-            .try_into()
-            .map_err(|_| JsError::new("pickle_key must be exactly 32 bytes"))?;
+        let key = pickle_key_bytes(pickle_key)?;
 
         Ok(self.inner.pickle().encrypt(key))
     }
@@ -230,6 +659,8 @@ impl VodozemacSession {
     /// Encrypt plaintext. Returns a JS object:
     /// `{ messageType: number, ciphertext: Uint8Array }`
     ///
+    /// `plaintext` is arbitrary bytes; callers encode text themselves, so
+    /// binary payloads need no conversion.
     /// `messageType` is 0 for PreKey, 1 for Normal.
     pub fn encrypt(&mut self, plaintext: &[u8]) -> Result<JsValue, JsError> {
         let olm_msg: OlmMessage = self.inner.encrypt(plaintext);
@@ -254,13 +685,232 @@ impl VodozemacSession {
     /// `ciphertext` — raw ciphertext bytes.
     ///
     /// Returns the plaintext as `Uint8Array`.
+    ///
+    /// # Errors
+    /// Returns a [`DecryptError`] message; its code says whether to ask for
+    /// a resend, pick another session, or drop the message.
     pub fn decrypt(&mut self, message_type: u8, ciphertext: &[u8]) -> Result<Vec<u8>, JsError> {
+        Ok(self.decrypt_message(message_type, ciphertext)?)
+    }
+
+    /// Decrypt several messages in order in a single call.
+    ///
+    /// `messages` — array of `{ messageType: number, ciphertext: Uint8Array }`,
+    /// the shape [`encrypt`](Self::encrypt) returns.
+    ///
+    /// Returns an array of the same length whose entries are
+    /// `{ ok: Uint8Array }` or `{ err: string }`. The ratchet is stateful, so
+    /// the first failure stops the batch: every later entry is
+    /// `{ err: "decrypt_batch_aborted: ..." }` and was not attempted.
+    ///
+    /// # Errors
+    /// Returns `JsError` if an entry does not have the expected shape;
+    /// nothing is decrypted in that case.
+    #[wasm_bindgen(js_name = "decryptBatch")]
+    pub fn decrypt_batch(&mut self, messages: js_sys::Array) -> Result<js_sys::Array, JsError> {
+        let mut parsed = Vec::with_capacity(messages.length() as usize);
+        for (i, entry) in messages.iter().enumerate() {
+            let message_type = js_sys::Reflect::get(&entry, &"messageType".into())
+                .ok()
+                .and_then(|v| v.as_f64());
+            let ciphertext = js_sys::Reflect::get(&entry, &"ciphertext".into())
+                .ok()
+                .filter(|v| v.is_instance_of::<js_sys::Uint8Array>());
+            let (Some(message_type), Some(ciphertext)) = (message_type, ciphertext) else {
+                return Err(JsError::new(&format!(
+                    "messages[{i}] needs messageType and ciphertext"
+                )));
+            };
+            parsed.push((
+                message_type as u8,
+                js_sys::Uint8Array::new(&ciphertext).to_vec(),
+            ));
+        }
+
+        let results = js_sys::Array::new();
+        for result in self.decrypt_messages(&parsed) {
+            let obj = js_sys::Object::new();
+            let (key, value): (&str, JsValue) = match result {
+                Ok(plaintext) => ("ok", js_sys::Uint8Array::from(plaintext.as_slice()).into()),
+                Err(err) => ("err", err.into()),
+            };
+            js_sys::Reflect::set(&obj, &key.into(), &value)
+                .map_err(|_| JsError::new("Reflect::set batch result"))?;
+            results.push(&obj);
+        }
+        Ok(results)
+    }
+
+    /// Whether a received PreKey message belongs to this session.
+    ///
+    /// Check existing sessions with this before calling
+    /// `VodozemacAccount.createInboundSession`, which always consumes a
+    /// one-time key. Normal messages carry no session ID, so they never
+    /// match; route those by sender instead.
+    ///
+    /// # Errors
+    /// Returns a `decrypt_malformed` [`DecryptError`] message if the bytes
+    /// are not an Olm message.
+    #[wasm_bindgen(js_name = "sessionMatches")]
+    pub fn session_matches(&self, message_type: u8, ciphertext: &[u8]) -> Result<bool, JsError> {
+        Ok(self.matches_message(message_type, ciphertext)?)
+    }
+
+    /// Return the globally unique session ID (base64).
+    #[wasm_bindgen(js_name = "sessionId")]
+    pub fn session_id(&self) -> String {
+        self.inner.session_id()
+    }
+
+    /// Snapshot the session's display state in one call.
+    ///
+    /// The returned `SessionInfo` is a detached copy, so callers can hold on
+    /// to it without keeping a reference to the live session.
+    pub fn info(&self) -> SessionInfo {
+        SessionInfo {
+            session_id: self.inner.session_id(),
+            has_received_message: self.inner.has_received_message(),
+        }
+    }
+}
+
+impl VodozemacSession {
+    fn matches_message(&self, message_type: u8, ciphertext: &[u8]) -> Result<bool, DecryptError> {
         let olm_msg = OlmMessage::from_parts(message_type as usize, ciphertext)
-            .map_err(|e| JsError::new(&format!("bad olm message: {e}")))?;
+            .map_err(|e| DecryptError::Malformed(format!("bad olm message: {e}")))?;
+        Ok(match olm_msg {
+            OlmMessage::PreKey(prekey) => prekey.session_id() == self.inner.session_id(),
+            OlmMessage::Normal(_) => false,
+        })
+    }
 
-        self.inner
-            .decrypt(&olm_msg)
-            .map_err(|e| JsError::new(&format!("decrypt failed: {e}")))
+    fn decrypt_message(
+        &mut self,
+        message_type: u8,
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, DecryptError> {
+        let olm_msg = OlmMessage::from_parts(message_type as usize, ciphertext)
+            .map_err(|e| DecryptError::Malformed(format!("bad olm message: {e}")))?;
+
+        // vodozemac decrypts a PreKey's inner message without comparing
+        // session IDs, so a PreKey for another session would otherwise
+        // surface as a bad MAC.
+        if let OlmMessage::PreKey(prekey) = &olm_msg {
+            let expected = self.inner.session_id();
+            if prekey.session_id() != expected {
+                return Err(DecryptError::WrongSession(format!(
+                    "PreKey message for session {}, this is {expected}",
+                    prekey.session_id()
+                )));
+            }
+        }
+
+        Ok(self.inner.decrypt(&olm_msg)?)
+    }
+
+    /// Decrypt `messages` in order, stopping at the first failure. Entries
+    /// after it are reported as aborted without touching the ratchet.
+    fn decrypt_messages(&mut self, messages: &[(u8, Vec<u8>)]) -> Vec<Result<Vec<u8>, String>> {
+        let mut results = Vec::with_capacity(messages.len());
+        let mut failed_at = None;
+        for (i, (message_type, ciphertext)) in messages.iter().enumerate() {
+            if let Some(failed) = failed_at {
+                results.push(Err(format!(
+                    "decrypt_batch_aborted: message {failed} failed to decrypt"
+                )));
+                continue;
+            }
+            match self.decrypt_message(*message_type, ciphertext) {
+                Ok(plaintext) => results.push(Ok(plaintext)),
+                Err(err) => {
+                    failed_at = Some(i);
+                    results.push(Err(err.to_string()));
+                }
+            }
+        }
+        results
+    }
+}
+
+/// Read-only snapshot of a `VodozemacSession`, returned by `info()`.
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionInfo {
+    /// Globally unique session ID (base64).
+    #[wasm_bindgen(readonly, js_name = "sessionId")]
+    pub session_id: String,
+    /// Whether a message from the other side has been decrypted, i.e. the
+    /// session is fully established and sends normal (not PreKey) messages.
+    #[wasm_bindgen(readonly, js_name = "hasReceivedMessage")]
+    pub has_received_message: bool,
+}
+
+// ---------------------------------------------------------------------------
+// VodozemacGroupSession
+// ---------------------------------------------------------------------------
+
+/// Megolm session config shared by both ends of a group session.
+fn megolm_config() -> vodozemac::megolm::SessionConfig {
+    vodozemac::megolm::SessionConfig::version_2()
+}
+
+/// Sending side of a Megolm group session.
+///
+/// One encryption reaches every member holding an inbound session created
+/// from [`session_key`](Self::session_key), instead of one Olm encryption
+/// per recipient. Share the session key over Olm.
+#[wasm_bindgen]
+pub struct VodozemacGroupSession {
+    inner: GroupSession,
+}
+
+#[wasm_bindgen]
+impl VodozemacGroupSession {
+    /// Start a new group session with a fresh ratchet.
+    pub fn create() -> Self {
+        Self {
+            inner: GroupSession::new(megolm_config()),
+        }
+    }
+
+    /// Restore a GroupSession from an encrypted pickle string.
+    /// `pickle_key` must be exactly 32 bytes.
+    ///
+    /// # Errors
+    /// Returns an [`InvalidPickleKey`] message for a bad key length, or an
+    /// [`UnpickleError`] message when the pickle is corrupt.
+    #[wasm_bindgen(js_name = "fromPickle")]
+    pub fn from_pickle(pickle: &str, pickle_key: &[u8]) -> Result<VodozemacGroupSession, JsError> {
+        let key = pickle_key_bytes(pickle_key)?;
+
+        let session_pickle = unpickle::<vodozemac::megolm::GroupSessionPickle>(pickle, key)?;
+
+        Ok(Self {
+            inner: GroupSession::from_pickle(session_pickle),
+        })
+    }
+
+    /// Serialize and encrypt the GroupSession into a pickle string.
+    /// `pickle_key` must be exactly 32 bytes.
+    pub fn pickle(&self, pickle_key: &[u8]) -> Result<String, JsError> {
+        let key = pickle_key_bytes(pickle_key)?;
+
+        Ok(self.inner.pickle().encrypt(key))
+    }
+
+    /// Encrypt plaintext for every member. Returns the Megolm message as
+    /// `Uint8Array`.
+    ///
+    /// `plaintext` is arbitrary bytes, as with `VodozemacSession.encrypt`.
+    pub fn encrypt(&mut self, plaintext: &[u8]) -> Vec<u8> {
+        self.inner.encrypt(plaintext).to_bytes()
+    }
+
+    /// Return the key members need to decrypt messages from the current
+    /// ratchet position onward (base64).
+    #[wasm_bindgen(js_name = "sessionKey")]
+    pub fn session_key(&self) -> String {
+        self.inner.session_key().to_base64()
     }
 
     /// Return the globally unique session ID (base64).
@@ -269,3 +919,615 @@ impl VodozemacSession {
         self.inner.session_id()
     }
 }
+
+// ---------------------------------------------------------------------------
+// VodozemacInboundGroupSession
+// ---------------------------------------------------------------------------
+
+/// Receiving side of a Megolm group session.
+#[wasm_bindgen]
+pub struct VodozemacInboundGroupSession {
+    inner: InboundGroupSession,
+}
+
+#[wasm_bindgen]
+impl VodozemacInboundGroupSession {
+    /// Create an inbound session from a sender's
+    /// `VodozemacGroupSession.sessionKey()` (base64).
+    ///
+    /// # Errors
+    /// Returns `JsError` if the session key does not decode.
+    #[wasm_bindgen(constructor)]
+    pub fn new(session_key: &str) -> Result<VodozemacInboundGroupSession, JsError> {
+        let key = SessionKey::from_base64(session_key)
+            .map_err(|e| JsError::new(&format!("bad session_key: {e}")))?;
+
+        Ok(Self {
+            inner: InboundGroupSession::new(&key, megolm_config()),
+        })
+    }
+
+    /// Restore an InboundGroupSession from an encrypted pickle string.
+    /// `pickle_key` must be exactly 32 bytes.
+    ///
+    /// # Errors
+    /// Returns an [`InvalidPickleKey`] message for a bad key length, or an
+    /// [`UnpickleError`] message when the pickle is corrupt.
+    #[wasm_bindgen(js_name = "fromPickle")]
+    pub fn from_pickle(
+        pickle: &str,
+        pickle_key: &[u8],
+    ) -> Result<VodozemacInboundGroupSession, JsError> {
+        let key = pickle_key_bytes(pickle_key)?;
+
+        let session_pickle = unpickle::<vodozemac::megolm::InboundGroupSessionPickle>(pickle, key)?;
+
+        Ok(Self {
+            inner: InboundGroupSession::from_pickle(session_pickle),
+        })
+    }
+
+    /// Serialize and encrypt the InboundGroupSession into a pickle string.
+    /// `pickle_key` must be exactly 32 bytes.
+    pub fn pickle(&self, pickle_key: &[u8]) -> Result<String, JsError> {
+        let key = pickle_key_bytes(pickle_key)?;
+
+        Ok(self.inner.pickle().encrypt(key))
+    }
+
+    /// Decrypt a Megolm message produced by `VodozemacGroupSession.encrypt`.
+    ///
+    /// Returns the plaintext as `Uint8Array`. Unlike Olm, decrypting does not
+    /// consume the message key, so a replayed message decrypts again; callers
+    /// that care must track what they have seen.
+    ///
+    /// # Errors
+    /// Returns a [`DecryptError`] message; `decrypt_message_too_old` means
+    /// the message predates the session key this member was given.
+    pub fn decrypt(&mut self, ciphertext: &[u8]) -> Result<Vec<u8>, JsError> {
+        Ok(self.decrypt_message(ciphertext)?)
+    }
+
+    /// Return the globally unique session ID (base64). Matches the sending
+    /// `VodozemacGroupSession.sessionId()`.
+    #[wasm_bindgen(js_name = "sessionId")]
+    pub fn session_id(&self) -> String {
+        self.inner.session_id()
+    }
+}
+
+impl VodozemacInboundGroupSession {
+    fn decrypt_message(&mut self, ciphertext: &[u8]) -> Result<Vec<u8>, DecryptError> {
+        let message = MegolmMessage::from_bytes(ciphertext)
+            .map_err(|e| DecryptError::Malformed(format!("bad megolm message: {e}")))?;
+
+        Ok(self.inner.decrypt(&message)?.plaintext)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_time_key_ids_match_one_time_keys() {
+        let mut account = VodozemacAccount::create();
+        account.generate_one_time_keys(5);
+
+        let mut ids = account.one_time_key_ids();
+        let mut expected: Vec<String> = account
+            .one_time_key_entries()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        ids.sort();
+        expected.sort();
+
+        assert_eq!(ids.len(), 5);
+        assert_eq!(ids, expected);
+
+        account.mark_keys_as_published();
+        assert!(account.one_time_key_ids().is_empty());
+    }
+
+    #[test]
+    fn info_reflects_received_messages() {
+        let mut alice = VodozemacAccount::create();
+        let mut bob = VodozemacAccount::create();
+        bob.generate_one_time_keys(1);
+        let (_, otk) = bob.one_time_key_entries().remove(0);
+
+        let mut alice_session = alice
+            .create_outbound_session(&bob.curve25519_key(), &otk)
+            .unwrap();
+        let before = alice_session.info();
+        assert_eq!(before.session_id, alice_session.session_id());
+        assert!(!before.has_received_message);
+
+        let OlmMessage::PreKey(prekey) = alice_session.inner.encrypt(b"hello") else {
+            panic!("first message should be a PreKey message");
+        };
+        let InboundCreationResult {
+            session: inner,
+            plaintext,
+        } = bob
+            .inner
+            .create_inbound_session(alice.inner.curve25519_key(), &prekey)
+            .unwrap();
+        let mut bob_session = VodozemacSession { inner };
+        assert_eq!(plaintext, b"hello");
+        assert!(bob_session.info().has_received_message);
+        assert_eq!(bob_session.info().session_id, before.session_id);
+
+        let reply = bob_session.inner.encrypt(b"hi");
+        alice_session.inner.decrypt(&reply).unwrap();
+        let after = alice_session.info();
+        assert!(after.has_received_message);
+        assert_eq!(after.session_id, before.session_id);
+        // Earlier snapshots are detached copies.
+        assert!(!before.has_received_message);
+    }
+
+    #[test]
+    fn base64_signature_verifies_over_raw_bytes() {
+        let account = VodozemacAccount::create();
+        let message = b"{\"deviceId\":\"abc\",\"keys\":[1,2,3]}";
+        let signature = account.sign(message);
+
+        let key = account.ed25519_key();
+        assert!(ed25519_verify_base64(&key, message, &signature).unwrap());
+        assert!(!ed25519_verify_base64(&key, b"something else", &signature).unwrap());
+
+        let other = VodozemacAccount::create().ed25519_key();
+        assert!(!ed25519_verify_base64(&other, message, &signature).unwrap());
+    }
+
+    #[test]
+    fn signs_non_utf8_bytes() {
+        let account = VodozemacAccount::create();
+        // Not valid UTF-8.
+        let message = [0xff, 0xfe, 0x00, 0x80, 0xc3];
+
+        let signature = account.sign(&message);
+        let key = account.ed25519_key();
+        assert!(ed25519_verify_base64(&key, &message, &signature).unwrap());
+        assert!(!ed25519_verify_base64(&key, &message[1..], &signature).unwrap());
+    }
+
+    const KEY: &[u8; 32] = &[7; 32];
+
+    fn restore_session(pickle: &str) -> Result<(), UnpickleError> {
+        unpickle::<vodozemac::olm::SessionPickle>(pickle, KEY)?;
+        Ok(())
+    }
+
+    fn outbound_session() -> VodozemacSession {
+        let mut alice = VodozemacAccount::create();
+        let mut bob = VodozemacAccount::create();
+        bob.generate_one_time_keys(1);
+        let (_, otk) = bob.one_time_key_entries().remove(0);
+        alice
+            .create_outbound_session(&bob.curve25519_key(), &otk)
+            .unwrap()
+    }
+
+    fn session_pickle() -> String {
+        outbound_session().inner.pickle().encrypt(KEY)
+    }
+
+    #[test]
+    fn valid_pickle_restores() {
+        assert!(restore_session(&session_pickle()).is_ok());
+    }
+
+    #[test]
+    fn truncated_or_garbage_pickle_is_malformed() {
+        let pickle = session_pickle();
+        for corrupt in [&pickle[..pickle.len() / 2], "not a pickle at all!", ""] {
+            let err = restore_session(corrupt).unwrap_err();
+            assert_eq!(err.code(), "pickle_malformed", "{corrupt:?}: {err}");
+        }
+
+        // Right bytes, wrong key: indistinguishable from garbage.
+        let other_key = outbound_session().inner.pickle().encrypt(&[8; 32]);
+        let err = restore_session(&other_key).unwrap_err();
+        assert!(matches!(err, UnpickleError::Malformed(_)), "{err}");
+    }
+
+    #[test]
+    fn account_pickle_round_trips_only_with_its_key() {
+        let account = VodozemacAccount::create();
+        let pickle = account.inner.pickle().encrypt(KEY);
+
+        let restored = vodozemac::olm::AccountPickle::from_encrypted(&pickle, KEY).unwrap();
+        assert_eq!(
+            Account::from_pickle(restored).curve25519_key(),
+            account.inner.curve25519_key()
+        );
+
+        let Err(err) = vodozemac::olm::AccountPickle::from_encrypted(&pickle, &[8; 32]) else {
+            panic!("a different key must not decrypt the pickle");
+        };
+        let err = UnpickleError::from(err);
+        assert!(matches!(err, UnpickleError::Malformed(_)), "{err}");
+    }
+
+    #[test]
+    fn pickle_key_must_be_32_bytes() {
+        assert!(pickle_key_bytes(&[0; 32]).is_ok());
+        for len in [0, 31, 33] {
+            let err = pickle_key_bytes(&vec![0; len]).unwrap_err();
+            assert_eq!(err, InvalidPickleKey { len });
+            assert!(err.to_string().starts_with("pickle_invalid_key: "));
+        }
+    }
+
+    #[test]
+    fn account_pickle_for_session_is_schema_mismatch() {
+        let account_pickle = VodozemacAccount::create().inner.pickle().encrypt(KEY);
+        let err = restore_session(&account_pickle).unwrap_err();
+        assert!(matches!(err, UnpickleError::SchemaMismatch(_)), "{err}");
+        assert!(err.to_string().contains("re-pair"));
+    }
+
+    #[test]
+    fn unknown_session_version_is_unsupported() {
+        // Pickles are only encrypted JSON, so rewrite the plaintext shape to
+        // what a newer Olm version would store.
+        let mut json = serde_json::to_value(outbound_session().inner.pickle()).unwrap();
+        json["config"]["version"] = "V9".into();
+        let plaintext = serde_json::to_vec(&json).unwrap();
+        let pickle = vodozemac::base64_encode(
+            vodozemac::hazmat::Cipher::new_pickle(KEY).encrypt_pickle(&plaintext),
+        );
+
+        let err = restore_session(&pickle).unwrap_err();
+        assert!(matches!(err, UnpickleError::UnsupportedVersion(_)), "{err}");
+
+        // Same version, but a field the schema needs is gone.
+        json["config"]["version"] = "V1".into();
+        json.as_object_mut().unwrap().remove("session_keys");
+        let plaintext = serde_json::to_vec(&json).unwrap();
+        let pickle = vodozemac::base64_encode(
+            vodozemac::hazmat::Cipher::new_pickle(KEY).encrypt_pickle(&plaintext),
+        );
+        let err = restore_session(&pickle).unwrap_err();
+        assert!(matches!(err, UnpickleError::SchemaMismatch(_)), "{err}");
+    }
+
+    /// Alice's outbound session and Bob's matching inbound session, after
+    /// Bob has decrypted Alice's first (PreKey) message.
+    fn session_pair() -> (VodozemacAccount, VodozemacSession, VodozemacSession) {
+        let mut alice = VodozemacAccount::create();
+        let mut bob = VodozemacAccount::create();
+        bob.generate_one_time_keys(1);
+        let (_, otk) = bob.one_time_key_entries().remove(0);
+        let mut alice_session = alice
+            .create_outbound_session(&bob.curve25519_key(), &otk)
+            .unwrap();
+
+        let OlmMessage::PreKey(prekey) = alice_session.inner.encrypt(b"hello") else {
+            panic!("first message should be a PreKey message");
+        };
+        let inner = bob
+            .inner
+            .create_inbound_session(alice.inner.curve25519_key(), &prekey)
+            .unwrap()
+            .session;
+        (bob, alice_session, VodozemacSession { inner })
+    }
+
+    #[test]
+    fn prekey_for_another_session_is_wrong_session() {
+        let (mut bob, _, mut bob_session) = session_pair();
+        bob.generate_one_time_keys(1);
+        let (_, otk) = bob.one_time_key_entries().remove(0);
+        let mut other = VodozemacAccount::create()
+            .create_outbound_session(&bob.curve25519_key(), &otk)
+            .unwrap();
+
+        let (message_type, ciphertext) = other.inner.encrypt(b"hi").to_parts();
+        let err = bob_session
+            .decrypt_message(message_type as u8, &ciphertext)
+            .unwrap_err();
+        assert!(matches!(err, DecryptError::WrongSession(_)), "{err}");
+    }
+
+    #[test]
+    fn later_prekey_matches_existing_session() {
+        let (mut bob, mut alice_session, mut bob_session) = session_pair();
+
+        // Alice has not heard back yet, so she still sends PreKey messages.
+        let (message_type, ciphertext) = alice_session.inner.encrypt(b"again").to_parts();
+        assert_eq!(message_type, 0);
+        assert!(bob_session
+            .matches_message(message_type as u8, &ciphertext)
+            .unwrap());
+
+        bob.generate_one_time_keys(1);
+        let (_, otk) = bob.one_time_key_entries().remove(0);
+        let mut other = VodozemacAccount::create()
+            .create_outbound_session(&bob.curve25519_key(), &otk)
+            .unwrap();
+        let (message_type, ciphertext) = other.inner.encrypt(b"hi").to_parts();
+        assert!(!bob_session
+            .matches_message(message_type as u8, &ciphertext)
+            .unwrap());
+
+        let (message_type, ciphertext) = bob_session.inner.encrypt(b"ack").to_parts();
+        assert_eq!(message_type, 1);
+        assert!(!alice_session
+            .matches_message(message_type as u8, &ciphertext)
+            .unwrap());
+    }
+
+    #[test]
+    fn tampered_message_is_bad_mac() {
+        let (_, mut alice_session, mut bob_session) = session_pair();
+        let (message_type, mut ciphertext) = alice_session.inner.encrypt(b"hi").to_parts();
+        let last = ciphertext.len() - 1;
+        ciphertext[last] ^= 0x01;
+
+        let err = bob_session
+            .decrypt_message(message_type as u8, &ciphertext)
+            .unwrap_err();
+        assert!(matches!(err, DecryptError::BadMac(_)), "{err}");
+        assert!(err.to_string().starts_with("decrypt_bad_mac: "));
+    }
+
+    #[test]
+    fn replayed_message_is_too_old() {
+        let (_, mut alice_session, mut bob_session) = session_pair();
+        let reply = bob_session.inner.encrypt(b"ack");
+        alice_session.inner.decrypt(&reply).unwrap();
+
+        let (message_type, ciphertext) = alice_session.inner.encrypt(b"hi").to_parts();
+        assert_eq!(message_type, 1, "established sessions send Normal messages");
+        let plaintext = bob_session
+            .decrypt_message(message_type as u8, &ciphertext)
+            .unwrap();
+        assert_eq!(plaintext, b"hi");
+
+        let err = bob_session
+            .decrypt_message(message_type as u8, &ciphertext)
+            .unwrap_err();
+        assert_eq!(err.code(), "decrypt_message_too_old", "{err}");
+    }
+
+    #[test]
+    fn garbage_is_malformed() {
+        let (_, _, mut bob_session) = session_pair();
+        let err = bob_session.decrypt_message(1, b"nope").unwrap_err();
+        assert!(matches!(err, DecryptError::Malformed(_)), "{err}");
+    }
+
+    #[test]
+    fn non_utf8_plaintext_round_trips() {
+        let (_, mut alice_session, mut bob_session) = session_pair();
+        let payload = [0xFF, 0xFE, 0x00, b'o', b'k', 0x80];
+
+        let (message_type, ciphertext) = alice_session.inner.encrypt(payload).to_parts();
+        let plaintext = bob_session
+            .decrypt_message(message_type as u8, &ciphertext)
+            .unwrap();
+        assert_eq!(plaintext, payload);
+    }
+
+    #[test]
+    fn batch_stops_at_first_bad_message() {
+        let (_, mut alice_session, mut bob_session) = session_pair();
+        let mut batch: Vec<(u8, Vec<u8>)> = (0..5)
+            .map(|i| {
+                let (message_type, ciphertext) = alice_session
+                    .inner
+                    .encrypt(format!("msg {i}").as_bytes())
+                    .to_parts();
+                (message_type as u8, ciphertext)
+            })
+            .collect();
+        *batch[2].1.last_mut().unwrap() ^= 0x01;
+
+        let results = bob_session.decrypt_messages(&batch);
+        assert_eq!(results.len(), 5);
+        assert_eq!(results[0].as_deref(), Ok(&b"msg 0"[..]));
+        assert_eq!(results[1].as_deref(), Ok(&b"msg 1"[..]));
+        assert!(results[2]
+            .as_ref()
+            .unwrap_err()
+            .starts_with("decrypt_bad_mac: "));
+        for result in &results[3..] {
+            assert_eq!(
+                result.as_ref().unwrap_err(),
+                "decrypt_batch_aborted: message 2 failed to decrypt"
+            );
+        }
+
+        // Aborted entries were not attempted, so they still decrypt later.
+        assert_eq!(
+            bob_session
+                .decrypt_message(batch[3].0, &batch[3].1)
+                .unwrap(),
+            b"msg 3"
+        );
+    }
+
+    /// Alice's first PreKey message to Bob, plus both accounts.
+    fn first_prekey() -> (VodozemacAccount, VodozemacAccount, Vec<u8>) {
+        let mut alice = VodozemacAccount::create();
+        let mut bob = VodozemacAccount::create();
+        bob.generate_one_time_keys(1);
+        let (_, otk) = bob.one_time_key_entries().remove(0);
+        let mut session = alice
+            .create_outbound_session(&bob.curve25519_key(), &otk)
+            .unwrap();
+        let OlmMessage::PreKey(prekey) = session.inner.encrypt(b"hello") else {
+            panic!("first message should be a PreKey message");
+        };
+        (alice, bob, prekey.to_bytes())
+    }
+
+    #[test]
+    fn key_counts_track_publishing_and_consumption() {
+        let mut bob = VodozemacAccount::create();
+        bob.generate_one_time_keys(10);
+        assert_eq!(bob.unpublished_key_count(), 10);
+        assert_eq!(bob.published_key_count(), 0);
+
+        let keys: Vec<String> = bob
+            .one_time_key_entries()
+            .into_iter()
+            .map(|(_, key)| key)
+            .collect();
+        bob.mark_keys_as_published();
+        assert_eq!(bob.unpublished_key_count(), 0);
+        assert_eq!(bob.published_key_count(), 10);
+
+        for otk in &keys[..2] {
+            let mut alice = VodozemacAccount::create();
+            let mut session = alice
+                .create_outbound_session(&bob.curve25519_key(), otk)
+                .unwrap();
+            let OlmMessage::PreKey(prekey) = session.inner.encrypt(b"hi") else {
+                panic!("first message should be a PreKey message");
+            };
+            bob.create_inbound(alice.inner.curve25519_key(), &prekey.to_bytes())
+                .unwrap();
+        }
+        assert_eq!(bob.published_key_count(), 8);
+        assert_eq!(bob.unpublished_key_count(), 0);
+
+        bob.generate_one_time_keys(3);
+        assert_eq!(bob.unpublished_key_count(), 3);
+        assert_eq!(bob.published_key_count(), 8);
+        assert!(bob.max_one_time_keys() > 0);
+    }
+
+    #[test]
+    fn fallback_key_establishes_sessions_after_otks_run_out() {
+        let (alice, mut bob, prekey) = first_prekey();
+        bob.create_inbound(alice.inner.curve25519_key(), &prekey)
+            .unwrap();
+        assert_eq!(bob.unpublished_key_count(), 0, "OTKs exhausted");
+
+        assert_eq!(bob.generate_fallback_key(), None, "no previous fallback");
+        let (_, fallback) = bob.fallback_key_entries().remove(0);
+        bob.mark_keys_as_published();
+        assert!(bob.fallback_key_entries().is_empty());
+
+        // The fallback key is not consumed, so several peers can use it.
+        for text in [&b"first"[..], b"second"] {
+            let mut peer = VodozemacAccount::create();
+            let mut session = peer
+                .create_outbound_session(&bob.curve25519_key(), &fallback)
+                .unwrap();
+            let OlmMessage::PreKey(prekey) = session.inner.encrypt(text) else {
+                panic!("first message should be a PreKey message");
+            };
+            let created = bob
+                .create_inbound(peer.inner.curve25519_key(), &prekey.to_bytes())
+                .unwrap();
+            assert_eq!(created.plaintext, text);
+        }
+
+        // Rotating keeps the old key as the previous one until forgotten.
+        assert_eq!(bob.generate_fallback_key(), None);
+        assert!(bob.forget_fallback_key());
+        assert!(!bob.forget_fallback_key());
+        bob.generate_fallback_key();
+        assert!(bob.generate_fallback_key().is_some());
+    }
+
+    #[test]
+    fn tampered_prekey_keeps_one_time_key() {
+        let (alice, mut bob, prekey) = first_prekey();
+        let mut tampered = prekey.clone();
+        *tampered.last_mut().unwrap() ^= 0x01;
+
+        let err = bob
+            .create_inbound(alice.inner.curve25519_key(), &tampered)
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                InboundSessionError::Decryption(DecryptError::BadMac(_))
+            ),
+            "{err}"
+        );
+        assert_eq!(bob.inner.one_time_keys().len(), 1, "OTK must not be burned");
+
+        let created = bob
+            .create_inbound(alice.inner.curve25519_key(), &prekey)
+            .unwrap();
+        assert_eq!(created.plaintext, b"hello");
+        assert!(bob.inner.one_time_keys().is_empty());
+    }
+
+    #[test]
+    fn reused_prekey_is_session_creation_error() {
+        let (alice, mut bob, prekey) = first_prekey();
+        bob.create_inbound(alice.inner.curve25519_key(), &prekey)
+            .unwrap();
+
+        let err = bob
+            .create_inbound(alice.inner.curve25519_key(), &prekey)
+            .unwrap_err();
+        assert!(
+            matches!(err, InboundSessionError::SessionCreation(_)),
+            "{err}"
+        );
+        assert!(err.to_string().starts_with("inbound_session_creation: "));
+    }
+
+    fn inbound_group_session(key: &str) -> VodozemacInboundGroupSession {
+        let key = SessionKey::from_base64(key).unwrap();
+        VodozemacInboundGroupSession {
+            inner: InboundGroupSession::new(&key, megolm_config()),
+        }
+    }
+
+    #[test]
+    fn group_message_decrypts_for_every_member() {
+        let mut outbound = VodozemacGroupSession::create();
+        let key = outbound.session_key();
+        let mut bob = inbound_group_session(&key);
+        let mut carol = inbound_group_session(&key);
+        assert_eq!(bob.session_id(), outbound.session_id());
+
+        let first = outbound.encrypt(b"hello room");
+        let second = outbound.encrypt(&[0xff, 0x00]);
+        assert_eq!(bob.decrypt_message(&first).unwrap(), b"hello room");
+        assert_eq!(bob.decrypt_message(&second).unwrap(), [0xff, 0x00]);
+        assert_eq!(carol.decrypt_message(&second).unwrap(), [0xff, 0x00]);
+        assert_eq!(carol.decrypt_message(&first).unwrap(), b"hello room");
+    }
+
+    #[test]
+    fn late_member_cannot_read_earlier_group_messages() {
+        let mut outbound = VodozemacGroupSession::create();
+        let early = outbound.encrypt(b"before");
+        let mut dave = inbound_group_session(&outbound.session_key());
+
+        let err = dave.decrypt_message(&early).unwrap_err();
+        assert!(matches!(err, DecryptError::MessageTooOld(_)), "{err}");
+
+        let mut tampered = outbound.encrypt(b"after");
+        tampered[10] ^= 0x01;
+        let err = dave.decrypt_message(&tampered).unwrap_err();
+        assert!(matches!(err, DecryptError::BadMac(_)), "{err}");
+    }
+
+    #[test]
+    fn group_sessions_pickle_round_trip() {
+        let outbound = VodozemacGroupSession::create();
+        let inbound = inbound_group_session(&outbound.session_key());
+
+        let mut outbound = VodozemacGroupSession::from_pickle(&outbound.pickle(KEY).unwrap(), KEY)
+            .unwrap_or_else(|_| panic!("group session pickle should restore"));
+        let mut inbound =
+            VodozemacInboundGroupSession::from_pickle(&inbound.pickle(KEY).unwrap(), KEY)
+                .unwrap_or_else(|_| panic!("inbound group session pickle should restore"));
+
+        let message = outbound.encrypt(b"still here");
+        assert_eq!(inbound.decrypt_message(&message).unwrap(), b"still here");
+    }
+}